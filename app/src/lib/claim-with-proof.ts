@@ -43,6 +43,7 @@ const USDC_MINT = new PublicKey('4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU');
 interface PayoutProof {
     payout: number;
     nonce: number;
+    expiry: number; // unix timestamp
     signature: Uint8Array; // 64 bytes
 }
 
@@ -60,23 +61,30 @@ interface ClaimPayoutParams {
 /**
  * Construct the payout message that MXE signs
  * 
- * Message Format: keccak256(market || user || payout || nonce)
+ * Message Format: keccak256(program_id || market || user || payout || relayer_fee || nonce || expiry)
  * 
  * CRITICAL: This MUST match the Anchor program's construct_payout_message()
  * exactly, or signature verification will fail.
  */
 function constructPayoutMessage(
+    programId: PublicKey,
     market: PublicKey,
     user: PublicKey,
     payout: number,
-    nonce: number
+    relayerFee: number,
+    nonce: number,
+    expiry: number
 ): Uint8Array {
-    // Concatenate: market (32) + user (32) + payout (8 LE) + nonce (8 LE)
+    // Concatenate: program_id (32) + market (32) + user (32) + payout (8 LE)
+    //   + relayer_fee (8 LE) + nonce (8 LE) + expiry (8 LE)
     const data = Buffer.concat([
+        programId.toBuffer(), // 32 bytes
         market.toBuffer(), // 32 bytes
         user.toBuffer(), // 32 bytes
         Buffer.from(new BN(payout).toArray('le', 8)), // 8 bytes little-endian
+        Buffer.from(new BN(relayerFee).toArray('le', 8)), // 8 bytes little-endian
         Buffer.from(new BN(nonce).toArray('le', 8)), // 8 bytes little-endian
+        Buffer.from(new BN(expiry).toArray('le', 8)), // 8 bytes little-endian
     ]);
 
     // Hash with Keccak-256 (same as Anchor's keccak::hash)
@@ -147,6 +155,7 @@ export async function claimPayoutWithProof({
     const proof: PayoutProof = {
         payout: result.payoutAmount,
         nonce: result.nonce,
+        expiry: result.expiry,
         signature: typeof result.signature === 'string' 
             ? Buffer.from(result.signature, 'hex') 
             : result.signature,
@@ -162,10 +171,13 @@ export async function claimPayoutWithProof({
     // ========================================================================
 
     const message = constructPayoutMessage(
+        program.programId,
         market,
         user,
         proof.payout,
-        proof.nonce
+        0, // direct claim: no relayer fee
+        proof.nonce,
+        proof.expiry
     );
 
     console.log(`📝 Message hash: ${Buffer.from(message).toString('hex').slice(0, 16)}...`);
@@ -212,6 +224,7 @@ export async function claimPayoutWithProof({
         .claimWithProof(
             new BN(proof.payout),
             new BN(proof.nonce),
+            new BN(proof.expiry),
             Array.from(proof.signature)
         )
         .accounts({
@@ -26,6 +26,7 @@ interface PayoutParams {
 export interface PayoutResult {
   payoutAmount: number;
   nonce: number;
+  expiry: number; // unix timestamp after which the proof is rejected
   proof: string;
   signature: string;
   userBetAmount?: number;
@@ -52,6 +52,7 @@ interface ComputationJob {
   result?: {
     payout: number;
     nonce: number;
+    expiry: number;
     signature: string; // hex encoded
   };
 }
@@ -69,6 +70,11 @@ const MXE_SEED = 'nexora_mxe_devnet_seed_v1_test_only_not_for_production_use!!!'
 const MXE_PUBLIC_KEY = 
   '4d50dbe4d3a07a4dc36efeef0adab6eb12fe3b0aa8c85fdccf0b3c87a7b9e6f8';
 
+// Nexora program ID (ZUjdEhJfsNMBV7QbABwSSocMzqrCfhivCgWrhwtaMFm) as hex,
+// bound into every payout message to prevent cross-deployment replay
+const NEXORA_PROGRAM_ID =
+  '0851f22367b3c6de9c5f9d7b0058dceef05ea2e455d2724149e2b0b7387c66c0';
+
 console.log(`🔑 MXE Public Key: ${MXE_PUBLIC_KEY}`);
 console.log(`⚠️  This is a test keypair. Update DEFAULT_ARCIUM_CONFIG with this value.`);
 
@@ -80,6 +86,9 @@ function generateComputationId(): string {
   return 'comp_' + crypto.randomBytes(16).toString('hex');
 }
 
+// How long a signed payout claim stays valid (seconds)
+const PROOF_TTL_SECS = 15 * 60;
+
 function generateNonce(): number {
   return Math.floor(Math.random() * Number.MAX_SAFE_INTEGER);
 }
@@ -94,17 +103,23 @@ function keccak256(data: Buffer): Buffer {
 
 /**
  * Construct message that MXE signs
- * Format: Keccak256(market || user || payout || nonce)
+ * Format: Keccak256(program_id || market || user || payout || relayer_fee || nonce || expiry)
  */
 function constructPayoutMessage(
   market: string,
   user: string,
   payout: number,
-  nonce: number
+  relayerFee: number,
+  nonce: number,
+  expiry: number
 ): Buffer {
-  const data = Buffer.alloc(80);
+  const data = Buffer.alloc(128);
   let offset = 0;
 
+  // Program ID (32 bytes)
+  hexToBuffer(NEXORA_PROGRAM_ID).copy(data, offset, 0, 32);
+  offset += 32;
+
   // Market (32 bytes, assume hex string)
   const marketBuf = hexToBuffer(market.padStart(64, '0'));
   marketBuf.copy(data, offset, 0, 32);
@@ -121,10 +136,22 @@ function constructPayoutMessage(
   payoutBuf.copy(data, offset);
   offset += 8;
 
+  // Relayer fee (8 bytes, little-endian; 0 for direct claims)
+  const relayerFeeBuf = Buffer.alloc(8);
+  relayerFeeBuf.writeBigUInt64LE(BigInt(relayerFee));
+  relayerFeeBuf.copy(data, offset);
+  offset += 8;
+
   // Nonce (8 bytes, little-endian)
   const nonceBuf = Buffer.alloc(8);
   nonceBuf.writeBigUInt64LE(BigInt(nonce));
   nonceBuf.copy(data, offset);
+  offset += 8;
+
+  // Expiry (8 bytes, little-endian unix timestamp)
+  const expiryBuf = Buffer.alloc(8);
+  expiryBuf.writeBigInt64LE(BigInt(expiry));
+  expiryBuf.copy(data, offset);
 
   // Hash with Keccak256 (using sha256 as placeholder for demo)
   return keccak256(data);
@@ -241,19 +268,23 @@ app.get('/computation/:id', (req: Request, res: Response) => {
  * Body:
  * {
  *   "market": "base58_pubkey",
- *   "user": "base58_pubkey"
+ *   "user": "base58_pubkey",
+ *   "relayer_fee": 0            // optional, paid to a relayer out of the payout
  * }
  * 
  * Response:
  * {
  *   "payout": 1000000,
+ *   "relayer_fee": 0,
  *   "nonce": 12345,
+ *   "expiry": 1700000000,
  *   "signature": [64 bytes as array]
  * }
  */
 app.post('/get_payout_claim', (req: Request, res: Response) => {
   try {
     const { market, user } = req.body;
+    const relayerFee = Number(req.body.relayer_fee ?? 0);
 
     if (!market || !user) {
       return res.status(400).json({ error: 'Missing market or user' });
@@ -263,13 +294,20 @@ app.post('/get_payout_claim', (req: Request, res: Response) => {
     // For now, simulate a claim
     const payout = 1000000; // 1 USDC (in lamports)
     const nonce = generateNonce();
+    const expiry = Math.floor(Date.now() / 1000) + PROOF_TTL_SECS;
+
+    if (!Number.isInteger(relayerFee) || relayerFee < 0 || relayerFee > payout) {
+      return res.status(400).json({ error: 'Invalid relayer_fee' });
+    }
 
     // Construct message and sign
     const message = constructPayoutMessage(
       market.slice(0, 44), // Base58 -> first 44 chars is ~32 bytes
       user.slice(0, 44),
       payout,
-      nonce
+      relayerFee,
+      nonce,
+      expiry
     );
 
     const signature = signMessage(message);
@@ -280,7 +318,9 @@ app.post('/get_payout_claim', (req: Request, res: Response) => {
 
     return res.status(200).json({
       payout,
+      relayer_fee: relayerFee,
       nonce,
+      expiry,
       signature: signature.split('').map((c) => parseInt(c, 16)), // Convert to array of bytes
     });
   } catch (error: any) {
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+
+declare_id!("4147vGHRcqLEAMfuzW6qGmYVdCHL5vFZhQmtcsvt8nPo");
+
+// ============================================================================
+// Pyth V2 Price Account Layout (pyth_sdk_solana::state::PriceAccount)
+// ============================================================================
+
+/// Size of a price account with 32 publisher components
+pub const PRICE_ACCOUNT_LEN: usize = 3312;
+
+const MAGIC: u32 = 0xa1b2_c3d4;
+const VERSION_2: u32 = 2;
+const ACCOUNT_TYPE_PRICE: u32 = 3;
+const PRICE_TYPE_PRICE: u32 = 1;
+const PRICE_STATUS_TRADING: u32 = 1;
+
+const MAGIC_OFFSET: usize = 0;
+const VERSION_OFFSET: usize = 4;
+const ACCOUNT_TYPE_OFFSET: usize = 8;
+const SIZE_OFFSET: usize = 12;
+const PRICE_TYPE_OFFSET: usize = 16;
+const EXPO_OFFSET: usize = 20;
+const EMA_PRICE_OFFSET: usize = 48;
+const EMA_CONF_OFFSET: usize = 72;
+const TIMESTAMP_OFFSET: usize = 96;
+const PREV_PRICE_OFFSET: usize = 184;
+const PREV_CONF_OFFSET: usize = 192;
+const PREV_TIMESTAMP_OFFSET: usize = 200;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_CONF_OFFSET: usize = 216;
+const AGG_STATUS_OFFSET: usize = 224;
+
+/// Stand-in for the Pyth oracle program on localnet
+///
+/// The nexora tests create a PRICE_ACCOUNT_LEN account owned by this
+/// program and publish whatever price and publish time they need.
+/// Never deploy outside a test validator.
+#[program]
+pub mod mock_pyth {
+    use super::*;
+
+    /// Publish `price` (with exponent `expo`) as the trading aggregate
+    pub fn set_price(
+        ctx: Context<SetPrice>,
+        price: i64,
+        expo: i32,
+        conf: u64,
+        publish_time: i64,
+    ) -> Result<()> {
+        let mut data = ctx.accounts.price.try_borrow_mut_data()?;
+        require!(data.len() >= PRICE_ACCOUNT_LEN, ErrorCode::PriceAccountTooSmall);
+
+        let mut write = |offset: usize, bytes: &[u8]| {
+            data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        };
+        write(MAGIC_OFFSET, &MAGIC.to_le_bytes());
+        write(VERSION_OFFSET, &VERSION_2.to_le_bytes());
+        write(ACCOUNT_TYPE_OFFSET, &ACCOUNT_TYPE_PRICE.to_le_bytes());
+        write(SIZE_OFFSET, &(PRICE_ACCOUNT_LEN as u32).to_le_bytes());
+        write(PRICE_TYPE_OFFSET, &PRICE_TYPE_PRICE.to_le_bytes());
+        write(EXPO_OFFSET, &expo.to_le_bytes());
+        write(EMA_PRICE_OFFSET, &price.to_le_bytes());
+        write(EMA_CONF_OFFSET, &(conf as i64).to_le_bytes());
+        write(TIMESTAMP_OFFSET, &publish_time.to_le_bytes());
+        write(PREV_PRICE_OFFSET, &price.to_le_bytes());
+        write(PREV_CONF_OFFSET, &conf.to_le_bytes());
+        write(PREV_TIMESTAMP_OFFSET, &publish_time.to_le_bytes());
+        write(AGG_PRICE_OFFSET, &price.to_le_bytes());
+        write(AGG_CONF_OFFSET, &conf.to_le_bytes());
+        write(AGG_STATUS_OFFSET, &PRICE_STATUS_TRADING.to_le_bytes());
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetPrice<'info> {
+    /// CHECK: Raw Pyth layout, written byte by byte; only accounts this
+    /// program owns can be written
+    #[account(mut, owner = crate::ID)]
+    pub price: AccountInfo<'info>,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Price account is smaller than a Pyth V2 price account")]
+    PriceAccountTooSmall,
+}
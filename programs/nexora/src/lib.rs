@@ -1,9 +1,20 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token::native_mint;
+use anchor_spl::token_2022;
+use anchor_spl::token_interface::{
+    self, CloseAccount, Mint, SyncNative, TokenAccount, TokenInterface, TransferChecked,
+};
+use pyth_sdk_solana::load_price_feed_from_account_info;
+use switchboard_solana::{AggregatorAccountData, SwitchboardDecimal, SWITCHBOARD_PROGRAM_ID};
 use solana_program::{
+    alt_bn128::prelude::{alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing},
     ed25519_program,
     keccak,
-    sysvar::instructions::{load_instruction_at_checked, ID as IX_SYSVAR_ID},
+    secp256k1_program,
+    sysvar::instructions::{
+        load_current_index_checked, load_instruction_at_checked, ID as IX_SYSVAR_ID,
+    },
 };
 
 declare_id!("ZUjdEhJfsNMBV7QbABwSSocMzqrCfhivCgWrhwtaMFm");
@@ -12,705 +23,11999 @@ declare_id!("ZUjdEhJfsNMBV7QbABwSSocMzqrCfhivCgWrhwtaMFm");
 // Security Configuration - HARDCODED FOR DEVNET V1
 // ============================================================================
 
-/// Only this pubkey can create markets on Devnet V1
+/// Initial protocol admin: only this key can run `initialize_protocol`,
+/// which records it as `Config.admin`. Later checks use `Config.admin`.
 pub const ADMIN_PUBKEY: Pubkey = pubkey!("GveKcrXTsLd2nqSPgwV1BifPS1fJvoaP5AajpAXitxez");
 
-/// Arcium MXE Enclave Public Key
-/// 
-/// This is the Ed25519 public key of the Arcium MXE (Multi-Party eXecution Environment).
-/// The MXE signs payout computation results with its private key.
-/// This program verifies those signatures against this public key.
-/// 
-/// ⚠️ PRODUCTION DEPLOYMENT STEPS:
-/// 1. Deploy NEXORA payout computation to Arcium MXE
-/// 2. Retrieve enclave attestation public key from Arcium dashboard
-/// 3. Update this constant with the actual MXE public key
-/// 4. Rebuild and deploy this program
-/// 
-/// SECURITY GUARANTEE:
-/// - Only payouts signed by this MXE private key will be accepted
-/// - Any tampering with payout amounts invalidates the signature
-/// - Frontend cannot forge payouts (no private key access)
-/// - Attackers cannot bypass verification (onchain check)
-/// 
-/// Current value: PLACEHOLDER - Replace after MXE deployment
-pub const MXE_PUBKEY: [u8; 32] = [
-    // TODO: Replace with actual Arcium MXE enclave public key
-    // Get from: https://dashboard.arcium.com after deploying enclave
-    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-];
+/// Protocol treasury receiving slashed bonds (Devnet V1: the admin wallet)
+pub const TREASURY_PUBKEY: Pubkey = ADMIN_PUBKEY;
+
+/// SPL Governance program whose realm `set_governance` may attach
+pub const SPL_GOVERNANCE_PROGRAM_ID: Pubkey = pubkey!("GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCVZw");
+
+/// Time after resolution during which the result can be disputed
+/// and claims are blocked (24 hours)
+#[cfg(not(feature = "short-timelocks"))]
+pub const DISPUTE_WINDOW_SECS: i64 = 86400;
+#[cfg(feature = "short-timelocks")]
+pub const DISPUTE_WINDOW_SECS: i64 = 5;
+
+/// Time a proposed result can be challenged before `finalize_resolution`
+/// may apply it (24 hours)
+#[cfg(not(feature = "short-timelocks"))]
+pub const CHALLENGE_WINDOW_SECS: i64 = 86400;
+#[cfg(feature = "short-timelocks")]
+pub const CHALLENGE_WINDOW_SECS: i64 = 5;
+
+/// SOL bond a disputant must lock in their DisputeRecord (0.1 SOL)
+pub const DISPUTE_BOND_LAMPORTS: u64 = 100_000_000;
+
+/// Default wait after expiry before anyone may force-resolve a market
+/// (7 days); the live value is `Config.force_resolve_delay`
+#[cfg(not(feature = "short-timelocks"))]
+pub const FORCE_RESOLVE_DELAY_SECS: i64 = 7 * 86400;
+#[cfg(feature = "short-timelocks")]
+pub const FORCE_RESOLVE_DELAY_SECS: i64 = 5;
+
+/// Default time after expiry during which payouts can be claimed
+/// (180 days); the live value is `Config.unclaimed_grace_period`
+pub const UNCLAIMED_GRACE_PERIOD_SECS: i64 = 180 * 86400;
+
+/// Time after expiry before `emergency_withdraw` may drain a market's
+/// custody (365 days)
+#[cfg(not(feature = "short-timelocks"))]
+pub const EMERGENCY_WITHDRAW_GRACE_SECS: i64 = 365 * 86400;
+#[cfg(feature = "short-timelocks")]
+pub const EMERGENCY_WITHDRAW_GRACE_SECS: i64 = 5;
+
+/// SOL bond a force-resolver must lock in their ForceResolution (0.5 SOL)
+pub const FORCE_RESOLVE_BOND_LAMPORTS: u64 = 500_000_000;
+
+/// Upper bound for the protocol fee on bets (10%)
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+/// Default lifetime volume for Silver, Gold and Platinum rakeback
+/// (100, 1,000 and 10,000 USDC); the live values are
+/// `Config.rakeback_thresholds`
+pub const DEFAULT_RAKEBACK_THRESHOLDS: [u64; 3] = [100_000_000, 1_000_000_000, 10_000_000_000];
+
+/// Upper bound for the creator fee taken at resolution (5%)
+pub const MAX_CREATOR_FEE_BPS: u16 = 500;
+
+/// Most positions `batch_close_positions` closes in one transaction
+pub const MAX_BATCH_CLOSE: usize = 10;
+
+/// Most markets `batch_claim` settles in one transaction
+pub const MAX_BATCH_CLAIM: usize = 4;
+
+/// Most markets `batch_resolve_markets` resolves in one transaction
+pub const MAX_BATCH_RESOLVE: usize = 10;
+
+/// Most markets one `get_market_page` call lists
+pub const MAX_MARKET_PAGE_SIZE: usize = 20;
+
+/// Most MXE signers a quorum can have
+pub const MAX_MXE_SIGNERS: usize = 5;
+
+/// Most mints `Config.allowed_mints` can hold
+pub const MAX_ALLOWED_MINTS: usize = 8;
+
+/// Public inputs of a Groth16 payout proof: (market, user, payout, nonce)
+pub const ZK_PUBLIC_INPUTS: usize = 4;
+
+/// How long before expiry a resolver may start recording evidence
+pub const EVIDENCE_WINDOW_SECS: i64 = 86400;
+
+/// Entries kept on the ForecastersLeaderboard
+pub const LEADERBOARD_SIZE: usize = 100;
+
+/// Fixed-point scale of Brier score probabilities (1.0 = 10_000)
+pub const BRIER_SCALE: u64 = 10_000;
+
+/// Largest payout a claim may receive, as a multiple of the position stake
+///
+/// Defense-in-depth against a faulty MXE proof; reachable only if the
+/// winning side holds less than 0.1% of the pool.
+pub const MAX_PAYOUT_MULTIPLIER: u64 = 1_000;
+
+/// How long the previous MXE signer set stays valid after a rotation
+/// (24 hours), so proofs signed before it can still be claimed
+#[cfg(not(feature = "short-timelocks"))]
+pub const MXE_ROTATION_GRACE_SECS: i64 = 24 * 3600;
+#[cfg(feature = "short-timelocks")]
+pub const MXE_ROTATION_GRACE_SECS: i64 = 5;
+
+/// Delay between proposing and finalizing an MXE key rotation (48 hours)
+#[cfg(not(feature = "short-timelocks"))]
+pub const MXE_ROTATION_DELAY_SECS: i64 = 48 * 3600;
+#[cfg(feature = "short-timelocks")]
+pub const MXE_ROTATION_DELAY_SECS: i64 = 5;
+
+/// Time after a price market's observation point (its expiry) during
+/// which oracle resolution is open (1 hour)
+///
+/// Only prices published inside the window count, and calls after it
+/// are rejected, so a caller cannot wait for a later price that suits
+/// them. Markets the window missed go to their resolver.
+#[cfg(not(feature = "short-timelocks"))]
+pub const ORACLE_RESOLUTION_WINDOW_SECS: i64 = 3600;
+#[cfg(feature = "short-timelocks")]
+pub const ORACLE_RESOLUTION_WINDOW_SECS: i64 = 10;
+
+/// Maximum Pyth confidence interval, in basis points of the price
+///
+/// Prices with a wider interval are treated as stale/unreliable and
+/// cannot resolve a market.
+pub const PYTH_MAX_CONFIDENCE_BPS: u128 = 100;
+
+/// Maximum age of a Switchboard round used for automatic resolution
+pub const SWITCHBOARD_MAX_STALENESS_SECS: i64 = 60;
+
+/// Maximum Switchboard round standard deviation, in basis points of the
+/// result
+pub const SWITCHBOARD_MAX_CONFIDENCE_BPS: u128 = 100;
+
+/// Decimals of `resolution_price_threshold` on Switchboard markets
+///
+/// Switchboard results carry a per-round scale, so the threshold is stored
+/// at this fixed one and the feed value is rescaled to match.
+pub const SWITCHBOARD_THRESHOLD_DECIMALS: u32 = 9;
+
+// ============================================================================
+// Structured Logging (feature = "structured-logs")
+// ============================================================================
+
+/// Log an instruction outcome as `nexora:<ix> key=value ...`
+///
+/// Some integrators parse program logs instead of Anchor events. With the
+/// `structured-logs` feature disabled this expands to nothing, so production
+/// builds pay no compute cost.
+#[cfg(feature = "structured-logs")]
+macro_rules! log_outcome {
+    ($ix:literal $(, $key:ident = $value:expr)* $(,)?) => {
+        msg!(
+            concat!("nexora:", $ix $(, " ", stringify!($key), "={:?}")*)
+            $(, $value)*
+        )
+    };
+}
+
+#[cfg(not(feature = "structured-logs"))]
+macro_rules! log_outcome {
+    ($($tt:tt)*) => {};
+}
 
 #[program]
 pub mod nexora {
     use super::*;
 
-    /// Create a new prediction market (ADMIN ONLY)
-    pub fn create_market(
-        ctx: Context<CreateMarket>,
-        question: String,
-        expiry_timestamp: i64,
+    /// Create the global protocol Config (ADMIN ONLY, once)
+    ///
+    /// `mxe_pubkeys` are the Ed25519 keys of the Arcium MXE signers, of
+    /// which `required_signatures` must sign each payout. Get them from the
+    /// Arcium dashboard after deploying the payout computation; later
+    /// changes go through the rotation time-lock.
+    pub fn initialize_protocol(
+        ctx: Context<InitializeProtocol>,
+        fee_bps: u16,
+        mxe_pubkeys: Vec<[u8; 32]>,
+        required_signatures: u8,
     ) -> Result<()> {
-        // ⚠️ ADMIN CHECK: Only hardcoded admin can create markets
-        require!(
-            ctx.accounts.authority.key() == ADMIN_PUBKEY,
-            ErrorCode::Unauthorized
-        );
+        require!(fee_bps <= MAX_FEE_BPS, ErrorCode::FeeTooHigh);
+        validate_mxe_quorum(&mxe_pubkeys, required_signatures)?;
 
-        require!(question.len() <= 280, ErrorCode::QuestionTooLong);
-        require!(
-            expiry_timestamp > Clock::get()?.unix_timestamp,
-            ErrorCode::ExpiryInPast
-        );
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.pending_admin = None;
+        config.governance = None;
+        config.admin_timelock_secs = 0;
+        config.queued_admin_action = None;
+        config.admin_action_execute_after = 0;
+        config.protocol_paused = false;
+        config.fee_bps = fee_bps;
+        config.rakeback_thresholds = DEFAULT_RAKEBACK_THRESHOLDS;
+        config.rakeback_bps = [0; 4];
+        config.insurance_fee_bps = 0;
+        config.vault_tolerance = None;
+        config.market_creation_fee = 0;
+        config.resolution_bond_amount = 0;
+        config.required_creator_stake = 0;
+        config.force_resolve_delay = FORCE_RESOLVE_DELAY_SECS;
+        config.unclaimed_grace_period = UNCLAIMED_GRACE_PERIOD_SECS;
+        config.allowed_mints = Vec::new();
+        config.mxe_pubkeys = mxe_pubkeys;
+        config.required_signatures = required_signatures;
+        config.pending_mxe_pubkeys = None;
+        config.pending_required_signatures = 0;
+        config.rotation_unlock_time = 0;
+        config.previous_mxe_pubkeys = Vec::new();
+        config.previous_required_signatures = 0;
+        config.rotation_deadline = 0;
+        config.signature_scheme = SignatureScheme::Ed25519;
+        config.mxe_eth_address = [0u8; 20];
+        config.pending_signature_scheme = None;
+        config.pending_mxe_eth_address = [0u8; 20];
+        config.scheme_unlock_time = 0;
+        config.bump = ctx.bumps.config;
 
-        let market = &mut ctx.accounts.market;
-        market.authority = ctx.accounts.authority.key();
-        market.question = question;
-        market.expiry_timestamp = expiry_timestamp;
-        market.total_pool = 0;
-        market.resolved = false;
-        market.result = MarketResult::None;
-        market.vault = ctx.accounts.vault.key();
-        market.usdc_mint = ctx.accounts.usdc_mint.key();
-        market.bump = ctx.bumps.market;
-        market.vault_bump = ctx.bumps.vault;
+        let stats = &mut ctx.accounts.stats;
+        stats.total_markets = 0;
+        stats.active_markets = 0;
+        stats.total_volume = 0;
+        stats.total_bets = 0;
+        stats.total_claims = 0;
+        stats.total_fees_collected = 0;
+        stats.bump = ctx.bumps.stats;
 
-        emit!(MarketCreatedEvent {
-            market: market.key(),
-            authority: market.authority,
-            question: market.question.clone(),
-            expiry_timestamp: market.expiry_timestamp,
+        emit!(ProtocolInitializedEvent {
+            admin: ctx.accounts.admin.key(),
+            fee_bps,
+            timestamp: Clock::get()?.unix_timestamp,
         });
 
+        log_outcome!("initialize_protocol", fee_bps = fee_bps);
+
         Ok(())
     }
 
-    /// Place an encrypted bet on a market
-    /// 
-    /// ARCIUM INTEGRATION POINT #1:
-    /// - encrypted_payload contains: { side: "yes"|"no", amount: u64 }
-    /// - This payload is encrypted client-side before sending
-    /// - In PRODUCTION: Arcium MXE will decrypt and store in TEE
-    /// - In DEVNET: Frontend mock client handles this
-    /// 
-    /// TRANSPARENCY WARNING:
-    /// - The 'amount' parameter is VISIBLE onchain
-    /// - Individual bet amounts are PUBLIC in UserPosition accounts
-    /// - Only the 'side' (yes/no) is intended to be confidential
-    /// - total_pool is PUBLIC (sum of all deposits)
-    pub fn place_bet(
-        ctx: Context<PlaceBet>,
-        encrypted_payload: Vec<u8>,
-        amount: u64,
+    /// Change the protocol fee taken on each bet (ADMIN or GOVERNANCE)
+    ///
+    /// A governance proposal already ran its vote and hold-up time, so it
+    /// applies directly even while the admin timelock is on.
+    pub fn set_fee_bps(
+        ctx: Context<GovernedConfig>,
+        fee_bps: u16,
+    ) -> Result<()> {
+        let governed = require_admin_or_governance(
+            &ctx.accounts.config,
+            &ctx.accounts.admin.key(),
+            ctx.remaining_accounts,
+        )?;
+        let config = &mut ctx.accounts.config;
+        if !governed {
+            require_admin_timelock_disabled(config)?;
+        }
+        apply_admin_action(config, AdminAction::SetFeeBps { fee_bps })
+    }
+
+    /// Change the rakeback tiers (ADMIN ONLY)
+    ///
+    /// `thresholds` is the lifetime volume, in base units, that reaches
+    /// Silver, Gold and Platinum; `rakeback_bps` is taken off `fee_bps` for
+    /// Bronze through Platinum.
+    pub fn set_rakeback_schedule(
+        ctx: Context<SetFeeBps>,
+        thresholds: [u64; 3],
+        rakeback_bps: [u16; 4],
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require_admin_timelock_disabled(config)?;
+        apply_admin_action(config, AdminAction::SetRakebackSchedule { thresholds, rakeback_bps })
+    }
+
+    /// Change the share of protocol fees routed to the insurance fund
+    /// (ADMIN ONLY)
+    ///
+    /// Open the insurance vault of every mint in use (`insurance_fund_deposit`)
+    /// before turning this on: bets on USDC-currency markets then need it.
+    pub fn set_insurance_fee_bps(
+        ctx: Context<SetFeeBps>,
+        insurance_fee_bps: u16,
     ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require_admin_timelock_disabled(config)?;
+        apply_admin_action(config, AdminAction::SetInsuranceFeeBps { insurance_fee_bps })
+    }
+
+    /// Set how far a vault may drift from `total_pool` at resolution
+    /// (ADMIN ONLY)
+    ///
+    /// None only reports the delta in VaultReconciliationEvent; Some(n)
+    /// fails resolutions off by more than n base units.
+    pub fn set_vault_tolerance(
+        ctx: Context<SetFeeBps>,
+        vault_tolerance: Option<u64>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require_admin_timelock_disabled(config)?;
+        apply_admin_action(config, AdminAction::SetVaultTolerance { vault_tolerance })
+    }
+
+    /// Create the global InsuranceFund (ADMIN ONLY, once)
+    ///
+    /// The fund is the token authority of every ["insurance_vault", mint]
+    /// account; those are opened by the first `insurance_fund_deposit` for
+    /// their mint.
+    pub fn initialize_insurance_fund(ctx: Context<InitializeInsuranceFund>) -> Result<()> {
+        let insurance_fund = &mut ctx.accounts.insurance_fund;
+        insurance_fund.total_deposited = 0;
+        insurance_fund.total_drawn = 0;
+        insurance_fund.bump = ctx.bumps.insurance_fund;
+
+        log_outcome!("initialize_insurance_fund", insurance_fund = insurance_fund.key());
+
+        Ok(())
+    }
+
+    /// Top up the insurance vault of a mint (anyone)
+    ///
+    /// Creates the vault on first use, with the depositor paying its rent.
+    pub fn insurance_fund_deposit(ctx: Context<InsuranceFundDeposit>, amount: u64) -> Result<()> {
         require!(amount > 0, ErrorCode::InvalidAmount);
-        require!(
-            encrypted_payload.len() <= 512,
-            ErrorCode::PayloadTooLarge
-        );
 
-        let market = &mut ctx.accounts.market;
-        
-        // Ensure market hasn't expired
-        require!(
-            Clock::get()?.unix_timestamp < market.expiry_timestamp,
-            ErrorCode::MarketExpired
-        );
+        let usdc_mint = &ctx.accounts.usdc_mint;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.depositor_token_account.to_account_info(),
+                    mint: usdc_mint.to_account_info(),
+                    to: ctx.accounts.insurance_vault.to_account_info(),
+                    authority: ctx.accounts.depositor.to_account_info(),
+                },
+            ),
+            amount,
+            usdc_mint.decimals,
+        )?;
 
-        // Ensure market hasn't been resolved
-        require!(!market.resolved, ErrorCode::MarketResolved);
+        let insurance_fund = &mut ctx.accounts.insurance_fund;
+        insurance_fund.total_deposited = insurance_fund.total_deposited
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
 
-        // Transfer USDC from user to vault
-        let transfer_ctx = CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.user_token_account.to_account_info(),
-                to: ctx.accounts.vault.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-            },
+        emit!(InsuranceDepositEvent {
+            mint: usdc_mint.key(),
+            depositor: ctx.accounts.depositor.key(),
+            amount,
+        });
+
+        log_outcome!(
+            "insurance_fund_deposit",
+            mint = usdc_mint.key(),
+            depositor = ctx.accounts.depositor.key(),
+            amount = amount,
         );
-        token::transfer(transfer_ctx, amount)?;
 
-        // Update total pool
-        market.total_pool = market.total_pool.checked_add(amount)
-            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
 
-        // Initialize or update user position
-        let position = &mut ctx.accounts.user_position;
-        if position.amount == 0 {
-            position.user = ctx.accounts.user.key();
-            position.market = market.key();
-            position.amount = amount;
-            position.claimed = false;
-            position.bump = ctx.bumps.user_position;
-        } else {
-            position.amount = position.amount.checked_add(amount)
-                .ok_or(ErrorCode::Overflow)?;
-        }
+    /// Move insurance funds into an underfunded market vault (ADMIN ONLY)
+    pub fn draw_from_insurance(ctx: Context<DrawFromInsurance>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
 
-        emit!(BetPlacedEvent {
-            market: market.key(),
-            user: ctx.accounts.user.key(),
+        let market = &ctx.accounts.market;
+        draw_insurance(
+            market,
+            &mut ctx.accounts.insurance_fund,
+            &ctx.accounts.insurance_vault,
+            &ctx.accounts.vault,
+            &ctx.accounts.usdc_mint,
+            &ctx.accounts.token_program,
             amount,
-            encrypted_payload,
-            timestamp: Clock::get()?.unix_timestamp,
+        )?;
+
+        emit!(InsuranceDrawdownEvent {
+            market: market.key(),
+            user: ctx.accounts.admin.key(),
+            drawdown_amount: amount,
         });
 
+        log_outcome!("draw_from_insurance", market = market.key(), amount = amount);
+
         Ok(())
     }
 
-    /// Resolve the market (authority only)
-    pub fn resolve_market(
-        ctx: Context<ResolveMarket>,
-        result: MarketResult,
+    /// Propose a new MXE signer set and quorum (ADMIN or GOVERNANCE)
+    ///
+    /// The set only takes effect through `finalize_mxe_rotation` once
+    /// `MXE_ROTATION_DELAY_SECS` have passed, giving users time to react to
+    /// a compromised admin. Proposing again replaces the pending set and
+    /// restarts the time-lock.
+    pub fn propose_mxe_rotation(
+        ctx: Context<GovernedConfig>,
+        new_keys: Vec<[u8; 32]>,
+        required_signatures: u8,
     ) -> Result<()> {
-        require!(
-            result != MarketResult::None,
-            ErrorCode::InvalidResult
+        require_admin_or_governance(
+            &ctx.accounts.config,
+            &ctx.accounts.admin.key(),
+            ctx.remaining_accounts,
+        )?;
+        validate_mxe_quorum(&new_keys, required_signatures)?;
+
+        let config = &mut ctx.accounts.config;
+        let now = Clock::get()?.unix_timestamp;
+
+        config.pending_mxe_pubkeys = Some(new_keys.clone());
+        config.pending_required_signatures = required_signatures;
+        config.rotation_unlock_time = now
+            .checked_add(MXE_ROTATION_DELAY_SECS)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(MxeRotationProposedEvent {
+            pending_mxe_pubkeys: new_keys,
+            required_signatures,
+            unlock_time: config.rotation_unlock_time,
+            timestamp: now,
+        });
+
+        log_outcome!(
+            "propose_mxe_rotation",
+            unlock_time = config.rotation_unlock_time,
         );
 
-        let market = &mut ctx.accounts.market;
-        
-        // Ensure market has expired
+        Ok(())
+    }
+
+    /// Activate the pending MXE signer set once its time-lock has elapsed
+    /// (ADMIN ONLY)
+    ///
+    /// The replaced set keeps verifying proofs for `MXE_ROTATION_GRACE_SECS`
+    /// so the MXE can drain its signing queue.
+    pub fn finalize_mxe_rotation(ctx: Context<FinalizeMxeRotation>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let now = Clock::get()?.unix_timestamp;
+
         require!(
-            Clock::get()?.unix_timestamp >= market.expiry_timestamp,
-            ErrorCode::MarketNotExpired
+            config.pending_mxe_pubkeys.is_some(),
+            ErrorCode::NoPendingRotation
+        );
+        require!(
+            now >= config.rotation_unlock_time,
+            ErrorCode::RotationTimeLockActive
         );
 
-        // Ensure not already resolved
-        require!(!market.resolved, ErrorCode::AlreadyResolved);
-
-        market.resolved = true;
-        market.result = result;
+        let new_keys = config.pending_mxe_pubkeys.take().unwrap();
+        let old_keys = std::mem::replace(&mut config.mxe_pubkeys, new_keys.clone());
+        config.previous_mxe_pubkeys = old_keys.clone();
+        config.previous_required_signatures = config.required_signatures;
+        config.rotation_deadline = now
+            .checked_add(MXE_ROTATION_GRACE_SECS)
+            .ok_or(ErrorCode::Overflow)?;
+        config.required_signatures = config.pending_required_signatures;
+        config.pending_required_signatures = 0;
 
-        emit!(MarketResolvedEvent {
-            market: market.key(),
-            result,
-            timestamp: Clock::get()?.unix_timestamp,
+        emit!(MxeRotationFinalizedEvent {
+            old_mxe_pubkeys: old_keys,
+            new_mxe_pubkeys: new_keys,
+            required_signatures: config.required_signatures,
+            rotation_deadline: config.rotation_deadline,
+            timestamp: now,
         });
 
+        log_outcome!("finalize_mxe_rotation", timestamp = now);
+
         Ok(())
     }
 
-    /// Claim winnings with cryptographic proof from Arcium MXE
-    /// 
-    /// TRUST-MINIMIZED PAYOUT FLOW:
-    /// 
-    /// 1️⃣ User places bet → encrypted side sent to MXE
-    /// 2️⃣ Market resolves → result recorded onchain
-    /// 3️⃣ User requests payout → MXE computes in TEE:
-    ///    - Decrypts user's bet side
-    ///    - Calculates payout based on:
-    ///      * Bet side matches market result?
-    ///      * User's bet amount
-    ///      * Total winning side pool
-    ///    - Generates unique nonce (timestamp + random)
-    ///    - Creates message: keccak256(market || user || payout || nonce)
-    ///    - Signs message with MXE private key (Ed25519)
-    /// 4️⃣ User submits claim transaction with:
-    ///    - payout amount
-    ///    - nonce
-    ///    - MXE signature
-    /// 5️⃣ This instruction verifies:
-    ///    - Ed25519 signature from MXE_PUBKEY ✅
-    ///    - Nonce not reused (replay protection) ✅
-    ///    - Market resolved ✅
-    ///    - Not already claimed ✅
-    ///    - Vault has sufficient balance ✅
-    /// 6️⃣ Only if ALL checks pass → transfer USDC
-    /// 
-    /// SECURITY GUARANTEES:
-    /// ❌ Frontend cannot forge payouts (no MXE private key)
-    /// ❌ Attackers cannot replay old proofs (nonce tracking)
-    /// ❌ Users cannot modify payout amounts (invalidates signature)
-    /// ❌ Vault draining impossible (each user can claim once)
-    /// 
-    /// CRYPTOGRAPHIC VERIFICATION:
-    /// - Ed25519 signature verification via Solana ed25519_program
-    /// - Message format: keccak256(market || user || payout || nonce)
-    /// - Public key: MXE_PUBKEY constant (hardcoded after MXE deployment)
-    pub fn claim_with_proof(
-        ctx: Context<ClaimWithProof>,
-        payout: u64,
-        nonce: u64,
-        signature: [u8; 64],
+    /// Propose the signature scheme MXE proofs use (ADMIN ONLY)
+    ///
+    /// Time-locked like an MXE rotation. `mxe_eth_address` is the MXE's
+    /// Ethereum address and only matters for `Secp256k1`.
+    pub fn propose_signature_scheme(
+        ctx: Context<ProposeMxeRotation>,
+        scheme: SignatureScheme,
+        mxe_eth_address: [u8; 20],
     ) -> Result<()> {
-        let market = &ctx.accounts.market;
-        let position = &mut ctx.accounts.user_position;
-
-        // ============================================================================
-        // SECURITY CHECKS - ALL MUST PASS
-        // ============================================================================
+        require!(
+            scheme == SignatureScheme::Ed25519 || mxe_eth_address != [0u8; 20],
+            ErrorCode::InvalidEthAddress
+        );
 
-        // 1️⃣ Ensure market is resolved
-        require!(market.resolved, ErrorCode::MarketNotResolved);
+        let config = &mut ctx.accounts.config;
+        let now = Clock::get()?.unix_timestamp;
+        config.pending_signature_scheme = Some(scheme);
+        config.pending_mxe_eth_address = mxe_eth_address;
+        config.scheme_unlock_time = now
+            .checked_add(MXE_ROTATION_DELAY_SECS)
+            .ok_or(ErrorCode::Overflow)?;
 
-        // 2️⃣ Ensure user hasn't already claimed
-        require!(!position.claimed, ErrorCode::AlreadyClaimed);
+        emit!(SignatureSchemeProposedEvent {
+            scheme,
+            mxe_eth_address,
+            unlock_time: config.scheme_unlock_time,
+            timestamp: now,
+        });
 
-        // 3️⃣ Ensure nonce hasn't been used (replay protection)
-        require!(
-            position.nonce_used == 0,
-            ErrorCode::NonceAlreadyUsed
+        log_outcome!(
+            "propose_signature_scheme",
+            scheme = scheme,
+            unlock_time = config.scheme_unlock_time,
         );
 
-        // 4️⃣ Construct the signed message
-        // Message format: keccak256(market || user || payout || nonce)
-        let message = construct_payout_message(
-            &market.key(),
-            &ctx.accounts.user.key(),
-            payout,
-            nonce,
-        );
+        Ok(())
+    }
 
-        // 5️⃣ Verify Ed25519 signature from MXE
-        verify_mxe_signature(
-            &ctx.accounts.ix_sysvar,
-            &message,
-            &signature,
-        )?;
+    /// Switch to the proposed signature scheme once its time-lock has
+    /// elapsed (ADMIN ONLY)
+    ///
+    /// Switching to `Ed25519` clears `mxe_eth_address`.
+    pub fn finalize_signature_scheme(ctx: Context<FinalizeMxeRotation>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let now = Clock::get()?.unix_timestamp;
 
-        // 6️⃣ Validate payout doesn't exceed vault balance
+        let scheme = config.pending_signature_scheme
+            .ok_or(ErrorCode::NoPendingRotation)?;
         require!(
-            payout <= ctx.accounts.vault.amount,
-            ErrorCode::InsufficientVaultBalance
+            now >= config.scheme_unlock_time,
+            ErrorCode::RotationTimeLockActive
         );
 
-        // ============================================================================
-        // ALL VERIFICATIONS PASSED - EXECUTE PAYOUT
-        // ============================================================================
+        let old_scheme = config.signature_scheme;
+        config.signature_scheme = scheme;
+        config.mxe_eth_address = match scheme {
+            SignatureScheme::Secp256k1 => config.pending_mxe_eth_address,
+            SignatureScheme::Ed25519 => [0u8; 20],
+        };
+        config.pending_signature_scheme = None;
+        config.pending_mxe_eth_address = [0u8; 20];
 
-        // Transfer verified payout from vault to user
-        if payout > 0 {
-            let market_key = market.key();
-            let seeds = &[
-                b"vault",
-                market_key.as_ref(),
-                &[market.vault_bump],
-            ];
-            let signer = &[&seeds[..]];
-
-            let transfer_ctx = CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.vault.to_account_info(),
-                    to: ctx.accounts.user_token_account.to_account_info(),
-                    authority: ctx.accounts.vault.to_account_info(),
-                },
-                signer,
-            );
-            token::transfer(transfer_ctx, payout)?;
-        }
+        emit!(SignatureSchemeUpdatedEvent {
+            old_scheme,
+            new_scheme: scheme,
+            mxe_eth_address: config.mxe_eth_address,
+            timestamp: now,
+        });
 
-        // Mark as claimed and record nonce (prevent replay)
-        position.claimed = true;
-        position.nonce_used = nonce;
+        log_outcome!(
+            "finalize_signature_scheme",
+            old = old_scheme,
+            new = scheme,
+        );
 
-        emit!(ClaimEvent {
-            market: market.key(),
-            user: ctx.accounts.user.key(),
-            amount: payout,
-            nonce,
-            timestamp: Clock::get()?.unix_timestamp,
+        Ok(())
+    }
+
+    /// Propose the Groth16 verifying key for `claim_with_zk_proof`
+    /// (ADMIN ONLY)
+    ///
+    /// Time-locked like an MXE rotation, including the first key: a key
+    /// decides payouts just as the MXE signer set does.
+    pub fn propose_zk_verifying_key(
+        ctx: Context<ProposeZkVerifyingKey>,
+        verifying_key: Groth16VerifyingKey,
+    ) -> Result<()> {
+        let zk_key = &mut ctx.accounts.zk_verifying_key;
+        let now = Clock::get()?.unix_timestamp;
+        zk_key.pending_verifying_key = Some(verifying_key);
+        zk_key.unlock_time = now
+            .checked_add(MXE_ROTATION_DELAY_SECS)
+            .ok_or(ErrorCode::Overflow)?;
+        zk_key.bump = ctx.bumps.zk_verifying_key;
+
+        emit!(ZkVerifyingKeyProposedEvent {
+            unlock_time: zk_key.unlock_time,
+            timestamp: now,
         });
 
+        log_outcome!(
+            "propose_zk_verifying_key",
+            unlock_time = zk_key.unlock_time,
+        );
+
         Ok(())
     }
-}
 
-// ============================================================================
-// Cryptographic Verification Helpers
-// ============================================================================
+    /// Activate the proposed Groth16 verifying key once its time-lock has
+    /// elapsed (ADMIN ONLY)
+    pub fn finalize_zk_verifying_key(ctx: Context<FinalizeZkVerifyingKey>) -> Result<()> {
+        let zk_key = &mut ctx.accounts.zk_verifying_key;
+        let now = Clock::get()?.unix_timestamp;
 
-/// Construct the message that MXE signs
-/// 
-/// Message Format: keccak256(market || user || payout || nonce)
-/// 
+        let verifying_key = zk_key.pending_verifying_key
+            .take()
+            .ok_or(ErrorCode::NoPendingRotation)?;
+        require!(now >= zk_key.unlock_time, ErrorCode::RotationTimeLockActive);
+        zk_key.verifying_key = Some(verifying_key);
+
+        emit!(ZkVerifyingKeyUpdatedEvent { timestamp: now });
+
+        log_outcome!("finalize_zk_verifying_key", timestamp = now);
+
+        Ok(())
+    }
+
+    /// Set the stake a resolver must hold to resolve markets (ADMIN ONLY)
+    ///
+    /// 0 disables the requirement.
+    pub fn set_min_resolver_stake(
+        ctx: Context<SetMinResolverStake>,
+        min_resolver_stake: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require_admin_timelock_disabled(config)?;
+        apply_admin_action(config, AdminAction::SetMinResolverStake { min_resolver_stake })
+    }
+
+    /// Nominate a new protocol admin (ADMIN ONLY)
+    ///
+    /// Step one of two: nothing changes until `new_admin` calls
+    /// `accept_admin_transfer`, so a mistyped key cannot lock the
+    /// protocol. Proposing again replaces the pending nominee, and
+    /// `cancel_admin_transfer` withdraws it.
+    pub fn propose_admin_transfer(
+        ctx: Context<ProposeAdminTransfer>,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.pending_admin = Some(new_admin);
+
+        emit!(AdminTransferProposedEvent {
+            admin: config.admin,
+            pending_admin: new_admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "propose_admin_transfer",
+            admin = config.admin,
+            pending_admin = new_admin,
+        );
+
+        Ok(())
+    }
+
+    /// Attach an SPL Governance realm (ADMIN ONLY)
+    ///
+    /// Proposals of the realm's governance over the Config account can
+    /// then run `set_fee_bps`, `propose_mxe_rotation` and
+    /// `emergency_pause_protocol` alongside the admin. Attaching another
+    /// realm replaces it. Governance bypasses the admin timelock, so while
+    /// it is on the realm can only be changed through
+    /// `queue_admin_action`.
+    pub fn set_governance(ctx: Context<SetGovernance>, realm: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require_admin_timelock_disabled(config)?;
+        apply_admin_action(config, AdminAction::SetGovernance { realm })
+    }
+
+    /// Withdraw the pending admin nomination (ADMIN ONLY)
+    pub fn cancel_admin_transfer(ctx: Context<CancelAdminTransfer>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let cancelled_admin = config.pending_admin
+            .take()
+            .ok_or(ErrorCode::NoPendingAdminTransfer)?;
+
+        emit!(AdminTransferCancelledEvent {
+            admin: config.admin,
+            cancelled_admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "cancel_admin_transfer",
+            admin = config.admin,
+            cancelled_admin = cancelled_admin,
+        );
+
+        Ok(())
+    }
+
+    /// Take over as protocol admin (signed by the pending admin)
+    pub fn accept_admin_transfer(ctx: Context<AcceptAdminTransfer>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let pending_admin = config.pending_admin
+            .ok_or(ErrorCode::NoPendingAdminTransfer)?;
+        require_keys_eq!(
+            ctx.accounts.new_admin.key(),
+            pending_admin,
+            ErrorCode::NotPendingAdmin
+        );
+
+        let previous_admin = config.admin;
+        config.admin = pending_admin;
+        config.pending_admin = None;
+
+        emit!(AdminTransferAcceptedEvent {
+            previous_admin,
+            new_admin: pending_admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "accept_admin_transfer",
+            previous_admin = previous_admin,
+            new_admin = pending_admin,
+        );
+
+        Ok(())
+    }
+
+    /// Change the lamports charged to create a market (ADMIN ONLY)
+    ///
+    /// The fee deters market spam; 0 disables it.
+    pub fn set_market_creation_fee(
+        ctx: Context<SetMarketCreationFee>,
+        market_creation_fee: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require_admin_timelock_disabled(config)?;
+        apply_admin_action(config, AdminAction::SetMarketCreationFee { market_creation_fee })
+    }
+
+    /// Halt betting and market creation protocol-wide (ADMIN or GOVERNANCE)
+    ///
+    /// For emergencies such as a discovered vulnerability. Claims keep
+    /// working so users can exit.
+    pub fn emergency_pause_protocol(ctx: Context<GovernedConfig>) -> Result<()> {
+        require_admin_or_governance(
+            &ctx.accounts.config,
+            &ctx.accounts.admin.key(),
+            ctx.remaining_accounts,
+        )?;
+        let config = &mut ctx.accounts.config;
+        require!(!config.protocol_paused, ErrorCode::ProtocolPaused);
+
+        config.protocol_paused = true;
+
+        emit!(ProtocolPausedEvent {
+            admin: ctx.accounts.admin.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!("emergency_pause_protocol", admin = ctx.accounts.admin.key());
+
+        Ok(())
+    }
+
+    /// Lift a protocol-wide pause (ADMIN ONLY)
+    pub fn resume_protocol(ctx: Context<ResumeProtocol>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(config.protocol_paused, ErrorCode::ProtocolNotPaused);
+
+        config.protocol_paused = false;
+
+        emit!(ProtocolResumedEvent {
+            admin: ctx.accounts.admin.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!("resume_protocol", admin = ctx.accounts.admin.key());
+
+        Ok(())
+    }
+
+    /// Change the lamports a market authority bonds at creation (ADMIN ONLY)
+    ///
+    /// Applies to markets created afterwards; 0 disables the bond.
+    pub fn set_resolution_bond_amount(
+        ctx: Context<SetResolutionBondAmount>,
+        resolution_bond_amount: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require_admin_timelock_disabled(config)?;
+        apply_admin_action(config, AdminAction::SetResolutionBondAmount { resolution_bond_amount })
+    }
+
+    /// Change the lamports a creator stakes at market creation (ADMIN ONLY)
+    ///
+    /// Applies to markets created afterwards; 0 disables the stake.
+    pub fn set_required_creator_stake(
+        ctx: Context<SetResolutionBondAmount>,
+        required_creator_stake: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require_admin_timelock_disabled(config)?;
+        apply_admin_action(config, AdminAction::SetRequiredCreatorStake { required_creator_stake })
+    }
+
+    /// Change how long after expiry markets become force-resolvable
+    /// (ADMIN ONLY)
+    pub fn set_force_resolve_delay(
+        ctx: Context<SetForceResolveDelay>,
+        force_resolve_delay: i64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require_admin_timelock_disabled(config)?;
+        apply_admin_action(config, AdminAction::SetForceResolveDelay { force_resolve_delay })
+    }
+
+    /// Change how long after expiry payouts stay claimable (ADMIN ONLY)
+    ///
+    /// Applies to markets created afterwards.
+    pub fn set_unclaimed_grace_period(
+        ctx: Context<SetUnclaimedGracePeriod>,
+        unclaimed_grace_period: i64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require_admin_timelock_disabled(config)?;
+        apply_admin_action(config, AdminAction::SetUnclaimedGracePeriod { unclaimed_grace_period })
+    }
+
+    /// Turn on the admin timelock (ADMIN ONLY)
+    ///
+    /// While `Config.admin_timelock_secs` is non-zero, the Config setters
+    /// above fail and their changes go through `queue_admin_action` and
+    /// `execute_admin_action` instead, including changes to the delay
+    /// itself. The emergency pause stays instant; MXE rotations and
+    /// signature scheme changes keep their own time-lock.
+    pub fn set_admin_timelock(
+        ctx: Context<SetAdminTimelock>,
+        admin_timelock_secs: i64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require_admin_timelock_disabled(config)?;
+        apply_admin_action(config, AdminAction::SetAdminTimelock { admin_timelock_secs })
+    }
+
+    /// Queue a time-locked Config change (ADMIN ONLY)
+    ///
+    /// Records keccak256 of the serialized action; `execute_admin_action`
+    /// may apply it once `Config.admin_timelock_secs` has passed. Queueing
+    /// again replaces the pending action.
+    pub fn queue_admin_action(
+        ctx: Context<QueueAdminAction>,
+        action: AdminAction,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(config.admin_timelock_secs > 0, ErrorCode::AdminTimelockDisabled);
+        action.validate()?;
+
+        let action_hash = action.hash()?;
+        let execute_after = Clock::get()?.unix_timestamp
+            .checked_add(config.admin_timelock_secs)
+            .ok_or(ErrorCode::Overflow)?;
+        config.queued_admin_action = Some(action_hash);
+        config.admin_action_execute_after = execute_after;
+
+        emit!(AdminActionQueuedEvent {
+            action,
+            action_hash,
+            execute_after,
+        });
+
+        log_outcome!(
+            "queue_admin_action",
+            action = action,
+            execute_after = execute_after,
+        );
+
+        Ok(())
+    }
+
+    /// Apply the queued Config change once its delay has passed (ADMIN ONLY)
+    ///
+    /// `action` must be the one passed to `queue_admin_action`.
+    pub fn execute_admin_action(
+        ctx: Context<QueueAdminAction>,
+        action: AdminAction,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let action_hash = action.hash()?;
+        require!(
+            config.queued_admin_action == Some(action_hash),
+            ErrorCode::AdminActionNotQueued
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= config.admin_action_execute_after,
+            ErrorCode::AdminTimelockActive
+        );
+
+        config.queued_admin_action = None;
+        config.admin_action_execute_after = 0;
+
+        emit!(AdminActionExecutedEvent { action_hash });
+
+        apply_admin_action(config, action)
+    }
+
+    /// Drop the queued Config change (ADMIN ONLY)
+    pub fn cancel_admin_action(ctx: Context<QueueAdminAction>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let action_hash = config.queued_admin_action
+            .take()
+            .ok_or(ErrorCode::AdminActionNotQueued)?;
+        config.admin_action_execute_after = 0;
+
+        emit!(AdminActionCancelledEvent { action_hash });
+
+        log_outcome!("cancel_admin_action", action_hash = action_hash);
+
+        Ok(())
+    }
+
+    /// Allow markets to be created against `mint` (ADMIN ONLY)
+    ///
+    /// Payout math trusts the mint's decimals, so only vetted mints (USDC,
+    /// wrapped SOL, ...) should be added. Adding a listed mint is a no-op.
+    pub fn allow_mint(ctx: Context<AllowMint>) -> Result<()> {
+        let mint = ctx.accounts.mint.key();
+        let config = &mut ctx.accounts.config;
+        if config.allowed_mints.contains(&mint) {
+            return Ok(());
+        }
+        require!(
+            config.allowed_mints.len() < MAX_ALLOWED_MINTS,
+            ErrorCode::TooManyAllowedMints
+        );
+        config.allowed_mints.push(mint);
+
+        emit!(AllowedMintAddedEvent {
+            mint,
+            decimals: ctx.accounts.mint.decimals,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!("allow_mint", mint = mint, decimals = ctx.accounts.mint.decimals);
+
+        Ok(())
+    }
+
+    /// Stop new markets from using `mint` (ADMIN ONLY)
+    ///
+    /// Existing markets on the mint are unaffected.
+    pub fn disallow_mint(ctx: Context<DisallowMint>, mint: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let index = config.allowed_mints.iter()
+            .position(|allowed| *allowed == mint)
+            .ok_or(ErrorCode::UnsupportedMint)?;
+        config.allowed_mints.remove(index);
+
+        emit!(AllowedMintRemovedEvent {
+            mint,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!("disallow_mint", mint = mint);
+
+        Ok(())
+    }
+
+    /// Set a user's betting caps, e.g. per KYC tier (ADMIN ONLY)
+    ///
+    /// Users without a limits account bet without caps. Updating keeps the
+    /// volume already counted for the current day.
+    pub fn set_user_limits(
+        ctx: Context<SetUserLimits>,
+        user: Pubkey,
+        limits: UserLimits,
+    ) -> Result<()> {
+        let user_limits = &mut ctx.accounts.user_limits;
+        user_limits.user = user;
+        user_limits.lifetime_volume_cap = limits.lifetime_volume_cap;
+        user_limits.daily_volume_cap = limits.daily_volume_cap;
+        user_limits.bump = ctx.bumps.user_limits;
+
+        emit!(UserLimitsSetEvent {
+            user,
+            lifetime_volume_cap: limits.lifetime_volume_cap,
+            daily_volume_cap: limits.daily_volume_cap,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "set_user_limits",
+            user = user,
+            lifetime_cap = limits.lifetime_volume_cap,
+            daily_cap = limits.daily_volume_cap,
+        );
+
+        Ok(())
+    }
+
+    /// Allow `address` to bet on a market (ADMIN ONLY)
+    ///
+    /// The first entry creates the market's whitelist and restricts
+    /// betting to listed addresses from then on.
+    pub fn add_to_whitelist(ctx: Context<AddToWhitelist>, address: Pubkey) -> Result<()> {
+        let whitelist = &mut ctx.accounts.whitelist;
+        require!(
+            !whitelist.entries.contains(&address),
+            ErrorCode::AlreadyWhitelisted
+        );
+        require!(
+            whitelist.entries.len() < Whitelist::MAX_ENTRIES,
+            ErrorCode::WhitelistFull
+        );
+        whitelist.market = ctx.accounts.market.key();
+        whitelist.entries.push(address);
+        whitelist.bump = ctx.bumps.whitelist;
+        ctx.accounts.market.whitelist_enabled = true;
+
+        emit!(WhitelistUpdatedEvent {
+            market: whitelist.market,
+            address,
+            added: true,
+        });
+
+        log_outcome!(
+            "add_to_whitelist",
+            market = whitelist.market,
+            address = address,
+            entries = whitelist.entries.len(),
+        );
+
+        Ok(())
+    }
+
+    /// Stop `address` from placing new bets on a market (ADMIN ONLY)
+    ///
+    /// Existing positions are unaffected and can still be claimed.
+    pub fn remove_from_whitelist(
+        ctx: Context<RemoveFromWhitelist>,
+        address: Pubkey,
+    ) -> Result<()> {
+        let whitelist = &mut ctx.accounts.whitelist;
+        let index = whitelist.entries
+            .iter()
+            .position(|entry| *entry == address)
+            .ok_or(ErrorCode::UserNotWhitelisted)?;
+        whitelist.entries.swap_remove(index);
+
+        emit!(WhitelistUpdatedEvent {
+            market: whitelist.market,
+            address,
+            added: false,
+        });
+
+        log_outcome!(
+            "remove_from_whitelist",
+            market = whitelist.market,
+            address = address,
+            entries = whitelist.entries.len(),
+        );
+
+        Ok(())
+    }
+
+    /// Allow `creator` to create markets (ADMIN ONLY)
+    ///
+    /// The first entry creates the ["creator_allowlist"] account. The admin
+    /// can always create markets without being listed.
+    pub fn add_creator(ctx: Context<AddCreator>, creator: Pubkey) -> Result<()> {
+        let allowlist = &mut ctx.accounts.creator_allowlist;
+        require!(
+            !allowlist.creators.contains(&creator),
+            ErrorCode::CreatorAlreadyAllowed
+        );
+        require!(
+            allowlist.creators.len() < CreatorAllowlist::MAX_CREATORS,
+            ErrorCode::CreatorAllowlistFull
+        );
+        allowlist.creators.push(creator);
+        allowlist.bump = ctx.bumps.creator_allowlist;
+
+        emit!(CreatorAllowlistUpdatedEvent { creator, added: true });
+
+        log_outcome!(
+            "add_creator",
+            creator = creator,
+            creators = allowlist.creators.len(),
+        );
+
+        Ok(())
+    }
+
+    /// Stop `creator` from creating new markets (ADMIN ONLY)
+    ///
+    /// Markets they already created are unaffected.
+    pub fn remove_creator(ctx: Context<RemoveCreator>, creator: Pubkey) -> Result<()> {
+        let allowlist = &mut ctx.accounts.creator_allowlist;
+        let index = allowlist.creators
+            .iter()
+            .position(|entry| *entry == creator)
+            .ok_or(ErrorCode::CreatorNotAllowed)?;
+        allowlist.creators.swap_remove(index);
+
+        emit!(CreatorAllowlistUpdatedEvent { creator, added: false });
+
+        log_outcome!(
+            "remove_creator",
+            creator = creator,
+            creators = allowlist.creators.len(),
+        );
+
+        Ok(())
+    }
+
+    /// Withdraw collected market creation fees (ADMIN ONLY)
+    ///
+    /// The vault may be left empty or above its rent-exempt minimum.
+    pub fn withdraw_creation_fees(ctx: Context<WithdrawCreationFees>, amount: u64) -> Result<()> {
+        let creation_fee_vault = &ctx.accounts.creation_fee_vault;
+        require!(
+            amount <= creation_fee_vault.lamports(),
+            ErrorCode::InsufficientFeeBalance
+        );
+
+        transfer_from_sol_vault(
+            creation_fee_vault,
+            ctx.accounts.destination.to_account_info(),
+            &ctx.accounts.system_program,
+            &[b"fee_vault", &[ctx.bumps.creation_fee_vault]],
+            amount,
+        )?;
+
+        emit!(CreationFeesWithdrawnEvent {
+            destination: ctx.accounts.destination.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "withdraw_creation_fees",
+            destination = ctx.accounts.destination.key(),
+            amount = amount,
+        );
+
+        Ok(())
+    }
+
+    /// Create a new prediction market (ADMIN ONLY)
+    ///
+    /// SOL markets still allocate the token vault today; their funds live in
+    /// the ["sol_vault", market] PDA instead.
+    pub fn create_market(
+        ctx: Context<CreateMarket>,
+        question: String,
+        expiry_timestamp: i64,
+        metadata_uri: String,
+        pyth_price_feed: Option<Pubkey>,
+        oracle: Option<Pubkey>,
+        resolution_price_threshold: Option<i64>,
+        resolution_direction: ResolutionDirection,
+        min_bet: u64,
+        max_bet: u64,
+        max_pool: u64,
+        is_token22: bool,
+        num_outcomes: u8,
+        currency: MarketCurrency,
+        creator_fee_bps: u16,
+        outcome_labels: Vec<String>,
+        resolution_commitment: Option<[u8; 32]>,
+        category: MarketCategory,
+        series_id: Option<Pubkey>,
+        betting_close_timestamp: Option<i64>,
+    ) -> Result<()> {
+        // ⚠️ CREATOR CHECK: the protocol admin or an allowlisted creator
+        let creator = ctx.accounts.authority.key();
+        let allowlisted = ctx.accounts.creator_allowlist
+            .as_ref()
+            .map_or(false, |allowlist| allowlist.creators.contains(&creator));
+        require!(
+            creator == ctx.accounts.config.admin || allowlisted,
+            ErrorCode::Unauthorized
+        );
+        require!(!ctx.accounts.config.protocol_paused, ErrorCode::ProtocolPaused);
+
+        require!(question.len() <= 280, ErrorCode::QuestionTooLong);
+        require!(
+            metadata_uri.len() <= Market::MAX_METADATA_URI_LEN,
+            ErrorCode::MetadataUriTooLong
+        );
+        require!(
+            expiry_timestamp > Clock::get()?.unix_timestamp,
+            ErrorCode::ExpiryInPast
+        );
+        // Betting closes at expiry unless an earlier cutoff is given
+        let betting_close_timestamp = betting_close_timestamp.unwrap_or(expiry_timestamp);
+        require!(
+            betting_close_timestamp <= expiry_timestamp,
+            ErrorCode::InvalidBettingClose
+        );
+        require!(
+            max_bet == 0 || max_bet >= min_bet,
+            ErrorCode::InvalidBetLimits
+        );
+        // Pyth and Switchboard read the shared threshold at different scales
+        require!(
+            pyth_price_feed.is_none() || oracle.is_none(),
+            ErrorCode::MultipleOraclesConfigured
+        );
+        // A committed result is revealed by the resolver, not read from a feed
+        require!(
+            resolution_commitment.is_none() || (pyth_price_feed.is_none() && oracle.is_none()),
+            ErrorCode::CommitmentWithOracle
+        );
+        require!(
+            is_token22 == (ctx.accounts.token_program.key() == token_2022::ID),
+            ErrorCode::Token22NotSupported
+        );
+        require!(
+            ctx.accounts.config.allowed_mints.contains(&ctx.accounts.usdc_mint.key()),
+            ErrorCode::UnsupportedMint
+        );
+        require!(
+            (2..=Market::MAX_OUTCOMES).contains(&num_outcomes),
+            ErrorCode::InvalidOutcomeCount
+        );
+        require!(
+            creator_fee_bps <= MAX_CREATOR_FEE_BPS,
+            ErrorCode::FeeTooHigh
+        );
+        // Labels are optional, but when given there must be one per outcome
+        require!(
+            outcome_labels.is_empty() || outcome_labels.len() == num_outcomes as usize,
+            ErrorCode::OutcomeLabelCountMismatch
+        );
+        require!(
+            outcome_labels.iter().all(|label| label.len() <= MarketMetadata::MAX_LABEL_LEN),
+            ErrorCode::OutcomeLabelTooLong
+        );
+        // Vaults are created together here, or together later by init_vault
+        require!(
+            ctx.accounts.vault.is_some() == ctx.accounts.fee_vault.is_some(),
+            ErrorCode::IncompleteVaultAccounts
+        );
+        match (series_id, ctx.accounts.series.as_ref()) {
+            (None, None) => {}
+            (Some(series_id), Some(series)) if series.series_id == series_id => {
+                require!(
+                    series.creator == ctx.accounts.authority.key(),
+                    ErrorCode::Unauthorized
+                );
+                require!(
+                    series.markets.len() < MarketSeries::MAX_MARKETS,
+                    ErrorCode::SeriesFull
+                );
+            }
+            _ => return err!(ErrorCode::SeriesNotFound),
+        }
+
+        // Anti-spam creation fee, collected before anything is initialized
+        let creation_fee = ctx.accounts.config.market_creation_fee;
+        if creation_fee > 0 {
+            require!(
+                ctx.accounts.authority.lamports() >= creation_fee,
+                ErrorCode::InsufficientCreationFee
+            );
+            transfer_to_sol_vault(
+                ctx.accounts.authority.to_account_info(),
+                &ctx.accounts.creation_fee_vault,
+                &ctx.accounts.system_program,
+                creation_fee,
+            )?;
+
+            emit!(CreationFeeCollectedEvent {
+                authority: ctx.accounts.authority.key(),
+                market: ctx.accounts.market.key(),
+                fee: creation_fee,
+            });
+        }
+
+        // Resolution bond, locked on top of the bond account's rent until
+        // the market's dispute window has passed
+        let bond_amount = ctx.accounts.config.resolution_bond_amount;
+        if bond_amount > 0 {
+            require!(
+                ctx.accounts.authority.lamports() >= bond_amount,
+                ErrorCode::InsufficientBond
+            );
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: ctx.accounts.resolution_bond.to_account_info(),
+                    },
+                ),
+                bond_amount,
+            )?;
+        }
+        let resolution_bond = &mut ctx.accounts.resolution_bond;
+        resolution_bond.market = ctx.accounts.market.key();
+        resolution_bond.authority = ctx.accounts.authority.key();
+        resolution_bond.amount = bond_amount;
+        resolution_bond.open_disputes = 0;
+        resolution_bond.bump = ctx.bumps.resolution_bond;
+
+        // Creator stake, a quality signal locked until the dispute window
+        // has passed
+        let stake_amount = ctx.accounts.config.required_creator_stake;
+        if stake_amount > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: ctx.accounts.creator_stake.to_account_info(),
+                    },
+                ),
+                stake_amount,
+            )?;
+        }
+        let creator_stake = &mut ctx.accounts.creator_stake;
+        creator_stake.market = ctx.accounts.market.key();
+        creator_stake.authority = ctx.accounts.authority.key();
+        creator_stake.amount = stake_amount;
+        creator_stake.reclaimed = false;
+        creator_stake.slashed = false;
+        creator_stake.bump = ctx.bumps.creator_stake;
+
+        let market = &mut ctx.accounts.market;
+        market.authority = ctx.accounts.authority.key();
+        market.pending_authority = None;
+        market.resolver = ctx.accounts.authority.key();
+        market.question = question;
+        market.question_v2 = None;
+        market.creator_stake = stake_amount;
+        market.metadata_uri = metadata_uri;
+        market.unclaimed_deadline = expiry_timestamp
+            .checked_add(ctx.accounts.config.unclaimed_grace_period)
+            .ok_or(ErrorCode::Overflow)?;
+        market.pyth_price_feed = pyth_price_feed;
+        market.oracle = oracle;
+        market.switchboard_feed = None;
+        market.resolution_price_threshold = resolution_price_threshold;
+        market.resolution_direction = resolution_direction;
+        market.expiry_timestamp = expiry_timestamp;
+        market.betting_close_timestamp = betting_close_timestamp;
+        market.total_pool = 0;
+        market.min_bet = min_bet;
+        market.max_bet = max_bet;
+        market.max_pool = max_pool;
+        market.creator_fee_bps = creator_fee_bps;
+        market.resolution_commitment = resolution_commitment;
+        market.resolved = false;
+        market.paused = false;
+        market.num_outcomes = num_outcomes;
+        market.result = MarketResult::None;
+        market.usdc_mint = ctx.accounts.usdc_mint.key();
+        market.is_token22 = is_token22;
+        market.currency = currency;
+        market.category = category;
+        market.series_id = series_id;
+        market.parent_market = None;
+        market.parent_required_outcome = None;
+        market.registry_page = 0;
+        market.bump = ctx.bumps.market;
+        let market_key = market.key();
+        if let Some(vault) = ctx.accounts.vault.as_ref() {
+            let (_, vault_bump) = Pubkey::find_program_address(
+                &[b"vault", market_key.as_ref()],
+                ctx.program_id,
+            );
+            let (_, fee_vault_bump) = Pubkey::find_program_address(
+                &[b"fee_vault", market_key.as_ref()],
+                ctx.program_id,
+            );
+            market.vault = vault.key();
+            market.vault_bump = vault_bump;
+            market.fee_vault_bump = fee_vault_bump;
+            market.vault_initialized = true;
+        }
+        if currency == MarketCurrency::Sol {
+            let (_, sol_vault_bump) = Pubkey::find_program_address(
+                &[b"sol_vault", market_key.as_ref()],
+                ctx.program_id,
+            );
+            let (_, sol_fee_vault_bump) = Pubkey::find_program_address(
+                &[b"sol_fee_vault", market_key.as_ref()],
+                ctx.program_id,
+            );
+            market.sol_vault_bump = sol_vault_bump;
+            market.sol_fee_vault_bump = sol_fee_vault_bump;
+        }
+
+        let stats = &mut ctx.accounts.stats;
+        stats.total_markets = stats.total_markets
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        stats.active_markets = stats.active_markets
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let market_metadata = &mut ctx.accounts.market_metadata;
+        market_metadata.market = market.key();
+        market_metadata.outcome_labels = outcome_labels;
+        market_metadata.bump = ctx.bumps.market_metadata;
+
+        // Index the market under its category, growing the index by one
+        // slot when no freed slot is left
+        let category_index = &mut ctx.accounts.category_index;
+        require!(
+            category_index.markets.len() < CategoryIndex::MAX_MARKETS,
+            ErrorCode::CategoryIndexFull
+        );
+        category_index.category = category;
+        category_index.bump = ctx.bumps.category_index;
+        grow_account(
+            &category_index.to_account_info(),
+            CategoryIndex::space(category_index.markets.len() + 1),
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+        category_index.markets.push(market_key);
+
+        // Add to the first registry page with a free slot, opening a new
+        // page when they are all full
+        let registry = &mut ctx.accounts.registry;
+        let page_index = registry.current_page();
+        require!(
+            (page_index as usize) < MarketRegistry::MAX_PAGES,
+            ErrorCode::RegistryFull
+        );
+        registry.bump = ctx.bumps.registry;
+        if page_index as usize == registry.page_lens.len() {
+            registry.page_lens.push(0);
+        }
+        let page_len = &mut registry.page_lens[page_index as usize];
+        *page_len = page_len.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        registry.total_markets = registry.total_markets
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        let registry_page = &mut ctx.accounts.registry_page;
+        registry_page.page = page_index;
+        registry_page.bump = ctx.bumps.registry_page;
+        grow_account(
+            &registry_page.to_account_info(),
+            MarketRegistryPage::space(registry_page.markets.len() + 1),
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+        registry_page.markets.push(market_key);
+        market.registry_page = page_index;
+
+        emit!(MarketCategorizedEvent {
+            market: market_key,
+            category,
+        });
+
+        if let Some(series) = ctx.accounts.series.as_mut() {
+            market.series_index = series.market_count;
+            series.markets.push(market_key);
+            series.market_count = series.market_count
+                .checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+
+            emit!(MarketAddedToSeriesEvent {
+                series: series.key(),
+                market: market_key,
+                index: market.series_index,
+            });
+        }
+
+        emit!(MarketCreatedEvent {
+            market: market.key(),
+            authority: market.authority,
+            question: market.question.clone(),
+            metadata_uri: market.metadata_uri.clone(),
+            outcome_labels: market_metadata.outcome_labels.clone(),
+            expiry_timestamp: market.expiry_timestamp,
+            betting_close_timestamp: market.betting_close_timestamp,
+        });
+
+        log_outcome!(
+            "create_market",
+            market = market.key(),
+            authority = market.authority,
+            expiry = market.expiry_timestamp,
+            vault_initialized = market.vault_initialized,
+        );
+
+        Ok(())
+    }
+
+    /// Attach structured resolution terms to a new market (authority only)
+    ///
+    /// Sent alongside `create_market`, before any bet: the MarketV2 account
+    /// (seeds ["market_v2", market]) holds `criteria` and `expiry` so
+    /// keepers can resolve the market without parsing its question.
+    /// Betting, custody and claims stay on the Market. Price criteria need
+    /// the market's Pyth feed and replace its legacy threshold; their
+    /// `at_time` falls between betting close and expiry, less than
+    /// ORACLE_RESOLUTION_WINDOW_SECS before expiry. A custom oracle must be
+    /// the market's Switchboard oracle.
+    pub fn create_market_v2(
+        ctx: Context<CreateMarketV2>,
+        criteria: ResolutionCriteria,
+        expiry: i64,
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.total_positions == 0, ErrorCode::MarketHasPositions);
+        require!(!market.resolved, ErrorCode::AlreadyResolved);
+        require!(
+            expiry == market.expiry_timestamp,
+            ErrorCode::InvalidResolutionCriteria
+        );
+
+        let terms_valid = match criteria {
+            ResolutionCriteria::PriceAbove { threshold_ui_amount, at_time, .. }
+            | ResolutionCriteria::PriceBelow { threshold_ui_amount, at_time, .. } => {
+                market.pyth_price_feed.is_some()
+                    && market.resolution_price_threshold.is_none()
+                    && threshold_ui_amount.is_finite()
+                    && threshold_ui_amount > 0.0
+                    && at_time >= market.betting_close_timestamp
+                    && at_time <= expiry
+                    && expiry - at_time < ORACLE_RESOLUTION_WINDOW_SECS
+            }
+            ResolutionCriteria::CustomOracle { oracle } => market.oracle == Some(oracle),
+            ResolutionCriteria::ManualAdmin => true,
+        };
+        require!(terms_valid, ErrorCode::InvalidResolutionCriteria);
+
+        let market_v2 = &mut ctx.accounts.market_v2;
+        market_v2.market = market.key();
+        market_v2.resolution_criteria = criteria;
+        market_v2.expiry_timestamp = expiry;
+        market_v2.bump = ctx.bumps.market_v2;
+
+        emit!(ResolutionCriteriaSetEvent {
+            market: market.key(),
+            criteria,
+            expiry_timestamp: expiry,
+        });
+
+        log_outcome!(
+            "create_market_v2",
+            market = market.key(),
+            expiry = expiry,
+        );
+
+        Ok(())
+    }
+
+    /// Start a series linking recurring markets (e.g. weekly BTC price)
+    ///
+    /// `series_id` is any unused key naming the series; only the series
+    /// creator can add markets to it via `create_market`.
+    pub fn create_market_series(ctx: Context<CreateMarketSeries>, title: String) -> Result<()> {
+        require!(title.len() <= MarketSeries::MAX_TITLE_LEN, ErrorCode::SeriesTitleTooLong);
+
+        let series = &mut ctx.accounts.series;
+        series.creator = ctx.accounts.creator.key();
+        series.series_id = ctx.accounts.series_id.key();
+        series.title = title;
+        series.market_count = 0;
+        series.markets = Vec::new();
+        series.bump = ctx.bumps.series;
+
+        emit!(MarketSeriesCreatedEvent {
+            series: series.key(),
+            series_id: series.series_id,
+            creator: series.creator,
+            title: series.title.clone(),
+        });
+
+        log_outcome!(
+            "create_market_series",
+            series = series.key(),
+            series_id = series.series_id,
+            creator = series.creator,
+        );
+
+        Ok(())
+    }
+
+    /// Create the vault and fee vault of a market made without them
+    ///
+    /// Splitting this out of `create_market` keeps each transaction within
+    /// size and compute limits for large market configs. Betting stays
+    /// blocked until it has run.
+    pub fn init_vault(ctx: Context<InitVault>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(!market.vault_initialized, ErrorCode::VaultAlreadyInitialized);
+        require!(
+            market.is_token22 == (ctx.accounts.token_program.key() == token_2022::ID),
+            ErrorCode::Token22NotSupported
+        );
+
+        market.vault = ctx.accounts.vault.key();
+        market.vault_bump = ctx.bumps.vault;
+        market.fee_vault_bump = ctx.bumps.fee_vault;
+        market.vault_initialized = true;
+
+        log_outcome!("init_vault", market = market.key(), vault = market.vault);
+
+        Ok(())
+    }
+
+    /// Place an encrypted bet on a market
+    /// 
+    /// ARCIUM INTEGRATION POINT #1:
+    /// - encrypted_payload contains: { side: "yes"|"no", amount: u64 }
+    /// - This payload is encrypted client-side before sending
+    /// - In PRODUCTION: Arcium MXE will decrypt and store in TEE
+    /// - In DEVNET: Frontend mock client handles this
+    /// 
+    /// TRANSPARENCY WARNING:
+    /// - The 'amount' parameter is VISIBLE onchain
+    /// - Individual bet amounts are PUBLIC in UserPosition accounts
+    /// - Only the 'side' (yes/no) is intended to be confidential
+    /// - total_pool is PUBLIC (sum of all deposits)
+    ///
+    /// CUSTODY:
+    /// - USDC markets pass vault, user_token_account, usdc_mint, token_program
+    /// - SOL markets pass sol_vault and pay in lamports from the user
+    ///
+    /// SLIPPAGE:
+    /// - `min_pool_share_bps` reverts the bet if the position ends up a
+    ///   smaller share of `total_pool` than expected, i.e. the pool grew
+    ///   between quoting and landing. Side pools are only revealed after
+    ///   resolution, so the whole pool is the only figure to check against
+    pub fn place_bet<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlaceBet<'info>>,
+        encrypted_payload: Vec<u8>,
+        amount: u64,
+        min_pool_share_bps: Option<u16>,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            encrypted_payload.len() <= 512,
+            ErrorCode::PayloadTooLarge
+        );
+        require_parent_open(&ctx.accounts.market, ctx.remaining_accounts)?;
+
+        let accounts = &mut *ctx.accounts;
+        let is_new_position = accounts.user_position.amount == 0;
+        if accounts.user_stats.user == Pubkey::default() {
+            accounts.user_stats.user = accounts.user.key();
+            accounts.user_stats.bump = ctx.bumps.user_stats;
+        }
+        let (fee, received) = deposit_bet(
+            BetAccounts {
+                market: &mut accounts.market,
+                config: &accounts.config,
+                stats: &mut accounts.stats,
+                position: &mut accounts.user_position,
+                user_stats: &mut accounts.user_stats,
+                user_limits: accounts.user_limits.as_mut(),
+                whitelist: accounts.whitelist.as_deref(),
+                vault: accounts.vault.as_ref(),
+                sol_vault: accounts.sol_vault.as_ref(),
+                fee_vault: accounts.fee_vault.as_ref(),
+                sol_fee_vault: accounts.sol_fee_vault.as_ref(),
+                insurance_vault: accounts.insurance_vault.as_ref(),
+                user_token_account: accounts.user_token_account.as_ref(),
+                usdc_mint: accounts.usdc_mint.as_ref(),
+                user: &accounts.user,
+                token_program: accounts.token_program.as_ref(),
+                system_program: &accounts.system_program,
+            },
+            amount,
+        )?;
+        if let Some(min_share_bps) = min_pool_share_bps {
+            require_pool_share(
+                accounts.user_position.amount,
+                accounts.market.total_pool,
+                min_share_bps,
+            )?;
+        }
+
+        // Initialize a fresh position; deposit_bet already set its amount
+        let market = &mut ctx.accounts.market;
+        if is_new_position {
+            let position = &mut ctx.accounts.user_position;
+            position.user = ctx.accounts.user.key();
+            position.market = market.key();
+            position.claimed = false;
+            position.bump = ctx.bumps.user_position;
+            market.total_positions = market.total_positions
+                .checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+
+            let user_stats = &mut ctx.accounts.user_stats;
+            user_stats.markets_entered = user_stats.markets_entered
+                .checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        let payload_hash = keccak::hash(&encrypted_payload).to_bytes();
+        let position_total = ctx.accounts.user_position.amount;
+        emit!(BetPlacedEvent {
+            market: market.key(),
+            user: ctx.accounts.user.key(),
+            amount,
+            fee,
+            payload_hash,
+            position_total,
+            total_positions: market.total_positions,
+            total_pool_after: market.total_pool,
+            yes_pool_after: market.yes_pool,
+            no_pool_after: market.no_pool,
+            timestamp: Clock::get()?.unix_timestamp,
+            checksum: bet_event_checksum(
+                &market.key(),
+                &ctx.accounts.user.key(),
+                amount,
+                &payload_hash,
+                position_total,
+                market.total_pool,
+            ),
+        });
+
+        log_outcome!(
+            "place_bet",
+            market = market.key(),
+            user = ctx.accounts.user.key(),
+            amount = amount,
+            fee = fee,
+            received = received,
+            pool = market.total_pool,
+        );
+
+        Ok(())
+    }
+
+    /// Add funds to an existing position on the side already recorded
+    ///
+    /// Same checks, fees and limits as `place_bet`, but no new
+    /// encrypted_payload: the MXE keeps the side from the original bet.
+    pub fn increase_position<'info>(
+        ctx: Context<'_, '_, 'info, 'info, IncreasePosition<'info>>,
+        additional_amount: u64,
+    ) -> Result<()> {
+        require!(additional_amount > 0, ErrorCode::InvalidAmount);
+        require_parent_open(&ctx.accounts.market, ctx.remaining_accounts)?;
+
+        // Positions opened before UserStats existed create it here
+        let accounts = &mut *ctx.accounts;
+        if accounts.user_stats.user == Pubkey::default() {
+            accounts.user_stats.user = accounts.user.key();
+            accounts.user_stats.bump = ctx.bumps.user_stats;
+        }
+        let (fee, received) = deposit_bet(
+            BetAccounts {
+                market: &mut accounts.market,
+                config: &accounts.config,
+                stats: &mut accounts.stats,
+                position: &mut accounts.user_position,
+                user_stats: &mut accounts.user_stats,
+                user_limits: accounts.user_limits.as_mut(),
+                whitelist: accounts.whitelist.as_deref(),
+                vault: accounts.vault.as_ref(),
+                sol_vault: accounts.sol_vault.as_ref(),
+                fee_vault: accounts.fee_vault.as_ref(),
+                sol_fee_vault: accounts.sol_fee_vault.as_ref(),
+                insurance_vault: accounts.insurance_vault.as_ref(),
+                user_token_account: accounts.user_token_account.as_ref(),
+                usdc_mint: accounts.usdc_mint.as_ref(),
+                user: &accounts.user,
+                token_program: accounts.token_program.as_ref(),
+                system_program: &accounts.system_program,
+            },
+            additional_amount,
+        )?;
+
+        let market = &ctx.accounts.market;
+        let new_total = ctx.accounts.user_position.amount;
+        emit!(PositionIncreasedEvent {
+            market: market.key(),
+            user: ctx.accounts.user.key(),
+            additional_amount,
+            new_total,
+        });
+
+        log_outcome!(
+            "increase_position",
+            market = market.key(),
+            user = ctx.accounts.user.key(),
+            amount = additional_amount,
+            fee = fee,
+            received = received,
+            position = new_total,
+        );
+
+        Ok(())
+    }
+
+    /// Bet native SOL on a wrapped-SOL market
+    ///
+    /// The market's mint must be the classic wSOL mint. Lamports go
+    /// straight from the user into the vault (and fee vault), which are
+    /// then synced, so no temporary wSOL account is created and the user
+    /// pays no extra rent. Otherwise identical to `place_bet`, which also
+    /// takes this path when `user_token_account` is omitted.
+    pub fn place_bet_sol<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlaceBet<'info>>,
+        encrypted_payload: Vec<u8>,
+        amount: u64,
+        min_pool_share_bps: Option<u16>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.market.currency == MarketCurrency::Usdc
+                && ctx.accounts.market.usdc_mint == native_mint::ID,
+            ErrorCode::NotWrappedSolMarket
+        );
+        require!(
+            ctx.accounts.user_token_account.is_none(),
+            ErrorCode::NotWrappedSolMarket
+        );
+
+        place_bet(ctx, encrypted_payload, amount, min_pool_share_bps)
+    }
+
+    /// Resolve the market (market.resolver only)
+    ///
+    /// `winning_outcome` is an index below `market.num_outcomes`. For binary
+    /// markets 0 = YES and 1 = NO. Markets with a Pyth feed, `market.oracle`
+    /// or an attached Switchboard feed resolve from their oracle instead.
+    pub fn resolve_market<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ResolveMarket<'info>>,
+        winning_outcome: u8,
+    ) -> Result<()> {
+        require_manual_resolution(&ctx.accounts.market)?;
+
+        let parent = load_parent_market(&ctx.accounts.market, ctx.remaining_accounts)?;
+        let creator_fee = resolve_as_authority(ctx.accounts, parent.as_ref(), winning_outcome)?;
+
+        let market = &ctx.accounts.market;
+        log_outcome!(
+            "resolve_market",
+            market = market.key(),
+            result = market.result,
+            creator_fee = creator_fee,
+            pool = market.total_pool,
+        );
+
+        Ok(())
+    }
+
+    /// Resolve several expired markets in one transaction (market.resolver only)
+    ///
+    /// `remaining_accounts` holds the markets, writable and in the order of
+    /// `resolutions`, at most `MAX_BATCH_RESOLVE` of them. Each one gets the
+    /// same checks as `resolve_market`, and any failure reverts the batch.
+    /// Markets that charge a creator fee, need a commitment reveal, have a
+    /// pending proposal or are conditional must go through `resolve_market`.
+    pub fn batch_resolve_markets<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchResolveMarkets<'info>>,
+        resolutions: Vec<MarketResolution>,
+    ) -> Result<()> {
+        let markets = ctx.remaining_accounts;
+        let resolver = ctx.accounts.authority.key();
+
+        require!(!resolutions.is_empty(), ErrorCode::InvalidBatchAccounts);
+        require!(resolutions.len() <= MAX_BATCH_RESOLVE, ErrorCode::BatchTooLarge);
+        require!(
+            markets.len() == resolutions.len(),
+            ErrorCode::BatchLengthMismatch
+        );
+
+        let min_stake = ctx.accounts.config.min_resolver_stake;
+        if min_stake > 0 {
+            let stake = ctx.accounts.resolver_stake.as_mut()
+                .ok_or(ErrorCode::InsufficientResolverStake)?;
+            require!(stake.amount >= min_stake, ErrorCode::InsufficientResolverStake);
+            stake.last_resolution_at = Clock::get()?.unix_timestamp;
+        }
+
+        for (info, resolution) in markets.iter().zip(resolutions.iter()) {
+            require_keys_eq!(info.key(), resolution.market, ErrorCode::InvalidBatchAccounts);
+            let mut market: Account<'info, Market> = Account::try_from(info)?;
+
+            require_keys_eq!(market.resolver, resolver, ErrorCode::Unauthorized);
+            require_manual_resolution(&market)?;
+            require!(
+                market.parent_market.is_none() && market.creator_fee_bps == 0,
+                ErrorCode::BatchResolveUnsupported
+            );
+
+            let result = MarketResult::from_outcome(resolution.winning_outcome, market.num_outcomes)?;
+            apply_resolution(&mut market, &mut ctx.accounts.stats, result)?;
+            // Persist now so a repeated market fails with AlreadyResolved
+            market.exit(ctx.program_id)?;
+        }
+
+        log_outcome!(
+            "batch_resolve_markets",
+            resolver = resolver,
+            markets = resolutions.len(),
+        );
+
+        Ok(())
+    }
+
+    /// Propose a result, open to challenge for CHALLENGE_WINDOW_SECS
+    /// (market.resolver only)
+    ///
+    /// Nothing is resolved yet: `finalize_resolution` applies the result
+    /// once the window passes unchallenged, so claims stay blocked until
+    /// then.
+    pub fn propose_resolution(
+        ctx: Context<ProposeResolution>,
+        winning_outcome: u8,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+
+        require_manual_resolution(market)?;
+        require!(
+            market.resolution_commitment.is_none(),
+            ErrorCode::ResolutionCommitmentRequired
+        );
+        require!(now >= market.expiry_timestamp, ErrorCode::MarketNotExpired);
+        require!(!market.resolved, ErrorCode::AlreadyResolved);
+        require!(
+            market.proposed_outcome.is_none(),
+            ErrorCode::ResolutionAlreadyProposed
+        );
+        // Validate now so a bad index cannot stall the market for a window
+        MarketResult::from_outcome(winning_outcome, market.num_outcomes)?;
+
+        market.proposed_outcome = Some(winning_outcome);
+        market.challenge_deadline = now
+            .checked_add(CHALLENGE_WINDOW_SECS)
+            .ok_or(ErrorCode::Overflow)?;
+        market.resolution_disputed = false;
+
+        emit!(ResolutionProposedEvent {
+            market: market.key(),
+            winning_outcome,
+            challenge_deadline: market.challenge_deadline,
+        });
+
+        log_outcome!(
+            "propose_resolution",
+            market = market.key(),
+            outcome = winning_outcome,
+            deadline = market.challenge_deadline,
+        );
+
+        Ok(())
+    }
+
+    /// Challenge a proposed result (position holders only)
+    ///
+    /// Freezes finalization until the admin rules with
+    /// `override_resolution`.
+    pub fn dispute_resolution(ctx: Context<DisputeResolution>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            market.proposed_outcome.is_some(),
+            ErrorCode::NoResolutionProposed
+        );
+        require!(now < market.challenge_deadline, ErrorCode::ChallengeWindowClosed);
+        require!(!market.resolution_disputed, ErrorCode::ResolutionDisputed);
+
+        market.resolution_disputed = true;
+
+        emit!(ResolutionChallengedEvent {
+            market: market.key(),
+            disputant: ctx.accounts.disputant.key(),
+            timestamp: now,
+        });
+
+        log_outcome!(
+            "dispute_resolution",
+            market = market.key(),
+            disputant = ctx.accounts.disputant.key(),
+        );
+
+        Ok(())
+    }
+
+    /// Rule on a challenged proposal (ADMIN ONLY)
+    ///
+    /// Replaces the proposed outcome and lifts the dispute; the authority
+    /// can finalize straight away.
+    pub fn override_resolution(
+        ctx: Context<OverrideResolution>,
+        winning_outcome: u8,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(market.resolution_disputed, ErrorCode::ResolutionNotDisputed);
+        MarketResult::from_outcome(winning_outcome, market.num_outcomes)?;
+
+        let proposed_outcome = market.proposed_outcome;
+        market.proposed_outcome = Some(winning_outcome);
+        market.challenge_deadline = now;
+        market.resolution_disputed = false;
+
+        emit!(ResolutionOverriddenEvent {
+            market: market.key(),
+            admin: ctx.accounts.admin.key(),
+            proposed_outcome,
+            winning_outcome,
+            timestamp: now,
+        });
+
+        log_outcome!(
+            "override_resolution",
+            market = market.key(),
+            outcome = winning_outcome,
+        );
+
+        Ok(())
+    }
+
+    /// Apply a proposed result once its challenge window has passed
+    /// (market.resolver only)
+    ///
+    /// Same stake check and creator fee as `resolve_market`.
+    pub fn finalize_resolution<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ResolveMarket<'info>>,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(!market.resolution_disputed, ErrorCode::ResolutionDisputed);
+        require!(
+            Clock::get()?.unix_timestamp >= market.challenge_deadline,
+            ErrorCode::ChallengeWindowActive
+        );
+        // Finalizing consumes the proposal, which apply_resolution requires
+        let winning_outcome = market
+            .proposed_outcome
+            .take()
+            .ok_or(ErrorCode::NoResolutionProposed)?;
+
+        let parent = load_parent_market(&ctx.accounts.market, ctx.remaining_accounts)?;
+        let creator_fee = resolve_as_authority(ctx.accounts, parent.as_ref(), winning_outcome)?;
+
+        let market = &ctx.accounts.market;
+        log_outcome!(
+            "finalize_resolution",
+            market = market.key(),
+            result = market.result,
+            creator_fee = creator_fee,
+            pool = market.total_pool,
+        );
+
+        Ok(())
+    }
+
+    /// Resolve a committed market by revealing its outcome
+    /// (market.resolver only)
+    ///
+    /// The authority committed keccak256(winning_outcome || salt) at
+    /// creation, before seeing any bets; the reveal must hash to that
+    /// commitment.
+    pub fn resolve_with_reveal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ResolveMarket<'info>>,
+        winning_outcome: u8,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        let commitment = ctx.accounts.market.resolution_commitment
+            .ok_or(ErrorCode::NoResolutionCommitment)?;
+        require!(
+            compute_resolution_commitment(winning_outcome, &salt) == commitment,
+            ErrorCode::RevealMismatch
+        );
+        // The reveal settles the commitment, which apply_resolution requires
+        ctx.accounts.market.resolution_commitment = None;
+
+        let parent = load_parent_market(&ctx.accounts.market, ctx.remaining_accounts)?;
+        let creator_fee = resolve_as_authority(ctx.accounts, parent.as_ref(), winning_outcome)?;
+
+        let market = &ctx.accounts.market;
+        log_outcome!(
+            "resolve_with_reveal",
+            market = market.key(),
+            result = market.result,
+            creator_fee = creator_fee,
+            pool = market.total_pool,
+        );
+
+        Ok(())
+    }
+
+    /// Resolve a price market from its Pyth feed (permissionless)
+    ///
+    /// Anyone can call this once the market has expired, until
+    /// ORACLE_RESOLUTION_WINDOW_SECS later. The feed account must match
+    /// `market.pyth_price_feed`, the price must be published inside that
+    /// window and be precise enough, and the outcome is decided by
+    /// comparing it against `resolution_price_threshold` (same exponent as
+    /// the feed) in the configured `resolution_direction`.
+    ///
+    /// Markets created with `create_market_v2` are resolved by their
+    /// criteria instead, which must be `PriceAbove` or `PriceBelow`; the
+    /// window then opens at the criteria's `at_time`.
+    pub fn auto_resolve_with_pyth(ctx: Context<AutoResolveWithPyth>) -> Result<()> {
+        // Only this program can allocate its market_v2 PDA
+        let criteria = if ctx.accounts.market_v2.data_is_empty() {
+            None
+        } else {
+            let data = ctx.accounts.market_v2.try_borrow_data()?;
+            Some(MarketV2::try_deserialize(&mut &data[..])?.resolution_criteria)
+        };
+        require!(
+            matches!(
+                criteria,
+                None | Some(ResolutionCriteria::PriceAbove { .. })
+                    | Some(ResolutionCriteria::PriceBelow { .. })
+            ),
+            ErrorCode::CriteriaTypeMismatch
+        );
+        let market = &mut ctx.accounts.market;
+
+        let feed_key = market
+            .pyth_price_feed
+            .ok_or(ErrorCode::NoPythFeedConfigured)?;
+        require_keys_eq!(
+            ctx.accounts.price_feed.key(),
+            feed_key,
+            ErrorCode::PythFeedMismatch
+        );
+
+        let price_feed = load_price_feed_from_account_info(&ctx.accounts.price_feed)
+            .map_err(|_| ErrorCode::PythFeedMismatch)?;
+        let price = price_feed.get_price_unchecked();
+
+        // Only a price published in the window after the observation
+        // time decides
+        let observed_at = match criteria {
+            Some(ResolutionCriteria::PriceAbove { at_time, .. })
+            | Some(ResolutionCriteria::PriceBelow { at_time, .. }) => at_time,
+            _ => market.expiry_timestamp,
+        };
+        let window_end = require_oracle_window_open(observed_at)?;
+        require!(
+            price.publish_time >= observed_at && price.publish_time <= window_end,
+            ErrorCode::PythPriceStale
+        );
+
+        // Reject prices whose confidence interval is too wide to trust
+        require!(
+            (price.conf as u128) * 10_000
+                <= price.price.unsigned_abs() as u128 * PYTH_MAX_CONFIDENCE_BPS,
+            ErrorCode::PythPriceStale
+        );
+
+        let (threshold, direction) = match criteria {
+            Some(ResolutionCriteria::PriceAbove { threshold_ui_amount, .. }) => (
+                ui_amount_to_price(threshold_ui_amount, price.expo)?,
+                ResolutionDirection::Above,
+            ),
+            Some(ResolutionCriteria::PriceBelow { threshold_ui_amount, .. }) => (
+                ui_amount_to_price(threshold_ui_amount, price.expo)?,
+                ResolutionDirection::Below,
+            ),
+            _ => (
+                market
+                    .resolution_price_threshold
+                    .ok_or(ErrorCode::NoPythFeedConfigured)?,
+                market.resolution_direction,
+            ),
+        };
+        let condition_met = match direction {
+            ResolutionDirection::Above => price.price > threshold,
+            ResolutionDirection::Below => price.price < threshold,
+        };
+        let result = if condition_met {
+            MarketResult::Yes
+        } else {
+            MarketResult::No
+        };
+
+        require!(
+            market.parent_market.is_none(),
+            ErrorCode::ConditionalMarketNeedsResolver
+        );
+        apply_resolution(market, &mut ctx.accounts.stats, result)?;
+
+        let custody = Custody {
+            vault: ctx.accounts.vault.as_ref(),
+            sol_vault: ctx.accounts.sol_vault.as_ref(),
+            usdc_mint: ctx.accounts.usdc_mint.as_ref(),
+            token_program: ctx.accounts.token_program.as_ref(),
+            system_program: &ctx.accounts.system_program,
+        };
+        let creator_fee = collect_creator_fee(
+            market,
+            &custody,
+            ctx.accounts.config.vault_tolerance,
+            ctx.accounts.creator_fee_account.as_ref(),
+            ctx.accounts.creator.as_ref(),
+        )?;
+
+        log_outcome!(
+            "auto_resolve_with_pyth",
+            market = market.key(),
+            price = price.price,
+            threshold = threshold,
+            result = market.result,
+            creator_fee = creator_fee,
+        );
+
+        Ok(())
+    }
+
+    /// Resolve a price market from its Switchboard feed (permissionless)
+    ///
+    /// Anyone can call this once the market has expired, until
+    /// ORACLE_RESOLUTION_WINDOW_SECS later. The aggregator must match
+    /// `market.oracle`, its latest round must have opened inside that
+    /// window and be fresh and tight enough, and the outcome is decided
+    /// by comparing it against
+    /// `resolution_price_threshold` (SWITCHBOARD_THRESHOLD_DECIMALS
+    /// decimals) in the configured `resolution_direction`.
+    pub fn resolve_with_oracle(ctx: Context<ResolveWithOracle>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        let oracle_key = market.oracle.ok_or(ErrorCode::NoOracleConfigured)?;
+        let threshold = market
+            .resolution_price_threshold
+            .ok_or(ErrorCode::NoOracleConfigured)?;
+        require_keys_eq!(
+            ctx.accounts.aggregator.key(),
+            oracle_key,
+            ErrorCode::OracleFeedMismatch
+        );
+
+        let value = read_switchboard_value(
+            &ctx.accounts.aggregator,
+            market.expiry_timestamp,
+            SWITCHBOARD_MAX_STALENESS_SECS,
+        )?;
+
+        let threshold = threshold as i128;
+        let condition_met = match market.resolution_direction {
+            ResolutionDirection::Above => value > threshold,
+            ResolutionDirection::Below => value < threshold,
+        };
+        let result = if condition_met {
+            MarketResult::Yes
+        } else {
+            MarketResult::No
+        };
+
+        require!(
+            market.parent_market.is_none(),
+            ErrorCode::ConditionalMarketNeedsResolver
+        );
+        apply_resolution(market, &mut ctx.accounts.stats, result)?;
+
+        let custody = Custody {
+            vault: ctx.accounts.vault.as_ref(),
+            sol_vault: ctx.accounts.sol_vault.as_ref(),
+            usdc_mint: ctx.accounts.usdc_mint.as_ref(),
+            token_program: ctx.accounts.token_program.as_ref(),
+            system_program: &ctx.accounts.system_program,
+        };
+        let creator_fee = collect_creator_fee(
+            market,
+            &custody,
+            ctx.accounts.config.vault_tolerance,
+            ctx.accounts.creator_fee_account.as_ref(),
+            ctx.accounts.creator.as_ref(),
+        )?;
+
+        log_outcome!(
+            "resolve_with_oracle",
+            market = market.key(),
+            value = value,
+            threshold = threshold,
+            result = market.result,
+            creator_fee = creator_fee,
+        );
+
+        Ok(())
+    }
+
+    /// Make a market conditional on another market's result (authority only)
+    ///
+    /// Only before the first bet. Bets, resolution and claims then take the
+    /// parent as their first remaining account; this market resolves and
+    /// pays out only once the parent is past its dispute window. If the
+    /// parent resolves to anything but `required_outcome`, resolving voids
+    /// this market and positions are refunded through `claim_refund`.
+    /// Oracle resolution is disabled.
+    pub fn set_parent_market(
+        ctx: Context<SetParentMarket>,
+        required_outcome: u8,
+    ) -> Result<()> {
+        let parent = &ctx.accounts.parent_market;
+        let required = MarketResult::from_outcome(required_outcome, parent.num_outcomes)?;
+
+        let market = &mut ctx.accounts.market;
+        require!(market.total_positions == 0, ErrorCode::MarketHasPositions);
+        require!(!market.resolved, ErrorCode::AlreadyResolved);
+        market.parent_market = Some(parent.key());
+        market.parent_required_outcome = Some(required);
+
+        emit!(ParentMarketSetEvent {
+            market: market.key(),
+            parent_market: parent.key(),
+            required_outcome: required,
+        });
+
+        log_outcome!(
+            "set_parent_market",
+            market = market.key(),
+            parent_market = parent.key(),
+            required_outcome = required,
+        );
+
+        Ok(())
+    }
+
+    /// Link a Switchboard feed to a market for `auto_resolve_with_switchboard`
+    /// (authority only)
+    ///
+    /// Only before the first bet, so bettors know the feed they bet on,
+    /// and only on markets without a Pyth feed, `market.oracle` or a
+    /// resolution commitment, so a single oracle decides the result.
+    /// `threshold` has
+    /// SWITCHBOARD_THRESHOLD_DECIMALS decimals.
+    pub fn attach_switchboard_feed(
+        ctx: Context<AttachSwitchboardFeed>,
+        threshold: i128,
+        direction: ResolutionDirection,
+        staleness_threshold: u32,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(market.total_positions == 0, ErrorCode::MarketHasPositions);
+        require!(
+            market.pyth_price_feed.is_none() && market.oracle.is_none(),
+            ErrorCode::MultipleOraclesConfigured
+        );
+        require!(
+            market.resolution_commitment.is_none(),
+            ErrorCode::CommitmentWithOracle
+        );
+        require!(staleness_threshold > 0, ErrorCode::InvalidAmount);
+        market.switchboard_feed = Some(ctx.accounts.aggregator.key());
+
+        let feed_config = &mut ctx.accounts.feed_config;
+        feed_config.market = market.key();
+        feed_config.aggregator = ctx.accounts.aggregator.key();
+        feed_config.threshold = threshold;
+        feed_config.direction = direction;
+        feed_config.staleness_threshold = staleness_threshold;
+        feed_config.bump = ctx.bumps.feed_config;
+
+        emit!(SwitchboardFeedAttachedEvent {
+            market: market.key(),
+            aggregator: feed_config.aggregator,
+            threshold,
+            direction,
+            staleness_threshold,
+        });
+
+        log_outcome!(
+            "attach_switchboard_feed",
+            market = market.key(),
+            aggregator = feed_config.aggregator,
+            threshold = threshold,
+        );
+
+        Ok(())
+    }
+
+    /// Resolve a market from its attached Switchboard feed (permissionless)
+    ///
+    /// Like `resolve_with_oracle`, including the resolution window after
+    /// expiry, but reads the aggregator, threshold, direction and
+    /// staleness bound from the market's SwitchboardFeedConfig.
+    pub fn auto_resolve_with_switchboard(ctx: Context<AutoResolveWithSwitchboard>) -> Result<()> {
+        let feed_config = &ctx.accounts.feed_config;
+        let value = read_switchboard_value(
+            &ctx.accounts.aggregator,
+            ctx.accounts.market.expiry_timestamp,
+            feed_config.staleness_threshold as i64,
+        )?;
+
+        let condition_met = match feed_config.direction {
+            ResolutionDirection::Above => value > feed_config.threshold,
+            ResolutionDirection::Below => value < feed_config.threshold,
+        };
+        let result = if condition_met {
+            MarketResult::Yes
+        } else {
+            MarketResult::No
+        };
+
+        let market = &mut ctx.accounts.market;
+        require!(
+            market.parent_market.is_none(),
+            ErrorCode::ConditionalMarketNeedsResolver
+        );
+        apply_resolution(market, &mut ctx.accounts.stats, result)?;
+
+        let custody = Custody {
+            vault: ctx.accounts.vault.as_ref(),
+            sol_vault: ctx.accounts.sol_vault.as_ref(),
+            usdc_mint: ctx.accounts.usdc_mint.as_ref(),
+            token_program: ctx.accounts.token_program.as_ref(),
+            system_program: &ctx.accounts.system_program,
+        };
+        let creator_fee = collect_creator_fee(
+            market,
+            &custody,
+            ctx.accounts.config.vault_tolerance,
+            ctx.accounts.creator_fee_account.as_ref(),
+            ctx.accounts.creator.as_ref(),
+        )?;
+
+        log_outcome!(
+            "auto_resolve_with_switchboard",
+            market = market.key(),
+            value = value,
+            threshold = feed_config.threshold,
+            result = market.result,
+            creator_fee = creator_fee,
+        );
+
+        Ok(())
+    }
+
+    /// Extend the market deadline (authority only)
+    ///
+    /// Used when the underlying event is delayed. The new expiry must be
+    /// later than the current one and still in the future, and the market
+    /// must not be resolved yet.
+    pub fn extend_expiry(
+        ctx: Context<ExtendExpiry>,
+        new_expiry_timestamp: i64,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        // Ensure not already resolved
+        require!(!market.resolved, ErrorCode::AlreadyResolved);
+
+        require!(
+            new_expiry_timestamp > market.expiry_timestamp,
+            ErrorCode::ExpiryNotExtended
+        );
+        require!(
+            new_expiry_timestamp > Clock::get()?.unix_timestamp,
+            ErrorCode::ExpiryInPast
+        );
+
+        let old_expiry_timestamp = market.expiry_timestamp;
+        market.expiry_timestamp = new_expiry_timestamp;
+        // Markets that close betting at expiry keep doing so; an earlier
+        // cutoff (e.g. kickoff) stays put
+        if market.betting_close_timestamp == old_expiry_timestamp {
+            market.betting_close_timestamp = new_expiry_timestamp;
+        }
+        // The claim period moves with the expiry
+        market.unclaimed_deadline = market.unclaimed_deadline
+            .checked_add(new_expiry_timestamp - old_expiry_timestamp)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(ExpiryExtendedEvent {
+            market: market.key(),
+            old_expiry_timestamp,
+            new_expiry_timestamp,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "extend_expiry",
+            market = market.key(),
+            old_expiry = old_expiry_timestamp,
+            new_expiry = new_expiry_timestamp,
+        );
+
+        Ok(())
+    }
+
+    /// Correct the off-chain metadata URI (authority only)
+    ///
+    /// Only allowed before any bets exist, so bettors never see the
+    /// resolution criteria change underneath them.
+    pub fn update_market_metadata(
+        ctx: Context<UpdateMarketMetadata>,
+        new_uri: String,
+    ) -> Result<()> {
+        require!(
+            new_uri.len() <= Market::MAX_METADATA_URI_LEN,
+            ErrorCode::MetadataUriTooLong
+        );
+
+        let market = &mut ctx.accounts.market;
+        require!(market.total_pool == 0, ErrorCode::MetadataUpdateForbidden);
+
+        let old_uri = std::mem::replace(&mut market.metadata_uri, new_uri);
+
+        emit!(MarketMetadataUpdatedEvent {
+            market: market.key(),
+            old_uri,
+            new_uri: market.metadata_uri.clone(),
+        });
+
+        log_outcome!(
+            "update_market_metadata",
+            market = market.key(),
+            uri = market.metadata_uri,
+        );
+
+        Ok(())
+    }
+
+    /// Fix a typo in the question (authority only)
+    ///
+    /// Alias of `update_market_question`, kept for existing clients.
+    pub fn update_question(
+        ctx: Context<UpdateQuestion>,
+        new_question: String,
+    ) -> Result<()> {
+        update_market_question(ctx, new_question)
+    }
+
+    /// Set a corrected display question (authority only)
+    ///
+    /// The original question is kept, so the market PDA can still be
+    /// re-derived from it; clients show `question_v2` when set. Only
+    /// allowed before any bets exist.
+    pub fn update_market_question(
+        ctx: Context<UpdateQuestion>,
+        corrected_question: String,
+    ) -> Result<()> {
+        require!(corrected_question.len() <= 280, ErrorCode::QuestionTooLong);
+
+        let market = &mut ctx.accounts.market;
+        require!(market.total_pool == 0, ErrorCode::QuestionUpdateForbidden);
+
+        let old_question_v2 = market.question_v2.replace(corrected_question);
+
+        emit!(QuestionUpdatedEvent {
+            market: market.key(),
+            old_question_v2,
+            new_question_v2: market.question_v2.clone(),
+        });
+
+        log_outcome!(
+            "update_market_question",
+            market = market.key(),
+            question_v2 = market.question_v2,
+        );
+
+        Ok(())
+    }
+
+    /// Open the TagIndex for `tag` (anyone, payer funds the rent)
+    ///
+    /// Must exist before `set_market_tags` can add a market under the tag.
+    pub fn init_tag_index(ctx: Context<InitTagIndex>, tag: String) -> Result<()> {
+        require_valid_tag(&tag)?;
+
+        let tag_index = &mut ctx.accounts.tag_index;
+        tag_index.tag_hash = tag_hash(&tag);
+        tag_index.tag = tag;
+        tag_index.markets = Vec::new();
+        tag_index.bump = ctx.bumps.tag_index;
+
+        log_outcome!(
+            "init_tag_index",
+            tag = tag_index.tag,
+            tag_index = tag_index.key(),
+        );
+
+        Ok(())
+    }
+
+    /// Replace the market's keyword tags (authority only, until resolution)
+    ///
+    /// `remaining_accounts` holds the writable TagIndex of every current
+    /// tag, in stored order, followed by the TagIndex of every tag in
+    /// `tags`, in order. The market is dropped from the former and added
+    /// to the latter, growing each index by one slot as needed.
+    pub fn set_market_tags<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SetMarketTags<'info>>,
+        tags: Vec<String>,
+    ) -> Result<()> {
+        require!(tags.len() <= MarketTags::MAX_TAGS, ErrorCode::TooManyTags);
+        for (i, tag) in tags.iter().enumerate() {
+            require_valid_tag(tag)?;
+            require!(!tags[..i].contains(tag), ErrorCode::InvalidTag);
+        }
+
+        let market = &ctx.accounts.market;
+        require!(!market.resolved, ErrorCode::AlreadyResolved);
+        let market_key = market.key();
+
+        let market_tags = &mut ctx.accounts.market_tags;
+        market_tags.market = market_key;
+        market_tags.bump = ctx.bumps.market_tags;
+        let old_tags = std::mem::replace(&mut market_tags.tags, tags);
+        require!(
+            ctx.remaining_accounts.len() == old_tags.len() + market_tags.tags.len(),
+            ErrorCode::TagIndexMismatch
+        );
+        let (old_indices, new_indices) = ctx.remaining_accounts.split_at(old_tags.len());
+
+        for (tag, info) in old_tags.iter().zip(old_indices) {
+            let mut tag_index = load_tag_index(info, tag)?;
+            let position = tag_index.markets.iter()
+                .position(|key| *key == market_key)
+                .ok_or(ErrorCode::MarketNotIndexed)?;
+            tag_index.markets.swap_remove(position);
+            tag_index.exit(ctx.program_id)?;
+        }
+
+        for (tag, info) in market_tags.tags.iter().zip(new_indices) {
+            let mut tag_index = load_tag_index(info, tag)?;
+            require!(
+                tag_index.markets.len() < TagIndex::MAX_MARKETS,
+                ErrorCode::TagIndexFull
+            );
+            grow_account(
+                info,
+                TagIndex::space(tag_index.markets.len() + 1),
+                &ctx.accounts.authority,
+                &ctx.accounts.system_program,
+            )?;
+            tag_index.markets.push(market_key);
+            tag_index.exit(ctx.program_id)?;
+        }
+
+        emit!(MarketTagsUpdatedEvent {
+            market: market_key,
+            tags: market_tags.tags.clone(),
+        });
+
+        log_outcome!(
+            "set_market_tags",
+            market = market_key,
+            tags = market_tags.tags,
+        );
+
+        Ok(())
+    }
+
+    /// Temporarily halt new bets (authority only)
+    pub fn pause_market(ctx: Context<PauseMarket>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        require!(!market.resolved, ErrorCode::MarketResolved);
+        require!(!market.paused, ErrorCode::MarketPaused);
+
+        market.paused = true;
+
+        emit!(MarketPausedEvent {
+            market: market.key(),
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!("pause_market", market = market.key());
+
+        Ok(())
+    }
+
+    /// Re-open a paused market for betting (authority only)
+    pub fn resume_market(ctx: Context<ResumeMarket>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        require!(market.paused, ErrorCode::MarketNotPaused);
+
+        market.paused = false;
+
+        emit!(MarketResumedEvent {
+            market: market.key(),
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!("resume_market", market = market.key());
+
+        Ok(())
+    }
+
+    /// Delegate resolution of the market to `resolver` (authority only)
+    ///
+    /// E.g. an oracle committee key. The authority keeps every other power
+    /// over the market, including the creator fee, and can take resolution
+    /// back by setting itself.
+    pub fn set_resolver(ctx: Context<SetResolver>, resolver: Pubkey) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(!market.resolved, ErrorCode::AlreadyResolved);
+
+        let old_resolver = market.resolver;
+        market.resolver = resolver;
+
+        emit!(ResolverUpdatedEvent {
+            market: market.key(),
+            old_resolver,
+            new_resolver: resolver,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "set_resolver",
+            market = market.key(),
+            old = old_resolver,
+            new = resolver,
+        );
+
+        Ok(())
+    }
+
+    /// Nominate a new market authority, e.g. a DAO multisig (authority only)
+    ///
+    /// Step one of two, like `propose_admin_transfer`: nothing changes
+    /// until `new_authority` calls `accept_authority_transfer`. Proposing
+    /// again replaces the pending nominee.
+    pub fn propose_authority_transfer(
+        ctx: Context<ProposeAuthorityTransfer>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        market.pending_authority = Some(new_authority);
+
+        emit!(AuthorityTransferProposedEvent {
+            market: market.key(),
+            authority: market.authority,
+            pending_authority: new_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "propose_authority_transfer",
+            market = market.key(),
+            authority = market.authority,
+            pending_authority = new_authority,
+        );
+
+        Ok(())
+    }
+
+    /// Take over as market authority (signed by the pending authority)
+    ///
+    /// A resolver that was never delegated away moves with the authority.
+    pub fn accept_authority_transfer(ctx: Context<AcceptAuthorityTransfer>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let pending_authority = market.pending_authority
+            .ok_or(ErrorCode::NoPendingAuthorityTransfer)?;
+        require_keys_eq!(
+            ctx.accounts.new_authority.key(),
+            pending_authority,
+            ErrorCode::NotPendingAuthority
+        );
+
+        let previous_authority = market.authority;
+        market.authority = pending_authority;
+        market.pending_authority = None;
+        if market.resolver == previous_authority {
+            market.resolver = pending_authority;
+        }
+
+        emit!(AuthorityTransferAcceptedEvent {
+            market: market.key(),
+            previous_authority,
+            new_authority: pending_authority,
+            resolver: market.resolver,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "accept_authority_transfer",
+            market = market.key(),
+            previous_authority = previous_authority,
+            new_authority = pending_authority,
+        );
+
+        Ok(())
+    }
+
+    /// Commit to the off-chain data justifying the result (resolver only)
+    ///
+    /// `evidence_hash` is the keccak256 of the evidence blob stored at
+    /// `evidence_uri`. Allowed from EVIDENCE_WINDOW_SECS before expiry until
+    /// resolution, and may be replaced in that window; the final hash is
+    /// included in `MarketResolvedEvent`.
+    pub fn record_resolution_evidence(
+        ctx: Context<RecordResolutionEvidence>,
+        evidence_hash: [u8; 32],
+        evidence_uri: String,
+    ) -> Result<()> {
+        require!(
+            evidence_uri.len() <= ResolutionEvidence::MAX_URI_LEN,
+            ErrorCode::EvidenceUriTooLong
+        );
+
+        let market = &mut ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            !market.resolved
+                && now >= market.expiry_timestamp.saturating_sub(EVIDENCE_WINDOW_SECS),
+            ErrorCode::EvidenceWindowClosed
+        );
+
+        let evidence = &mut ctx.accounts.evidence;
+        evidence.market = market.key();
+        evidence.evidence_hash = evidence_hash;
+        evidence.evidence_uri = evidence_uri;
+        evidence.resolver = ctx.accounts.resolver.key();
+        evidence.timestamp = now;
+        evidence.bump = ctx.bumps.evidence;
+        market.evidence_hash = Some(evidence_hash);
+
+        emit!(ResolutionEvidenceRecordedEvent {
+            market: market.key(),
+            resolver: evidence.resolver,
+            evidence_hash,
+            evidence_uri: evidence.evidence_uri.clone(),
+            timestamp: now,
+        });
+
+        log_outcome!(
+            "record_resolution_evidence",
+            market = market.key(),
+            resolver = evidence.resolver,
+        );
+
+        Ok(())
+    }
+
+    /// Choose the market that receives this market's residual (authority only)
+    ///
+    /// Used for recurring markets: once settled, `roll_residual` moves the
+    /// leftover custody into `successor` as seed liquidity. `None` clears it.
+    pub fn set_successor_market(
+        ctx: Context<SetSuccessorMarket>,
+        successor: Option<Pubkey>,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(
+            successor != Some(market.key()),
+            ErrorCode::InvalidSuccessorMarket
+        );
+
+        market.successor_market = successor;
+
+        log_outcome!(
+            "set_successor_market",
+            market = market.key(),
+            successor = successor,
+        );
+
+        Ok(())
+    }
+
+    /// Move a settled market's leftover custody into its successor
+    ///
+    /// Allowed once every position has claimed. The successor must be the
+    /// configured one, unresolved, and settle in the same currency and mint.
+    /// The residual is added to the successor's pool and recorded as
+    /// `seeded_liquidity`.
+    pub fn roll_residual(ctx: Context<RollResidual>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let successor = &mut ctx.accounts.successor_market;
+
+        require!(market.resolved, ErrorCode::MarketNotResolved);
+        require!(
+            market.claimed_positions == market.total_positions,
+            ErrorCode::UnclaimedPositions
+        );
+        require!(
+            market.successor_market == Some(successor.key()),
+            ErrorCode::InvalidSuccessorMarket
+        );
+        require!(!successor.resolved, ErrorCode::MarketResolved);
+        require!(
+            successor.currency == market.currency && successor.usdc_mint == market.usdc_mint,
+            ErrorCode::SuccessorMintMismatch
+        );
+        require!(successor.vault_initialized, ErrorCode::VaultNotInitialized);
+
+        let custody = Custody {
+            vault: ctx.accounts.vault.as_ref(),
+            sol_vault: ctx.accounts.sol_vault.as_ref(),
+            usdc_mint: ctx.accounts.usdc_mint.as_ref(),
+            token_program: ctx.accounts.token_program.as_ref(),
+            system_program: &ctx.accounts.system_program,
+        };
+        let residual = custody.balance(market)?;
+        require!(residual > 0, ErrorCode::InvalidAmount);
+
+        // SOL residual goes to the successor's SOL vault; the wallet slot
+        // is unused for USDC markets
+        let sol_destination = match market.currency {
+            MarketCurrency::Sol => ctx.accounts.successor_sol_vault.as_ref()
+                .ok_or(ErrorCode::CurrencyMismatch)?
+                .to_account_info(),
+            MarketCurrency::Usdc => ctx.accounts.authority.to_account_info(),
+        };
+        custody.pay_out(
+            market,
+            ctx.accounts.successor_vault.as_ref(),
+            sol_destination,
+            residual,
+        )?;
+
+        successor.total_pool = successor.total_pool
+            .checked_add(residual)
+            .ok_or(ErrorCode::Overflow)?;
+        successor.seeded_liquidity = successor.seeded_liquidity
+            .checked_add(residual)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(ResidualRolledEvent {
+            market: market.key(),
+            successor: successor.key(),
+            amount: residual,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "roll_residual",
+            market = market.key(),
+            successor = successor.key(),
+            amount = residual,
+        );
+
+        Ok(())
+    }
+
+    /// Export a deterministic commitment over the full market state
+    ///
+    /// Read-only: nothing is mutated. The commitment is
+    /// keccak256("nexora:snapshot:v1" || market || borsh(Market)), so it is
+    /// stable for identical state and changes when any field changes. If the
+    /// market authority co-signs, the event records it as an attested snapshot.
+    pub fn export_snapshot(ctx: Context<ExportSnapshot>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let commitment = compute_market_snapshot(&market.key(), market)?;
+
+        let authority_signed = match &ctx.accounts.authority {
+            Some(authority) => {
+                require_keys_eq!(
+                    authority.key(),
+                    market.authority,
+                    ErrorCode::Unauthorized
+                );
+                true
+            }
+            None => false,
+        };
+
+        emit!(SnapshotEvent {
+            market: market.key(),
+            commitment,
+            authority_signed,
+            authority: market.authority,
+            expiry_timestamp: market.expiry_timestamp,
+            total_pool: market.total_pool,
+            min_bet: market.min_bet,
+            max_bet: market.max_bet,
+            max_pool: market.max_pool,
+            resolved: market.resolved,
+            paused: market.paused,
+            result: market.result,
+            resolution_timestamp: market.resolution_timestamp,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "export_snapshot",
+            market = market.key(),
+            commitment = commitment,
+        );
+
+        Ok(())
+    }
+
+    /// Return the payout message the MXE would sign for `user`
+    ///
+    /// Read-only: no accounts are mutated and no funds move. Clients
+    /// simulate this (Anchor `.view()`) to check an MXE signature off-chain
+    /// before submitting a claim.
+    pub fn preview_payout(
+        ctx: Context<PreviewPayout>,
+        user: Pubkey,
+        payout: u64,
+        relayer_fee: u64,
+        nonce: u64,
+        expiry: i64,
+    ) -> Result<[u8; 32]> {
+        let market_key = ctx.accounts.market.key();
+        let message = construct_payout_message(
+            &market_key,
+            &user,
+            payout,
+            relayer_fee,
+            nonce,
+            expiry,
+        );
+
+        log_outcome!(
+            "preview_payout",
+            market = market_key,
+            user = user,
+            payout = payout,
+            nonce = nonce,
+            expiry = expiry,
+        );
+
+        Ok(message)
+    }
+
+    /// Emit the user's odds for MXE-quoted payouts on each side
+    ///
+    /// Read-only. `payout_if_yes` / `payout_if_no` come from the MXE (the
+    /// side is private); odds are payout per unit staked, in basis points.
+    pub fn get_user_payout_estimate(
+        ctx: Context<GetUserPayoutEstimate>,
+        payout_if_yes: u64,
+        payout_if_no: u64,
+    ) -> Result<()> {
+        let position = &ctx.accounts.user_position;
+        let odds_bps = |payout: u64| -> Result<u64> {
+            if position.amount == 0 {
+                return Ok(0);
+            }
+            let odds = (payout as u128)
+                .checked_mul(10_000)
+                .ok_or(ErrorCode::Overflow)?
+                / position.amount as u128;
+            Ok(u64::try_from(odds).map_err(|_| ErrorCode::Overflow)?)
+        };
+        let odds_yes_bps = odds_bps(payout_if_yes)?;
+        let odds_no_bps = odds_bps(payout_if_no)?;
+
+        emit!(PayoutEstimateEvent {
+            market: ctx.accounts.market.key(),
+            user: position.user,
+            stake: position.amount,
+            payout_if_yes,
+            payout_if_no,
+            odds_yes_bps,
+            odds_no_bps,
+        });
+
+        log_outcome!(
+            "get_user_payout_estimate",
+            market = ctx.accounts.market.key(),
+            user = position.user,
+            odds_yes_bps = odds_yes_bps,
+            odds_no_bps = odds_no_bps,
+        );
+
+        Ok(())
+    }
+
+    /// Emit a market's live status for dashboards (read-only)
+    pub fn get_market_status(ctx: Context<GetMarketStatus>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+        let time_to_expiry = market.expiry_timestamp.saturating_sub(now).max(0);
+
+        emit!(MarketStatusEvent {
+            market: market.key(),
+            time_to_expiry,
+            pool_size: market.total_pool,
+            bet_count: market.total_positions,
+            resolved: market.resolved,
+            paused: market.paused,
+            result: market.result,
+            timestamp: now,
+        });
+
+        log_outcome!(
+            "get_market_status",
+            market = market.key(),
+            time_to_expiry = time_to_expiry,
+            pool = market.total_pool,
+        );
+
+        Ok(())
+    }
+
+    /// Emit what market custody currently holds (read-only)
+    ///
+    /// USDC markets pass `vault`; SOL markets pass `sol_vault`.
+    pub fn get_vault_balance(ctx: Context<GetVaultBalance>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let balance = ctx.accounts.balance()?;
+
+        emit!(VaultBalanceEvent {
+            market: market.key(),
+            currency: market.currency,
+            balance,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!("get_vault_balance", market = market.key(), balance = balance);
+
+        Ok(())
+    }
+
+    /// Check market custody still covers what it owes (read-only)
+    ///
+    /// Owed is `total_pool - total_claimed` until every position has
+    /// claimed or the claim deadline passes, then nothing. An underfunded
+    /// vault emits VaultUnderfundedEvent and fails with VaultInsolvency;
+    /// keepers read the event from the simulation or failed transaction.
+    pub fn verify_vault_solvency(ctx: Context<GetVaultBalance>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let vault_balance = ctx.accounts.balance()?;
+
+        let settled = market.claimed_positions == market.total_positions
+            || Clock::get()?.unix_timestamp >= market.unclaimed_deadline;
+        let expected_minimum = if settled {
+            0
+        } else {
+            market.total_pool.saturating_sub(market.total_claimed)
+        };
+
+        if vault_balance < expected_minimum {
+            emit!(VaultUnderfundedEvent {
+                market: market.key(),
+                vault_balance,
+                expected_minimum,
+                deficit: expected_minimum - vault_balance,
+            });
+            return err!(ErrorCode::VaultInsolvency);
+        }
+
+        log_outcome!(
+            "verify_vault_solvency",
+            market = market.key(),
+            balance = vault_balance,
+            expected_minimum = expected_minimum,
+        );
+
+        Ok(())
+    }
+
+    /// Emit every market of a series in creation order (read-only)
+    pub fn get_series_markets(ctx: Context<GetSeriesMarkets>) -> Result<()> {
+        let series = &ctx.accounts.series;
+
+        emit!(SeriesMarketsEvent {
+            series: series.key(),
+            title: series.title.clone(),
+            markets: series.markets.clone(),
+        });
+
+        log_outcome!(
+            "get_series_markets",
+            series = series.key(),
+            markets = series.markets.len(),
+        );
+
+        Ok(())
+    }
+
+    /// Claim winnings with cryptographic proof from Arcium MXE
+    /// 
+    /// TRUST-MINIMIZED PAYOUT FLOW:
+    /// 
+    /// 1️⃣ User places bet → encrypted side sent to MXE
+    /// 2️⃣ Market resolves → result recorded onchain
+    /// 3️⃣ User requests payout → MXE computes in TEE:
+    ///    - Decrypts user's bet side
+    ///    - Calculates payout based on:
+    ///      * Bet side matches market result?
+    ///      * User's bet amount
+    ///      * Total winning side pool
+    ///    - Generates unique nonce (timestamp + random)
+    ///    - Picks an expiry timestamp for the proof
+    ///    - Creates message: keccak256(program_id || market || user || payout || relayer_fee || nonce || expiry)
+    ///    - Signs message with MXE private key (Ed25519)
+    /// 4️⃣ User submits claim transaction with:
+    ///    - payout amount
+    ///    - nonce
+    ///    - expiry
+    ///    - MXE signatures (one Ed25519 instruction each, at indices 0..n)
+    /// 5️⃣ This instruction verifies:
+    ///    - A quorum of Ed25519 signatures from config.mxe_pubkeys ✅
+    ///    - Proof not expired ✅
+    ///    - Market resolved ✅
+    ///    - Not already claimed (replay protection) ✅
+    ///    - Payout ≤ total_pool and ≤ MAX_PAYOUT_MULTIPLIER × stake ✅
+    ///    - Vault has sufficient balance ✅
+    /// 6️⃣ Only if ALL checks pass → transfer USDC
+    /// 
+    /// SECURITY GUARANTEES:
+    /// ❌ Frontend cannot forge payouts (no MXE private key)
+    /// ❌ Attackers cannot replay old proofs (claimed flag)
+    /// ❌ Leaked proofs cannot be used forever (expiry)
+    /// ❌ Users cannot modify payout amounts (invalidates signature)
+    /// ❌ Vault draining impossible (each user can claim once, bounded payout)
+    /// 
+    /// CRYPTOGRAPHIC VERIFICATION:
+    /// - Ed25519 signature verification via Solana ed25519_program
+    /// - Message format: keccak256(program_id || market || user || payout || relayer_fee || nonce || expiry)
+    /// - Public keys: config.mxe_pubkeys, `required_signatures` of them
+    ///   (rotated via a 48h time-lock)
+    pub fn claim_with_proof<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimWithProof<'info>>,
+        payout: u64,
+        nonce: u64,
+        expiry: i64,
+        signatures: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        // ============================================================================
+        // SECURITY CHECKS - ALL MUST PASS
+        // ============================================================================
+
+        require_parent_settled(&ctx.accounts.market, ctx.remaining_accounts)?;
+        verify_claim(
+            &ctx.accounts.market,
+            &ctx.accounts.config,
+            &ctx.accounts.user_position,
+            &ctx.accounts.user.key(),
+            payout,
+            0,
+            nonce,
+            expiry,
+            &signatures,
+            &ctx.accounts.ix_sysvar,
+            0,
+        )?;
+        require_proof_instructions(
+            ctx.accounts.config.signature_scheme,
+            &ctx.accounts.ix_sysvar,
+            signatures.len(),
+        )?;
+
+        settle_claim(ctx.accounts, payout, nonce)?;
+
+        log_outcome!(
+            "claim_with_proof",
+            market = ctx.accounts.market.key(),
+            user = ctx.accounts.user.key(),
+            payout = payout,
+            nonce = nonce,
+            expiry = expiry,
+        );
+
+        Ok(())
+    }
+
+    /// Claim a proven payout in installments of `partial_amount`
+    ///
+    /// The first installment verifies the proof exactly like
+    /// `claim_with_proof` and pins `payout` and `nonce` to the position;
+    /// later ones must repeat them but pass no signatures. The position is
+    /// marked claimed, and counted in stats, once `claimed_amount` reaches
+    /// `payout`. Useful to rate-limit outflows of very large payouts.
+    pub fn partial_claim<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimWithProof<'info>>,
+        payout: u64,
+        nonce: u64,
+        expiry: i64,
+        signatures: Vec<[u8; 64]>,
+        partial_amount: u64,
+    ) -> Result<()> {
+        require_parent_settled(&ctx.accounts.market, ctx.remaining_accounts)?;
+        let position = &ctx.accounts.user_position;
+        if position.claimed_amount == 0 {
+            verify_claim(
+                &ctx.accounts.market,
+                &ctx.accounts.config,
+                position,
+                &ctx.accounts.user.key(),
+                payout,
+                0,
+                nonce,
+                expiry,
+                &signatures,
+                &ctx.accounts.ix_sysvar,
+                0,
+            )?;
+            require_proof_instructions(
+                ctx.accounts.config.signature_scheme,
+                &ctx.accounts.ix_sysvar,
+                signatures.len(),
+            )?;
+        } else {
+            require!(!position.claimed, ErrorCode::AlreadyClaimed);
+            require!(
+                payout == position.payout && nonce == position.nonce_used,
+                ErrorCode::MessageMismatch
+            );
+            require!(
+                Clock::get()?.unix_timestamp < ctx.accounts.market.unclaimed_deadline,
+                ErrorCode::ClaimDeadlinePassed
+            );
+        }
+
+        let remaining = payout
+            .checked_sub(position.claimed_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(remaining > 0, ErrorCode::NothingLeftToClaim);
+        require!(partial_amount > 0, ErrorCode::InvalidAmount);
+        require!(partial_amount <= remaining, ErrorCode::PartialClaimExceedsProof);
+
+        let accounts = &mut *ctx.accounts;
+        let market = &mut accounts.market;
+        let custody = Custody {
+            vault: accounts.vault.as_ref(),
+            sol_vault: accounts.sol_vault.as_ref(),
+            usdc_mint: accounts.usdc_mint.as_ref(),
+            token_program: accounts.token_program.as_ref(),
+            system_program: &accounts.system_program,
+        };
+        require!(
+            partial_amount <= custody.balance(market)?,
+            ErrorCode::InsufficientVaultBalance
+        );
+
+        // Record the claim before the transfer CPI
+        let position = &mut accounts.user_position;
+        position.payout = payout;
+        position.nonce_used = nonce;
+        position.claimed_amount += partial_amount;
+        market.total_claimed = market.total_claimed
+            .checked_add(partial_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        accounts.user_stats.total_claimed = accounts.user_stats.total_claimed
+            .checked_add(partial_amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let remaining = remaining - partial_amount;
+        if remaining == 0 {
+            position.claimed = true;
+            market.claimed_positions = market.claimed_positions
+                .checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+            accounts.stats.total_claims = accounts.stats.total_claims
+                .checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+            score_claim(&mut accounts.user_stats, market, position.amount, payout)?;
+        }
+
+        custody.pay_out(
+            market,
+            accounts.user_token_account.as_ref(),
+            accounts.user.to_account_info(),
+            partial_amount,
+        )?;
+
+        emit!(PartialClaimEvent {
+            market: market.key(),
+            user: accounts.user.key(),
+            partial_amount,
+            total_claimed: position.claimed_amount,
+            remaining,
+        });
+
+        log_outcome!(
+            "partial_claim",
+            market = market.key(),
+            user = accounts.user.key(),
+            partial = partial_amount,
+            claimed = position.claimed_amount,
+            remaining = remaining,
+        );
+
+        Ok(())
+    }
+
+    /// Claim with a Groth16 proof instead of MXE signatures
+    ///
+    /// The proof attests that `payout` is correct for the user's encrypted
+    /// bet. It is checked via the alt_bn128 syscalls against the key in
+    /// ["zk_vk"], over public inputs (market, user, payout, nonce); the
+    /// claim preconditions and settlement match `claim_with_proof`.
+    pub fn claim_with_zk_proof<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimWithZkProof<'info>>,
+        payout: u64,
+        nonce: u64,
+        proof_a: [u8; 64],
+        proof_b: [u8; 128],
+        proof_c: [u8; 64],
+    ) -> Result<()> {
+        let verifying_key = ctx.accounts.zk_verifying_key.verifying_key
+            .as_ref()
+            .ok_or(ErrorCode::ZkVerifierNotConfigured)?;
+        let claim = &ctx.accounts.claim;
+        check_claimable(&claim.market, &claim.user_position, payout)?;
+        require_parent_settled(&claim.market, ctx.remaining_accounts)?;
+
+        let inputs = zk_payout_inputs(&claim.market.key(), &claim.user.key(), payout, nonce);
+        verify_groth16(verifying_key, &proof_a, &proof_b, &proof_c, &inputs)?;
+
+        settle_claim(&mut ctx.accounts.claim, payout, nonce)?;
+
+        log_outcome!(
+            "claim_with_zk_proof",
+            market = ctx.accounts.claim.market.key(),
+            user = ctx.accounts.claim.user.key(),
+            payout = payout,
+            nonce = nonce,
+        );
+
+        Ok(())
+    }
+
+    /// Reclaim a position's stake from a voided conditional market
+    ///
+    /// A voided market has no winners, so no MXE proof is needed: every
+    /// position gets back its net stake (protocol fees are kept). Same
+    /// dispute window and deadline as `claim_with_proof`.
+    pub fn claim_refund<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimWithProof<'info>>,
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(
+            market.resolved && market.result == MarketResult::None,
+            ErrorCode::MarketNotVoided
+        );
+        let refund = ctx.accounts.user_position.amount;
+        check_claimable(market, &ctx.accounts.user_position, refund)?;
+        require_parent_settled(market, ctx.remaining_accounts)?;
+
+        settle_claim(ctx.accounts, refund, 0)?;
+
+        log_outcome!(
+            "claim_refund",
+            market = ctx.accounts.market.key(),
+            user = ctx.accounts.user.key(),
+            refund = refund,
+        );
+
+        Ok(())
+    }
+
+    /// Claim with a Secp256k1 proof from `config.mxe_eth_address`
+    ///
+    /// Only under the `Secp256k1` signature scheme, which is switched on
+    /// through the time-locked `propose_signature_scheme`. The Secp256k1
+    /// instruction at index 0 signs the same payout message as
+    /// `claim_with_proof`; everything else matches it.
+    pub fn claim_with_proof_secp256k1<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimWithProof<'info>>,
+        payout: u64,
+        nonce: u64,
+        expiry: i64,
+        signature: [u8; 64],
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(
+            config.signature_scheme == SignatureScheme::Secp256k1,
+            ErrorCode::Secp256k1SchemeInactive
+        );
+        require!(config.mxe_eth_address != [0u8; 20], ErrorCode::InvalidEthAddress);
+        require_parent_settled(&ctx.accounts.market, ctx.remaining_accounts)?;
+
+        // The active scheme is Secp256k1, so verify_claim checks the
+        // signature against the MXE Ethereum address
+        verify_claim(
+            &ctx.accounts.market,
+            config,
+            &ctx.accounts.user_position,
+            &ctx.accounts.user.key(),
+            payout,
+            0,
+            nonce,
+            expiry,
+            &[signature],
+            &ctx.accounts.ix_sysvar,
+            0,
+        )?;
+        require_proof_instructions(SignatureScheme::Secp256k1, &ctx.accounts.ix_sysvar, 1)?;
+
+        settle_claim(ctx.accounts, payout, nonce)?;
+
+        log_outcome!(
+            "claim_with_proof_secp256k1",
+            market = ctx.accounts.market.key(),
+            user = ctx.accounts.user.key(),
+            payout = payout,
+            nonce = nonce,
+            expiry = expiry,
+        );
+
+        Ok(())
+    }
+
+    /// Claim from a wrapped-SOL market, optionally unwrapping
+    ///
+    /// Same checks and payout as `claim_with_proof`. With `unwrap`, the
+    /// user's wSOL account is closed afterwards, so the payout, any wSOL
+    /// already held there and the account's rent all arrive as lamports.
+    pub fn claim_with_proof_sol<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimWithProof<'info>>,
+        payout: u64,
+        nonce: u64,
+        expiry: i64,
+        signatures: Vec<[u8; 64]>,
+        unwrap: bool,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.market.currency == MarketCurrency::Usdc
+                && ctx.accounts.market.usdc_mint == native_mint::ID,
+            ErrorCode::NotWrappedSolMarket
+        );
+
+        // Taken before claim_with_proof consumes the context
+        let user = ctx.accounts.user.to_account_info();
+        let user_token_account = ctx.accounts.user_token_account.as_ref()
+            .ok_or(ErrorCode::CurrencyMismatch)?
+            .to_account_info();
+        let token_program = ctx.accounts.token_program.as_ref()
+            .ok_or(ErrorCode::CurrencyMismatch)?
+            .to_account_info();
+        let market_key = ctx.accounts.market.key();
+
+        claim_with_proof(ctx, payout, nonce, expiry, signatures)?;
+
+        if unwrap {
+            token_interface::close_account(CpiContext::new(
+                token_program,
+                CloseAccount {
+                    account: user_token_account,
+                    destination: user.clone(),
+                    authority: user.clone(),
+                },
+            ))?;
+        }
+
+        log_outcome!(
+            "claim_with_proof_sol",
+            market = market_key,
+            user = user.key(),
+            payout = payout,
+            unwrap = unwrap,
+        );
+
+        Ok(())
+    }
+
+    /// Claim on behalf of a user through a fee-paying relayer
+    ///
+    /// Lets winners without SOL for fees settle. The user does not sign: the
+    /// MXE proof already binds the payout to `user`, and funds can only go
+    /// to the user's own token account (or wallet, for SOL markets). The
+    /// proof also signs `relayer_fee`, which goes to the relayer out of the
+    /// payout; the user receives `payout - relayer_fee`.
+    pub fn claim_with_proof_relayed<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimWithProofRelayed<'info>>,
+        payout: u64,
+        relayer_fee: u64,
+        nonce: u64,
+        expiry: i64,
+        signatures: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        require_parent_settled(&ctx.accounts.market, ctx.remaining_accounts)?;
+        let market = &mut ctx.accounts.market;
+        let position = &mut ctx.accounts.user_position;
+        let custody = Custody {
+            vault: ctx.accounts.vault.as_ref(),
+            sol_vault: ctx.accounts.sol_vault.as_ref(),
+            usdc_mint: ctx.accounts.usdc_mint.as_ref(),
+            token_program: ctx.accounts.token_program.as_ref(),
+            system_program: &ctx.accounts.system_program,
+        };
+
+        verify_claim(
+            market,
+            &ctx.accounts.config,
+            position,
+            &ctx.accounts.user.key(),
+            payout,
+            relayer_fee,
+            nonce,
+            expiry,
+            &signatures,
+            &ctx.accounts.ix_sysvar,
+            0,
+        )?;
+        require_proof_instructions(
+            ctx.accounts.config.signature_scheme,
+            &ctx.accounts.ix_sysvar,
+            signatures.len(),
+        )?;
+        require!(relayer_fee <= payout, ErrorCode::RelayerFeeExceedsPayout);
+
+        let vault_balance = custody.balance(market)?;
+        require!(
+            payout <= vault_balance,
+            ErrorCode::InsufficientVaultBalance
+        );
+
+        // Mark as claimed (prevents replay) and record the nonce
+        record_claim(market, &mut ctx.accounts.stats, position, payout, nonce)?;
+        record_user_claim(&mut ctx.accounts.user_stats, market, position.amount, payout)?;
+
+        let user_amount = payout - relayer_fee;
+        if user_amount > 0 {
+            custody.pay_out(
+                market,
+                ctx.accounts.user_token_account.as_ref(),
+                ctx.accounts.user.to_account_info(),
+                user_amount,
+            )?;
+        }
+        if relayer_fee > 0 {
+            custody.pay_out(
+                market,
+                ctx.accounts.relayer_token_account.as_ref(),
+                ctx.accounts.relayer.to_account_info(),
+                relayer_fee,
+            )?;
+        }
+
+        emit!(ClaimEvent {
+            market: market.key(),
+            user: ctx.accounts.user.key(),
+            amount: payout,
+            nonce,
+            vault_balance_after: vault_balance - payout,
+            position_amount: position.amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "claim_with_proof_relayed",
+            market = market.key(),
+            user = ctx.accounts.user.key(),
+            relayer = ctx.accounts.relayer.key(),
+            payout = payout,
+            relayer_fee = relayer_fee,
+            nonce = nonce,
+            expiry = expiry,
+        );
+
+        Ok(())
+    }
+
+    /// Record the MXE-attested YES/NO pool split of a resolved market
+    ///
+    /// Bet sides are encrypted, so only the MXE knows how the pool divides.
+    /// Publishing the split lets clients sanity-check payouts. The quorum
+    /// signs `keccak256(market || yes_pool || no_pool)`; it can be recorded
+    /// once per binary market, and not after `reveal_side` or
+    /// `change_side` have started filling the pools.
+    pub fn record_pool_split(
+        ctx: Context<RecordPoolSplit>,
+        yes_pool: u64,
+        no_pool: u64,
+        signatures: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(market.resolved, ErrorCode::MarketNotResolved);
+        require!(market.num_outcomes == 2, ErrorCode::InvalidOutcomeCount);
+        require!(!market.pool_split_recorded, ErrorCode::PoolSplitAlreadyRecorded);
+        require!(
+            market.yes_pool == 0 && market.no_pool == 0,
+            ErrorCode::PoolsAlreadyRevealed
+        );
+
+        let message = construct_pool_split_message(&market.key(), yes_pool, no_pool);
+        verify_mxe_quorum(
+            &ctx.accounts.config,
+            &ctx.accounts.ix_sysvar,
+            &message,
+            &signatures,
+            0,
+        )?;
+        require_proof_instructions(
+            ctx.accounts.config.signature_scheme,
+            &ctx.accounts.ix_sysvar,
+            signatures.len(),
+        )?;
+
+        market.yes_pool = yes_pool;
+        market.no_pool = no_pool;
+        market.pool_split_recorded = true;
+
+        emit!(PoolSplitRecordedEvent {
+            market: market.key(),
+            yes_pool,
+            no_pool,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "record_pool_split",
+            market = market.key(),
+            yes_pool = yes_pool,
+            no_pool = no_pool,
+        );
+
+        Ok(())
+    }
+
+    /// Add a position's stake to the pool of the side it backed
+    ///
+    /// Post-resolution alternative to `record_pool_split`: each user
+    /// reveals their own side with an MXE quorum signature over
+    /// `keccak256(SIDE_REVEAL_DOMAIN || market || user || side)`, where
+    /// side 0 = YES and 1 = NO. Not allowed once the split was recorded in
+    /// bulk, so stakes are never counted twice.
+    pub fn reveal_side(
+        ctx: Context<RevealSide>,
+        side: u8,
+        signatures: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(market.resolved, ErrorCode::MarketNotResolved);
+        require!(market.num_outcomes == 2, ErrorCode::InvalidOutcomeCount);
+        require!(!market.pool_split_recorded, ErrorCode::PoolSplitAlreadyRecorded);
+
+        let position = &mut ctx.accounts.user_position;
+        require!(!position.side_revealed, ErrorCode::SideAlreadyRevealed);
+
+        let message = construct_side_reveal_message(&market.key(), &position.user, side);
+        verify_mxe_quorum(
+            &ctx.accounts.config,
+            &ctx.accounts.ix_sysvar,
+            &message,
+            &signatures,
+            0,
+        )?;
+        require_proof_instructions(
+            ctx.accounts.config.signature_scheme,
+            &ctx.accounts.ix_sysvar,
+            signatures.len(),
+        )?;
+
+        let pool = side_pool(market, side)?;
+        *pool = pool.checked_add(position.amount).ok_or(ErrorCode::Overflow)?;
+        position.side_revealed = true;
+        position.revealed_side = side;
+
+        emit!(SideRevealedEvent {
+            market: market.key(),
+            user: position.user,
+            side,
+            amount: position.amount,
+            yes_pool: market.yes_pool,
+            no_pool: market.no_pool,
+        });
+
+        log_outcome!(
+            "reveal_side",
+            market = market.key(),
+            user = position.user,
+            side = side,
+            amount = position.amount,
+        );
+
+        Ok(())
+    }
+
+    /// Move a position to the other side while betting is open
+    ///
+    /// The user submits a new encrypted payload and the MXE quorum signs
+    /// `keccak256(SIDE_CHANGE_DOMAIN || market || user || old_side ||
+    /// new_side || keccak256(payload))`. This reveals the position: its
+    /// stake leaves the old side's pool (if it was counted there) and is
+    /// counted in the new side's, and later top-ups follow it. `amount` and
+    /// `total_pool` are unchanged.
+    pub fn change_side(
+        ctx: Context<RevealSide>,
+        encrypted_payload: Vec<u8>,
+        old_side: u8,
+        new_side: u8,
+        signatures: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        require!(
+            encrypted_payload.len() <= 512,
+            ErrorCode::PayloadTooLarge
+        );
+        let market = &mut ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+        require!(!market.resolved, ErrorCode::MarketResolved);
+        require!(now < market.expiry_timestamp, ErrorCode::MarketExpired);
+        require!(now < market.betting_close_timestamp, ErrorCode::BettingClosed);
+        require!(!market.paused, ErrorCode::MarketPaused);
+        require!(market.num_outcomes == 2, ErrorCode::InvalidOutcomeCount);
+        require!(old_side != new_side, ErrorCode::InvalidSideChange);
+
+        let position = &mut ctx.accounts.user_position;
+        require!(position.amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            !position.side_revealed || position.revealed_side == old_side,
+            ErrorCode::InvalidSideChange
+        );
+
+        let payload_hash = keccak::hash(&encrypted_payload).to_bytes();
+        let message = construct_side_change_message(
+            &market.key(),
+            &position.user,
+            old_side,
+            new_side,
+            &payload_hash,
+        );
+        verify_mxe_quorum(
+            &ctx.accounts.config,
+            &ctx.accounts.ix_sysvar,
+            &message,
+            &signatures,
+            0,
+        )?;
+        require_proof_instructions(
+            ctx.accounts.config.signature_scheme,
+            &ctx.accounts.ix_sysvar,
+            signatures.len(),
+        )?;
+
+        if position.side_revealed {
+            let pool = side_pool(market, old_side)?;
+            *pool = pool.checked_sub(position.amount).ok_or(ErrorCode::Overflow)?;
+        }
+        let pool = side_pool(market, new_side)?;
+        *pool = pool.checked_add(position.amount).ok_or(ErrorCode::Overflow)?;
+        position.side_revealed = true;
+        position.revealed_side = new_side;
+
+        emit!(SideChangedEvent {
+            market: market.key(),
+            user: position.user,
+            old_side,
+            new_side,
+            payload_hash,
+            amount: position.amount,
+            yes_pool: market.yes_pool,
+            no_pool: market.no_pool,
+        });
+
+        log_outcome!(
+            "change_side",
+            market = market.key(),
+            user = position.user,
+            old_side = old_side,
+            new_side = new_side,
+            amount = position.amount,
+        );
+
+        Ok(())
+    }
+
+    /// Post an MXE-signed Merkle root of every payout in a market
+    ///
+    /// Lets large markets settle without one Ed25519 proof per claim. The
+    /// MXE builds a tree of `keccak256(user || payout || nonce)` leaves and
+    /// signs `keccak256(BATCH_ROOT_DOMAIN || market || merkle_root)` with the
+    /// same quorum `claim_with_proof` requires. Anyone may submit it.
+    pub fn create_batch_payout(
+        ctx: Context<CreateBatchPayout>,
+        merkle_root: [u8; 32],
+        signatures: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.resolved, ErrorCode::MarketNotResolved);
+
+        let message = construct_batch_root_message(&market.key(), &merkle_root);
+        verify_mxe_quorum(
+            &ctx.accounts.config,
+            &ctx.accounts.ix_sysvar,
+            &message,
+            &signatures,
+            0,
+        )?;
+        require_proof_instructions(
+            ctx.accounts.config.signature_scheme,
+            &ctx.accounts.ix_sysvar,
+            signatures.len(),
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let batch_payout = &mut ctx.accounts.batch_payout;
+        batch_payout.market = market.key();
+        batch_payout.merkle_root = merkle_root;
+        batch_payout.created_at = now;
+        batch_payout.bump = ctx.bumps.batch_payout;
+
+        emit!(BatchPayoutCreatedEvent {
+            market: market.key(),
+            merkle_root,
+            timestamp: now,
+        });
+
+        log_outcome!(
+            "create_batch_payout",
+            market = market.key(),
+            merkle_root = merkle_root,
+        );
+
+        Ok(())
+    }
+
+    /// Claim a payout included in the market's `BatchPayout` root
+    ///
+    /// `proof` is the list of sibling hashes from the user's leaf up to the
+    /// root. Pairs are hashed in sorted order, so no position bits are
+    /// needed. All other claim checks match `claim_with_proof`.
+    pub fn claim_from_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimFromBatch<'info>>,
+        payout: u64,
+        nonce: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require_parent_settled(&ctx.accounts.market, ctx.remaining_accounts)?;
+        let market = &mut ctx.accounts.market;
+        let position = &mut ctx.accounts.user_position;
+        let custody = Custody {
+            vault: ctx.accounts.vault.as_ref(),
+            sol_vault: ctx.accounts.sol_vault.as_ref(),
+            usdc_mint: ctx.accounts.usdc_mint.as_ref(),
+            token_program: ctx.accounts.token_program.as_ref(),
+            system_program: &ctx.accounts.system_program,
+        };
+
+        check_claimable(market, position, payout)?;
+
+        let leaf = compute_batch_leaf(&ctx.accounts.user.key(), payout, nonce);
+        require!(
+            verify_merkle_proof(&proof, &ctx.accounts.batch_payout.merkle_root, leaf),
+            ErrorCode::MerkleProofInvalid
+        );
+
+        let vault_balance = custody.balance(market)?;
+        require!(
+            payout <= vault_balance,
+            ErrorCode::InsufficientVaultBalance
+        );
+
+        // Mark as claimed (prevents replay) and record the nonce
+        record_claim(market, &mut ctx.accounts.stats, position, payout, nonce)?;
+        record_user_claim(&mut ctx.accounts.user_stats, market, position.amount, payout)?;
+
+        if payout > 0 {
+            custody.pay_out(
+                market,
+                ctx.accounts.user_token_account.as_ref(),
+                ctx.accounts.user.to_account_info(),
+                payout,
+            )?;
+        }
+
+        emit!(ClaimEvent {
+            market: market.key(),
+            user: ctx.accounts.user.key(),
+            amount: payout,
+            nonce,
+            vault_balance_after: vault_balance - payout,
+            position_amount: position.amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "claim_from_batch",
+            market = market.key(),
+            user = ctx.accounts.user.key(),
+            payout = payout,
+            nonce = nonce,
+        );
+
+        Ok(())
+    }
+
+    /// Claim payouts from several resolved markets in one transaction
+    ///
+    /// `remaining_accounts` holds `[market, position, vault,
+    /// user_token_account]` groups, at most `MAX_BATCH_CLAIM` of them, for
+    /// USDC markets sharing `usdc_mint`. Claim `i` uses `payouts[i]`,
+    /// `nonces[i]`, `expiries[i]` and `signatures[i]`, and its Ed25519
+    /// instructions follow those of claim `i - 1`. Each claim runs the
+    /// same checks as `claim_with_proof`; any failure reverts the batch.
+    /// Conditional markets must be claimed individually.
+    pub fn batch_claim<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchClaim<'info>>,
+        payouts: Vec<u64>,
+        nonces: Vec<u64>,
+        expiries: Vec<i64>,
+        signatures: Vec<Vec<[u8; 64]>>,
+    ) -> Result<()> {
+        let groups = ctx.remaining_accounts;
+        let user = ctx.accounts.user.key();
+
+        require!(
+            !groups.is_empty() && groups.len() % 4 == 0,
+            ErrorCode::InvalidBatchClaimAccounts
+        );
+        let count = groups.len() / 4;
+        require!(count <= MAX_BATCH_CLAIM, ErrorCode::BatchTooLarge);
+        require!(
+            payouts.len() == count
+                && nonces.len() == count
+                && expiries.len() == count
+                && signatures.len() == count,
+            ErrorCode::BatchLengthMismatch
+        );
+
+        let mut first_ix: usize = 0;
+        let mut total_paid: u64 = 0;
+        for (i, group) in groups.chunks(4).enumerate() {
+            let mut market: Account<'info, Market> = Account::try_from(&group[0])?;
+            let mut position: Account<'info, UserPosition> = Account::try_from(&group[1])?;
+            let vault: InterfaceAccount<'info, TokenAccount> = InterfaceAccount::try_from(&group[2])?;
+            let user_token_account: InterfaceAccount<'info, TokenAccount> =
+                InterfaceAccount::try_from(&group[3])?;
+            let market_key = market.key();
+
+            require!(
+                market.currency == MarketCurrency::Usdc,
+                ErrorCode::CurrencyMismatch
+            );
+            require!(market.parent_market.is_none(), ErrorCode::BatchClaimUnsupported);
+            require_keys_eq!(
+                market.usdc_mint,
+                ctx.accounts.usdc_mint.key(),
+                ErrorCode::InvalidBatchClaimAccounts
+            );
+
+            // Same PDAs ClaimWithProof derives through its seeds constraints
+            let position_pda = Pubkey::create_program_address(
+                &[b"position", market_key.as_ref(), user.as_ref(), &[position.bump]],
+                ctx.program_id,
+            ).map_err(|_| ErrorCode::InvalidBatchClaimAccounts)?;
+            let vault_pda = Pubkey::create_program_address(
+                &[b"vault", market_key.as_ref(), &[market.vault_bump]],
+                ctx.program_id,
+            ).map_err(|_| ErrorCode::InvalidBatchClaimAccounts)?;
+            require_keys_eq!(position.key(), position_pda, ErrorCode::InvalidBatchClaimAccounts);
+            require_keys_eq!(vault.key(), vault_pda, ErrorCode::InvalidBatchClaimAccounts);
+            require_keys_eq!(
+                user_token_account.mint,
+                market.usdc_mint,
+                ErrorCode::InvalidBatchClaimAccounts
+            );
+            require_keys_eq!(
+                user_token_account.owner,
+                user,
+                ErrorCode::InvalidBatchClaimAccounts
+            );
+
+            let (payout, nonce) = (payouts[i], nonces[i]);
+            verify_claim(
+                &market,
+                &ctx.accounts.config,
+                &position,
+                &user,
+                payout,
+                0,
+                nonce,
+                expiries[i],
+                &signatures[i],
+                &ctx.accounts.ix_sysvar,
+                first_ix,
+            )?;
+            first_ix += signatures[i].len();
+
+            let custody = Custody {
+                vault: Some(&vault),
+                sol_vault: None,
+                usdc_mint: Some(&ctx.accounts.usdc_mint),
+                token_program: Some(&ctx.accounts.token_program),
+                system_program: &ctx.accounts.system_program,
+            };
+            let vault_balance = custody.balance(&market)?;
+            require!(
+                payout <= vault_balance,
+                ErrorCode::InsufficientVaultBalance
+            );
+            record_claim(&mut market, &mut ctx.accounts.stats, &mut position, payout, nonce)?;
+            record_user_claim(&mut ctx.accounts.user_stats, &market, position.amount, payout)?;
+            if payout > 0 {
+                custody.pay_out(
+                    &market,
+                    Some(&user_token_account),
+                    ctx.accounts.user.to_account_info(),
+                    payout,
+                )?;
+            }
+
+            // Persist now so a repeated group fails with AlreadyClaimed
+            market.exit(ctx.program_id)?;
+            position.exit(ctx.program_id)?;
+            total_paid = total_paid.checked_add(payout).ok_or(ErrorCode::Overflow)?;
+
+            emit!(ClaimEvent {
+                market: market_key,
+                user,
+                amount: payout,
+                nonce,
+                vault_balance_after: vault_balance - payout,
+                position_amount: position.amount,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+        require_proof_instructions(
+            ctx.accounts.config.signature_scheme,
+            &ctx.accounts.ix_sysvar,
+            first_ix,
+        )?;
+
+        log_outcome!(
+            "batch_claim",
+            user = user,
+            claims = count,
+            paid = total_paid,
+        );
+
+        Ok(())
+    }
+
+    /// Report the realized P&L of a claimed position
+    ///
+    /// Read-only. P&L is `payout - (amount + fees_paid)`, i.e. against
+    /// everything the user deposited including protocol fees. It is emitted
+    /// as `UserPnlEvent` and returned for `.view()` callers.
+    pub fn read_user_pnl(ctx: Context<ReadUserPnl>) -> Result<i64> {
+        let position = &ctx.accounts.user_position;
+        require!(position.claimed, ErrorCode::PositionNotSettled);
+
+        let deposited = (position.amount as i128) + (position.fees_paid as i128);
+        let pnl = i64::try_from(position.payout as i128 - deposited)
+            .map_err(|_| ErrorCode::Overflow)?;
+
+        emit!(UserPnlEvent {
+            market: position.market,
+            user: position.user,
+            amount: position.amount,
+            fees_paid: position.fees_paid,
+            payout: position.payout,
+            pnl,
+        });
+
+        log_outcome!(
+            "read_user_pnl",
+            market = position.market,
+            user = position.user,
+            pnl = pnl,
+        );
+
+        Ok(pnl)
+    }
+
+    /// Audit a claimed payout against the recorded pool split
+    ///
+    /// Read-only. Recomputes the pro-rata winning payout
+    /// `amount * total_pool / winning_pool` from the MXE-attested
+    /// `yes_pool`/`no_pool` and compares it with the recorded payout. Sides
+    /// are encrypted, so a zero payout is taken to be a losing position.
+    /// The result is emitted as `ClaimAuditEvent` and returned for `.view()`.
+    pub fn verify_claim_correctness(ctx: Context<VerifyClaimCorrectness>) -> Result<bool> {
+        let market = &ctx.accounts.market;
+        let position = &ctx.accounts.user_position;
+        require!(position.claimed, ErrorCode::PositionNotSettled);
+        require!(market.num_outcomes == 2, ErrorCode::InvalidOutcomeCount);
+        require!(
+            market.yes_pool > 0 || market.no_pool > 0,
+            ErrorCode::PoolSplitNotRecorded
+        );
+
+        let winning_pool = match market.result {
+            MarketResult::Yes => market.yes_pool,
+            MarketResult::No => market.no_pool,
+            _ => return err!(ErrorCode::InvalidResult),
+        };
+        let expected = compute_pro_rata_payout(position.amount, market.total_pool, winning_pool)?;
+        let actual = position.payout;
+        let correct = actual == expected || actual == 0;
+
+        emit!(ClaimAuditEvent {
+            market: market.key(),
+            user: position.user,
+            correct,
+            expected,
+            actual,
+        });
+
+        log_outcome!(
+            "verify_claim_correctness",
+            market = market.key(),
+            user = position.user,
+            correct = correct,
+            expected = expected,
+            actual = actual,
+        );
+
+        Ok(correct)
+    }
+
+    /// Hand an open position to another user, e.g. after an OTC sale
+    ///
+    /// Positions are PDAs of their owner, so the old account is closed and
+    /// its stake moves to ["position", market, new_owner], merging into
+    /// any position the new owner already holds. The MXE follows the
+    /// PositionTransferredEvent to re-key the encrypted bet. No transfer
+    /// fee is charged yet; one would be deducted from `amount` here.
+    ///
+    /// The new owner's UserStats is created when missing, so a buyer who
+    /// never bet can still claim.
+    pub fn transfer_position(ctx: Context<TransferPosition>, new_owner: Pubkey) -> Result<()> {
+        require!(
+            new_owner != ctx.accounts.user.key(),
+            ErrorCode::CannotTransferToSelf
+        );
+
+        let market = &mut ctx.accounts.market;
+        require!(!market.resolved, ErrorCode::MarketResolved);
+        require!(!market.paused, ErrorCode::MarketPaused);
+
+        let from = &ctx.accounts.user_position;
+        require!(!from.claimed, ErrorCode::AlreadyClaimed);
+        let amount = from.amount;
+        let fees_paid = from.fees_paid;
+
+        let to = &mut ctx.accounts.new_position;
+        // A revealed side lives in the side pools and cannot be merged blind
+        require!(
+            !from.side_revealed && !to.side_revealed,
+            ErrorCode::SideAlreadyRevealed
+        );
+        if to.amount == 0 {
+            to.user = new_owner;
+            to.market = market.key();
+            to.amount = amount;
+            to.fees_paid = fees_paid;
+            to.claimed = false;
+            to.bump = ctx.bumps.new_position;
+
+            let new_owner_stats = &mut ctx.accounts.new_owner_stats;
+            if new_owner_stats.user == Pubkey::default() {
+                new_owner_stats.user = new_owner;
+                new_owner_stats.bump = ctx.bumps.new_owner_stats;
+            }
+            new_owner_stats.markets_entered = new_owner_stats.markets_entered
+                .checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        } else {
+            to.amount = to.amount
+                .checked_add(amount)
+                .ok_or(ErrorCode::Overflow)?;
+            to.fees_paid = to.fees_paid
+                .checked_add(fees_paid)
+                .ok_or(ErrorCode::Overflow)?;
+            // Two positions became one
+            market.total_positions = market.total_positions
+                .checked_sub(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(PositionTransferredEvent {
+            market: market.key(),
+            from: ctx.accounts.user.key(),
+            to: new_owner,
+            amount,
+        });
+
+        log_outcome!(
+            "transfer_position",
+            market = market.key(),
+            from = ctx.accounts.user.key(),
+            to = new_owner,
+            amount = amount,
+        );
+
+        Ok(())
+    }
+
+    /// Close a settled position and return its rent to the user
+    ///
+    /// Only claimed positions on resolved markets can be closed; anything
+    /// else may still be owed a payout.
+    pub fn close_user_position(ctx: Context<CloseUserPosition>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let position = &ctx.accounts.user_position;
+
+        require!(market.resolved, ErrorCode::MarketNotResolved);
+        require!(position.claimed, ErrorCode::PositionNotSettled);
+
+        let rent_recovered = position.to_account_info().lamports();
+
+        emit!(PositionClosedEvent {
+            market: market.key(),
+            user: position.user,
+            rent_recovered,
+        });
+
+        log_outcome!(
+            "close_user_position",
+            market = market.key(),
+            user = position.user,
+            rent = rent_recovered,
+        );
+
+        Ok(())
+    }
+
+    /// Close many claimed positions of a resolved market (permissionless)
+    ///
+    /// `remaining_accounts` holds `[position, owner]` pairs, at most
+    /// `MAX_BATCH_CLOSE` of them. Each position must belong to this market
+    /// and be claimed; its rent goes back to its owner, so anyone can run
+    /// the cleanup.
+    pub fn batch_close_positions<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchClosePositions<'info>>,
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let pairs = ctx.remaining_accounts;
+
+        require!(market.resolved, ErrorCode::MarketNotResolved);
+        require!(
+            !pairs.is_empty() && pairs.len() % 2 == 0,
+            ErrorCode::InvalidBatchAccounts
+        );
+        require!(pairs.len() / 2 <= MAX_BATCH_CLOSE, ErrorCode::BatchTooLarge);
+
+        let mut rent_recovered: u64 = 0;
+        for pair in pairs.chunks(2) {
+            let (position_info, owner_info) = (&pair[0], &pair[1]);
+            let position: Account<'info, UserPosition> = Account::try_from(position_info)?;
+
+            require_keys_eq!(position.market, market.key(), ErrorCode::InvalidBatchAccounts);
+            require_keys_eq!(position.user, owner_info.key(), ErrorCode::InvalidBatchAccounts);
+            require!(position.claimed, ErrorCode::PositionNotSettled);
+
+            let rent = position_info.lamports();
+            position.close(owner_info.clone())?;
+            rent_recovered = rent_recovered.checked_add(rent).ok_or(ErrorCode::Overflow)?;
+
+            emit!(PositionClosedEvent {
+                market: market.key(),
+                user: owner_info.key(),
+                rent_recovered: rent,
+            });
+        }
+
+        log_outcome!(
+            "batch_close_positions",
+            market = market.key(),
+            closed = pairs.len() / 2,
+            rent = rent_recovered,
+        );
+
+        Ok(())
+    }
+
+    /// Close a fully settled market and its vaults (authority only)
+    ///
+    /// Every position must have claimed and custody must be empty, including
+    /// protocol fees. Rent from the market and both token vaults goes back
+    /// to `market.authority`.
+    pub fn close_market(ctx: Context<CloseMarket>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let market_key = market.key();
+
+        require!(market.resolved, ErrorCode::MarketNotResolved);
+        require!(
+            market.claimed_positions == market.total_positions,
+            ErrorCode::UnclaimedPositions
+        );
+        require!(
+            ctx.accounts.vault.amount == 0 && ctx.accounts.fee_vault.amount == 0,
+            ErrorCode::VaultNotEmpty
+        );
+        if market.currency == MarketCurrency::Sol {
+            let sol_vault = ctx.accounts.sol_vault.as_ref()
+                .ok_or(ErrorCode::CurrencyMismatch)?;
+            let sol_fee_vault = ctx.accounts.sol_fee_vault.as_ref()
+                .ok_or(ErrorCode::CurrencyMismatch)?;
+            require!(
+                sol_vault.lamports() == 0 && sol_fee_vault.lamports() == 0,
+                ErrorCode::VaultNotEmpty
+            );
+        }
+
+        // Drop the market from its registry page
+        let registry_page = &mut ctx.accounts.registry_page;
+        let index = registry_page.markets
+            .iter()
+            .position(|key| *key == market_key)
+            .ok_or(ErrorCode::MarketNotIndexed)?;
+        registry_page.markets.swap_remove(index);
+        let registry = &mut ctx.accounts.registry;
+        let page_len = registry.page_lens
+            .get_mut(registry_page.page as usize)
+            .ok_or(ErrorCode::MarketNotIndexed)?;
+        *page_len = page_len.checked_sub(1).ok_or(ErrorCode::Overflow)?;
+        registry.total_markets = registry.total_markets
+            .checked_sub(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let destination = ctx.accounts.authority.to_account_info();
+        close_vault(
+            &ctx.accounts.vault,
+            destination.clone(),
+            &ctx.accounts.token_program,
+            &[b"vault", market_key.as_ref(), &[market.vault_bump]],
+        )?;
+        close_vault(
+            &ctx.accounts.fee_vault,
+            destination,
+            &ctx.accounts.token_program,
+            &[b"fee_vault", market_key.as_ref(), &[market.fee_vault_bump]],
+        )?;
+
+        emit!(MarketClosedEvent {
+            market: market_key,
+            authority: market.authority,
+            total_positions: market.total_positions,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "close_market",
+            market = market_key,
+            positions = market.total_positions,
+        );
+
+        Ok(())
+    }
+
+    /// Drop a closed market from its category index (permissionless)
+    ///
+    /// The market account must already be gone, so live markets cannot be
+    /// hidden from discovery. The freed slot is reused by the next
+    /// `create_market` in the category.
+    pub fn remove_from_category_index(
+        ctx: Context<RemoveFromCategoryIndex>,
+        market_key: Pubkey,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.market.data_is_empty(),
+            ErrorCode::MarketStillOpen
+        );
+
+        let category_index = &mut ctx.accounts.category_index;
+        let position = category_index.markets.iter()
+            .position(|market| *market == market_key)
+            .ok_or(ErrorCode::MarketNotIndexed)?;
+        category_index.markets.swap_remove(position);
+
+        log_outcome!(
+            "remove_from_category_index",
+            market = market_key,
+            category = category_index.category,
+            remaining = category_index.markets.len(),
+        );
+
+        Ok(())
+    }
+
+    /// List markets from the global registry, `page_size` at a time
+    ///
+    /// Read-only. Page `page` covers registry positions
+    /// `page * page_size ..`, up to MAX_MARKET_PAGE_SIZE of them.
+    /// `remaining_accounts` holds consecutive MarketRegistryPage accounts,
+    /// starting with the one holding the first key (found from
+    /// `MarketRegistry.page_lens`). The keys are emitted as `MarketPageEvent`
+    /// and returned for `.view()`.
+    pub fn get_market_page<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetMarketPage<'info>>,
+        page: u32,
+        page_size: u8,
+    ) -> Result<Vec<Pubkey>> {
+        require!(
+            page_size > 0 && page_size as usize <= MAX_MARKET_PAGE_SIZE,
+            ErrorCode::InvalidPageSize
+        );
+        let registry = &ctx.accounts.registry;
+        let offset = page as u64 * page_size as u64;
+        require!(
+            offset < registry.total_markets as u64,
+            ErrorCode::PageOutOfBounds
+        );
+
+        // Find the registry page and slot holding position `offset`
+        let mut skip = offset;
+        let mut registry_page = 0u32;
+        for len in registry.page_lens.iter() {
+            if skip < *len as u64 {
+                break;
+            }
+            skip -= *len as u64;
+            registry_page += 1;
+        }
+
+        let wanted = (page_size as u64).min(registry.total_markets as u64 - offset) as usize;
+        let mut market_keys = Vec::with_capacity(wanted);
+        for info in ctx.remaining_accounts.iter() {
+            if market_keys.len() == wanted {
+                break;
+            }
+            let page_account: Account<'info, MarketRegistryPage> = Account::try_from(info)?;
+            require!(
+                page_account.page == registry_page,
+                ErrorCode::InvalidRegistryPage
+            );
+            let take = wanted - market_keys.len();
+            market_keys.extend(page_account.markets.iter().skip(skip as usize).take(take));
+            skip = 0;
+            registry_page += 1;
+        }
+        require!(market_keys.len() == wanted, ErrorCode::InvalidRegistryPage);
+
+        emit!(MarketPageEvent {
+            page,
+            total_markets: registry.total_markets,
+            market_keys: market_keys.clone(),
+        });
+
+        log_outcome!(
+            "get_market_page",
+            page = page,
+            returned = market_keys.len(),
+            total = registry.total_markets,
+        );
+
+        Ok(market_keys)
+    }
+
+    /// Withdraw accumulated protocol fees for a market (ADMIN ONLY)
+    ///
+    /// USDC fees go to `destination_token_account`; SOL fees go to
+    /// `destination`.
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>, amount: u64) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let market_key = market.key();
+
+        match market.currency {
+            MarketCurrency::Usdc => {
+                let fee_vault = ctx.accounts.fee_vault.as_ref()
+                    .ok_or(ErrorCode::CurrencyMismatch)?;
+                require!(amount <= fee_vault.amount, ErrorCode::InsufficientFeeBalance);
+                transfer_from_vault(
+                    market,
+                    fee_vault,
+                    ctx.accounts.usdc_mint.as_ref().ok_or(ErrorCode::CurrencyMismatch)?,
+                    ctx.accounts.destination_token_account.as_ref()
+                        .ok_or(ErrorCode::CurrencyMismatch)?,
+                    ctx.accounts.token_program.as_ref().ok_or(ErrorCode::CurrencyMismatch)?,
+                    &[b"fee_vault", market_key.as_ref(), &[market.fee_vault_bump]],
+                    amount,
+                )?;
+            }
+            MarketCurrency::Sol => {
+                let sol_fee_vault = ctx.accounts.sol_fee_vault.as_ref()
+                    .ok_or(ErrorCode::CurrencyMismatch)?;
+                require!(
+                    amount <= sol_fee_vault.lamports(),
+                    ErrorCode::InsufficientFeeBalance
+                );
+                transfer_from_sol_vault(
+                    sol_fee_vault,
+                    ctx.accounts.destination.as_ref()
+                        .ok_or(ErrorCode::CurrencyMismatch)?
+                        .to_account_info(),
+                    &ctx.accounts.system_program,
+                    &[b"sol_fee_vault", market_key.as_ref(), &[market.sol_fee_vault_bump]],
+                    amount,
+                )?;
+            }
+        }
+
+        emit!(FeesWithdrawnEvent {
+            market: market_key,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!("withdraw_fees", market = market_key, amount = amount);
+
+        Ok(())
+    }
+
+    /// Sweep payouts nobody claimed before the deadline (authority only)
+    ///
+    /// Claims stop at `market.unclaimed_deadline`; after it the authority
+    /// can move what is left in custody to itself or the treasury.
+    pub fn withdraw_unclaimed(ctx: Context<WithdrawUnclaimed>, amount: u64) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(market.resolved, ErrorCode::MarketNotResolved);
+        require!(
+            now >= market.unclaimed_deadline,
+            ErrorCode::UnclaimedDeadlineNotReached
+        );
+
+        let custody = Custody {
+            vault: ctx.accounts.vault.as_ref(),
+            sol_vault: ctx.accounts.sol_vault.as_ref(),
+            usdc_mint: ctx.accounts.usdc_mint.as_ref(),
+            token_program: ctx.accounts.token_program.as_ref(),
+            system_program: &ctx.accounts.system_program,
+        };
+        require!(
+            amount <= custody.balance(market)?,
+            ErrorCode::InsufficientVaultBalance
+        );
+
+        let destination = match market.currency {
+            MarketCurrency::Usdc => ctx.accounts.destination_token_account.as_ref()
+                .ok_or(ErrorCode::CurrencyMismatch)?
+                .owner,
+            MarketCurrency::Sol => ctx.accounts.destination.as_ref()
+                .ok_or(ErrorCode::CurrencyMismatch)?
+                .key(),
+        };
+        require!(
+            destination == market.authority || destination == TREASURY_PUBKEY,
+            ErrorCode::InvalidUnclaimedDestination
+        );
+
+        custody.pay_out(
+            market,
+            ctx.accounts.destination_token_account.as_ref(),
+            match ctx.accounts.destination.as_ref() {
+                Some(destination) => destination.to_account_info(),
+                None => ctx.accounts.authority.to_account_info(),
+            },
+            amount,
+        )?;
+
+        emit!(UnclaimedFundsWithdrawnEvent {
+            market: market.key(),
+            amount,
+            destination,
+            timestamp: now,
+        });
+
+        log_outcome!(
+            "withdraw_unclaimed",
+            market = market.key(),
+            amount = amount,
+            destination = destination,
+        );
+
+        Ok(())
+    }
+
+    /// Recover stranded market funds to the treasury (ADMIN ONLY)
+    ///
+    /// Last resort for funds no claim, refund or sweep can reach. Only
+    /// works while the protocol is paused and once
+    /// EMERGENCY_WITHDRAW_GRACE_SECS have passed since expiry, resolved or
+    /// not. Funds always go to TREASURY_PUBKEY.
+    pub fn emergency_withdraw(ctx: Context<EmergencyWithdraw>, amount: u64) -> Result<()> {
+        require!(ctx.accounts.config.protocol_paused, ErrorCode::ProtocolNotPaused);
+
+        let market = &ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+        let unlock_time = market.expiry_timestamp
+            .checked_add(EMERGENCY_WITHDRAW_GRACE_SECS)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(now > unlock_time, ErrorCode::EmergencyGraceNotElapsed);
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let custody = Custody {
+            vault: ctx.accounts.vault.as_ref(),
+            sol_vault: ctx.accounts.sol_vault.as_ref(),
+            usdc_mint: ctx.accounts.usdc_mint.as_ref(),
+            token_program: ctx.accounts.token_program.as_ref(),
+            system_program: &ctx.accounts.system_program,
+        };
+        require!(
+            amount <= custody.balance(market)?,
+            ErrorCode::InsufficientVaultBalance
+        );
+
+        custody.pay_out(
+            market,
+            ctx.accounts.treasury_token_account.as_ref(),
+            ctx.accounts.treasury.to_account_info(),
+            amount,
+        )?;
+
+        emit!(EmergencyWithdrawEvent {
+            market: market.key(),
+            admin: ctx.accounts.admin.key(),
+            amount,
+            treasury: TREASURY_PUBKEY,
+            timestamp: now,
+        });
+
+        log_outcome!(
+            "emergency_withdraw",
+            market = market.key(),
+            amount = amount,
+        );
+
+        Ok(())
+    }
+
+    /// Flag a resolution as incorrect during the dispute window
+    ///
+    /// Creates a DisputeRecord holding `reason_hash` (keccak of the
+    /// off-chain dispute text) and locks DISPUTE_BOND_LAMPORTS from the
+    /// disputant. The market authority reviews and closes it, or the
+    /// admin while the market's resolution bond is at stake.
+    pub fn dispute_market_resolution(
+        ctx: Context<DisputeMarketResolution>,
+        reason_hash: [u8; 32],
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(market.resolved, ErrorCode::MarketNotResolved);
+        let dispute_deadline = market.resolution_timestamp
+            .checked_add(DISPUTE_WINDOW_SECS)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(now < dispute_deadline, ErrorCode::DisputeWindowClosed);
+
+        // Lock the bond inside the dispute record (on top of its rent)
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.disputant.to_account_info(),
+                    to: ctx.accounts.dispute.to_account_info(),
+                },
+            ),
+            DISPUTE_BOND_LAMPORTS,
+        )?;
+
+        let resolution_bond = &mut ctx.accounts.resolution_bond;
+        resolution_bond.open_disputes = resolution_bond.open_disputes
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.market = market.key();
+        dispute.disputant = ctx.accounts.disputant.key();
+        dispute.reason_hash = reason_hash;
+        dispute.bond = DISPUTE_BOND_LAMPORTS;
+        dispute.created_at = now;
+        dispute.bump = ctx.bumps.dispute;
+
+        emit!(MarketDisputedEvent {
+            market: market.key(),
+            disputant: dispute.disputant,
+            reason_hash,
+            bond: dispute.bond,
+            timestamp: now,
+        });
+
+        log_outcome!(
+            "dispute_market_resolution",
+            market = market.key(),
+            disputant = dispute.disputant,
+            bond = dispute.bond,
+        );
+
+        Ok(())
+    }
+
+    /// Close a dispute record (authority, or ADMIN ONLY once bonded)
+    ///
+    /// `slash = false` returns the bond to the disputant; `slash = true`
+    /// sends it to the treasury. Rent always goes back to the disputant.
+    ///
+    /// While the market's ResolutionBond holds lamports only the admin
+    /// rules: otherwise the bonded authority could close every dispute
+    /// against its own resolution and release its bond.
+    pub fn close_dispute(ctx: Context<CloseDispute>, slash: bool) -> Result<()> {
+        let closer = ctx.accounts.authority.key();
+        let admin = ctx.accounts.config.admin;
+        let allowed = if ctx.accounts.resolution_bond.amount > 0 {
+            closer == admin
+        } else {
+            closer == ctx.accounts.market.authority || closer == admin
+        };
+        require!(allowed, ErrorCode::Unauthorized);
+
+        let dispute = &ctx.accounts.dispute;
+        let bond = dispute.bond;
+
+        let resolution_bond = &mut ctx.accounts.resolution_bond;
+        resolution_bond.open_disputes = resolution_bond.open_disputes
+            .checked_sub(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        if slash {
+            let dispute_info = dispute.to_account_info();
+            let treasury_info = ctx.accounts.treasury.to_account_info();
+            **dispute_info.try_borrow_mut_lamports()? = dispute_info
+                .lamports()
+                .checked_sub(bond)
+                .ok_or(ErrorCode::Overflow)?;
+            **treasury_info.try_borrow_mut_lamports()? = treasury_info
+                .lamports()
+                .checked_add(bond)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(DisputeClosedEvent {
+            market: dispute.market,
+            disputant: dispute.disputant,
+            bond,
+            slashed: slash,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "close_dispute",
+            market = dispute.market,
+            disputant = dispute.disputant,
+            slashed = slash,
+        );
+
+        Ok(())
+    }
+
+    /// Return a market's resolution bond to its authority
+    ///
+    /// Allowed once the dispute window has passed with no dispute still
+    /// open. Closes the bond account, so its rent comes back too.
+    pub fn release_resolution_bond(ctx: Context<ReleaseResolutionBond>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let resolution_bond = &ctx.accounts.resolution_bond;
+
+        require!(market.resolved, ErrorCode::MarketNotResolved);
+        let dispute_deadline = market.resolution_timestamp
+            .checked_add(DISPUTE_WINDOW_SECS)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(
+            Clock::get()?.unix_timestamp >= dispute_deadline,
+            ErrorCode::BondLocked
+        );
+        require!(resolution_bond.open_disputes == 0, ErrorCode::BondDisputed);
+
+        emit!(ResolutionBondReleasedEvent {
+            market: market.key(),
+            authority: resolution_bond.authority,
+            amount: resolution_bond.amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "release_resolution_bond",
+            market = market.key(),
+            amount = resolution_bond.amount,
+        );
+
+        Ok(())
+    }
+
+    /// Uphold a dispute by sending the resolution bond to the treasury
+    /// (ADMIN ONLY)
+    ///
+    /// Needs at least one open dispute on the market. The bond account
+    /// stays, emptied, so open disputes can still be closed; its rent is
+    /// recovered by `release_resolution_bond` afterwards.
+    pub fn slash_resolution_bond(ctx: Context<SlashResolutionBond>) -> Result<()> {
+        let resolution_bond = &mut ctx.accounts.resolution_bond;
+        require!(resolution_bond.open_disputes > 0, ErrorCode::NoOpenDispute);
+
+        let amount = resolution_bond.amount;
+        move_stake_lamports(
+            &resolution_bond.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            amount,
+        )?;
+        resolution_bond.amount = 0;
+
+        emit!(ResolutionBondSlashedEvent {
+            market: resolution_bond.market,
+            authority: resolution_bond.authority,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "slash_resolution_bond",
+            market = resolution_bond.market,
+            amount = amount,
+        );
+
+        Ok(())
+    }
+
+    /// Return a market's creator stake to its authority
+    ///
+    /// Allowed once the market is resolved and its dispute window has
+    /// passed, unless the admin slashed the stake first.
+    pub fn reclaim_creator_stake(ctx: Context<ReclaimCreatorStake>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let creator_stake = &mut ctx.accounts.creator_stake;
+
+        require!(!creator_stake.slashed, ErrorCode::StakeSlashed);
+        require!(!creator_stake.reclaimed, ErrorCode::StakeAlreadyReclaimed);
+        require!(market.resolved, ErrorCode::MarketNotResolved);
+        let dispute_deadline = market.resolution_timestamp
+            .checked_add(DISPUTE_WINDOW_SECS)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(
+            Clock::get()?.unix_timestamp >= dispute_deadline,
+            ErrorCode::DisputeWindowNotExpired
+        );
+
+        let amount = creator_stake.amount;
+        move_stake_lamports(
+            &creator_stake.to_account_info(),
+            &ctx.accounts.authority.to_account_info(),
+            amount,
+        )?;
+        creator_stake.reclaimed = true;
+
+        emit!(CreatorStakeReclaimedEvent {
+            market: market.key(),
+            authority: creator_stake.authority,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "reclaim_creator_stake",
+            market = market.key(),
+            amount = amount,
+        );
+
+        Ok(())
+    }
+
+    /// Send a market's creator stake to the treasury (ADMIN ONLY)
+    ///
+    /// Allowed until the market's dispute window closes. `reason_hash`
+    /// commits to the off-chain justification.
+    pub fn slash_creator_stake(
+        ctx: Context<SlashCreatorStake>,
+        reason_hash: [u8; 32],
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let creator_stake = &mut ctx.accounts.creator_stake;
+
+        require!(!creator_stake.slashed, ErrorCode::StakeSlashed);
+        require!(!creator_stake.reclaimed, ErrorCode::StakeAlreadyReclaimed);
+        if market.resolved {
+            let dispute_deadline = market.resolution_timestamp
+                .checked_add(DISPUTE_WINDOW_SECS)
+                .ok_or(ErrorCode::Overflow)?;
+            require!(
+                Clock::get()?.unix_timestamp < dispute_deadline,
+                ErrorCode::DisputeWindowClosed
+            );
+        }
+
+        let amount = creator_stake.amount;
+        move_stake_lamports(
+            &creator_stake.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            amount,
+        )?;
+        creator_stake.slashed = true;
+
+        emit!(CreatorStakeSlashedEvent {
+            market: market.key(),
+            authority: creator_stake.authority,
+            amount,
+            reason_hash,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "slash_creator_stake",
+            market = market.key(),
+            amount = amount,
+        );
+
+        Ok(())
+    }
+
+    /// Resolve a market whose authority went dark (permissionless)
+    ///
+    /// Allowed once `config.force_resolve_delay` has passed since expiry.
+    /// The caller locks FORCE_RESOLVE_BOND_LAMPORTS in a ForceResolution
+    /// record: the admin and market authority can overturn the result
+    /// during the dispute window and slash it, otherwise
+    /// `release_force_resolve_bond` returns it. No creator fee is charged.
+    /// Conditional markets pass their parent, which must be final. Markets
+    /// with a resolution commitment or a pending proposal are left to
+    /// their resolver.
+    pub fn force_resolve<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ForceResolve<'info>>,
+        result: MarketResult,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        if let Some(parent) = load_parent_market(&ctx.accounts.market, ctx.remaining_accounts)? {
+            require_parent_final(&parent)?;
+        }
+        let unlock_at = ctx.accounts.market.expiry_timestamp
+            .checked_add(ctx.accounts.config.force_resolve_delay)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(now >= unlock_at, ErrorCode::ForceResolveDelayNotElapsed);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.resolver.to_account_info(),
+                    to: ctx.accounts.force_resolution.to_account_info(),
+                },
+            ),
+            FORCE_RESOLVE_BOND_LAMPORTS,
+        )?;
+
+        let market = &mut ctx.accounts.market;
+        apply_resolution(market, &mut ctx.accounts.stats, result)?;
+
+        let force_resolution = &mut ctx.accounts.force_resolution;
+        force_resolution.market = market.key();
+        force_resolution.resolver = ctx.accounts.resolver.key();
+        force_resolution.result = result;
+        force_resolution.bond = FORCE_RESOLVE_BOND_LAMPORTS;
+        force_resolution.created_at = now;
+        force_resolution.bump = ctx.bumps.force_resolution;
+
+        emit!(MarketForceResolvedEvent {
+            market: market.key(),
+            resolver: force_resolution.resolver,
+            result,
+            bond: force_resolution.bond,
+            timestamp: now,
+        });
+
+        log_outcome!(
+            "force_resolve",
+            market = market.key(),
+            resolver = force_resolution.resolver,
+            result = result,
+        );
+
+        Ok(())
+    }
+
+    /// Replace a forced result with the authority's counter-result
+    ///
+    /// Arbitration path: the market authority signs the counter-result and
+    /// the admin approves it, within the dispute window. The forced
+    /// resolver's bond goes to the treasury and the dispute window restarts
+    /// for the new result.
+    pub fn overturn_force_resolution(
+        ctx: Context<OverturnForceResolution>,
+        result: MarketResult,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let now = Clock::get()?.unix_timestamp;
+
+        let dispute_deadline = market.resolution_timestamp
+            .checked_add(DISPUTE_WINDOW_SECS)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(now < dispute_deadline, ErrorCode::DisputeWindowClosed);
+        require!(market.is_valid_result(result), ErrorCode::InvalidResult);
+        require!(
+            result != ctx.accounts.force_resolution.result,
+            ErrorCode::ForceResultUnchanged
+        );
+
+        let bond = ctx.accounts.force_resolution.bond;
+        let record_info = ctx.accounts.force_resolution.to_account_info();
+        let treasury_info = ctx.accounts.treasury.to_account_info();
+        **record_info.try_borrow_mut_lamports()? = record_info
+            .lamports()
+            .checked_sub(bond)
+            .ok_or(ErrorCode::Overflow)?;
+        **treasury_info.try_borrow_mut_lamports()? = treasury_info
+            .lamports()
+            .checked_add(bond)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let forced_result = market.result;
+        market.result = result;
+        market.resolution_timestamp = now;
+
+        emit!(ForceResolutionOverturnedEvent {
+            market: market.key(),
+            resolver: ctx.accounts.force_resolution.resolver,
+            forced_result,
+            result,
+            bond,
+            timestamp: now,
+        });
+
+        log_outcome!(
+            "overturn_force_resolution",
+            market = market.key(),
+            forced = forced_result,
+            result = result,
+        );
+
+        Ok(())
+    }
+
+    /// Return a force-resolver's bond once the result stands
+    /// (permissionless)
+    ///
+    /// The dispute window must have passed without the result being
+    /// overturned. Bond and rent go back to the resolver.
+    pub fn release_force_resolve_bond(ctx: Context<ReleaseForceResolveBond>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let dispute_deadline = market.resolution_timestamp
+            .checked_add(DISPUTE_WINDOW_SECS)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(
+            Clock::get()?.unix_timestamp >= dispute_deadline,
+            ErrorCode::BondLocked
+        );
+
+        let force_resolution = &ctx.accounts.force_resolution;
+        emit!(ForceResolveBondReleasedEvent {
+            market: market.key(),
+            resolver: force_resolution.resolver,
+            bond: force_resolution.bond,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!(
+            "release_force_resolve_bond",
+            market = market.key(),
+            resolver = force_resolution.resolver,
+        );
+
+        Ok(())
+    }
+
+    /// Lock `amount` lamports as resolver stake
+    pub fn stake_resolver(ctx: Context<StakeResolver>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.resolver.to_account_info(),
+                    to: ctx.accounts.resolver_stake.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let stake = &mut ctx.accounts.resolver_stake;
+        stake.resolver = ctx.accounts.resolver.key();
+        stake.amount = stake.amount.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        stake.bump = ctx.bumps.resolver_stake;
+
+        emit!(ResolverStakeChangedEvent {
+            resolver: stake.resolver,
+            amount: stake.amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        log_outcome!("stake_resolver", resolver = stake.resolver, staked = stake.amount);
+
+        Ok(())
+    }
+
+    /// Withdraw resolver stake
+    ///
+    /// Stake stays locked until the dispute window of the resolver's last
+    /// resolution has passed, so it backs every resolution while it can
+    /// still be challenged.
+    pub fn unstake_resolver(ctx: Context<UnstakeResolver>, amount: u64) -> Result<()> {
+        let stake = &mut ctx.accounts.resolver_stake;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(amount <= stake.amount, ErrorCode::InsufficientResolverStake);
+        let unlock_at = stake.last_resolution_at
+            .checked_add(DISPUTE_WINDOW_SECS)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(now >= unlock_at, ErrorCode::ResolverStakeLocked);
+
+        let stake_info = stake.to_account_info();
+        let resolver_info = ctx.accounts.resolver.to_account_info();
+        **stake_info.try_borrow_mut_lamports()? = stake_info
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        **resolver_info.try_borrow_mut_lamports()? = resolver_info
+            .lamports()
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        stake.amount -= amount;
+
+        emit!(ResolverStakeChangedEvent {
+            resolver: stake.resolver,
+            amount: stake.amount,
+            timestamp: now,
+        });
+
+        log_outcome!("unstake_resolver", resolver = stake.resolver, staked = stake.amount);
+
+        Ok(())
+    }
+
+    /// Copy a user's Brier score onto the leaderboard and re-sort it
+    /// (permissionless)
+    ///
+    /// Lower scores rank higher. Once the board holds LEADERBOARD_SIZE
+    /// entries, a new user must beat the last one to get on.
+    pub fn update_leaderboard(ctx: Context<UpdateLeaderboard>) -> Result<()> {
+        let user_stats = &ctx.accounts.user_stats;
+        require!(user_stats.markets_resolved > 0, ErrorCode::UserNotEligible);
+
+        let entry = LeaderboardEntry {
+            user: user_stats.user,
+            brier_score_numerator: user_stats.brier_score_numerator,
+            brier_score_denominator: user_stats.brier_score_denominator,
+            markets_resolved: user_stats.markets_resolved,
+        };
+
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        leaderboard.bump = ctx.bumps.leaderboard;
+        let entries = &mut leaderboard.entries;
+        entries.retain(|existing| existing.user != entry.user);
+        if entries.len() < LEADERBOARD_SIZE {
+            entries.push(entry);
+        } else {
+            let last = entries.last_mut().ok_or(ErrorCode::LeaderboardFull)?;
+            require!(entry.ranks_above(last), ErrorCode::LeaderboardFull);
+            *last = entry;
+        }
+        entries.sort_by(|a, b| {
+            if a.ranks_above(b) {
+                std::cmp::Ordering::Less
+            } else if b.ranks_above(a) {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        });
+        let rank = entries.iter()
+            .position(|existing| existing.user == entry.user)
+            .ok_or(ErrorCode::LeaderboardFull)?;
+
+        emit!(LeaderboardUpdatedEvent {
+            user: entry.user,
+            rank: rank as u32,
+            brier_score_numerator: entry.brier_score_numerator,
+            brier_score_denominator: entry.brier_score_denominator,
+            markets_resolved: entry.markets_resolved,
+        });
+
+        log_outcome!(
+            "update_leaderboard",
+            user = entry.user,
+            rank = rank,
+            entries = entries.len(),
+        );
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Admin Helpers
+// ============================================================================
+
+/// `ProposalV2` discriminant of the SPL Governance account types
+const GOVERNANCE_PROPOSAL_V2: u8 = 14;
+/// ProposalV2 layout: account_type, governance, governing_token_mint, state
+const GOVERNANCE_PROPOSAL_STATE_OFFSET: usize = 1 + 32 + 32;
+/// ProposalState::Succeeded and ::Executing: the vote passed its threshold
+const GOVERNANCE_PROPOSAL_SUCCEEDED: u8 = 3;
+const GOVERNANCE_PROPOSAL_EXECUTING: u8 = 4;
+
+/// Governance account of `realm` governing the Config PDA
+fn governance_address(realm: &Pubkey, config: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"account-governance", realm.as_ref(), config.as_ref()],
+        &SPL_GOVERNANCE_PROGRAM_ID,
+    ).0
+}
+
+/// Accept `signer` if it is the admin or the attached realm's governance
+///
+/// The governance PDA's signature is the authorization: SPL Governance
+/// only signs for it while executing a passed proposal. The proposal,
+/// first in `remaining_accounts`, is only sanity-checked to belong to that
+/// governance and be Succeeded or Executing; it need not be the proposal
+/// carrying this instruction. Returns whether governance signed.
+fn require_admin_or_governance(
+    config: &Account<Config>,
+    signer: &Pubkey,
+    remaining_accounts: &[AccountInfo],
+) -> Result<bool> {
+    if *signer == config.admin {
+        return Ok(false);
+    }
+    let realm = config.governance.ok_or(ErrorCode::Unauthorized)?;
+    require_keys_eq!(
+        *signer,
+        governance_address(&realm, &config.key()),
+        ErrorCode::GovernanceCheckFailed
+    );
+
+    let proposal = remaining_accounts.first().ok_or(ErrorCode::ProposalNotExecuted)?;
+    require_keys_eq!(
+        *proposal.owner,
+        SPL_GOVERNANCE_PROGRAM_ID,
+        ErrorCode::GovernanceCheckFailed
+    );
+    let data = proposal.try_borrow_data()?;
+    require!(
+        data.len() > GOVERNANCE_PROPOSAL_STATE_OFFSET
+            && data[0] == GOVERNANCE_PROPOSAL_V2
+            && data[1..33] == signer.to_bytes(),
+        ErrorCode::GovernanceCheckFailed
+    );
+    require!(
+        matches!(
+            data[GOVERNANCE_PROPOSAL_STATE_OFFSET],
+            GOVERNANCE_PROPOSAL_SUCCEEDED | GOVERNANCE_PROPOSAL_EXECUTING
+        ),
+        ErrorCode::ProposalNotExecuted
+    );
+    Ok(true)
+}
+
+/// Config setters apply directly only while the admin timelock is off
+fn require_admin_timelock_disabled(config: &Config) -> Result<()> {
+    require!(config.admin_timelock_secs == 0, ErrorCode::AdminTimelockEnabled);
+    Ok(())
+}
+
+/// Apply a Config change, from its setter or `execute_admin_action`
+fn apply_admin_action(config: &mut Config, action: AdminAction) -> Result<()> {
+    action.validate()?;
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    match action {
+        AdminAction::SetFeeBps { fee_bps } => {
+            let old_fee_bps = config.fee_bps;
+            config.fee_bps = fee_bps;
+
+            emit!(FeeUpdatedEvent {
+                old_fee_bps,
+                new_fee_bps: fee_bps,
+                timestamp,
+            });
+
+            log_outcome!("set_fee_bps", old = old_fee_bps, new = fee_bps);
+        }
+        AdminAction::SetMinResolverStake { min_resolver_stake } => {
+            let old_min = config.min_resolver_stake;
+            config.min_resolver_stake = min_resolver_stake;
+
+            emit!(MinResolverStakeUpdatedEvent {
+                old_min,
+                new_min: min_resolver_stake,
+                timestamp,
+            });
+
+            log_outcome!(
+                "set_min_resolver_stake",
+                old = old_min,
+                new = min_resolver_stake,
+            );
+        }
+        AdminAction::SetMarketCreationFee { market_creation_fee } => {
+            let old_fee = config.market_creation_fee;
+            config.market_creation_fee = market_creation_fee;
+
+            emit!(MarketCreationFeeUpdatedEvent {
+                old_fee,
+                new_fee: market_creation_fee,
+                timestamp,
+            });
+
+            log_outcome!(
+                "set_market_creation_fee",
+                old = old_fee,
+                new = market_creation_fee,
+            );
+        }
+        AdminAction::SetResolutionBondAmount { resolution_bond_amount } => {
+            let old_amount = config.resolution_bond_amount;
+            config.resolution_bond_amount = resolution_bond_amount;
+
+            emit!(ResolutionBondAmountUpdatedEvent {
+                old_amount,
+                new_amount: resolution_bond_amount,
+                timestamp,
+            });
+
+            log_outcome!(
+                "set_resolution_bond_amount",
+                old = old_amount,
+                new = resolution_bond_amount,
+            );
+        }
+        AdminAction::SetRequiredCreatorStake { required_creator_stake } => {
+            let old_amount = config.required_creator_stake;
+            config.required_creator_stake = required_creator_stake;
+
+            emit!(RequiredCreatorStakeUpdatedEvent {
+                old_amount,
+                new_amount: required_creator_stake,
+                timestamp,
+            });
+
+            log_outcome!(
+                "set_required_creator_stake",
+                old = old_amount,
+                new = required_creator_stake,
+            );
+        }
+        AdminAction::SetForceResolveDelay { force_resolve_delay } => {
+            let old_delay = config.force_resolve_delay;
+            config.force_resolve_delay = force_resolve_delay;
+
+            log_outcome!(
+                "set_force_resolve_delay",
+                old = old_delay,
+                new = force_resolve_delay,
+            );
+        }
+        AdminAction::SetUnclaimedGracePeriod { unclaimed_grace_period } => {
+            let old_period = config.unclaimed_grace_period;
+            config.unclaimed_grace_period = unclaimed_grace_period;
+
+            emit!(UnclaimedGracePeriodUpdatedEvent {
+                old_period,
+                new_period: unclaimed_grace_period,
+                timestamp,
+            });
+
+            log_outcome!(
+                "set_unclaimed_grace_period",
+                old = old_period,
+                new = unclaimed_grace_period,
+            );
+        }
+        AdminAction::SetRakebackSchedule { thresholds, rakeback_bps } => {
+            config.rakeback_thresholds = thresholds;
+            config.rakeback_bps = rakeback_bps;
+
+            emit!(RakebackScheduleUpdatedEvent {
+                thresholds,
+                rakeback_bps,
+                timestamp,
+            });
+
+            log_outcome!(
+                "set_rakeback_schedule",
+                thresholds = thresholds,
+                rakeback_bps = rakeback_bps,
+            );
+        }
+        AdminAction::SetInsuranceFeeBps { insurance_fee_bps } => {
+            let old_bps = config.insurance_fee_bps;
+            config.insurance_fee_bps = insurance_fee_bps;
+
+            emit!(InsuranceFeeUpdatedEvent {
+                old_bps,
+                new_bps: insurance_fee_bps,
+                timestamp,
+            });
+
+            log_outcome!(
+                "set_insurance_fee_bps",
+                old = old_bps,
+                new = insurance_fee_bps,
+            );
+        }
+        AdminAction::SetVaultTolerance { vault_tolerance } => {
+            let old_tolerance = config.vault_tolerance;
+            config.vault_tolerance = vault_tolerance;
+
+            emit!(VaultToleranceUpdatedEvent {
+                old_tolerance,
+                new_tolerance: vault_tolerance,
+                timestamp,
+            });
+
+            log_outcome!(
+                "set_vault_tolerance",
+                old = old_tolerance,
+                new = vault_tolerance,
+            );
+        }
+        AdminAction::SetGovernance { realm } => {
+            let old_realm = config.governance.replace(realm);
+            let (config_key, _) = Pubkey::find_program_address(&[b"config"], &crate::ID);
+            let governance = governance_address(&realm, &config_key);
+
+            emit!(GovernanceUpdatedEvent {
+                old_realm,
+                realm,
+                governance,
+                timestamp,
+            });
+
+            log_outcome!(
+                "set_governance",
+                realm = realm,
+                governance = governance,
+            );
+        }
+        AdminAction::SetAdminTimelock { admin_timelock_secs } => {
+            let old_delay = config.admin_timelock_secs;
+            config.admin_timelock_secs = admin_timelock_secs;
+
+            emit!(AdminTimelockUpdatedEvent {
+                old_delay,
+                new_delay: admin_timelock_secs,
+                timestamp,
+            });
+
+            log_outcome!(
+                "set_admin_timelock",
+                old = old_delay,
+                new = admin_timelock_secs,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Betting Helpers
+// ============================================================================
+
+/// Accounts a bet touches, borrowed from `PlaceBet` or `IncreasePosition`
+struct BetAccounts<'a, 'info> {
+    market: &'a mut Account<'info, Market>,
+    config: &'a Config,
+    stats: &'a mut ProtocolStats,
+    position: &'a mut UserPosition,
+    user_stats: &'a mut UserStats,
+    user_limits: Option<&'a mut Account<'info, UserBettingLimits>>,
+    whitelist: Option<&'a Whitelist>,
+    vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    sol_vault: Option<&'a SystemAccount<'info>>,
+    fee_vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    sol_fee_vault: Option<&'a SystemAccount<'info>>,
+    insurance_vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    user_token_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    usdc_mint: Option<&'a InterfaceAccount<'info, Mint>>,
+    user: &'a Signer<'info>,
+    token_program: Option<&'a Interface<'info, TokenInterface>>,
+    system_program: &'a Program<'info, System>,
+}
+
+/// CRC-32 (IEEE) that `BetPlacedEvent.checksum` carries
+///
+/// Covers market || user || amount || payload_hash || position_total ||
+/// total_pool_after, integers little-endian, so indexers can spot a
+/// corrupted event.
+fn bet_event_checksum(
+    market: &Pubkey,
+    user: &Pubkey,
+    amount: u64,
+    payload_hash: &[u8; 32],
+    position_total: u64,
+    total_pool_after: u64,
+) -> u32 {
+    let fields: [&[u8]; 6] = [
+        market.as_ref(),
+        user.as_ref(),
+        &amount.to_le_bytes(),
+        payload_hash,
+        &position_total.to_le_bytes(),
+        &total_pool_after.to_le_bytes(),
+    ];
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in fields.iter().flat_map(|field| field.iter()) {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+/// The `yes_pool` (side 0) or `no_pool` (side 1) of a binary market
+fn side_pool(market: &mut Market, side: u8) -> Result<&mut u64> {
+    match MarketResult::from_outcome(side, market.num_outcomes)? {
+        MarketResult::Yes => Ok(&mut market.yes_pool),
+        MarketResult::No => Ok(&mut market.no_pool),
+        _ => err!(ErrorCode::InvalidOutcomeCount),
+    }
+}
+
+/// Fail with `SlippageExceeded` if a position is under `min_share_bps`
+/// of the pool it was just added to
+fn require_pool_share(position_amount: u64, total_pool: u64, min_share_bps: u16) -> Result<()> {
+    require!(min_share_bps <= 10_000, ErrorCode::InvalidSlippageBps);
+    let share_bps = (position_amount as u128)
+        .checked_mul(10_000)
+        .ok_or(ErrorCode::Overflow)?
+        .checked_div(total_pool as u128)
+        .ok_or(ErrorCode::Overflow)?;
+    require!(share_bps >= min_share_bps as u128, ErrorCode::SlippageExceeded);
+    Ok(())
+}
+
+/// Move a bet into custody and credit it to the position and pool
+///
+/// Checks the market is open, skims the protocol fee, enforces the bet,
+/// user and pool limits against what custody received, and updates the
+/// stats. Returns `(fee, received)`.
+fn deposit_bet(bet: BetAccounts, amount: u64) -> Result<(u64, u64)> {
+    let market = bet.market;
+
+    // Ensure market hasn't expired and is still taking bets
+    let now = Clock::get()?.unix_timestamp;
+    require!(now < market.expiry_timestamp, ErrorCode::MarketExpired);
+    require!(now < market.betting_close_timestamp, ErrorCode::BettingClosed);
+
+    // Ensure market hasn't been resolved
+    require!(!market.resolved, ErrorCode::MarketResolved);
+
+    // Ensure betting hasn't been halted by the authority or the admin
+    require!(!market.paused, ErrorCode::MarketPaused);
+    require!(!bet.config.protocol_paused, ErrorCode::ProtocolPaused);
+
+    // Whitelisted markets only take bets from listed addresses
+    if market.whitelist_enabled {
+        let listed = bet.whitelist
+            .map_or(false, |whitelist| whitelist.entries.contains(&bet.user.key()));
+        require!(listed, ErrorCode::UserNotWhitelisted);
+    }
+
+    // Ensure custody exists (deferred markets need init_vault first)
+    require!(market.vault_initialized, ErrorCode::VaultNotInitialized);
+
+    // Split off the protocol fee, less the user's rakeback; only the net
+    // amount enters the pool
+    let fee = compute_user_fee(bet.config, bet.user_stats.rakeback_tier, amount)?;
+    let net_amount = amount - fee;
+    require!(amount >= market.min_bet, ErrorCode::BetBelowMinimum);
+
+    // Transfer the stake from user into market custody. Limits below
+    // use what custody received, which is less than `net_amount` for
+    // Token-2022 mints with a transfer fee.
+    let custody = Custody {
+        vault: bet.vault,
+        sol_vault: bet.sol_vault,
+        usdc_mint: bet.usdc_mint,
+        token_program: bet.token_program,
+        system_program: bet.system_program,
+    };
+    let received = custody.deposit_received(
+        market,
+        bet.user_token_account,
+        bet.user.to_account_info(),
+        net_amount,
+    )?;
+
+    // Part of the fee on token markets goes to the insurance fund
+    let insurance_cut = match market.currency {
+        MarketCurrency::Usdc => compute_fee(fee, bet.config.insurance_fee_bps)?,
+        MarketCurrency::Sol => 0,
+    };
+    let mut fee_received: u64 = 0;
+    if fee > insurance_cut {
+        let fee_custody = Custody {
+            vault: bet.fee_vault,
+            sol_vault: bet.sol_fee_vault,
+            ..custody
+        };
+        fee_received = fee_custody.deposit_received(
+            market,
+            bet.user_token_account,
+            bet.user.to_account_info(),
+            fee - insurance_cut,
+        )?;
+        market.fees_collected = market.fees_collected
+            .checked_add(fee_received)
+            .ok_or(ErrorCode::Overflow)?;
+    }
+    if insurance_cut > 0 {
+        let insurance_custody = Custody {
+            vault: Some(bet.insurance_vault.ok_or(ErrorCode::InsuranceVaultMissing)?),
+            sol_vault: None,
+            ..custody
+        };
+        insurance_custody.deposit(
+            market,
+            bet.user_token_account,
+            bet.user.to_account_info(),
+            insurance_cut,
+        )?;
+    }
+
+    // Enforce per-market bet limits (max_bet == 0 means unlimited)
+    let position = bet.position;
+    let new_position_amount = position.amount
+        .checked_add(received)
+        .ok_or(ErrorCode::Overflow)?;
+    require!(
+        market.max_bet == 0 || new_position_amount <= market.max_bet,
+        ErrorCode::BetAboveMaximum
+    );
+
+    // Enforce the user's admin-set caps, when they have any
+    if let Some(user_limits) = bet.user_limits {
+        user_limits.apply_bet(new_position_amount, amount, Clock::get()?.unix_timestamp)?;
+    }
+
+    // Enforce the pool cap against the post-transfer total (0 = unlimited)
+    let new_total_pool = market.total_pool
+        .checked_add(received)
+        .ok_or(ErrorCode::Overflow)?;
+    require!(
+        market.max_pool == 0 || new_total_pool <= market.max_pool,
+        ErrorCode::PoolCapExceeded
+    );
+
+    market.total_pool = new_total_pool;
+    position.amount = new_position_amount;
+    // A revealed position (see `change_side`) keeps its side pool in step
+    if position.side_revealed {
+        let pool = side_pool(market, position.revealed_side)?;
+        *pool = pool.checked_add(received).ok_or(ErrorCode::Overflow)?;
+    }
+    position.fees_paid = position.fees_paid
+        .checked_add(fee)
+        .ok_or(ErrorCode::Overflow)?;
+
+    let stats = bet.stats;
+    stats.total_bets = stats.total_bets
+        .checked_add(1)
+        .ok_or(ErrorCode::Overflow)?;
+    stats.total_volume = stats.total_volume
+        .checked_add(amount)
+        .ok_or(ErrorCode::Overflow)?;
+    stats.total_fees_collected = stats.total_fees_collected
+        .checked_add(fee_received)
+        .ok_or(ErrorCode::Overflow)?;
+
+    let user_stats = bet.user_stats;
+    user_stats.total_wagered = user_stats.total_wagered
+        .checked_add(amount)
+        .ok_or(ErrorCode::Overflow)?;
+
+    // Tiers only go up; the new one applies from the next bet
+    let tier = RakebackTier::for_volume(bet.config, user_stats.total_wagered);
+    if tier > user_stats.rakeback_tier {
+        emit!(TierUpgradeEvent {
+            user: user_stats.user,
+            old_tier: user_stats.rakeback_tier,
+            new_tier: tier,
+            total_wagered: user_stats.total_wagered,
+        });
+        user_stats.rakeback_tier = tier;
+    }
+
+    Ok((fee, received))
+}
+
+// ============================================================================
+// Market Resolution Helpers
+// ============================================================================
+
+/// Record a market result
+///
+/// Shared by every resolution path (manual and oracle-driven) so the
+/// expiry/double-resolution checks and the emitted event stay identical.
+/// A commitment must be revealed and a proposal finalized first: those
+/// paths clear the field before calling in.
+fn apply_resolution(
+    market: &mut Account<Market>,
+    stats: &mut ProtocolStats,
+    result: MarketResult,
+) -> Result<()> {
+    require!(
+        market.is_valid_result(result),
+        ErrorCode::InvalidResult
+    );
+    // Committed markets can only be resolved by revealing the commitment
+    require!(
+        market.resolution_commitment.is_none(),
+        ErrorCode::ResolutionCommitmentRequired
+    );
+    // A proposed result must run its challenge window
+    require!(
+        market.proposed_outcome.is_none(),
+        ErrorCode::ResolutionAlreadyProposed
+    );
+
+    let now = Clock::get()?.unix_timestamp;
+
+    // Ensure market has expired
+    require!(
+        now >= market.expiry_timestamp,
+        ErrorCode::MarketNotExpired
+    );
+
+    // Ensure not already resolved
+    require!(!market.resolved, ErrorCode::AlreadyResolved);
+
+    market.resolved = true;
+    market.result = result;
+    market.resolution_timestamp = now;
+    stats.active_markets = stats.active_markets
+        .checked_sub(1)
+        .ok_or(ErrorCode::Overflow)?;
+
+    // Side pools are only tracked for binary markets, and stay 0 until
+    // record_pool_split or reveal_side fill them in
+    let (winning_pool, losing_pool) = match result {
+        MarketResult::Yes => (market.yes_pool, market.no_pool),
+        MarketResult::No => (market.no_pool, market.yes_pool),
+        _ => (0, 0),
+    };
+
+    emit!(MarketResolvedEvent {
+        market: market.key(),
+        result,
+        total_pool: market.total_pool,
+        winning_pool,
+        losing_pool,
+        evidence_hash: market.evidence_hash,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Fail if an oracle decides this market's result
+///
+/// Resolver paths call this so a configured Pyth feed, `market.oracle` or
+/// attached Switchboard feed cannot be overridden by hand; `force_resolve`
+/// stays available to the admin.
+fn require_manual_resolution(market: &Market) -> Result<()> {
+    require!(
+        market.pyth_price_feed.is_none()
+            && market.oracle.is_none()
+            && market.switchboard_feed.is_none(),
+        ErrorCode::OracleResolutionRequired
+    );
+    Ok(())
+}
+
+/// A `ResolutionCriteria` price in UI units, scaled to a Pyth price with
+/// exponent `expo` (e.g. 100000.0 at expo -8 is 10^13)
+fn ui_amount_to_price(ui_amount: f64, expo: i32) -> Result<i64> {
+    let scaled = (ui_amount * 10f64.powi(-expo)).round();
+    require!(
+        scaled.is_finite() && scaled <= i64::MAX as f64,
+        ErrorCode::Overflow
+    );
+    Ok(scaled as i64)
+}
+
+/// End of the oracle resolution window opening at `observed_at`
+///
+/// Fails once the window has closed.
+fn require_oracle_window_open(observed_at: i64) -> Result<i64> {
+    let window_end = observed_at
+        .checked_add(ORACLE_RESOLUTION_WINDOW_SECS)
+        .ok_or(ErrorCode::Overflow)?;
+    require!(
+        Clock::get()?.unix_timestamp <= window_end,
+        ErrorCode::OracleWindowClosed
+    );
+    Ok(window_end)
+}
+
+/// Latest Switchboard result, at SWITCHBOARD_THRESHOLD_DECIMALS decimals
+///
+/// Rejects rounds that did not open inside the oracle resolution window
+/// starting at `window_start`, rounds older than `max_staleness` seconds
+/// and rounds whose standard deviation exceeds
+/// SWITCHBOARD_MAX_CONFIDENCE_BPS of the result.
+fn read_switchboard_value(
+    aggregator: &AccountInfo,
+    window_start: i64,
+    max_staleness: i64,
+) -> Result<i128> {
+    let window_end = require_oracle_window_open(window_start)?;
+    let data = aggregator.try_borrow_data()?;
+    let aggregator = AggregatorAccountData::new_from_bytes(&data)
+        .map_err(|_| ErrorCode::SwitchboardReadError)?;
+    let round_opened_at = aggregator.latest_confirmed_round.round_open_timestamp;
+    require!(
+        round_opened_at >= window_start && round_opened_at <= window_end,
+        ErrorCode::SwitchboardFeedStale
+    );
+    aggregator
+        .check_staleness(Clock::get()?.unix_timestamp, max_staleness)
+        .map_err(|_| ErrorCode::SwitchboardFeedStale)?;
+    let value = aggregator
+        .get_result()
+        .map_err(|_| ErrorCode::SwitchboardReadError)?;
+    let value = rescale_switchboard_decimal(&value, SWITCHBOARD_THRESHOLD_DECIMALS)?;
+    let std_deviation = rescale_switchboard_decimal(
+        &aggregator.latest_confirmed_round.std_deviation,
+        SWITCHBOARD_THRESHOLD_DECIMALS,
+    )?;
+
+    // Reject rounds whose oracles disagree too much to trust
+    require!(
+        std_deviation.unsigned_abs() * 10_000
+            <= value.unsigned_abs() * SWITCHBOARD_MAX_CONFIDENCE_BPS,
+        ErrorCode::SwitchboardFeedStale
+    );
+
+    Ok(value)
+}
+
+/// Express a Switchboard decimal as a fixed-point integer with `decimals`
+///
+/// Extra precision is truncated toward zero.
+fn rescale_switchboard_decimal(value: &SwitchboardDecimal, decimals: u32) -> Result<i128> {
+    if value.scale >= decimals {
+        let divisor = 10i128
+            .checked_pow(value.scale - decimals)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(value.mantissa / divisor)
+    } else {
+        let multiplier = 10i128
+            .checked_pow(decimals - value.scale)
+            .ok_or(ErrorCode::Overflow)?;
+        value.mantissa
+            .checked_mul(multiplier)
+            .ok_or(ErrorCode::Overflow.into())
+    }
+}
+
+/// Resolver resolution shared by `resolve_market`, `resolve_with_reveal`
+/// and `finalize_resolution`
+///
+/// Enforces the resolver stake, records the result and pays the creator
+/// fee through `collect_creator_fee`. Returns the creator fee charged.
+///
+/// Conditional markets pass their `parent`: once its result is final, a
+/// result other than the required one voids the market instead (no
+/// creator fee).
+fn resolve_as_authority(
+    accounts: &mut ResolveMarket,
+    parent: Option<&Account<Market>>,
+    winning_outcome: u8,
+) -> Result<u64> {
+    let min_stake = accounts.config.min_resolver_stake;
+    if min_stake > 0 {
+        let stake = accounts.resolver_stake.as_mut()
+            .ok_or(ErrorCode::InsufficientResolverStake)?;
+        require!(stake.amount >= min_stake, ErrorCode::InsufficientResolverStake);
+        stake.last_resolution_at = Clock::get()?.unix_timestamp;
+    }
+
+    if let Some(parent) = parent {
+        require_parent_final(parent)?;
+        if Some(parent.result) != accounts.market.parent_required_outcome {
+            void_market(&mut accounts.market, &mut accounts.stats, parent)?;
+            return Ok(0);
+        }
+    }
+
+    let market = &mut accounts.market;
+    let result = MarketResult::from_outcome(winning_outcome, market.num_outcomes)?;
+    apply_resolution(market, &mut accounts.stats, result)?;
+
+    let custody = Custody {
+        vault: accounts.vault.as_ref(),
+        sol_vault: accounts.sol_vault.as_ref(),
+        usdc_mint: accounts.usdc_mint.as_ref(),
+        token_program: accounts.token_program.as_ref(),
+        system_program: &accounts.system_program,
+    };
+    collect_creator_fee(
+        market,
+        &custody,
+        accounts.config.vault_tolerance,
+        accounts.creator_fee_account.as_ref(),
+        accounts.creator.as_ref(),
+    )
+}
+
+/// Reconcile custody and take the creator fee from a just-resolved market
+///
+/// Called by every resolver after `apply_resolution`, so oracle-resolved
+/// markets pay their creator like resolver-resolved ones. The fee goes to
+/// `creator_fee_account` on USDC markets and to `creator` on SOL markets.
+/// Returns the creator fee charged.
+fn collect_creator_fee<'info>(
+    market: &mut Account<'info, Market>,
+    custody: &Custody<'_, 'info>,
+    tolerance: Option<u64>,
+    creator_fee_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    creator: Option<&SystemAccount<'info>>,
+) -> Result<u64> {
+    // Reconcile before the creator fee leaves the vault
+    reconcile_vault(market, custody, tolerance)?;
+
+    // Creator fee comes out of the pool before the MXE computes payouts,
+    // so payouts are sized against what is actually left in the vault.
+    // apply_resolution rejects a second resolve, so this runs once.
+    let creator_fee = compute_fee(market.total_pool, market.creator_fee_bps)?;
+    if creator_fee > 0 {
+        // The resolver may be a delegate or anyone; the fee belongs to the
+        // creator. The wallet slot is unused for USDC markets
+        let sol_destination = match market.currency {
+            MarketCurrency::Usdc => market.to_account_info(),
+            MarketCurrency::Sol => creator
+                .ok_or(ErrorCode::CurrencyMismatch)?
+                .to_account_info(),
+        };
+        market.total_pool -= creator_fee;
+        market.creator_fee_collected = creator_fee;
+
+        custody.pay_out(market, creator_fee_account, sol_destination, creator_fee)?;
+
+        emit!(CreatorFeeCollectedEvent {
+            market: market.key(),
+            creator: market.authority,
+            amount: creator_fee,
+            remaining_pool: market.total_pool,
+        });
+    }
+
+    Ok(creator_fee)
+}
+
+/// Compare what custody holds against `market.total_pool`
+///
+/// Emits the delta (positive when the vault holds more). Without a
+/// `tolerance` the check only reports, and custody may be omitted.
+fn reconcile_vault(
+    market: &Account<Market>,
+    custody: &Custody,
+    tolerance: Option<u64>,
+) -> Result<()> {
+    let vault_balance = match (custody.balance(market), tolerance) {
+        (Ok(balance), _) => balance,
+        (Err(_), None) => return Ok(()),
+        (Err(err), Some(_)) => return Err(err),
+    };
+    let delta = i64::try_from(vault_balance as i128 - market.total_pool as i128)
+        .map_err(|_| ErrorCode::Overflow)?;
+
+    emit!(VaultReconciliationEvent {
+        market: market.key(),
+        vault_balance,
+        expected_balance: market.total_pool,
+        delta,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    if let Some(tolerance) = tolerance {
+        require!(delta.unsigned_abs() <= tolerance, ErrorCode::VaultInconsistent);
+    }
+    Ok(())
+}
+
+/// Void a conditional market whose parent missed the required outcome
+///
+/// Resolves to `MarketResult::None`: nobody wins, and every position can
+/// reclaim its stake with `claim_refund` after the dispute window.
+fn void_market(
+    market: &mut Account<Market>,
+    stats: &mut ProtocolStats,
+    parent: &Account<Market>,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(now >= market.expiry_timestamp, ErrorCode::MarketNotExpired);
+    require!(!market.resolved, ErrorCode::AlreadyResolved);
+
+    market.resolved = true;
+    market.result = MarketResult::None;
+    market.resolution_timestamp = now;
+    stats.active_markets = stats.active_markets
+        .checked_sub(1)
+        .ok_or(ErrorCode::Overflow)?;
+
+    emit!(MarketVoidedEvent {
+        market: market.key(),
+        parent_market: parent.key(),
+        parent_result: parent.result,
+        total_pool: market.total_pool,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Parent of a conditional market, passed as `remaining_accounts[0]`
+///
+/// None for markets without a parent.
+fn load_parent_market<'info>(
+    market: &Market,
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<Option<Account<'info, Market>>> {
+    let parent_key = match market.parent_market {
+        Some(key) => key,
+        None => return Ok(None),
+    };
+    let info = remaining_accounts
+        .first()
+        .ok_or(ErrorCode::ParentMarketMismatch)?;
+    require_keys_eq!(info.key(), parent_key, ErrorCode::ParentMarketMismatch);
+    Ok(Some(Account::try_from(info)?))
+}
+
+/// Tags are 1 to MAX_TAG_LEN bytes
+fn require_valid_tag(tag: &str) -> Result<()> {
+    require!(
+        !tag.is_empty() && tag.len() <= MarketTags::MAX_TAG_LEN,
+        ErrorCode::InvalidTag
+    );
+    Ok(())
+}
+
+/// Load a TagIndex from `remaining_accounts`, checking it indexes `tag`
+fn load_tag_index<'info>(
+    info: &'info AccountInfo<'info>,
+    tag: &str,
+) -> Result<Account<'info, TagIndex>> {
+    let tag_index: Account<'info, TagIndex> = Account::try_from(info)?;
+    require!(tag_index.tag_hash == tag_hash(tag), ErrorCode::TagIndexMismatch);
+    Ok(tag_index)
+}
+
+/// Conditional markets take bets until their parent resolves the wrong way
+fn require_parent_open<'info>(
+    market: &Market,
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    if let Some(parent) = load_parent_market(market, remaining_accounts)? {
+        require!(
+            !parent.resolved || Some(parent.result) == market.parent_required_outcome,
+            ErrorCode::ConditionalRequirementNotMet
+        );
+    }
+    Ok(())
+}
+
+/// A parent's result is final once its dispute window has passed
+///
+/// Until then `overturn_force_resolution` can still change it.
+fn require_parent_final(parent: &Market) -> Result<()> {
+    require!(parent.resolved, ErrorCode::ParentMarketNotResolved);
+    let final_at = parent.resolution_timestamp
+        .checked_add(DISPUTE_WINDOW_SECS)
+        .ok_or(ErrorCode::Overflow)?;
+    require!(
+        Clock::get()?.unix_timestamp > final_at,
+        ErrorCode::ParentNotFinal
+    );
+    Ok(())
+}
+
+/// Conditional markets pay out only once their parent's result is final
+fn require_parent_settled<'info>(
+    market: &Market,
+    remaining_accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    if let Some(parent) = load_parent_market(market, remaining_accounts)? {
+        require_parent_final(&parent)?;
+    }
+    Ok(())
+}
+
+/// Commitment to a future outcome: keccak256(winning_outcome || salt)
+fn compute_resolution_commitment(winning_outcome: u8, salt: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[&[winning_outcome], salt]).to_bytes()
+}
+
+// ============================================================================
+// Fee Helpers
+// ============================================================================
+
+/// Protocol fee on `amount` at `fee_bps`, rounded down
+///
+/// Rounding down means dust always stays with the bettor's stake.
+fn compute_fee(amount: u64, fee_bps: u16) -> Result<u64> {
+    let fee = (amount as u128)
+        .checked_mul(fee_bps as u128)
+        .ok_or(ErrorCode::Overflow)?
+        / 10_000;
+    Ok(fee as u64)
+}
+
+/// Protocol fee on a bet of `amount` for a user in `tier`: `fee_bps`
+/// less the tier's rakeback
+fn compute_user_fee(config: &Config, tier: RakebackTier, amount: u64) -> Result<u64> {
+    let rakeback_bps = config.rakeback_bps[tier as usize];
+    compute_fee(amount, config.fee_bps.saturating_sub(rakeback_bps))
+}
+
+/// Winning share of `total_pool` for a stake of `amount`, rounded down
+///
+/// Returns 0 when nobody backed the winning side.
+fn compute_pro_rata_payout(amount: u64, total_pool: u64, winning_pool: u64) -> Result<u64> {
+    if winning_pool == 0 {
+        return Ok(0);
+    }
+    let payout = (amount as u128)
+        .checked_mul(total_pool as u128)
+        .ok_or(ErrorCode::Overflow)?
+        / winning_pool as u128;
+    Ok(u64::try_from(payout).map_err(|_| ErrorCode::Overflow)?)
+}
+
+// ============================================================================
+// Vault Transfer Helpers
+// ============================================================================
+
+/// Reject Token-2022 transfers for markets that did not opt in at creation
+///
+/// Token-2022 mints can carry transfer hooks and fees, so a market has to be
+/// created with `is_token22 = true` before its vault may move them.
+fn require_supported_token_program(market: &Market, token_program: &Pubkey) -> Result<()> {
+    if *token_program == token_2022::ID {
+        require!(market.is_token22, ErrorCode::Token22NotSupported);
+    }
+    Ok(())
+}
+
+/// Move `amount` tokens from a user token account into the market vault
+fn transfer_to_vault<'info>(
+    market: &Market,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    usdc_mint: &InterfaceAccount<'info, Mint>,
+    authority: AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+    amount: u64,
+) -> Result<()> {
+    require_supported_token_program(market, &token_program.key())?;
+
+    let transfer_ctx = CpiContext::new(
+        token_program.to_account_info(),
+        TransferChecked {
+            from: from.to_account_info(),
+            mint: usdc_mint.to_account_info(),
+            to: vault.to_account_info(),
+            authority,
+        },
+    );
+    token_interface::transfer_checked(transfer_ctx, amount, usdc_mint.decimals)
+}
+
+/// Pay `amount` tokens out of a PDA-owned vault
+///
+/// `seeds` are the signer seeds of the vault PDA, which is its own
+/// token authority.
+fn transfer_from_vault<'info>(
+    market: &Market,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    usdc_mint: &InterfaceAccount<'info, Mint>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    token_program: &Interface<'info, TokenInterface>,
+    seeds: &[&[u8]],
+    amount: u64,
+) -> Result<()> {
+    require_supported_token_program(market, &token_program.key())?;
+
+    let signer = &[seeds];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        TransferChecked {
+            from: vault.to_account_info(),
+            mint: usdc_mint.to_account_info(),
+            to: to.to_account_info(),
+            authority: vault.to_account_info(),
+        },
+        signer,
+    );
+    token_interface::transfer_checked(transfer_ctx, amount, usdc_mint.decimals)
+}
+
+/// Move `amount` tokens from the insurance vault into a market vault
+///
+/// The InsuranceFund PDA is the insurance vaults' token authority.
+fn draw_insurance<'info>(
+    market: &Market,
+    insurance_fund: &mut Account<'info, InsuranceFund>,
+    insurance_vault: &InterfaceAccount<'info, TokenAccount>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    usdc_mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    amount: u64,
+) -> Result<()> {
+    require!(insurance_vault.amount > 0, ErrorCode::InsuranceFundEmpty);
+    require!(amount <= insurance_vault.amount, ErrorCode::DrawdownExceedsFund);
+    require_supported_token_program(market, &token_program.key())?;
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        TransferChecked {
+            from: insurance_vault.to_account_info(),
+            mint: usdc_mint.to_account_info(),
+            to: vault.to_account_info(),
+            authority: insurance_fund.to_account_info(),
+        },
+        &[&[b"insurance", &[insurance_fund.bump]]],
+    );
+    token_interface::transfer_checked(transfer_ctx, amount, usdc_mint.decimals)?;
+
+    insurance_fund.total_drawn = insurance_fund.total_drawn
+        .checked_add(amount)
+        .ok_or(ErrorCode::Overflow)?;
+    Ok(())
+}
+
+/// Move `amount` lamports from a user into the market's SOL vault
+///
+/// The SOL vault is a system-owned PDA with no data, so the first deposit
+/// must cover its rent-exempt minimum or the runtime rejects the transfer.
+fn transfer_to_sol_vault<'info>(
+    from: AccountInfo<'info>,
+    sol_vault: &SystemAccount<'info>,
+    system_program: &Program<'info, System>,
+    amount: u64,
+) -> Result<()> {
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from,
+                to: sol_vault.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
+
+/// Pay `amount` lamports out of a system-owned PDA vault via invoke_signed
+///
+/// A payout may leave the vault at zero or above its rent-exempt minimum,
+/// but not in between.
+fn transfer_from_sol_vault<'info>(
+    sol_vault: &SystemAccount<'info>,
+    to: AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    seeds: &[&[u8]],
+    amount: u64,
+) -> Result<()> {
+    let signer = &[seeds];
+
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: sol_vault.to_account_info(),
+                to,
+            },
+            signer,
+        ),
+        amount,
+    )
+}
+
+/// Close an empty PDA-owned token vault, sending its rent to `destination`
+fn close_vault<'info>(
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    destination: AccountInfo<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let signer = &[seeds];
+
+    token_interface::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: vault.to_account_info(),
+            destination,
+            authority: vault.to_account_info(),
+        },
+        signer,
+    ))
+}
+
+/// Fund a wSOL vault with `amount` lamports from `authority`
+///
+/// Lamports sent to a native token account only count as wSOL once the
+/// account is synced, so this replaces wrapping into a temporary account.
+fn wrap_sol_into_vault<'info>(
+    authority: AccountInfo<'info>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    token_program: &Interface<'info, TokenInterface>,
+    system_program: &Program<'info, System>,
+    amount: u64,
+) -> Result<()> {
+    require_keys_eq!(vault.mint, native_mint::ID, ErrorCode::CurrencyMismatch);
+
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: authority,
+                to: vault.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    token_interface::sync_native(CpiContext::new(
+        token_program.to_account_info(),
+        SyncNative {
+            account: vault.to_account_info(),
+        },
+    ))
+}
+
+/// Borrowed view of a market's custody accounts
+///
+/// USDC markets pass the token vault, mint and token program; SOL markets
+/// pass the SOL vault. Missing accounts for the market's currency surface
+/// as `CurrencyMismatch`.
+struct Custody<'a, 'info> {
+    vault: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    sol_vault: Option<&'a SystemAccount<'info>>,
+    usdc_mint: Option<&'a InterfaceAccount<'info, Mint>>,
+    token_program: Option<&'a Interface<'info, TokenInterface>>,
+    system_program: &'a Program<'info, System>,
+}
+
+impl<'a, 'info> Custody<'a, 'info> {
+    /// Funds currently held for the market
+    fn balance(&self, market: &Market) -> Result<u64> {
+        Ok(match market.currency {
+            MarketCurrency::Usdc => self.vault.ok_or(ErrorCode::CurrencyMismatch)?.amount,
+            MarketCurrency::Sol => self.sol_vault.ok_or(ErrorCode::CurrencyMismatch)?.lamports(),
+        })
+    }
+
+    /// Move `amount` from a user into market custody
+    ///
+    /// `authority` signs the token transfer (USDC) or funds the lamport
+    /// transfer (SOL). A USDC-currency market without `from` is funded in
+    /// lamports, which only works if its mint is wSOL.
+    fn deposit(
+        &self,
+        market: &Market,
+        from: Option<&InterfaceAccount<'info, TokenAccount>>,
+        authority: AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        match market.currency {
+            MarketCurrency::Usdc => match from {
+                Some(from) => transfer_to_vault(
+                    market,
+                    from,
+                    self.vault.ok_or(ErrorCode::CurrencyMismatch)?,
+                    self.usdc_mint.ok_or(ErrorCode::CurrencyMismatch)?,
+                    authority,
+                    self.token_program.ok_or(ErrorCode::CurrencyMismatch)?,
+                    amount,
+                ),
+                None => wrap_sol_into_vault(
+                    authority,
+                    self.vault.ok_or(ErrorCode::CurrencyMismatch)?,
+                    self.token_program.ok_or(ErrorCode::CurrencyMismatch)?,
+                    self.system_program,
+                    amount,
+                ),
+            },
+            MarketCurrency::Sol => transfer_to_sol_vault(
+                authority,
+                self.sol_vault.ok_or(ErrorCode::CurrencyMismatch)?,
+                self.system_program,
+                amount,
+            ),
+        }
+    }
+
+    /// Like `deposit`, but return what custody actually received
+    ///
+    /// Token-2022 mints with a transfer fee deliver less than `amount`.
+    fn deposit_received(
+        &self,
+        market: &Market,
+        from: Option<&InterfaceAccount<'info, TokenAccount>>,
+        authority: AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<u64> {
+        let before = self.live_balance(market)?;
+        self.deposit(market, from, authority, amount)?;
+        let after = self.live_balance(market)?;
+        Ok(after.checked_sub(before).ok_or(ErrorCode::Overflow)?)
+    }
+
+    /// Like `balance`, but re-read from account data so CPIs made earlier
+    /// in this instruction are reflected
+    fn live_balance(&self, market: &Market) -> Result<u64> {
+        Ok(match market.currency {
+            MarketCurrency::Usdc => {
+                let info = self.vault.ok_or(ErrorCode::CurrencyMismatch)?.to_account_info();
+                let data = info.try_borrow_data()?;
+                TokenAccount::try_deserialize(&mut &data[..])?.amount
+            }
+            MarketCurrency::Sol => self.sol_vault.ok_or(ErrorCode::CurrencyMismatch)?.lamports(),
+        })
+    }
+
+    /// Pay `amount` out of market custody
+    ///
+    /// USDC goes to `token_destination`; SOL goes to `sol_destination`.
+    fn pay_out(
+        &self,
+        market: &Account<'info, Market>,
+        token_destination: Option<&InterfaceAccount<'info, TokenAccount>>,
+        sol_destination: AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        let market_key = market.key();
+        match market.currency {
+            MarketCurrency::Usdc => transfer_from_vault(
+                market,
+                self.vault.ok_or(ErrorCode::CurrencyMismatch)?,
+                self.usdc_mint.ok_or(ErrorCode::CurrencyMismatch)?,
+                token_destination.ok_or(ErrorCode::CurrencyMismatch)?,
+                self.token_program.ok_or(ErrorCode::CurrencyMismatch)?,
+                &[b"vault", market_key.as_ref(), &[market.vault_bump]],
+                amount,
+            ),
+            MarketCurrency::Sol => transfer_from_sol_vault(
+                self.sol_vault.ok_or(ErrorCode::CurrencyMismatch)?,
+                sol_destination,
+                self.system_program,
+                &[b"sol_vault", market_key.as_ref(), &[market.sol_vault_bump]],
+                amount,
+            ),
+        }
+    }
+}
+
+/// Realloc a program-owned index account up to `required_len`, with the
+/// extra rent paid by `payer`
+///
+/// Indexes grow one slot at a time, so no call exceeds the realloc limit.
+fn grow_account<'info>(
+    info: &AccountInfo<'info>,
+    required_len: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if info.data_len() >= required_len {
+        return Ok(());
+    }
+    let rent_due = Rent::get()?
+        .minimum_balance(required_len)
+        .saturating_sub(info.lamports());
+    if rent_due > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            rent_due,
+        )?;
+    }
+    info.realloc(required_len, false)?;
+    Ok(())
+}
+
+/// Move lamports out of a program-owned stake or bond account
+fn move_stake_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    **from.try_borrow_mut_lamports()? = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ErrorCode::Overflow)?;
+    **to.try_borrow_mut_lamports()? = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ErrorCode::Overflow)?;
+    Ok(())
+}
+
+// ============================================================================
+// Claim Verification Helpers
+// ============================================================================
+
+/// Run every claim precondition and verify the MXE proof
+///
+/// Shared by all claim entry points so replay protection and signature
+/// checks cannot drift between them. The vault balance check is left to
+/// the caller since it depends on the payout route. The proof's Ed25519
+/// instructions start at `first_ix`.
+fn verify_claim(
+    market: &Account<Market>,
+    config: &Config,
+    position: &UserPosition,
+    user: &Pubkey,
+    payout: u64,
+    relayer_fee: u64,
+    nonce: u64,
+    expiry: i64,
+    signatures: &[[u8; 64]],
+    ix_sysvar: &AccountInfo,
+    first_ix: usize,
+) -> Result<()> {
+    check_claimable(market, position, payout)?;
+
+    // 3️⃣b Ensure the proof has not expired
+    require!(
+        Clock::get()?.unix_timestamp <= expiry,
+        ErrorCode::ProofExpired
+    );
+
+    // 4️⃣ Construct the signed message
+    // Message format: keccak256(program_id || market || user || payout || relayer_fee || nonce || expiry)
+    let message = construct_payout_message(
+        &market.key(),
+        user,
+        payout,
+        relayer_fee,
+        nonce,
+        expiry,
+    );
+
+    // 5️⃣ Verify a quorum of Ed25519 signatures from the configured MXE set
+    verify_mxe_quorum(config, ix_sysvar, &message, signatures, first_ix)
+}
+
+/// Pay out a verified claim and record it
+///
+/// Shared by the `ClaimWithProof` instructions once their proof checks
+/// have passed.
+fn settle_claim(accounts: &mut ClaimWithProof, payout: u64, nonce: u64) -> Result<()> {
+    let market = &mut accounts.market;
+    let position = &mut accounts.user_position;
+    let custody = Custody {
+        vault: accounts.vault.as_ref(),
+        sol_vault: accounts.sol_vault.as_ref(),
+        usdc_mint: accounts.usdc_mint.as_ref(),
+        token_program: accounts.token_program.as_ref(),
+        system_program: &accounts.system_program,
+    };
+
+    // 6️⃣ Validate payout doesn't exceed vault balance, covering a
+    // shortfall from the insurance fund when it was passed in
+    let mut vault_balance = custody.balance(market)?;
+    if payout > vault_balance {
+        let shortfall = payout - vault_balance;
+        match (accounts.insurance_fund.as_mut(), accounts.insurance_vault.as_ref()) {
+            (Some(insurance_fund), Some(insurance_vault))
+                if market.currency == MarketCurrency::Usdc =>
+            {
+                draw_insurance(
+                    market,
+                    insurance_fund,
+                    insurance_vault,
+                    custody.vault.ok_or(ErrorCode::CurrencyMismatch)?,
+                    custody.usdc_mint.ok_or(ErrorCode::CurrencyMismatch)?,
+                    custody.token_program.ok_or(ErrorCode::CurrencyMismatch)?,
+                    shortfall,
+                )?;
+                emit!(InsuranceDrawdownEvent {
+                    market: market.key(),
+                    user: accounts.user.key(),
+                    drawdown_amount: shortfall,
+                });
+                vault_balance = payout;
+            }
+            _ => return err!(ErrorCode::InsufficientVaultBalance),
+        }
+    }
+
+    // ============================================================================
+    // ALL VERIFICATIONS PASSED - RECORD, THEN EXECUTE PAYOUT
+    // ============================================================================
+
+    // Mark as claimed (prevents replay) and record the nonce before the
+    // transfer CPI; a failed transfer reverts these writes with it
+    record_claim(market, &mut accounts.stats, position, payout, nonce)?;
+    record_user_claim(&mut accounts.user_stats, market, position.amount, payout)?;
+
+    // Transfer verified payout from vault to user
+    if payout > 0 {
+        custody.pay_out(
+            market,
+            accounts.user_token_account.as_ref(),
+            accounts.user.to_account_info(),
+            payout,
+        )?;
+    }
+
+    emit!(ClaimEvent {
+        market: market.key(),
+        user: accounts.user.key(),
+        amount: payout,
+        nonce,
+        vault_balance_after: vault_balance - payout,
+        position_amount: position.amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Add a completed claim to the user's totals, wins and Brier score
+fn record_user_claim(
+    user_stats: &mut UserStats,
+    market: &Market,
+    stake: u64,
+    payout: u64,
+) -> Result<()> {
+    user_stats.total_claimed = user_stats.total_claimed
+        .checked_add(payout)
+        .ok_or(ErrorCode::Overflow)?;
+    score_claim(user_stats, market, stake, payout)
+}
+
+/// Count a completed claim towards the user's wins and Brier score
+fn score_claim(user_stats: &mut UserStats, market: &Market, stake: u64, payout: u64) -> Result<()> {
+    // Refunds from a voided market are neither wins nor forecasts
+    let voided = market.result == MarketResult::None;
+    if payout > 0 && !voided {
+        user_stats.wins = user_stats.wins
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+    }
+    if let Some(score) = brier_contribution(market, stake, payout).filter(|_| !voided) {
+        user_stats.brier_score_numerator = user_stats.brier_score_numerator
+            .checked_add(score as i64)
+            .ok_or(ErrorCode::Overflow)?;
+        user_stats.brier_score_denominator = user_stats.brier_score_denominator
+            .checked_add(BRIER_SCALE * BRIER_SCALE)
+            .ok_or(ErrorCode::Overflow)?;
+        user_stats.markets_resolved = user_stats.markets_resolved
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+    }
+    Ok(())
+}
+
+/// Claim preconditions that do not depend on how the payout is proven
+fn check_claimable(market: &Market, position: &UserPosition, payout: u64) -> Result<()> {
+    // 1️⃣ Ensure market is resolved
+    require!(market.resolved, ErrorCode::MarketNotResolved);
+
+    // 1️⃣a Unclaimed funds may already be swept after the deadline
+    require!(
+        Clock::get()?.unix_timestamp < market.unclaimed_deadline,
+        ErrorCode::ClaimDeadlinePassed
+    );
+
+    // 1️⃣b Ensure the dispute window has passed
+    let dispute_deadline = market.resolution_timestamp
+        .checked_add(DISPUTE_WINDOW_SECS)
+        .ok_or(ErrorCode::Overflow)?;
+    require!(
+        Clock::get()?.unix_timestamp >= dispute_deadline,
+        ErrorCode::DisputeWindowActive
+    );
+
+    // 2️⃣ Ensure user hasn't already claimed (replay protection)
+    //
+    // `claimed` alone guards replays: `nonce_used` cannot, since a
+    // legitimate nonce of 0 is indistinguishable from "unused".
+    require!(!position.claimed, ErrorCode::AlreadyClaimed);
+    // A payout part-paid by `partial_claim` can only be finished there
+    require!(position.claimed_amount == 0, ErrorCode::AlreadyClaimed);
+
+    // 3️⃣ Bound the payout regardless of what the proof says
+    let max_by_stake = position.amount
+        .checked_mul(MAX_PAYOUT_MULTIPLIER)
+        .ok_or(ErrorCode::Overflow)?;
+    require!(
+        payout <= market.total_pool && payout <= max_by_stake,
+        ErrorCode::PayoutExceedsBound
+    );
+
+    Ok(())
+}
+
+/// Verify `message` was signed by the configured MXE, using
+/// `config.signature_scheme`
+fn verify_mxe_quorum(
+    config: &Config,
+    ix_sysvar: &AccountInfo,
+    message: &[u8; 32],
+    signatures: &[[u8; 64]],
+    first_ix: usize,
+) -> Result<()> {
+    match config.signature_scheme {
+        SignatureScheme::Ed25519 => {
+            verify_ed25519_mxe(config, ix_sysvar, message, signatures, first_ix)
+        }
+        SignatureScheme::Secp256k1 => {
+            verify_secp256k1_mxe(config, ix_sysvar, message, signatures, first_ix)
+        }
+    }
+}
+
+/// Reject transactions whose proof instructions are not exactly the
+/// `signatures` MXE proofs this instruction verifies, placed right before it
+///
+/// Proofs sit at indices 0..signatures, so the executing instruction must
+/// be at index `signatures`; a signed proof bundled into some other
+/// transaction cannot be picked up. Stray Ed25519 instructions could be
+/// mistaken for, or hide next to, the real proofs, so none are allowed
+/// beyond the proofs (and none at all under the Secp256k1 scheme).
+fn require_proof_instructions(
+    scheme: SignatureScheme,
+    ix_sysvar: &AccountInfo,
+    signatures: usize,
+) -> Result<()> {
+    require!(
+        ix_sysvar.key() == &IX_SYSVAR_ID,
+        ErrorCode::InvalidInstructionSysvar
+    );
+    require!(
+        load_current_index_checked(ix_sysvar)? as usize == signatures,
+        ErrorCode::Ed25519InstructionMisplaced
+    );
+
+    let mut ed25519_instructions = 0;
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, ix_sysvar) {
+        if ix.program_id == ed25519_program::ID {
+            ed25519_instructions += 1;
+        }
+        index += 1;
+    }
+
+    let expected = match scheme {
+        SignatureScheme::Ed25519 => signatures,
+        SignatureScheme::Secp256k1 => 0,
+    };
+    require!(
+        ed25519_instructions == expected,
+        ErrorCode::MultipleEd25519Instructions
+    );
+
+    Ok(())
+}
+
+/// Check an Ed25519 quorum of `config.mxe_pubkeys` signed `message`
+///
+/// Falls back to `previous_mxe_pubkeys` during the rotation grace window.
+fn verify_ed25519_mxe(
+    config: &Config,
+    ix_sysvar: &AccountInfo,
+    message: &[u8; 32],
+    signatures: &[[u8; 64]],
+    first_ix: usize,
+) -> Result<()> {
+    let current = verify_mxe_signatures(
+        ix_sysvar,
+        message,
+        signatures,
+        first_ix,
+        &config.mxe_pubkeys,
+        config.required_signatures,
+    );
+
+    // 5️⃣b During the rotation grace window the previous set still counts
+    let in_grace = !config.previous_mxe_pubkeys.is_empty()
+        && Clock::get()?.unix_timestamp <= config.rotation_deadline;
+    if current.is_err() && in_grace {
+        let previous = verify_mxe_signatures(
+            ix_sysvar,
+            message,
+            signatures,
+            first_ix,
+            &config.previous_mxe_pubkeys,
+            config.previous_required_signatures,
+        );
+        if previous.is_ok() {
+            return previous;
+        }
+    }
+
+    current
+}
+
+/// Mark a position as claimed after its payout has been sent
+///
+/// `claimed` blocks replays; `nonce` is kept for auditing and `payout`
+/// for `read_user_pnl`.
+fn record_claim(
+    market: &mut Market,
+    stats: &mut ProtocolStats,
+    position: &mut UserPosition,
+    payout: u64,
+    nonce: u64,
+) -> Result<()> {
+    position.claimed = true;
+    position.claimed_amount = payout;
+    position.nonce_used = nonce;
+    position.payout = payout;
+    market.claimed_positions = market.claimed_positions
+        .checked_add(1)
+        .ok_or(ErrorCode::Overflow)?;
+    market.total_claimed = market.total_claimed
+        .checked_add(payout)
+        .ok_or(ErrorCode::Overflow)?;
+    stats.total_claims = stats.total_claims
+        .checked_add(1)
+        .ok_or(ErrorCode::Overflow)?;
+    Ok(())
+}
+
+/// Brier score contribution of a claim, in BRIER_SCALE² units
+///
+/// The forecast is the probability the pool implied for the user's side:
+/// stake / payout for a winner, the losing side's share of the recorded
+/// YES/NO split for a loser. None when a loser's side pool is unknown.
+fn brier_contribution(market: &Market, stake: u64, payout: u64) -> Option<u64> {
+    let scale = BRIER_SCALE as u128;
+    if payout > 0 {
+        let probability = (stake as u128 * scale / payout as u128).min(scale);
+        return Some(((scale - probability) * (scale - probability)) as u64);
+    }
+
+    if !market.pool_split_recorded {
+        return None;
+    }
+    let winning_pool = match market.result {
+        MarketResult::Yes => market.yes_pool,
+        MarketResult::No => market.no_pool,
+        _ => return None,
+    };
+    let total = market.yes_pool as u128 + market.no_pool as u128;
+    if total == 0 {
+        return None;
+    }
+    let probability = (total - winning_pool as u128) * scale / total;
+    Some((probability * probability) as u64)
+}
+
+/// Message the MXE signs to attest a market's YES/NO pool split
+///
+/// Format: keccak256(market || yes_pool || no_pool)
+fn construct_pool_split_message(market: &Pubkey, yes_pool: u64, no_pool: u64) -> [u8; 32] {
+    keccak::hashv(&[market.as_ref(), &yes_pool.to_le_bytes(), &no_pool.to_le_bytes()]).to_bytes()
+}
+
+/// Domain separator for MXE-signed side reveals
+const SIDE_REVEAL_DOMAIN: &[u8] = b"nexora:side_reveal:v1";
+
+/// Message the MXE signs to reveal which side a position backed
+///
+/// Format: keccak256(SIDE_REVEAL_DOMAIN || market || user || side)
+fn construct_side_reveal_message(market: &Pubkey, user: &Pubkey, side: u8) -> [u8; 32] {
+    keccak::hashv(&[SIDE_REVEAL_DOMAIN, market.as_ref(), user.as_ref(), &[side]]).to_bytes()
+}
+
+/// Domain separator for MXE-signed side changes
+const SIDE_CHANGE_DOMAIN: &[u8] = b"nexora:side_change:v1";
+
+/// Message the MXE signs to move a position between sides
+///
+/// Format: keccak256(SIDE_CHANGE_DOMAIN || market || user || old_side ||
+/// new_side || payload_hash)
+fn construct_side_change_message(
+    market: &Pubkey,
+    user: &Pubkey,
+    old_side: u8,
+    new_side: u8,
+    payload_hash: &[u8; 32],
+) -> [u8; 32] {
+    keccak::hashv(&[
+        SIDE_CHANGE_DOMAIN,
+        market.as_ref(),
+        user.as_ref(),
+        &[old_side, new_side],
+        payload_hash,
+    ]).to_bytes()
+}
+
+/// Domain separator for MXE-signed batch payout roots
+const BATCH_ROOT_DOMAIN: &[u8] = b"nexora:batch_root:v1";
+
+/// Message the MXE signs to publish a batch payout root
+///
+/// Format: keccak256(BATCH_ROOT_DOMAIN || market || merkle_root)
+fn construct_batch_root_message(market: &Pubkey, merkle_root: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[BATCH_ROOT_DOMAIN, market.as_ref(), merkle_root]).to_bytes()
+}
+
+/// Seed of a tag's TagIndex
+///
+/// Format: keccak256(tag)[..8]
+fn tag_hash(tag: &str) -> [u8; 8] {
+    let hash = keccak::hash(tag.as_bytes()).to_bytes();
+    let mut out = [0u8; 8];
+    out.copy_from_slice(&hash[..8]);
+    out
+}
+
+/// Leaf of a batch payout tree
+///
+/// Format: keccak256(user || payout || nonce)
+fn compute_batch_leaf(user: &Pubkey, payout: u64, nonce: u64) -> [u8; 32] {
+    keccak::hashv(&[user.as_ref(), &payout.to_le_bytes(), &nonce.to_le_bytes()]).to_bytes()
+}
+
+/// Walk `proof` from `leaf` and compare against `root`
+///
+/// Each step hashes the pair in ascending byte order: keccak256(min || max).
+fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (left, right) = if node <= *sibling {
+            (node, *sibling)
+        } else {
+            (*sibling, node)
+        };
+        keccak::hashv(&[&left, &right]).to_bytes()
+    });
+    computed == *root
+}
+
+// ============================================================================
+// Snapshot Helpers
+// ============================================================================
+
+/// Domain separator for market state snapshots
+const SNAPSHOT_DOMAIN: &[u8] = b"nexora:snapshot:v1";
+
+/// Hash the complete serialized market state
+///
+/// Format: keccak256(SNAPSHOT_DOMAIN || market || borsh(Market))
+fn compute_market_snapshot(market_key: &Pubkey, market: &Market) -> Result<[u8; 32]> {
+    let state = market.try_to_vec()?;
+    Ok(keccak::hashv(&[SNAPSHOT_DOMAIN, market_key.as_ref(), &state]).to_bytes())
+}
+
+// ============================================================================
+// Cryptographic Verification Helpers
+// ============================================================================
+
+/// Construct the message that MXE signs
+/// 
+/// Message Format: keccak256(program_id || market || user || payout || relayer_fee || nonce || expiry)
+/// 
 /// This creates a unique, deterministic message that ties together:
+/// - program_id: This deployment (prevents cross-deployment replay)
 /// - market: Which prediction market (prevents cross-market replay)
 /// - user: Who is claiming (prevents claim theft)
 /// - payout: How much they're claiming (prevents amount tampering)
+/// - relayer_fee: Share of `payout` a relayer may take (0 for direct claims)
 /// - nonce: Unique identifier (prevents replay attacks)
+/// - expiry: Unix timestamp after which the proof is rejected
+/// 
+/// The MXE signs this message with its Ed25519 private key.
+/// This program verifies the signatures against config.mxe_pubkeys.
+fn construct_payout_message(
+    market: &Pubkey,
+    user: &Pubkey,
+    payout: u64,
+    relayer_fee: u64,
+    nonce: u64,
+    expiry: i64,
+) -> [u8; 32] {
+    let mut data = Vec::with_capacity(32 + 32 + 32 + 8 + 8 + 8 + 8);
+    data.extend_from_slice(crate::ID.as_ref());
+    data.extend_from_slice(market.as_ref());
+    data.extend_from_slice(user.as_ref());
+    data.extend_from_slice(&payout.to_le_bytes());
+    data.extend_from_slice(&relayer_fee.to_le_bytes());
+    data.extend_from_slice(&nonce.to_le_bytes());
+    data.extend_from_slice(&expiry.to_le_bytes());
+    
+    // Hash the concatenated data
+    keccak::hash(&data).to_bytes()
+}
+
+/// BN254 base field modulus, big-endian
+const BN254_FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+/// Groth16 public inputs of a payout proof
+///
+/// Keys enter as keccak256(key) with the top byte cleared, so they fit
+/// the BN254 scalar field; payout and nonce as 32-byte big-endian integers.
+fn zk_payout_inputs(
+    market: &Pubkey,
+    user: &Pubkey,
+    payout: u64,
+    nonce: u64,
+) -> [[u8; 32]; ZK_PUBLIC_INPUTS] {
+    let key_input = |key: &Pubkey| {
+        let mut input = keccak::hash(key.as_ref()).to_bytes();
+        input[0] = 0;
+        input
+    };
+    let amount_input = |amount: u64| {
+        let mut input = [0u8; 32];
+        input[24..].copy_from_slice(&amount.to_be_bytes());
+        input
+    };
+    [key_input(market), key_input(user), amount_input(payout), amount_input(nonce)]
+}
+
+/// Negate a G1 point (x, y) -> (x, p - y); the identity maps to itself
+fn negate_g1(point: &[u8; 64]) -> Result<[u8; 64]> {
+    let mut negated = *point;
+    let y = &point[32..];
+    if y.iter().all(|byte| *byte == 0) {
+        return Ok(negated);
+    }
+    require!(y < &BN254_FIELD_MODULUS[..], ErrorCode::InvalidZkProof);
+
+    let mut borrow = 0u16;
+    for i in (0..32).rev() {
+        let difference = (BN254_FIELD_MODULUS[i] as u16)
+            .wrapping_sub(y[i] as u16)
+            .wrapping_sub(borrow);
+        negated[32 + i] = difference as u8;
+        borrow = (difference >> 8) & 1;
+    }
+    Ok(negated)
+}
+
+/// Verify a Groth16 proof over BN254 with the alt_bn128 syscalls
+///
+/// Checks e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1,
+/// where vk_x = IC[0] + sum(input[i] * IC[i + 1]).
+fn verify_groth16(
+    verifying_key: &Groth16VerifyingKey,
+    proof_a: &[u8; 64],
+    proof_b: &[u8; 128],
+    proof_c: &[u8; 64],
+    inputs: &[[u8; 32]; ZK_PUBLIC_INPUTS],
+) -> Result<()> {
+    let mut vk_x = verifying_key.ic[0];
+    for (input, point) in inputs.iter().zip(verifying_key.ic[1..].iter()) {
+        let product = alt_bn128_multiplication(&[&point[..], &input[..]].concat())
+            .map_err(|_| ErrorCode::InvalidZkProof)?;
+        let sum = alt_bn128_addition(&[&vk_x[..], &product[..]].concat())
+            .map_err(|_| ErrorCode::InvalidZkProof)?;
+        vk_x.copy_from_slice(&sum);
+    }
+
+    let pairing_input = [
+        &negate_g1(proof_a)?[..],
+        &proof_b[..],
+        &verifying_key.alpha_g1[..],
+        &verifying_key.beta_g2[..],
+        &vk_x[..],
+        &verifying_key.gamma_g2[..],
+        &proof_c[..],
+        &verifying_key.delta_g2[..],
+    ]
+    .concat();
+    let result = alt_bn128_pairing(&pairing_input).map_err(|_| ErrorCode::InvalidZkProof)?;
+
+    // The syscall returns 1 as a 32-byte big-endian integer on success
+    require!(
+        result.len() == 32 && result[..31].iter().all(|byte| *byte == 0) && result[31] == 1,
+        ErrorCode::InvalidZkProof
+    );
+    Ok(())
+}
+
+/// Check an MXE signer set before it goes into Config
+fn validate_mxe_quorum(keys: &[[u8; 32]], required_signatures: u8) -> Result<()> {
+    require!(
+        !keys.is_empty() && keys.len() <= MAX_MXE_SIGNERS,
+        ErrorCode::InvalidMxeQuorum
+    );
+    require!(
+        required_signatures >= 1 && required_signatures as usize <= keys.len(),
+        ErrorCode::InvalidMxeQuorum
+    );
+    for (i, key) in keys.iter().enumerate() {
+        require!(!keys[..i].contains(key), ErrorCode::DuplicateMXESigner);
+    }
+    Ok(())
+}
+
+/// Verify an m-of-n quorum of Arcium MXE signatures
+///
+/// Signature `i` must be carried by the Ed25519 instruction at index
+/// `first_ix + i`; single claims use `first_ix = 0`, so a transaction with
+/// n signatures starts with n Ed25519 instructions.
+/// Every signer must be a distinct member of `mxe_pubkeys`, all signing
+/// `expected_message`, and there must be at least `required_signatures`.
+fn verify_mxe_signatures(
+    ix_sysvar: &AccountInfo,
+    expected_message: &[u8; 32],
+    signatures: &[[u8; 64]],
+    first_ix: usize,
+    mxe_pubkeys: &[[u8; 32]],
+    required_signatures: u8,
+) -> Result<()> {
+    require!(
+        signatures.len() <= MAX_MXE_SIGNERS,
+        ErrorCode::InvalidSignatureCount
+    );
+    require!(!signatures.is_empty(), ErrorCode::InsufficientMXESignatures);
+
+    let mut signers: Vec<[u8; 32]> = Vec::with_capacity(signatures.len());
+    for (index, signature) in signatures.iter().enumerate() {
+        let signer = verify_ed25519_instruction(
+            ix_sysvar,
+            first_ix + index,
+            expected_message,
+            signature,
+        )?;
+        require!(mxe_pubkeys.contains(&signer), ErrorCode::UnknownMXESigner);
+        require!(!signers.contains(&signer), ErrorCode::DuplicateMXESigner);
+        signers.push(signer);
+    }
+
+    require!(
+        signers.len() >= required_signatures as usize,
+        ErrorCode::InsufficientMXESignatures
+    );
+
+    Ok(())
+}
+
+/// Check `config.mxe_eth_address` signed `message` with Secp256k1
+///
+/// The Secp256k1 instruction at `first_ix` must carry exactly one
+/// signature, over the 32-byte `message` (the precompile hashes it with
+/// keccak256 before recovery) and with its data in that same instruction.
+/// `signatures` holds that signature's 64-byte r || s.
+fn verify_secp256k1_mxe(
+    config: &Config,
+    ix_sysvar: &AccountInfo,
+    message: &[u8; 32],
+    signatures: &[[u8; 64]],
+    first_ix: usize,
+) -> Result<()> {
+    require!(signatures.len() == 1, ErrorCode::InvalidSignatureCount);
+    require!(
+        ix_sysvar.key() == &IX_SYSVAR_ID,
+        ErrorCode::InvalidInstructionSysvar
+    );
+
+    let ix = load_instruction_at_checked(first_ix, ix_sysvar)
+        .map_err(|_| ErrorCode::Secp256k1InstructionMissing)?;
+    require!(
+        ix.program_id == secp256k1_program::ID,
+        ErrorCode::Secp256k1InstructionMissing
+    );
+
+    // Format: count (u8), then per signature signature_offset (u16),
+    // signature_instruction_index (u8), eth_address_offset (u16),
+    // eth_address_instruction_index (u8), message_data_offset (u16),
+    // message_data_size (u16), message_instruction_index (u8)
+    let data = &ix.data;
+    require!(data.len() >= 12 && data[0] == 1, ErrorCode::InvalidSecp256k1Data);
+    let signature_offset = u16::from_le_bytes([data[1], data[2]]) as usize;
+    let eth_address_offset = u16::from_le_bytes([data[4], data[5]]) as usize;
+    let message_offset = u16::from_le_bytes([data[7], data[8]]) as usize;
+    let message_len = u16::from_le_bytes([data[9], data[10]]) as usize;
+    // Offsets must point into this instruction, not another one
+    let this_ix = first_ix as u8;
+    require!(
+        data[3] == this_ix && data[6] == this_ix && data[11] == this_ix,
+        ErrorCode::InvalidSecp256k1Data
+    );
+    require!(
+        signature_offset + 64 <= data.len()
+            && eth_address_offset + 20 <= data.len()
+            && message_offset + message_len <= data.len(),
+        ErrorCode::InvalidSecp256k1Data
+    );
+
+    require!(
+        data[eth_address_offset..eth_address_offset + 20] == config.mxe_eth_address,
+        ErrorCode::InvalidEthAddress
+    );
+    require!(
+        data[signature_offset..signature_offset + 64] == signatures[0],
+        ErrorCode::SignatureMismatch
+    );
+    require!(message_len == 32, ErrorCode::InvalidMessageLength);
+    require!(
+        data[message_offset..message_offset + 32] == message[..],
+        ErrorCode::MessageMismatch
+    );
+
+    Ok(())
+}
+
+/// Verify one Ed25519 instruction and return its signer
+/// 
+/// VERIFICATION PROCESS:
+/// 1. Load Ed25519 instruction from ix_sysvar (at `index`)
+/// 2. Verify instruction is from ed25519_program
+/// 3. Parse instruction data:
+///    - Signature count (u8) = 1
+///    - Padding (u8) = 0
+///    - Signature offset and instruction index (u16, u16)
+///    - Public key offset and instruction index (u16, u16)
+///    - Message offset and length (u16, u16), instruction index (u16)
+///    - Public key (32 bytes)
+///    - Signature (64 bytes)
+///    - Message (variable length)
+/// 4. Verify signature and message match what we expect
+/// 5. Ed25519 program already verified signature ✅
+/// 
+/// SECURITY:
+/// - Uses Solana's native Ed25519 program (verified by runtime)
+/// - The caller checks the returned signer against Config
+/// - Message constructed onchain (no tampering possible)
+/// - Signature verification happens BEFORE this instruction executes
+fn verify_ed25519_instruction(
+    ix_sysvar: &AccountInfo,
+    index: usize,
+    expected_message: &[u8; 32],
+    expected_signature: &[u8; 64],
+) -> Result<[u8; 32]> {
+    // Verify ix_sysvar is the instructions sysvar
+    require!(
+        ix_sysvar.key() == &IX_SYSVAR_ID,
+        ErrorCode::InvalidInstructionSysvar
+    );
+
+    // Load the Ed25519 instruction carrying this signature
+    let ix = load_instruction_at_checked(index, ix_sysvar)
+        .map_err(|_| ErrorCode::Ed25519InstructionMissing)?;
+
+    // Verify it's the Ed25519 program
+    require!(
+        ix.program_id == ed25519_program::ID,
+        ErrorCode::InvalidEd25519Program
+    );
+
+    // Parse Ed25519 instruction data
+    // Format: https://docs.solana.com/developing/runtime-facilities/programs#ed25519-program
+    require!(
+        ix.data.len() >= 112, // Minimum: 2 + 2 + 2 + 2 + 2 + 32 + 64 + message
+        ErrorCode::InvalidEd25519Data
+    );
+
+    // Verify signature count = 1
+    let num_signatures = ix.data[0];
+    require!(
+        num_signatures == 1,
+        ErrorCode::InvalidSignatureCount
+    );
+
+    // Extract offsets (little-endian u16). Each is followed by the index
+    // of the instruction holding that data; u16::MAX means this one, so
+    // the bytes checked below are the bytes the precompile verified.
+    let read_u16 = |at: usize| u16::from_le_bytes([ix.data[at], ix.data[at + 1]]);
+    let signature_offset = read_u16(2) as usize;
+    let pubkey_offset = read_u16(6) as usize;
+    let message_offset = read_u16(10) as usize;
+    let message_len = read_u16(12) as usize;
+    require!(
+        read_u16(4) == u16::MAX && read_u16(8) == u16::MAX && read_u16(14) == u16::MAX,
+        ErrorCode::InvalidEd25519Data
+    );
+
+    // Extract the signer; the caller checks it against Config
+    require!(
+        pubkey_offset + 32 <= ix.data.len(),
+        ErrorCode::InvalidEd25519Data
+    );
+    let mut pubkey = [0u8; 32];
+    pubkey.copy_from_slice(&ix.data[pubkey_offset..pubkey_offset + 32]);
+
+    // Verify signature matches
+    require!(
+        signature_offset + 64 <= ix.data.len(),
+        ErrorCode::InvalidEd25519Data
+    );
+    let signature = &ix.data[signature_offset..signature_offset + 64];
+    require!(
+        signature == expected_signature,
+        ErrorCode::SignatureMismatch
+    );
+
+    // Verify message matches
+    require!(
+        message_len == 32,
+        ErrorCode::InvalidMessageLength
+    );
+    require!(
+        message_offset + message_len <= ix.data.len(),
+        ErrorCode::InvalidEd25519Data
+    );
+    let message = &ix.data[message_offset..message_offset + message_len];
+    require!(
+        message == expected_message,
+        ErrorCode::MessageMismatch
+    );
+
+    // All checks passed - Ed25519 signature is valid!
+    Ok(pubkey)
+}
+
+// ============================================================================
+// Accounts
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct InitializeProtocol<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = Config::LEN,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = ProtocolStats::LEN,
+        seeds = [b"stats"],
+        bump
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+
+    #[account(mut, address = ADMIN_PUBKEY @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeInsuranceFund<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = InsuranceFund::LEN,
+        seeds = [b"insurance"],
+        bump
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    #[account(mut, address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InsuranceFundDeposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"insurance"],
+        bump = insurance_fund.bump,
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    /// Insurance vault of `usdc_mint`, owned by the InsuranceFund
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        token::mint = usdc_mint,
+        token::authority = insurance_fund,
+        token::token_program = token_program,
+        seeds = [b"insurance_vault", usdc_mint.key().as_ref()],
+        bump
+    )]
+    pub insurance_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        constraint = depositor_token_account.mint == usdc_mint.key(),
+        constraint = depositor_token_account.owner == depositor.key(),
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DrawFromInsurance<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = market.vault_bump,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"insurance"],
+        bump = insurance_fund.bump,
+    )]
+    pub insurance_fund: Account<'info, InsuranceFund>,
+
+    #[account(
+        mut,
+        seeds = [b"insurance_vault", market.usdc_mint.as_ref()],
+        bump,
+    )]
+    pub insurance_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = market.usdc_mint)]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SetAdminTimelock<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QueueAdminAction<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeBps<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeMxeRotation<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeMxeRotation<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeZkVerifyingKey<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = ZkVerifyingKey::LEN,
+        seeds = [b"zk_vk"],
+        bump
+    )]
+    pub zk_verifying_key: Box<Account<'info, ZkVerifyingKey>>,
+
+    #[account(mut, address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeZkVerifyingKey<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"zk_vk"],
+        bump = zk_verifying_key.bump,
+    )]
+    pub zk_verifying_key: Box<Account<'info, ZkVerifyingKey>>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMinResolverStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdminTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAdminTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdminTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Must equal config.pending_admin (checked in instruction)
+    pub new_admin: Signer<'info>,
+}
+
+/// Config changes the attached governance may also make
+#[derive(Accounts)]
+pub struct GovernedConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// config.admin, or the governance PDA executing a proposal (checked
+    /// in instruction)
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGovernance<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResumeProtocol<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMarketCreationFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetResolutionBondAmount<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetUnclaimedGracePeriod<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetForceResolveDelay<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLeaderboard<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ForecastersLeaderboard::LEN,
+        seeds = [b"leaderboard"],
+        bump
+    )]
+    pub leaderboard: Account<'info, ForecastersLeaderboard>,
+
+    #[account(
+        seeds = [b"stats", user_stats.user.as_ref()],
+        bump = user_stats.bump,
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AllowMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Parsed so only real SPL / Token-2022 mints can be listed
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DisallowMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct SetUserLimits<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = UserBettingLimits::LEN,
+        seeds = [b"limits", user.as_ref()],
+        bump
+    )]
+    pub user_limits: Account<'info, UserBettingLimits>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddToWhitelist<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = Whitelist::LEN,
+        seeds = [b"whitelist", market.key().as_ref()],
+        bump
+    )]
+    pub whitelist: Account<'info, Whitelist>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromWhitelist<'info> {
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"whitelist", market.key().as_ref()],
+        bump = whitelist.bump,
+        has_one = market,
+    )]
+    pub whitelist: Account<'info, Whitelist>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddCreator<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = CreatorAllowlist::LEN,
+        seeds = [b"creator_allowlist"],
+        bump
+    )]
+    pub creator_allowlist: Account<'info, CreatorAllowlist>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveCreator<'info> {
+    #[account(
+        mut,
+        seeds = [b"creator_allowlist"],
+        bump = creator_allowlist.bump,
+    )]
+    pub creator_allowlist: Account<'info, CreatorAllowlist>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawCreationFees<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault"],
+        bump,
+    )]
+    pub creation_fee_vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub destination: SystemAccount<'info>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StakeResolver<'info> {
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        space = ResolverStake::LEN,
+        seeds = [b"resolver_stake", resolver.key().as_ref()],
+        bump
+    )]
+    pub resolver_stake: Account<'info, ResolverStake>,
+
+    #[account(mut)]
+    pub resolver: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnstakeResolver<'info> {
+    #[account(
+        mut,
+        seeds = [b"resolver_stake", resolver.key().as_ref()],
+        bump = resolver_stake.bump,
+    )]
+    pub resolver_stake: Account<'info, ResolverStake>,
+
+    #[account(mut)]
+    pub resolver: Signer<'info>,
+}
+
+/// Create Market Account Context
+/// 
+/// PDA DESIGN:
+/// - Market PDA: seeds = ["market", authority, question_hash]
+/// - Vault PDA: seeds = ["vault", market_pubkey]
+/// - Fee Vault PDA: seeds = ["fee_vault", market_pubkey] (protocol fees)
+/// - Metadata PDA: seeds = ["metadata", market_pubkey] (display-only data)
+/// 
+/// The Vault PDA is owned by the Token Program (legacy SPL or Token-2022)
+/// and holds all USDC deposits.
+/// The Market PDA owns the vault via PDA authority derivation.
+/// 
+/// ADMIN RESTRICTION:
+/// - Only config.admin can sign to create markets
+/// - Check is enforced in instruction logic
+#[derive(Accounts)]
+#[instruction(
+    question: String,
+    expiry_timestamp: i64,
+    metadata_uri: String,
+    pyth_price_feed: Option<Pubkey>,
+    oracle: Option<Pubkey>,
+    resolution_price_threshold: Option<i64>,
+    resolution_direction: ResolutionDirection,
+    min_bet: u64,
+    max_bet: u64,
+    max_pool: u64,
+    is_token22: bool,
+    num_outcomes: u8,
+    currency: MarketCurrency,
+    creator_fee_bps: u16,
+    outcome_labels: Vec<String>,
+    resolution_commitment: Option<[u8; 32]>,
+    category: MarketCategory,
+    series_id: Option<Pubkey>,
+)]
+pub struct CreateMarket<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = Market::LEN,
+        seeds = [b"market", authority.key().as_ref(), question.as_bytes()],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Protocol-wide counters
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump,
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+
+    /// Source of the market creation fee
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Needed when the authority is an allowlisted creator, not the admin
+    #[account(
+        seeds = [b"creator_allowlist"],
+        bump = creator_allowlist.bump,
+    )]
+    pub creator_allowlist: Option<Account<'info, CreatorAllowlist>>,
+
+    /// Protocol PDA collecting market creation fees
+    #[account(
+        mut,
+        seeds = [b"fee_vault"],
+        bump,
+    )]
+    pub creation_fee_vault: SystemAccount<'info>,
+
+    /// Display metadata kept out of the Market account
+    #[account(
+        init,
+        payer = authority,
+        space = MarketMetadata::LEN,
+        seeds = [b"metadata", market.key().as_ref()],
+        bump
+    )]
+    pub market_metadata: Account<'info, MarketMetadata>,
+
+    /// Holds config.resolution_bond_amount from the authority
+    #[account(
+        init,
+        payer = authority,
+        space = ResolutionBond::LEN,
+        seeds = [b"bond", market.key().as_ref()],
+        bump
+    )]
+    pub resolution_bond: Account<'info, ResolutionBond>,
+
+    /// Holds config.required_creator_stake from the authority
+    #[account(
+        init,
+        payer = authority,
+        space = CreatorStake::LEN,
+        seeds = [b"creator_stake", market.key().as_ref()],
+        bump
+    )]
+    pub creator_stake: Account<'info, CreatorStake>,
+
+    /// Markets of `category`; grown by create_market as it fills
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = CategoryIndex::space(0),
+        seeds = [b"category_index", &[category as u8]],
+        bump
+    )]
+    pub category_index: Account<'info, CategoryIndex>,
+
+    /// Global market registry; created with the first market
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = MarketRegistry::LEN,
+        seeds = [b"registry"],
+        bump
+    )]
+    pub registry: Box<Account<'info, MarketRegistry>>,
+
+    /// Registry page the market is added to, see
+    /// `MarketRegistry::current_page`; grown by create_market as it fills
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = MarketRegistryPage::space(0),
+        seeds = [b"registry_page", &registry.current_page().to_le_bytes()],
+        bump
+    )]
+    pub registry_page: Box<Account<'info, MarketRegistryPage>>,
+
+    /// Series the market joins; required exactly when `series_id` is set
+    #[account(
+        mut,
+        seeds = [b"series", series.series_id.as_ref()],
+        bump = series.bump,
+    )]
+    pub series: Option<Account<'info, MarketSeries>>,
+
+    /// Vault PDA - holds all USDC deposits for this market
+    /// Authority is the vault itself (PDA as signer)
+    /// Omit together with fee_vault to defer creation to init_vault
+    #[account(
+        init,
+        payer = authority,
+        token::mint = usdc_mint,
+        token::authority = vault,
+        token::token_program = token_program,
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Fee Vault PDA - holds protocol fees skimmed from bets
+    #[account(
+        init,
+        payer = authority,
+        token::mint = usdc_mint,
+        token::authority = fee_vault,
+        token::token_program = token_program,
+        seeds = [b"fee_vault", market.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Market mint; must be listed in `config.allowed_mints`
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+
+    /// Authority must be config.admin or allowlisted (checked in instruction)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMarketSeries<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = MarketSeries::LEN,
+        seeds = [b"series", series_id.key().as_ref()],
+        bump
+    )]
+    pub series: Account<'info, MarketSeries>,
+
+    /// CHECK: only its key is used, as the series seed
+    pub series_id: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitVault<'info> {
+    #[account(
+        mut,
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Vault PDA - holds all USDC deposits for this market
+    #[account(
+        init,
+        payer = authority,
+        token::mint = usdc_mint,
+        token::authority = vault,
+        token::token_program = token_program,
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Fee Vault PDA - holds protocol fees skimmed from bets
+    #[account(
+        init,
+        payer = authority,
+        token::mint = usdc_mint,
+        token::authority = fee_vault,
+        token::token_program = token_program,
+        seeds = [b"fee_vault", market.key().as_ref()],
+        bump
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = market.usdc_mint)]
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct PlaceBet<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump,
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserPosition::LEN,
+        seeds = [b"position", market.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// Lifetime totals; created on the user's first bet
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserStats::LEN,
+        seeds = [b"stats", user.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    /// The user's betting caps; users without one bet without caps
+    #[account(
+        mut,
+        seeds = [b"limits", user.key().as_ref()],
+        bump = user_limits.bump,
+    )]
+    pub user_limits: Option<Account<'info, UserBettingLimits>>,
+
+    /// Required when market.whitelist_enabled
+    #[account(
+        seeds = [b"whitelist", market.key().as_ref()],
+        bump = whitelist.bump,
+    )]
+    pub whitelist: Option<Account<'info, Whitelist>>,
+
+    /// Token vault (USDC markets only)
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = market.vault_bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL vault PDA (SOL markets only)
+    #[account(
+        mut,
+        seeds = [b"sol_vault", market.key().as_ref()],
+        bump = market.sol_vault_bump,
+    )]
+    pub sol_vault: Option<SystemAccount<'info>>,
+
+    /// Fee vault (USDC markets only)
+    #[account(
+        mut,
+        seeds = [b"fee_vault", market.key().as_ref()],
+        bump = market.fee_vault_bump,
+    )]
+    pub fee_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL fee vault PDA (SOL markets only)
+    #[account(
+        mut,
+        seeds = [b"sol_fee_vault", market.key().as_ref()],
+        bump = market.sol_fee_vault_bump,
+    )]
+    pub sol_fee_vault: Option<SystemAccount<'info>>,
+
+    /// Insurance vault of the market's mint; required on USDC-currency
+    /// markets while `config.insurance_fee_bps` is non-zero
+    #[account(
+        mut,
+        seeds = [b"insurance_vault", market.usdc_mint.as_ref()],
+        bump,
+    )]
+    pub insurance_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == market.usdc_mint,
+        constraint = user_token_account.owner == user.key(),
+    )]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Required by transfer_checked for the mint decimals
+    #[account(address = market.usdc_mint)]
+    pub usdc_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Same custody accounts as `PlaceBet`
+#[derive(Accounts)]
+pub struct IncreasePosition<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump,
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+
+    /// Must already exist: the side comes from its original bet
+    #[account(
+        mut,
+        seeds = [b"position", market.key().as_ref(), user.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.user == user.key(),
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// Lifetime totals; created on the user's first bet
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserStats::LEN,
+        seeds = [b"stats", user.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    /// The user's betting caps; users without one bet without caps
+    #[account(
+        mut,
+        seeds = [b"limits", user.key().as_ref()],
+        bump = user_limits.bump,
+    )]
+    pub user_limits: Option<Account<'info, UserBettingLimits>>,
+
+    /// Required when market.whitelist_enabled
+    #[account(
+        seeds = [b"whitelist", market.key().as_ref()],
+        bump = whitelist.bump,
+    )]
+    pub whitelist: Option<Account<'info, Whitelist>>,
+
+    /// Token vault (USDC markets only)
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = market.vault_bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL vault PDA (SOL markets only)
+    #[account(
+        mut,
+        seeds = [b"sol_vault", market.key().as_ref()],
+        bump = market.sol_vault_bump,
+    )]
+    pub sol_vault: Option<SystemAccount<'info>>,
+
+    /// Fee vault (USDC markets only)
+    #[account(
+        mut,
+        seeds = [b"fee_vault", market.key().as_ref()],
+        bump = market.fee_vault_bump,
+    )]
+    pub fee_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL fee vault PDA (SOL markets only)
+    #[account(
+        mut,
+        seeds = [b"sol_fee_vault", market.key().as_ref()],
+        bump = market.sol_fee_vault_bump,
+    )]
+    pub sol_fee_vault: Option<SystemAccount<'info>>,
+
+    /// Insurance vault of the market's mint; required on USDC-currency
+    /// markets while `config.insurance_fee_bps` is non-zero
+    #[account(
+        mut,
+        seeds = [b"insurance_vault", market.usdc_mint.as_ref()],
+        bump,
+    )]
+    pub insurance_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == market.usdc_mint,
+        constraint = user_token_account.owner == user.key(),
+    )]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Required by transfer_checked for the mint decimals
+    #[account(address = market.usdc_mint)]
+    pub usdc_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Resolve Market Account Context
+///
+/// Custody accounts are only needed when the market charges a creator fee;
+/// `resolver_stake` only when the protocol sets a minimum resolver stake.
+#[derive(Accounts)]
+pub struct ResolveMarket<'info> {
+    #[account(
+        mut,
+        constraint = market.resolver == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump,
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+
+    #[account(
+        mut,
+        seeds = [b"resolver_stake", authority.key().as_ref()],
+        bump = resolver_stake.bump,
+    )]
+    pub resolver_stake: Option<Account<'info, ResolverStake>>,
+
+    /// Token vault (USDC markets only)
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = market.vault_bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL vault PDA (SOL markets only)
+    #[account(
+        mut,
+        seeds = [b"sol_vault", market.key().as_ref()],
+        bump = market.sol_vault_bump,
+    )]
+    pub sol_vault: Option<SystemAccount<'info>>,
+
+    /// Creator-chosen token account receiving the creator fee (USDC markets)
+    #[account(
+        mut,
+        constraint = creator_fee_account.mint == market.usdc_mint,
+        constraint = creator_fee_account.owner == market.authority,
+    )]
+    pub creator_fee_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the creator fee on SOL markets
+    #[account(mut, address = market.authority)]
+    pub creator: Option<SystemAccount<'info>>,
+
+    #[account(address = market.usdc_mint)]
+    pub usdc_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// market.resolver: the authority unless delegated with `set_resolver`
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Markets to resolve are passed as `remaining_accounts`; `resolver_stake`
+/// is only needed when the protocol sets a minimum resolver stake
+#[derive(Accounts)]
+pub struct BatchResolveMarkets<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump,
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+
+    #[account(
+        mut,
+        seeds = [b"resolver_stake", authority.key().as_ref()],
+        bump = resolver_stake.bump,
+    )]
+    pub resolver_stake: Option<Account<'info, ResolverStake>>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendExpiry<'info> {
+    #[account(
+        mut,
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMarketMetadata<'info> {
+    #[account(
+        mut,
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Custody accounts are only needed when the market charges a creator fee,
+/// as for `ResolveMarket`
+#[derive(Accounts)]
+pub struct AutoResolveWithPyth<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump,
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+
+    /// CHECK: Pyth price account, verified against market.pyth_price_feed
+    /// and parsed by pyth_sdk_solana
+    pub price_feed: AccountInfo<'info>,
+
+    /// CHECK: the market's MarketV2 PDA, always passed; its criteria are
+    /// read when `create_market_v2` has allocated it
+    #[account(
+        seeds = [b"market_v2", market.key().as_ref()],
+        bump,
+    )]
+    pub market_v2: UncheckedAccount<'info>,
+
+    /// Token vault (USDC markets only)
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = market.vault_bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL vault PDA (SOL markets only)
+    #[account(
+        mut,
+        seeds = [b"sol_vault", market.key().as_ref()],
+        bump = market.sol_vault_bump,
+    )]
+    pub sol_vault: Option<SystemAccount<'info>>,
+
+    /// Creator-chosen token account receiving the creator fee (USDC markets)
+    #[account(
+        mut,
+        constraint = creator_fee_account.mint == market.usdc_mint,
+        constraint = creator_fee_account.owner == market.authority,
+    )]
+    pub creator_fee_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the creator fee on SOL markets
+    #[account(mut, address = market.authority)]
+    pub creator: Option<SystemAccount<'info>>,
+
+    #[account(address = market.usdc_mint)]
+    pub usdc_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMarketV2<'info> {
+    #[account(
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = MarketV2::LEN,
+        seeds = [b"market_v2", market.key().as_ref()],
+        bump
+    )]
+    pub market_v2: Account<'info, MarketV2>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetParentMarket<'info> {
+    #[account(
+        mut,
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        constraint = parent_market.key() != market.key() @ ErrorCode::ParentMarketMismatch,
+    )]
+    pub parent_market: Account<'info, Market>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AttachSwitchboardFeed<'info> {
+    #[account(
+        mut,
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = SwitchboardFeedConfig::LEN,
+        seeds = [b"sw_config", market.key().as_ref()],
+        bump
+    )]
+    pub feed_config: Account<'info, SwitchboardFeedConfig>,
+
+    /// CHECK: Switchboard aggregator; only its owner is checked here, the
+    /// data is parsed at resolution
+    #[account(owner = SWITCHBOARD_PROGRAM_ID @ ErrorCode::SwitchboardFeedMismatch)]
+    pub aggregator: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Custody accounts are only needed when the market charges a creator fee,
+/// as for `ResolveMarket`
+#[derive(Accounts)]
+pub struct AutoResolveWithSwitchboard<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump,
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+
+    #[account(
+        seeds = [b"sw_config", market.key().as_ref()],
+        bump = feed_config.bump,
+        has_one = market,
+        has_one = aggregator @ ErrorCode::SwitchboardFeedMismatch,
+    )]
+    pub feed_config: Account<'info, SwitchboardFeedConfig>,
+
+    /// CHECK: Switchboard aggregator, verified against feed_config and
+    /// parsed by switchboard_solana
+    #[account(owner = SWITCHBOARD_PROGRAM_ID @ ErrorCode::SwitchboardFeedMismatch)]
+    pub aggregator: AccountInfo<'info>,
+
+    /// Token vault (USDC markets only)
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = market.vault_bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL vault PDA (SOL markets only)
+    #[account(
+        mut,
+        seeds = [b"sol_vault", market.key().as_ref()],
+        bump = market.sol_vault_bump,
+    )]
+    pub sol_vault: Option<SystemAccount<'info>>,
+
+    /// Creator-chosen token account receiving the creator fee (USDC markets)
+    #[account(
+        mut,
+        constraint = creator_fee_account.mint == market.usdc_mint,
+        constraint = creator_fee_account.owner == market.authority,
+    )]
+    pub creator_fee_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the creator fee on SOL markets
+    #[account(mut, address = market.authority)]
+    pub creator: Option<SystemAccount<'info>>,
+
+    #[account(address = market.usdc_mint)]
+    pub usdc_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Custody accounts are only needed when the market charges a creator fee,
+/// as for `ResolveMarket`
+#[derive(Accounts)]
+pub struct ResolveWithOracle<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump,
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+
+    /// CHECK: Switchboard aggregator, verified against market.oracle and
+    /// parsed by switchboard_solana
+    #[account(owner = SWITCHBOARD_PROGRAM_ID @ ErrorCode::OracleFeedMismatch)]
+    pub aggregator: AccountInfo<'info>,
+
+    /// Token vault (USDC markets only)
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = market.vault_bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL vault PDA (SOL markets only)
+    #[account(
+        mut,
+        seeds = [b"sol_vault", market.key().as_ref()],
+        bump = market.sol_vault_bump,
+    )]
+    pub sol_vault: Option<SystemAccount<'info>>,
+
+    /// Creator-chosen token account receiving the creator fee (USDC markets)
+    #[account(
+        mut,
+        constraint = creator_fee_account.mint == market.usdc_mint,
+        constraint = creator_fee_account.owner == market.authority,
+    )]
+    pub creator_fee_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the creator fee on SOL markets
+    #[account(mut, address = market.authority)]
+    pub creator: Option<SystemAccount<'info>>,
+
+    #[account(address = market.usdc_mint)]
+    pub usdc_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(tag: String)]
+pub struct InitTagIndex<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = TagIndex::space(0),
+        seeds = [b"tag_index", &tag_hash(&tag)],
+        bump
+    )]
+    pub tag_index: Account<'info, TagIndex>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// TagIndex accounts are passed as `remaining_accounts`
+#[derive(Accounts)]
+pub struct SetMarketTags<'info> {
+    #[account(
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = MarketTags::LEN,
+        seeds = [b"tags", market.key().as_ref()],
+        bump
+    )]
+    pub market_tags: Account<'info, MarketTags>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateQuestion<'info> {
+    #[account(
+        mut,
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PauseMarket<'info> {
+    #[account(
+        mut,
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResumeMarket<'info> {
+    #[account(
+        mut,
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetResolver<'info> {
+    #[account(
+        mut,
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthorityTransfer<'info> {
+    #[account(
+        mut,
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthorityTransfer<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    /// Must equal market.pending_authority (checked in instruction)
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecordResolutionEvidence<'info> {
+    #[account(
+        mut,
+        constraint = market.resolver == resolver.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init_if_needed,
+        payer = resolver,
+        space = ResolutionEvidence::LEN,
+        seeds = [b"evidence", market.key().as_ref()],
+        bump
+    )]
+    pub evidence: Account<'info, ResolutionEvidence>,
+
+    #[account(mut)]
+    pub resolver: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSuccessorMarket<'info> {
+    #[account(
+        mut,
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RollResidual<'info> {
+    #[account(
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Must equal market.successor_market (checked in instruction)
+    #[account(mut)]
+    pub successor_market: Account<'info, Market>,
+
+    /// Token vault (USDC markets only)
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = market.vault_bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL vault PDA (SOL markets only)
+    #[account(
+        mut,
+        seeds = [b"sol_vault", market.key().as_ref()],
+        bump = market.sol_vault_bump,
+    )]
+    pub sol_vault: Option<SystemAccount<'info>>,
+
+    /// Successor token vault (USDC markets only)
+    #[account(
+        mut,
+        seeds = [b"vault", successor_market.key().as_ref()],
+        bump = successor_market.vault_bump,
+    )]
+    pub successor_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Successor SOL vault PDA (SOL markets only)
+    #[account(
+        mut,
+        seeds = [b"sol_vault", successor_market.key().as_ref()],
+        bump = successor_market.sol_vault_bump,
+    )]
+    pub successor_sol_vault: Option<SystemAccount<'info>>,
+
+    /// Required by transfer_checked for the mint decimals
+    #[account(address = market.usdc_mint)]
+    pub usdc_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExportSnapshot<'info> {
+    pub market: Account<'info, Market>,
+
+    /// Optional co-signature from market.authority to attest the snapshot
+    pub authority: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct PreviewPayout<'info> {
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct GetUserPayoutEstimate<'info> {
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"position", market.key().as_ref(), user_position.user.as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+}
+
+#[derive(Accounts)]
+pub struct GetMarketStatus<'info> {
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct GetSeriesMarkets<'info> {
+    #[account(
+        seeds = [b"series", series.series_id.as_ref()],
+        bump = series.bump,
+    )]
+    pub series: Account<'info, MarketSeries>,
+}
+
+#[derive(Accounts)]
+pub struct GetVaultBalance<'info> {
+    pub market: Account<'info, Market>,
+
+    /// Token vault (USDC markets only)
+    #[account(
+        seeds = [b"vault", market.key().as_ref()],
+        bump = market.vault_bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL vault PDA (SOL markets only)
+    #[account(
+        seeds = [b"sol_vault", market.key().as_ref()],
+        bump = market.sol_vault_bump,
+    )]
+    pub sol_vault: Option<SystemAccount<'info>>,
+}
+
+impl GetVaultBalance<'_> {
+    /// Funds custody holds for the market's currency
+    fn balance(&self) -> Result<u64> {
+        Ok(match self.market.currency {
+            MarketCurrency::Usdc => self.vault.as_ref()
+                .ok_or(ErrorCode::CurrencyMismatch)?
+                .amount,
+            MarketCurrency::Sol => self.sol_vault.as_ref()
+                .ok_or(ErrorCode::CurrencyMismatch)?
+                .lamports(),
+        })
+    }
+}
+
+/// Claim With Proof Account Context
+/// 
+/// CRITICAL SECURITY ACCOUNT: ix_sysvar
 /// 
-/// The MXE signs this message with its Ed25519 private key.
-/// This program verifies the signature against MXE_PUBKEY.
-fn construct_payout_message(
-    market: &Pubkey,
-    user: &Pubkey,
-    payout: u64,
-    nonce: u64,
-) -> [u8; 32] {
-    let mut data = Vec::with_capacity(32 + 32 + 8 + 8);
-    data.extend_from_slice(market.as_ref());
-    data.extend_from_slice(user.as_ref());
-    data.extend_from_slice(&payout.to_le_bytes());
-    data.extend_from_slice(&nonce.to_le_bytes());
-    
-    // Hash the concatenated data
-    keccak::hash(&data).to_bytes()
+/// The ix_sysvar (Instructions Sysvar) is used to verify the Ed25519 signature.
+/// The transaction MUST start with one Ed25519 instruction per signature
+/// (indices 0..n), each with:
+/// - Public Key: a member of config.mxe_pubkeys
+/// - Signature: MXE's signature over the payout message
+/// - Message: keccak256(program_id || market || user || payout || relayer_fee || nonce || expiry)
+/// 
+/// Solana's Ed25519 program verifies the signature BEFORE this instruction executes.
+/// We then validate that the signature is from the correct MXE public key.
+/// 
+/// This makes forgery cryptographically impossible without MXE's private key.
+///
+/// The market address is not re-derived from `market.question` because the
+/// question can be edited (`update_question`) after creation; the account is
+/// still owner/discriminator checked and the vault is bound by its PDA seeds.
+#[derive(Accounts)]
+pub struct ClaimWithProof<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    /// Source of the MXE public key claims are verified against
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump,
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+
+    #[account(
+        mut,
+        seeds = [b"position", market.key().as_ref(), user.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.user == user.key(),
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(
+        mut,
+        seeds = [b"stats", user.key().as_ref()],
+        bump = user_stats.bump,
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    /// Token vault (USDC markets only)
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = market.vault_bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL vault PDA (SOL markets only)
+    #[account(
+        mut,
+        seeds = [b"sol_vault", market.key().as_ref()],
+        bump = market.sol_vault_bump,
+    )]
+    pub sol_vault: Option<SystemAccount<'info>>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == market.usdc_mint,
+        constraint = user_token_account.owner == user.key(),
+    )]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Required by transfer_checked for the mint decimals
+    #[account(address = market.usdc_mint)]
+    pub usdc_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Covers a vault shortfall together with `insurance_vault`
+    #[account(
+        mut,
+        seeds = [b"insurance"],
+        bump = insurance_fund.bump,
+    )]
+    pub insurance_fund: Option<Account<'info, InsuranceFund>>,
+
+    #[account(
+        mut,
+        seeds = [b"insurance_vault", market.usdc_mint.as_ref()],
+        bump,
+    )]
+    pub insurance_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Mutable so SOL payouts can be credited directly
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: This is the Solana Instructions Sysvar
+    /// Used to verify the Ed25519 signature instruction
+    #[account(address = IX_SYSVAR_ID)]
+    pub ix_sysvar: AccountInfo<'info>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Claim With ZK Proof Account Context
+///
+/// The ClaimWithProof accounts (its `ix_sysvar` goes unused) plus the
+/// Groth16 verifying key.
+#[derive(Accounts)]
+pub struct ClaimWithZkProof<'info> {
+    pub claim: ClaimWithProof<'info>,
+
+    #[account(
+        seeds = [b"zk_vk"],
+        bump = zk_verifying_key.bump,
+    )]
+    pub zk_verifying_key: Box<Account<'info, ZkVerifyingKey>>,
+}
+
+/// Claim With Proof Relayed Account Context
+///
+/// Same accounts as ClaimWithProof, except `user` does not sign. The
+/// position PDA and the token account owner check pin the payout to the
+/// user the MXE signed for; `relayer` pays the transaction fee and
+/// receives only the signed `relayer_fee`.
+#[derive(Accounts)]
+pub struct ClaimWithProofRelayed<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    /// Source of the MXE public key claims are verified against
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump,
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+
+    #[account(
+        mut,
+        seeds = [b"position", market.key().as_ref(), user.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.user == user.key(),
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(
+        mut,
+        seeds = [b"stats", user.key().as_ref()],
+        bump = user_stats.bump,
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    /// Token vault (USDC markets only)
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = market.vault_bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL vault PDA (SOL markets only)
+    #[account(
+        mut,
+        seeds = [b"sol_vault", market.key().as_ref()],
+        bump = market.sol_vault_bump,
+    )]
+    pub sol_vault: Option<SystemAccount<'info>>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == market.usdc_mint,
+        constraint = user_token_account.owner == user.key(),
+    )]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Required by transfer_checked for the mint decimals
+    #[account(address = market.usdc_mint)]
+    pub usdc_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Position owner; authorization comes from the MXE proof bound
+    /// to this key. Mutable so SOL payouts can be credited directly.
+    #[account(mut)]
+    pub user: UncheckedAccount<'info>,
+
+    /// Pays the transaction fee on the user's behalf. Mutable so SOL
+    /// relayer fees can be credited directly.
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    /// Receives the relayer fee (USDC markets only)
+    #[account(
+        mut,
+        constraint = relayer_token_account.mint == market.usdc_mint,
+        constraint = relayer_token_account.owner == relayer.key(),
+    )]
+    pub relayer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: This is the Solana Instructions Sysvar
+    /// Used to verify the Ed25519 signature instruction
+    #[account(address = IX_SYSVAR_ID)]
+    pub ix_sysvar: AccountInfo<'info>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordPoolSplit<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    /// Source of the MXE keys the split signature is verified against
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: This is the Solana Instructions Sysvar
+    /// Used to verify the Ed25519 signature instructions
+    #[account(address = IX_SYSVAR_ID)]
+    pub ix_sysvar: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevealSide<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    /// Source of the MXE keys the reveal signature is verified against
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"position", market.key().as_ref(), user.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.user == user.key(),
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    pub user: Signer<'info>,
+
+    /// CHECK: This is the Solana Instructions Sysvar
+    /// Used to verify the Ed25519 signature instructions
+    #[account(address = IX_SYSVAR_ID)]
+    pub ix_sysvar: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateBatchPayout<'info> {
+    pub market: Account<'info, Market>,
+
+    /// Source of the MXE keys the root signature is verified against
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = BatchPayout::LEN,
+        seeds = [b"batch_payout", market.key().as_ref()],
+        bump
+    )]
+    pub batch_payout: Account<'info, BatchPayout>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: This is the Solana Instructions Sysvar
+    /// Used to verify the Ed25519 signature instructions
+    #[account(address = IX_SYSVAR_ID)]
+    pub ix_sysvar: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Claim From Batch Account Context
+///
+/// Same accounts as ClaimWithProof, with the market's BatchPayout root in
+/// place of Config and the Instructions Sysvar.
+#[derive(Accounts)]
+pub struct ClaimFromBatch<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"batch_payout", market.key().as_ref()],
+        bump = batch_payout.bump,
+        has_one = market,
+    )]
+    pub batch_payout: Account<'info, BatchPayout>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump,
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+
+    #[account(
+        mut,
+        seeds = [b"position", market.key().as_ref(), user.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.user == user.key(),
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(
+        mut,
+        seeds = [b"stats", user.key().as_ref()],
+        bump = user_stats.bump,
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    /// Token vault (USDC markets only)
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = market.vault_bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL vault PDA (SOL markets only)
+    #[account(
+        mut,
+        seeds = [b"sol_vault", market.key().as_ref()],
+        bump = market.sol_vault_bump,
+    )]
+    pub sol_vault: Option<SystemAccount<'info>>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.mint == market.usdc_mint,
+        constraint = user_token_account.owner == user.key(),
+    )]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Required by transfer_checked for the mint decimals
+    #[account(address = market.usdc_mint)]
+    pub usdc_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Mutable so SOL payouts can be credited directly
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Batch Claim Account Context
+///
+/// Per-market accounts arrive through `remaining_accounts` and are checked
+/// in `batch_claim`; only accounts shared by every claim are listed here.
+#[derive(Accounts)]
+pub struct BatchClaim<'info> {
+    /// Source of the MXE public key claims are verified against
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump,
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+
+    /// Credited once per claim in the batch
+    #[account(
+        mut,
+        seeds = [b"stats", user.key().as_ref()],
+        bump = user_stats.bump,
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    /// Mint of every market in the batch
+    pub usdc_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: This is the Solana Instructions Sysvar
+    /// Used to verify the Ed25519 signature instructions
+    #[account(address = IX_SYSVAR_ID)]
+    pub ix_sysvar: AccountInfo<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReadUserPnl<'info> {
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"position", market.key().as_ref(), user_position.user.as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyClaimCorrectness<'info> {
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"position", market.key().as_ref(), user_position.user.as_ref()],
+        bump = user_position.bump,
+    )]
+    pub user_position: Account<'info, UserPosition>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_owner: Pubkey)]
+pub struct TransferPosition<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    /// Sender's position; closed, rent back to the sender
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"position", market.key().as_ref(), user.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.user == user.key(),
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// Receives the stake; created if the new owner has no position yet
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserPosition::LEN,
+        seeds = [b"position", market.key().as_ref(), new_owner.as_ref()],
+        bump
+    )]
+    pub new_position: Account<'info, UserPosition>,
+
+    /// New owner's stats, which claims update; created if missing
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = UserStats::LEN,
+        seeds = [b"stats", new_owner.as_ref()],
+        bump
+    )]
+    pub new_owner_stats: Account<'info, UserStats>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseUserPosition<'info> {
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"position", market.key().as_ref(), user.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.user == user.key(),
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BatchClosePositions<'info> {
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_key: Pubkey)]
+pub struct RemoveFromCategoryIndex<'info> {
+    #[account(
+        mut,
+        seeds = [b"category_index", &[category_index.category as u8]],
+        bump = category_index.bump,
+    )]
+    pub category_index: Account<'info, CategoryIndex>,
+
+    /// CHECK: only checked to be closed (empty)
+    #[account(address = market_key)]
+    pub market: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseMarket<'info> {
+    #[account(
+        mut,
+        close = authority,
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = market.vault_bump,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"fee_vault", market.key().as_ref()],
+        bump = market.fee_vault_bump,
+    )]
+    pub fee_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// SOL vault PDA (SOL markets only)
+    #[account(
+        seeds = [b"sol_vault", market.key().as_ref()],
+        bump = market.sol_vault_bump,
+    )]
+    pub sol_vault: Option<SystemAccount<'info>>,
+
+    /// SOL fee vault PDA (SOL markets only)
+    #[account(
+        seeds = [b"sol_fee_vault", market.key().as_ref()],
+        bump = market.sol_fee_vault_bump,
+    )]
+    pub sol_fee_vault: Option<SystemAccount<'info>>,
+
+    #[account(
+        mut,
+        seeds = [b"registry"],
+        bump = registry.bump,
+    )]
+    pub registry: Account<'info, MarketRegistry>,
+
+    /// Registry page holding the market
+    #[account(
+        mut,
+        seeds = [b"registry_page", &market.registry_page.to_le_bytes()],
+        bump = registry_page.bump,
+    )]
+    pub registry_page: Account<'info, MarketRegistryPage>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Registry pages to read are passed as `remaining_accounts`
+#[derive(Accounts)]
+pub struct GetMarketPage<'info> {
+    #[account(
+        seeds = [b"registry"],
+        bump = registry.bump,
+    )]
+    pub registry: Account<'info, MarketRegistry>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// Fee vault (USDC markets only)
+    #[account(
+        mut,
+        seeds = [b"fee_vault", market.key().as_ref()],
+        bump = market.fee_vault_bump,
+    )]
+    pub fee_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL fee vault PDA (SOL markets only)
+    #[account(
+        mut,
+        seeds = [b"sol_fee_vault", market.key().as_ref()],
+        bump = market.sol_fee_vault_bump,
+    )]
+    pub sol_fee_vault: Option<SystemAccount<'info>>,
+
+    #[account(address = market.usdc_mint)]
+    pub usdc_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Receives USDC fees
+    #[account(
+        mut,
+        constraint = destination_token_account.mint == market.usdc_mint,
+    )]
+    pub destination_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives SOL fees
+    #[account(mut)]
+    pub destination: Option<SystemAccount<'info>>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawUnclaimed<'info> {
+    #[account(
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Token vault (USDC markets only)
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = market.vault_bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL vault PDA (SOL markets only)
+    #[account(
+        mut,
+        seeds = [b"sol_vault", market.key().as_ref()],
+        bump = market.sol_vault_bump,
+    )]
+    pub sol_vault: Option<SystemAccount<'info>>,
+
+    #[account(address = market.usdc_mint)]
+    pub usdc_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Receives USDC; owned by the authority or the treasury
+    #[account(
+        mut,
+        constraint = destination_token_account.mint == market.usdc_mint,
+    )]
+    pub destination_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives SOL; the authority or the treasury
+    #[account(mut)]
+    pub destination: Option<SystemAccount<'info>>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyWithdraw<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    pub market: Account<'info, Market>,
+
+    /// Token vault (USDC markets only)
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump = market.vault_bump,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SOL vault PDA (SOL markets only)
+    #[account(
+        mut,
+        seeds = [b"sol_vault", market.key().as_ref()],
+        bump = market.sol_vault_bump,
+    )]
+    pub sol_vault: Option<SystemAccount<'info>>,
+
+    #[account(address = market.usdc_mint)]
+    pub usdc_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Receives USDC (USDC markets only)
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == TREASURY_PUBKEY @ ErrorCode::Unauthorized,
+        constraint = treasury_token_account.mint == market.usdc_mint,
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives SOL (SOL markets only)
+    #[account(mut, address = TREASURY_PUBKEY)]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeResolution<'info> {
+    #[account(
+        mut,
+        constraint = market.resolver == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DisputeResolution<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    /// Only bettors on this market may challenge
+    #[account(
+        seeds = [b"position", market.key().as_ref(), disputant.key().as_ref()],
+        bump = user_position.bump,
+        constraint = user_position.user == disputant.key(),
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    pub disputant: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OverrideResolution<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DisputeMarketResolution<'info> {
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = disputant,
+        space = DisputeRecord::LEN,
+        seeds = [b"dispute", market.key().as_ref(), disputant.key().as_ref()],
+        bump
+    )]
+    pub dispute: Account<'info, DisputeRecord>,
+
+    /// Counts the dispute so the bond cannot be released under it
+    #[account(
+        mut,
+        seeds = [b"bond", market.key().as_ref()],
+        bump = resolution_bond.bump,
+    )]
+    pub resolution_bond: Account<'info, ResolutionBond>,
+
+    #[account(mut)]
+    pub disputant: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseDispute<'info> {
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        close = disputant,
+        seeds = [b"dispute", market.key().as_ref(), disputant.key().as_ref()],
+        bump = dispute.bump,
+        has_one = market,
+        has_one = disputant,
+    )]
+    pub dispute: Account<'info, DisputeRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"bond", market.key().as_ref()],
+        bump = resolution_bond.bump,
+    )]
+    pub resolution_bond: Account<'info, ResolutionBond>,
+
+    #[account(mut)]
+    pub disputant: SystemAccount<'info>,
+
+    #[account(mut, address = TREASURY_PUBKEY)]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    /// market.authority, or config.admin while the resolution bond is
+    /// held (checked in instruction)
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ForceResolve<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"stats"],
+        bump = stats.bump,
+    )]
+    pub stats: Account<'info, ProtocolStats>,
+
+    /// Source of the force-resolve delay
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = resolver,
+        space = ForceResolution::LEN,
+        seeds = [b"force_resolve", market.key().as_ref()],
+        bump
+    )]
+    pub force_resolution: Account<'info, ForceResolution>,
+
+    #[account(mut)]
+    pub resolver: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OverturnForceResolution<'info> {
+    #[account(
+        mut,
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Closed once overturned; rent goes back to the resolver
+    #[account(
+        mut,
+        close = resolver,
+        seeds = [b"force_resolve", market.key().as_ref()],
+        bump = force_resolution.bump,
+        has_one = market,
+        has_one = resolver,
+    )]
+    pub force_resolution: Account<'info, ForceResolution>,
+
+    #[account(mut)]
+    pub resolver: SystemAccount<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, address = TREASURY_PUBKEY)]
+    pub treasury: SystemAccount<'info>,
+
+    /// Signs the counter-result
+    pub authority: Signer<'info>,
+
+    /// Approves the arbitration
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseForceResolveBond<'info> {
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        close = resolver,
+        seeds = [b"force_resolve", market.key().as_ref()],
+        bump = force_resolution.bump,
+        has_one = market,
+        has_one = resolver,
+    )]
+    pub force_resolution: Account<'info, ForceResolution>,
+
+    #[account(mut)]
+    pub resolver: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseResolutionBond<'info> {
+    #[account(
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"bond", market.key().as_ref()],
+        bump = resolution_bond.bump,
+        has_one = market,
+        has_one = authority,
+    )]
+    pub resolution_bond: Account<'info, ResolutionBond>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SlashResolutionBond<'info> {
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"bond", market.key().as_ref()],
+        bump = resolution_bond.bump,
+        has_one = market,
+    )]
+    pub resolution_bond: Account<'info, ResolutionBond>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, address = TREASURY_PUBKEY)]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimCreatorStake<'info> {
+    #[account(
+        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_stake", market.key().as_ref()],
+        bump = creator_stake.bump,
+        has_one = market,
+        has_one = authority,
+    )]
+    pub creator_stake: Account<'info, CreatorStake>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SlashCreatorStake<'info> {
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"creator_stake", market.key().as_ref()],
+        bump = creator_stake.bump,
+        has_one = market,
+    )]
+    pub creator_stake: Account<'info, CreatorStake>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, address = TREASURY_PUBKEY)]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(address = config.admin @ ErrorCode::Unauthorized)]
+    pub admin: Signer<'info>,
+}
+
+// ============================================================================
+// State
+// ============================================================================
+
+/// Display-only market data that no instruction reads on the hot path
+/// (seeds = ["metadata", market])
+#[account]
+pub struct MarketMetadata {
+    pub market: Pubkey,
+    /// One label per outcome, indexed like `MarketResult::Outcome`; empty
+    /// when the market uses default labels
+    pub outcome_labels: Vec<String>,
+    pub bump: u8,
+}
+
+impl MarketMetadata {
+    pub const MAX_LABEL_LEN: usize = 32;
+
+    pub const LEN: usize = 8 + // discriminator
+        32 + // market
+        4 + (Market::MAX_OUTCOMES as usize) * (4 + Self::MAX_LABEL_LEN) + // outcome_labels
+        1; // bump
+}
+
+/// Machine-readable resolution terms of a market
+/// (seeds = ["market_v2", market])
+#[account]
+pub struct MarketV2 {
+    pub market: Pubkey,
+    pub resolution_criteria: ResolutionCriteria,
+    /// Copy of the market's expiry, so keepers need only this account
+    pub expiry_timestamp: i64,
+    pub bump: u8,
+}
+
+impl MarketV2 {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // market
+        ResolutionCriteria::MAX_LEN + // resolution_criteria
+        8 + // expiry_timestamp
+        1; // bump
+}
+
+/// Markets of one category, for discovery without scanning every market
+/// (seeds = ["category_index", category as u8])
+///
+/// Allocated one slot at a time by `create_market`.
+#[account]
+pub struct CategoryIndex {
+    pub category: MarketCategory,
+    pub markets: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl CategoryIndex {
+    pub const MAX_MARKETS: usize = 512;
+
+    pub const fn space(num_markets: usize) -> usize {
+        8 + // discriminator
+        1 + // category enum
+        4 + num_markets * 32 + // markets
+        1 // bump
+    }
+}
+
+/// Backstop for vault shortfalls (seeds = ["insurance"])
+///
+/// Funds sit in one ["insurance_vault", mint] token account per mint, with
+/// this PDA as their authority. Fed by `Config.insurance_fee_bps` of every
+/// protocol fee and by `insurance_fund_deposit`.
+#[account]
+pub struct InsuranceFund {
+    /// Sum of `insurance_fund_deposit` amounts, across mints
+    pub total_deposited: u64,
+    /// Sum paid into market vaults, across mints
+    pub total_drawn: u64,
+    pub bump: u8,
+}
+
+impl InsuranceFund {
+    pub const LEN: usize = 8 + // discriminator
+        8 + // total_deposited
+        8 + // total_drawn
+        1; // bump
+}
+
+/// Keyword tags of a market (seeds = ["tags", market])
+#[account]
+pub struct MarketTags {
+    pub market: Pubkey,
+    pub tags: Vec<String>,
+    pub bump: u8,
+}
+
+impl MarketTags {
+    pub const MAX_TAGS: usize = 8;
+    pub const MAX_TAG_LEN: usize = 32;
+
+    pub const LEN: usize = 8 + // discriminator
+        32 + // market
+        4 + Self::MAX_TAGS * (4 + Self::MAX_TAG_LEN) + // tags
+        1; // bump
+}
+
+/// Markets carrying one tag (seeds = ["tag_index", keccak256(tag)[..8]])
+///
+/// Created by `init_tag_index`, then grown one slot at a time by
+/// `set_market_tags`.
+#[account]
+pub struct TagIndex {
+    pub tag_hash: [u8; 8],
+    pub tag: String,
+    pub markets: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl TagIndex {
+    pub const MAX_MARKETS: usize = 512;
+
+    pub const fn space(num_markets: usize) -> usize {
+        8 + // discriminator
+        8 + // tag_hash
+        4 + MarketTags::MAX_TAG_LEN + // tag
+        4 + num_markets * 32 + // markets
+        1 // bump
+    }
+}
+
+/// Every market, for paginated discovery (seeds = ["registry"])
+///
+/// Keys live in MarketRegistryPage accounts; this one tracks how many
+/// each page holds.
+#[account]
+pub struct MarketRegistry {
+    pub total_markets: u32,
+    /// Markets in each page, indexed by page number
+    pub page_lens: Vec<u16>,
+    pub bump: u8,
+}
+
+impl MarketRegistry {
+    /// MAX_PAGES full pages hold 10,000 open markets
+    pub const MAX_PAGES: usize = 40;
+
+    pub const LEN: usize = 8 + // discriminator
+        4 + // total_markets
+        4 + Self::MAX_PAGES * 2 + // page_lens
+        1; // bump
+
+    /// Page the next market goes to: the first one with a free slot, so
+    /// slots freed by `close_market` are reused, or a new one once every
+    /// page has been filled
+    pub fn current_page(&self) -> u32 {
+        self.page_lens
+            .iter()
+            .position(|len| (*len as usize) < MarketRegistryPage::MAX_MARKETS)
+            .unwrap_or(self.page_lens.len()) as u32
+    }
+}
+
+/// One page of market keys in the registry
+/// (seeds = ["registry_page", page as u32 little-endian])
+///
+/// Allocated one slot at a time by `create_market`.
+#[account]
+pub struct MarketRegistryPage {
+    pub page: u32,
+    pub markets: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl MarketRegistryPage {
+    /// 8 KB of keys, small enough to deserialize within the heap limit
+    pub const MAX_MARKETS: usize = 250;
+
+    pub const fn space(num_markets: usize) -> usize {
+        8 + // discriminator
+        4 + // page
+        4 + num_markets * 32 + // markets
+        1 // bump
+    }
+}
+
+/// Chain of recurring markets (seeds = ["series", series_id])
+#[account]
+pub struct MarketSeries {
+    pub creator: Pubkey,
+    pub series_id: Pubkey,
+    pub title: String,
+    pub market_count: u32,
+    /// In creation order; `Market.series_index` indexes it
+    pub markets: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl MarketSeries {
+    pub const MAX_TITLE_LEN: usize = 64;
+    pub const MAX_MARKETS: usize = 52;
+
+    pub const LEN: usize = 8 + // discriminator
+        32 + // creator
+        32 + // series_id
+        4 + Self::MAX_TITLE_LEN + // title
+        4 + // market_count
+        4 + Self::MAX_MARKETS * 32 + // markets
+        1; // bump
+}
+
+/// Global protocol configuration (seeds = ["config"])
+#[account]
+pub struct Config {
+    /// Signs every ADMIN ONLY instruction (initially ADMIN_PUBKEY)
+    pub admin: Pubkey,
+    /// Nominee of `propose_admin_transfer`, not yet accepted
+    pub pending_admin: Option<Pubkey>,
+    /// SPL Governance realm attached by `set_governance`
+    pub governance: Option<Pubkey>,
+    /// Delay of queued admin actions; 0 lets the setters apply instantly
+    pub admin_timelock_secs: i64,
+    /// keccak256 of the AdminAction waiting in `queue_admin_action`
+    pub queued_admin_action: Option<[u8; 32]>,
+    /// Earliest time the queued action can be executed
+    pub admin_action_execute_after: i64,
+    /// Set by `emergency_pause_protocol`: blocks bets and market creation
+    pub protocol_paused: bool,
+    /// Protocol fee taken from each bet, in basis points
+    pub fee_bps: u16,
+    /// Lifetime volume reaching Silver, Gold and Platinum rakeback
+    pub rakeback_thresholds: [u64; 3],
+    /// Basis points taken off `fee_bps` per RakebackTier
+    pub rakeback_bps: [u16; 4],
+    /// Share of each protocol fee, in basis points of the fee, paid into
+    /// the insurance vault of the market's mint (USDC-currency markets)
+    pub insurance_fee_bps: u16,
+    /// Largest vault vs `total_pool` delta a resolution accepts; None
+    /// only reports it
+    pub vault_tolerance: Option<u64>,
+    /// Lamports a resolver must have staked to resolve (0 = no requirement)
+    pub min_resolver_stake: u64,
+    /// Lamports charged to create a market, paid into ["fee_vault"]
+    pub market_creation_fee: u64,
+    /// Lamports a market authority bonds in ["bond", market] at creation
+    pub resolution_bond_amount: u64,
+    /// Lamports a creator locks in ["creator_stake", market] at creation
+    pub required_creator_stake: u64,
+    /// Seconds after expiry before anyone may `force_resolve` a market
+    pub force_resolve_delay: i64,
+    /// Seconds after expiry that payouts stay claimable
+    pub unclaimed_grace_period: i64,
+    /// Mints `create_market` accepts (at most MAX_ALLOWED_MINTS)
+    pub allowed_mints: Vec<Pubkey>,
+    /// Ed25519 keys of the Arcium MXE signers (at most MAX_MXE_SIGNERS)
+    pub mxe_pubkeys: Vec<[u8; 32]>,
+    /// Distinct MXE signatures a payout proof needs
+    pub required_signatures: u8,
+    /// Signer set proposed by `propose_mxe_rotation`, not yet active
+    pub pending_mxe_pubkeys: Option<Vec<[u8; 32]>>,
+    pub pending_required_signatures: u8,
+    /// Earliest time `pending_mxe_pubkeys` can be finalized
+    pub rotation_unlock_time: i64,
+    /// Signer set replaced by the last rotation
+    pub previous_mxe_pubkeys: Vec<[u8; 32]>,
+    pub previous_required_signatures: u8,
+    /// Until this time, proofs from `previous_mxe_pubkeys` are still valid
+    pub rotation_deadline: i64,
+    /// How MXE proofs are signed
+    pub signature_scheme: SignatureScheme,
+    /// Ethereum address of the MXE key, for `SignatureScheme::Secp256k1`
+    pub mxe_eth_address: [u8; 20],
+    /// Scheme proposed by `propose_signature_scheme`, not yet active
+    pub pending_signature_scheme: Option<SignatureScheme>,
+    pub pending_mxe_eth_address: [u8; 20],
+    /// Earliest time `pending_signature_scheme` can be finalized
+    pub scheme_unlock_time: i64,
+    pub bump: u8,
+}
+
+impl Config {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // admin
+        1 + 32 + // pending_admin
+        1 + 32 + // governance
+        8 + // admin_timelock_secs
+        1 + 32 + // queued_admin_action
+        8 + // admin_action_execute_after
+        1 + // protocol_paused
+        2 + // fee_bps
+        3 * 8 + // rakeback_thresholds
+        4 * 2 + // rakeback_bps
+        2 + // insurance_fee_bps
+        1 + 8 + // vault_tolerance
+        8 + // min_resolver_stake
+        8 + // market_creation_fee
+        8 + // resolution_bond_amount
+        8 + // required_creator_stake
+        8 + // force_resolve_delay
+        8 + // unclaimed_grace_period
+        4 + MAX_ALLOWED_MINTS * 32 + // allowed_mints
+        4 + MAX_MXE_SIGNERS * 32 + // mxe_pubkeys
+        1 + // required_signatures
+        1 + 4 + MAX_MXE_SIGNERS * 32 + // pending_mxe_pubkeys
+        1 + // pending_required_signatures
+        8 + // rotation_unlock_time
+        4 + MAX_MXE_SIGNERS * 32 + // previous_mxe_pubkeys
+        1 + // previous_required_signatures
+        8 + // rotation_deadline
+        1 + // signature_scheme enum
+        20 + // mxe_eth_address
+        1 + 1 + // pending_signature_scheme (Option<enum>)
+        20 + // pending_mxe_eth_address
+        8 + // scheme_unlock_time
+        1; // bump
+}
+
+/// Protocol-wide counters for indexers and dashboards
+/// (seeds = ["stats"])
+#[account]
+pub struct ProtocolStats {
+    pub total_markets: u32,
+    /// Created and not yet resolved
+    pub active_markets: u32,
+    /// Gross amount bet, fees included, summed across currencies
+    pub total_volume: u64,
+    pub total_bets: u64,
+    pub total_claims: u64,
+    pub total_fees_collected: u64,
+    pub bump: u8,
+}
+
+impl ProtocolStats {
+    pub const LEN: usize = 8 + // discriminator
+        4 + // total_markets
+        4 + // active_markets
+        8 + // total_volume
+        8 + // total_bets
+        8 + // total_claims
+        8 + // total_fees_collected
+        1; // bump
+}
+
+/// Lamports a resolver has locked as skin in the game
+/// (seeds = ["resolver_stake", resolver])
+#[account]
+pub struct ResolverStake {
+    pub resolver: Pubkey,
+    /// Staked lamports, on top of rent
+    pub amount: u64,
+    pub last_resolution_at: i64,
+    pub bump: u8,
+}
+
+impl ResolverStake {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // resolver
+        8 + // amount
+        8 + // last_resolution_at
+        1; // bump
+}
+
+/// One entry of `batch_resolve_markets`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MarketResolution {
+    pub market: Pubkey,
+    /// Outcome index, as in `resolve_market`
+    pub winning_outcome: u8,
+}
+
+#[account]
+pub struct Market {
+    pub authority: Pubkey,
+    /// Nominee of `propose_authority_transfer`, not yet accepted
+    pub pending_authority: Option<Pubkey>,
+    /// Signs resolutions; the authority unless delegated via `set_resolver`
+    pub resolver: Pubkey,
+    /// Question the market PDA is derived from
+    pub question: String,
+    /// Corrected display question set by `update_market_question`
+    pub question_v2: Option<String>,
+    /// Off-chain resolution criteria (IPFS CID or Arweave TX ID)
+    pub metadata_uri: String,
+    /// Pyth price account used by `auto_resolve_with_pyth` (None = manual only)
+    pub pyth_price_feed: Option<Pubkey>,
+    /// Switchboard aggregator used by `resolve_with_oracle` (None = manual only)
+    pub oracle: Option<Pubkey>,
+    /// Aggregator of the SwitchboardFeedConfig used by
+    /// `auto_resolve_with_switchboard` (None = no feed attached)
+    pub switchboard_feed: Option<Pubkey>,
+    /// Price threshold: the Pyth feed's native exponent, or
+    /// SWITCHBOARD_THRESHOLD_DECIMALS decimals for `oracle`
+    pub resolution_price_threshold: Option<i64>,
+    pub resolution_direction: ResolutionDirection,
+    pub expiry_timestamp: i64,
+    /// Bets are rejected from here on; at or before `expiry_timestamp`
+    pub betting_close_timestamp: i64,
+    /// Claims close here; afterwards `withdraw_unclaimed` may sweep custody
+    pub unclaimed_deadline: i64,
+    pub total_pool: u64,
+    /// Stake on YES, as attested by the MXE after resolution
+    pub yes_pool: u64,
+    /// Stake on NO, as attested by the MXE after resolution
+    pub no_pool: u64,
+    /// Pools were set in bulk by `record_pool_split` (not via `reveal_side`)
+    pub pool_split_recorded: bool,
+    /// Smallest accepted single bet
+    pub min_bet: u64,
+    /// Largest cumulative position per user (0 = unlimited)
+    pub max_bet: u64,
+    /// Largest total pool the market accepts (0 = unlimited)
+    pub max_pool: u64,
+    /// Protocol fees skimmed into the fee vault so far
+    pub fees_collected: u64,
+    /// Share of the pool paid to the creator at resolution, in basis points
+    pub creator_fee_bps: u16,
+    /// Creator fee paid out at resolution (already removed from total_pool)
+    pub creator_fee_collected: u64,
+    /// keccak256(winning_outcome || salt) committed at creation; when set,
+    /// the market can only be resolved via `resolve_with_reveal`
+    pub resolution_commitment: Option<[u8; 32]>,
+    /// Positions opened on this market
+    pub total_positions: u32,
+    /// Positions that have claimed their payout
+    pub claimed_positions: u32,
+    /// Sum of payouts made to claimed positions
+    pub total_claimed: u64,
+    /// Market that receives this one's residual via `roll_residual`
+    pub successor_market: Option<Pubkey>,
+    /// Residual rolled in from a predecessor (already included in total_pool)
+    pub seeded_liquidity: u64,
+    /// Lamports the creator locked in ["creator_stake", market]
+    pub creator_stake: u64,
+    pub resolved: bool,
+    /// New bets are rejected while true
+    pub paused: bool,
+    /// Only addresses in ["whitelist", market] may bet
+    pub whitelist_enabled: bool,
+    /// Number of discrete outcomes (2 = binary YES/NO)
+    pub num_outcomes: u8,
+    pub result: MarketResult,
+    /// When the market was resolved (start of the dispute window)
+    pub resolution_timestamp: i64,
+    /// Outcome index awaiting `finalize_resolution`
+    pub proposed_outcome: Option<u8>,
+    /// End of the challenge window for `proposed_outcome`
+    pub challenge_deadline: i64,
+    /// Set by `dispute_resolution`; finalization waits for the admin
+    pub resolution_disputed: bool,
+    /// Latest hash from `record_resolution_evidence`
+    pub evidence_hash: Option<[u8; 32]>,
+    pub vault: Pubkey,
+    /// False until the vault and fee vault exist; bets are rejected until then
+    pub vault_initialized: bool,
+    pub usdc_mint: Pubkey,
+    /// Mint lives under Token-2022 (opted in at creation)
+    pub is_token22: bool,
+    /// Settlement currency: SPL token vault or native SOL vault
+    pub currency: MarketCurrency,
+    /// Topic, indexed in the matching CategoryIndex
+    pub category: MarketCategory,
+    /// MarketSeries seed of the series this market belongs to
+    pub series_id: Option<Pubkey>,
+    /// Position within the series (0 when not in one)
+    pub series_index: u32,
+    /// MarketRegistryPage listing this market
+    pub registry_page: u32,
+    /// Market this one is conditional on (see `set_parent_market`)
+    pub parent_market: Option<Pubkey>,
+    /// Parent result this market needs; any other parent result voids it
+    pub parent_required_outcome: Option<MarketResult>,
+    pub bump: u8,
+    pub vault_bump: u8,
+    /// Bump of the ["sol_vault", market] PDA (SOL markets only)
+    pub sol_vault_bump: u8,
+    pub fee_vault_bump: u8,
+    /// Bump of the ["sol_fee_vault", market] PDA (SOL markets only)
+    pub sol_fee_vault_bump: u8,
+}
+
+impl Market {
+    pub const MAX_METADATA_URI_LEN: usize = 128;
+    pub const MAX_OUTCOMES: u8 = 8;
+
+    pub const LEN: usize = 8 + // discriminator
+        32 + // authority
+        1 + 32 + // pending_authority
+        32 + // resolver
+        4 + 280 + // question (String with max 280 chars)
+        1 + 4 + 280 + // question_v2 (Option<String> with max 280 chars)
+        4 + 128 + // metadata_uri (String with max 128 bytes)
+        1 + 32 + // pyth_price_feed (Option<Pubkey>)
+        1 + 32 + // oracle (Option<Pubkey>)
+        1 + 32 + // switchboard_feed (Option<Pubkey>)
+        1 + 8 + // resolution_price_threshold (Option<i64>)
+        1 + // resolution_direction enum
+        8 + // expiry_timestamp
+        8 + // betting_close_timestamp
+        8 + // unclaimed_deadline
+        8 + // total_pool
+        8 + // yes_pool
+        8 + // no_pool
+        1 + // pool_split_recorded
+        8 + // min_bet
+        8 + // max_bet
+        8 + // max_pool
+        8 + // fees_collected
+        2 + // creator_fee_bps
+        8 + // creator_fee_collected
+        1 + 32 + // resolution_commitment
+        4 + // total_positions
+        4 + // claimed_positions
+        8 + // total_claimed
+        1 + 32 + // successor_market
+        8 + // seeded_liquidity
+        8 + // creator_stake
+        1 + // resolved
+        1 + // paused
+        1 + // whitelist_enabled
+        1 + // num_outcomes
+        2 + // result enum (tag + outcome index)
+        8 + // resolution_timestamp
+        1 + 1 + // proposed_outcome (Option<u8>)
+        8 + // challenge_deadline
+        1 + // resolution_disputed
+        1 + 32 + // evidence_hash (Option<[u8; 32]>)
+        32 + // vault
+        1 + // vault_initialized
+        32 + // usdc_mint
+        1 + // is_token22
+        1 + // currency enum
+        1 + // category enum
+        1 + 32 + // series_id (Option<Pubkey>)
+        4 + // series_index
+        4 + // registry_page
+        1 + 32 + // parent_market (Option<Pubkey>)
+        1 + 2 + // parent_required_outcome (Option<MarketResult>)
+        1 + // bump
+        1 + // vault_bump
+        1 + // sol_vault_bump
+        1 + // fee_vault_bump
+        1; // sol_fee_vault_bump
+
+    /// Whether `result` is a legal final outcome for this market
+    ///
+    /// Binary markets resolve to Yes/No; multi-outcome markets resolve to
+    /// `Outcome(i)` with `i < num_outcomes`.
+    pub fn is_valid_result(&self, result: MarketResult) -> bool {
+        match result {
+            MarketResult::None => false,
+            MarketResult::Yes | MarketResult::No => self.num_outcomes == 2,
+            MarketResult::Outcome(index) => {
+                self.num_outcomes > 2 && index < self.num_outcomes
+            }
+        }
+    }
+}
+
+#[account]
+pub struct UserPosition {
+    pub user: Pubkey,
+    pub market: Pubkey,
+    /// Net stake in the pool (after protocol fees)
+    pub amount: u64,
+    /// Protocol fees paid on top of `amount`
+    pub fees_paid: u64,
+    pub claimed: bool,
+    /// Paid out so far; below `payout` while `partial_claim` installments
+    /// are outstanding
+    pub claimed_amount: u64,
+    /// Payout received at claim time
+    pub payout: u64,
+    /// Nonce used in the claim proof, kept for auditing
+    /// 
+    /// The MXE generates a unique nonce for each payout computation.
+    /// Replays are blocked by `claimed`, since 0 is a valid nonce.
+    pub nonce_used: u64,
+    /// Stake already added to yes_pool/no_pool via `reveal_side` or
+    /// `change_side`
+    pub side_revealed: bool,
+    /// Side the stake is counted in when `side_revealed` (0 = YES, 1 = NO)
+    pub revealed_side: u8,
+    pub bump: u8,
+}
+
+impl UserPosition {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // user
+        32 + // market
+        8 + // amount
+        8 + // fees_paid
+        1 + // claimed
+        8 + // claimed_amount
+        8 + // payout
+        8 + // nonce_used
+        1 + // side_revealed
+        1 + // revealed_side
+        1; // bump
+}
+
+/// Lifetime betting totals for one user (seeds = ["stats", user])
+#[account]
+pub struct UserStats {
+    pub user: Pubkey,
+    /// Gross amount bet, fees included
+    pub total_wagered: u64,
+    /// Sum of claimed payouts
+    pub total_claimed: u64,
+    /// Positions opened
+    pub markets_entered: u64,
+    /// Claims that paid out
+    pub wins: u64,
+    /// Sum of Brier score contributions, in BRIER_SCALE² units
+    pub brier_score_numerator: i64,
+    /// BRIER_SCALE² per scored market
+    pub brier_score_denominator: u64,
+    /// Markets that added to the Brier score
+    pub markets_resolved: u32,
+    /// Fee rebate level reached by `total_wagered`
+    pub rakeback_tier: RakebackTier,
+    pub bump: u8,
+}
+
+impl UserStats {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // user
+        8 + // total_wagered
+        8 + // total_claimed
+        8 + // markets_entered
+        8 + // wins
+        8 + // brier_score_numerator
+        8 + // brier_score_denominator
+        4 + // markets_resolved
+        1 + // rakeback_tier
+        1; // bump
+}
+
+/// Groth16 verifying key for `claim_with_zk_proof` (seeds = ["zk_vk"])
+///
+/// Kept out of Config: at ~1.5 KB with its pending copy it would crowd the
+/// stack of every instruction that loads Config.
+#[account]
+pub struct ZkVerifyingKey {
+    /// Key proofs are checked against (None until the first finalize)
+    pub verifying_key: Option<Groth16VerifyingKey>,
+    /// Key proposed by `propose_zk_verifying_key`, not yet active
+    pub pending_verifying_key: Option<Groth16VerifyingKey>,
+    /// Earliest time `pending_verifying_key` can be finalized
+    pub unlock_time: i64,
+    pub bump: u8,
+}
+
+impl ZkVerifyingKey {
+    pub const LEN: usize = 8 + // discriminator
+        1 + Groth16VerifyingKey::LEN + // verifying_key
+        1 + Groth16VerifyingKey::LEN + // pending_verifying_key
+        8 + // unlock_time
+        1; // bump
+}
+
+/// Groth16 verifying key, points in alt_bn128 (EIP-197, big-endian) encoding
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Groth16VerifyingKey {
+    pub alpha_g1: [u8; 64],
+    pub beta_g2: [u8; 128],
+    pub gamma_g2: [u8; 128],
+    pub delta_g2: [u8; 128],
+    /// Constant term, then one point per public input (ZK_PUBLIC_INPUTS)
+    pub ic: [[u8; 64]; 5],
+}
+
+impl Groth16VerifyingKey {
+    pub const LEN: usize = 64 + 3 * 128 + (ZK_PUBLIC_INPUTS + 1) * 64;
+}
+
+/// Best-calibrated forecasters, lowest Brier score first
+/// (seeds = ["leaderboard"])
+#[account]
+pub struct ForecastersLeaderboard {
+    /// At most LEADERBOARD_SIZE entries
+    pub entries: Vec<LeaderboardEntry>,
+    pub bump: u8,
+}
+
+impl ForecastersLeaderboard {
+    pub const LEN: usize = 8 + // discriminator
+        4 + LEADERBOARD_SIZE * LeaderboardEntry::LEN + // entries
+        1; // bump
+}
+
+/// A user's Brier score as of their last `update_leaderboard`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LeaderboardEntry {
+    pub user: Pubkey,
+    pub brier_score_numerator: i64,
+    pub brier_score_denominator: u64,
+    pub markets_resolved: u32,
+}
+
+impl LeaderboardEntry {
+    pub const LEN: usize = 32 + 8 + 8 + 4;
+
+    /// Lower mean score first, then more markets scored
+    pub fn ranks_above(&self, other: &LeaderboardEntry) -> bool {
+        let lhs = self.brier_score_numerator as i128 * other.brier_score_denominator as i128;
+        let rhs = other.brier_score_numerator as i128 * self.brier_score_denominator as i128;
+        lhs < rhs || (lhs == rhs && self.markets_resolved > other.markets_resolved)
+    }
+}
+
+/// Addresses allowed to bet on a restricted market
+/// (seeds = ["whitelist", market])
+#[account]
+pub struct Whitelist {
+    pub market: Pubkey,
+    pub entries: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl Whitelist {
+    pub const MAX_ENTRIES: usize = 200;
+
+    pub const LEN: usize = 8 + // discriminator
+        32 + // market
+        4 + Self::MAX_ENTRIES * 32 + // entries
+        1; // bump
+}
+
+/// Accounts besides the admin allowed to create markets
+/// (seeds = ["creator_allowlist"])
+#[account]
+pub struct CreatorAllowlist {
+    pub creators: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl CreatorAllowlist {
+    pub const MAX_CREATORS: usize = 50;
+
+    pub const LEN: usize = 8 + // discriminator
+        4 + Self::MAX_CREATORS * 32 + // creators
+        1; // bump
+}
+
+/// Admin-set betting caps for one user (seeds = ["limits", user])
+#[account]
+pub struct UserBettingLimits {
+    pub user: Pubkey,
+    /// Most a single position may reach (0 = no cap)
+    pub lifetime_volume_cap: u64,
+    /// Most the user may bet per UTC day across markets (0 = no cap)
+    pub daily_volume_cap: u64,
+    /// Day number (unix time / 86400) that `volume_today` counts
+    pub current_day: i64,
+    pub volume_today: u64,
+    pub bump: u8,
+}
+
+impl UserBettingLimits {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // user
+        8 + // lifetime_volume_cap
+        8 + // daily_volume_cap
+        8 + // current_day
+        8 + // volume_today
+        1; // bump
+
+    /// Check a bet of `amount` against the caps and count it for today
+    ///
+    /// `position_amount` is the position size after the bet.
+    pub fn apply_bet(&mut self, position_amount: u64, amount: u64, now: i64) -> Result<()> {
+        require!(
+            self.lifetime_volume_cap == 0 || position_amount <= self.lifetime_volume_cap,
+            ErrorCode::LifetimeLimitExceeded
+        );
+
+        let today = now.div_euclid(86_400);
+        if today != self.current_day {
+            self.current_day = today;
+            self.volume_today = 0;
+        }
+        let volume_today = self.volume_today
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(
+            self.daily_volume_cap == 0 || volume_today <= self.daily_volume_cap,
+            ErrorCode::DailyLimitExceeded
+        );
+        self.volume_today = volume_today;
+
+        Ok(())
+    }
+}
+
+/// Switchboard feed a market resolves from (seeds = ["sw_config", market])
+#[account]
+pub struct SwitchboardFeedConfig {
+    pub market: Pubkey,
+    pub aggregator: Pubkey,
+    /// SWITCHBOARD_THRESHOLD_DECIMALS decimals
+    pub threshold: i128,
+    pub direction: ResolutionDirection,
+    /// Maximum age of the round used, in seconds
+    pub staleness_threshold: u32,
+    pub bump: u8,
+}
+
+impl SwitchboardFeedConfig {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // market
+        32 + // aggregator
+        16 + // threshold
+        1 + // direction
+        4 + // staleness_threshold
+        1; // bump
+}
+
+/// Bond and result of a permissionless force resolution
+/// (seeds = ["force_resolve", market])
+#[account]
+pub struct ForceResolution {
+    pub market: Pubkey,
+    pub resolver: Pubkey,
+    pub result: MarketResult,
+    /// Lamports locked on top of rent
+    pub bond: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl ForceResolution {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // market
+        32 + // resolver
+        2 + // result
+        8 + // bond
+        8 + // created_at
+        1; // bump
+}
+
+/// Resolver's commitment to off-chain evidence for a market's result
+/// (seeds = ["evidence", market])
+#[account]
+pub struct ResolutionEvidence {
+    pub market: Pubkey,
+    /// keccak256 of the evidence blob
+    pub evidence_hash: [u8; 32],
+    /// Where the blob is stored (IPFS, Arweave, ...)
+    pub evidence_uri: String,
+    pub resolver: Pubkey,
+    pub timestamp: i64,
+    pub bump: u8,
+}
+
+impl ResolutionEvidence {
+    pub const MAX_URI_LEN: usize = 128;
+
+    pub const LEN: usize = 8 + // discriminator
+        32 + // market
+        32 + // evidence_hash
+        4 + Self::MAX_URI_LEN + // evidence_uri
+        32 + // resolver
+        8 + // timestamp
+        1; // bump
+}
+
+/// Lamports a market authority stakes on resolving honestly
+/// (seeds = ["bond", market])
+#[account]
+pub struct ResolutionBond {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    /// Bonded lamports, on top of rent; 0 once slashed
+    pub amount: u64,
+    /// Disputes filed and not yet closed; the bond stays locked while > 0
+    pub open_disputes: u32,
+    pub bump: u8,
+}
+
+impl ResolutionBond {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // market
+        32 + // authority
+        8 + // amount
+        4 + // open_disputes
+        1; // bump
+}
+
+/// Lamports a creator locks as a quality signal for their market
+/// (seeds = ["creator_stake", market])
+#[account]
+pub struct CreatorStake {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    /// Staked lamports, on top of rent
+    pub amount: u64,
+    pub reclaimed: bool,
+    pub slashed: bool,
+    pub bump: u8,
+}
+
+impl CreatorStake {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // market
+        32 + // authority
+        8 + // amount
+        1 + // reclaimed
+        1 + // slashed
+        1; // bump
+}
+
+#[account]
+pub struct DisputeRecord {
+    pub market: Pubkey,
+    pub disputant: Pubkey,
+    /// keccak256 of the off-chain dispute text
+    pub reason_hash: [u8; 32],
+    /// Lamports locked on top of rent
+    pub bond: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl DisputeRecord {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // market
+        32 + // disputant
+        32 + // reason_hash
+        8 + // bond
+        8 + // created_at
+        1; // bump
+}
+
+/// MXE-signed Merkle root of a market's payouts
+/// (seeds = ["batch_payout", market])
+#[account]
+pub struct BatchPayout {
+    pub market: Pubkey,
+    /// Root over keccak256(user || payout || nonce) leaves
+    pub merkle_root: [u8; 32],
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl BatchPayout {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // market
+        32 + // merkle_root
+        8 + // created_at
+        1; // bump
+}
+
+// ============================================================================
+// Enums
+// ============================================================================
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MarketResult {
+    None,
+    Yes,
+    No,
+    /// Winning outcome index for markets with more than two outcomes
+    Outcome(u8),
+}
+
+impl MarketResult {
+    /// Map an outcome index onto a result (binary: 0 = Yes, 1 = No)
+    pub fn from_outcome(outcome: u8, num_outcomes: u8) -> Result<Self> {
+        require!(outcome < num_outcomes, ErrorCode::InvalidResult);
+        Ok(match (num_outcomes, outcome) {
+            (2, 0) => MarketResult::Yes,
+            (2, _) => MarketResult::No,
+            _ => MarketResult::Outcome(outcome),
+        })
+    }
+}
+
+/// What a market is denominated in
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MarketCurrency {
+    /// SPL token (USDC) held in the ["vault", market] token account
+    Usdc,
+    /// Native lamports held in the ["sol_vault", market] system PDA
+    Sol,
+}
+
+/// Fee rebate level of a user, by lifetime betting volume
+///
+/// Variants are ordered and index `Config.rakeback_bps`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum RakebackTier {
+    Bronze,
+    Silver,
+    Gold,
+    Platinum,
+}
+
+impl RakebackTier {
+    /// Highest tier whose `Config.rakeback_thresholds` entry `volume` reaches
+    pub fn for_volume(config: &Config, volume: u64) -> Self {
+        let reached = config.rakeback_thresholds
+            .iter()
+            .filter(|threshold| volume >= **threshold)
+            .count();
+        match reached {
+            0 => RakebackTier::Bronze,
+            1 => RakebackTier::Silver,
+            2 => RakebackTier::Gold,
+            _ => RakebackTier::Platinum,
+        }
+    }
+}
+
+/// Config change applied directly by its setter, or through
+/// `execute_admin_action` while the admin timelock is on
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AdminAction {
+    SetFeeBps { fee_bps: u16 },
+    SetMinResolverStake { min_resolver_stake: u64 },
+    SetMarketCreationFee { market_creation_fee: u64 },
+    SetResolutionBondAmount { resolution_bond_amount: u64 },
+    SetRequiredCreatorStake { required_creator_stake: u64 },
+    SetForceResolveDelay { force_resolve_delay: i64 },
+    SetUnclaimedGracePeriod { unclaimed_grace_period: i64 },
+    SetRakebackSchedule { thresholds: [u64; 3], rakeback_bps: [u16; 4] },
+    SetInsuranceFeeBps { insurance_fee_bps: u16 },
+    SetVaultTolerance { vault_tolerance: Option<u64> },
+    SetAdminTimelock { admin_timelock_secs: i64 },
+    SetGovernance { realm: Pubkey },
+}
+
+impl AdminAction {
+    /// Reject values `apply_admin_action` would refuse, so they fail when
+    /// queued rather than after the delay
+    pub fn validate(&self) -> Result<()> {
+        match *self {
+            AdminAction::SetFeeBps { fee_bps } => {
+                require!(fee_bps <= MAX_FEE_BPS, ErrorCode::FeeTooHigh);
+            }
+            AdminAction::SetForceResolveDelay { force_resolve_delay } => {
+                require!(force_resolve_delay >= 0, ErrorCode::InvalidAmount);
+            }
+            AdminAction::SetUnclaimedGracePeriod { unclaimed_grace_period } => {
+                require!(unclaimed_grace_period > 0, ErrorCode::InvalidAmount);
+            }
+            AdminAction::SetRakebackSchedule { thresholds, rakeback_bps } => {
+                require!(
+                    thresholds.windows(2).all(|pair| pair[0] < pair[1])
+                        && rakeback_bps.iter().all(|bps| *bps <= MAX_FEE_BPS),
+                    ErrorCode::InvalidRakebackSchedule
+                );
+            }
+            AdminAction::SetInsuranceFeeBps { insurance_fee_bps } => {
+                require!(insurance_fee_bps <= 10_000, ErrorCode::FeeTooHigh);
+            }
+            AdminAction::SetAdminTimelock { admin_timelock_secs } => {
+                require!(admin_timelock_secs >= 0, ErrorCode::InvalidAmount);
+            }
+            AdminAction::SetMinResolverStake { .. }
+            | AdminAction::SetMarketCreationFee { .. }
+            | AdminAction::SetResolutionBondAmount { .. }
+            | AdminAction::SetRequiredCreatorStake { .. }
+            | AdminAction::SetVaultTolerance { .. }
+            | AdminAction::SetGovernance { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// keccak256 of the Borsh-serialized action, as stored by
+    /// `queue_admin_action`
+    pub fn hash(&self) -> Result<[u8; 32]> {
+        Ok(keccak::hash(&self.try_to_vec()?).to_bytes())
+    }
+}
+
+/// Signature scheme of MXE proofs
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SignatureScheme {
+    /// Ed25519 program instructions from `Config.mxe_pubkeys`
+    Ed25519,
+    /// One Secp256k1 program instruction from `Config.mxe_eth_address`
+    Secp256k1,
+}
+
+/// Market topic; the discriminant seeds the category's CategoryIndex
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MarketCategory {
+    Sports,
+    Crypto,
+    Politics,
+    Entertainment,
+    Science,
+    Finance,
+    Other,
+}
+
+/// Caps passed to `set_user_limits`; 0 leaves a cap off
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct UserLimits {
+    pub lifetime_volume_cap: u64,
+    pub daily_volume_cap: u64,
+}
+
+/// How a `create_market_v2` market is decided
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ResolutionCriteria {
+    /// YES if the price of `mint` is above the threshold at `at_time`
+    PriceAbove { mint: Pubkey, threshold_ui_amount: f64, at_time: i64 },
+    /// YES if the price of `mint` is below the threshold at `at_time`
+    PriceBelow { mint: Pubkey, threshold_ui_amount: f64, at_time: i64 },
+    /// Decided by the market's Switchboard oracle
+    CustomOracle { oracle: Pubkey },
+    /// Decided by the market resolver
+    ManualAdmin,
+}
+
+impl ResolutionCriteria {
+    /// Largest variant: tag + mint + threshold_ui_amount + at_time
+    pub const MAX_LEN: usize = 1 + 32 + 8 + 8;
+}
+
+/// Which side of `resolution_price_threshold` resolves a price market YES
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResolutionDirection {
+    Above,
+    Below,
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct ProtocolInitializedEvent {
+    pub admin: Pubkey,
+    pub fee_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeUpdatedEvent {
+    pub old_fee_bps: u16,
+    pub new_fee_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MxeRotationProposedEvent {
+    pub pending_mxe_pubkeys: Vec<[u8; 32]>,
+    pub required_signatures: u8,
+    pub unlock_time: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MxeRotationFinalizedEvent {
+    pub old_mxe_pubkeys: Vec<[u8; 32]>,
+    pub new_mxe_pubkeys: Vec<[u8; 32]>,
+    pub required_signatures: u8,
+    /// Old keys stay valid until this time
+    pub rotation_deadline: i64,
+    pub timestamp: i64,
 }
 
-/// Verify Ed25519 signature from Arcium MXE
-/// 
-/// VERIFICATION PROCESS:
-/// 1. Load Ed25519 instruction from ix_sysvar (index 0)
-/// 2. Verify instruction is from ed25519_program
-/// 3. Parse instruction data:
-///    - Signature count (u8) = 1
-///    - Padding (u8) = 0
-///    - Signature offset (u16)
-///    - Public key offset (u16)
-///    - Message offset (u16)
-///    - Message length (u16)
-///    - Public key (32 bytes)
-///    - Signature (64 bytes)
-///    - Message (variable length)
-/// 4. Verify public key matches MXE_PUBKEY
-/// 5. Verify message matches our constructed message
-/// 6. Ed25519 program already verified signature ✅
-/// 
-/// SECURITY:
-/// - Uses Solana's native Ed25519 program (verified by runtime)
-/// - Public key hardcoded (no substitution possible)
-/// - Message constructed onchain (no tampering possible)
-/// - Signature verification happens BEFORE this instruction executes
-fn verify_mxe_signature(
-    ix_sysvar: &AccountInfo,
-    expected_message: &[u8; 32],
-    expected_signature: &[u8; 64],
-) -> Result<()> {
-    // Verify ix_sysvar is the instructions sysvar
-    require!(
-        ix_sysvar.key() == &IX_SYSVAR_ID,
-        ErrorCode::InvalidInstructionSysvar
-    );
+#[event]
+pub struct SignatureSchemeProposedEvent {
+    pub scheme: SignatureScheme,
+    pub mxe_eth_address: [u8; 20],
+    pub unlock_time: i64,
+    pub timestamp: i64,
+}
 
-    // Load the Ed25519 instruction (must be at index 0)
-    let ix = load_instruction_at_checked(0, ix_sysvar)
-        .map_err(|_| ErrorCode::Ed25519InstructionMissing)?;
+#[event]
+pub struct SignatureSchemeUpdatedEvent {
+    pub old_scheme: SignatureScheme,
+    pub new_scheme: SignatureScheme,
+    pub mxe_eth_address: [u8; 20],
+    pub timestamp: i64,
+}
 
-    // Verify it's the Ed25519 program
-    require!(
-        ix.program_id == ed25519_program::ID,
-        ErrorCode::InvalidEd25519Program
-    );
+#[event]
+pub struct ZkVerifyingKeyProposedEvent {
+    pub unlock_time: i64,
+    pub timestamp: i64,
+}
 
-    // Parse Ed25519 instruction data
-    // Format: https://docs.solana.com/developing/runtime-facilities/programs#ed25519-program
-    require!(
-        ix.data.len() >= 112, // Minimum: 2 + 2 + 2 + 2 + 2 + 32 + 64 + message
-        ErrorCode::InvalidEd25519Data
-    );
+#[event]
+pub struct ZkVerifyingKeyUpdatedEvent {
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MinResolverStakeUpdatedEvent {
+    pub old_min: u64,
+    pub new_min: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolPausedEvent {
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolResumedEvent {
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminTransferProposedEvent {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RakebackScheduleUpdatedEvent {
+    pub thresholds: [u64; 3],
+    pub rakeback_bps: [u16; 4],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TierUpgradeEvent {
+    pub user: Pubkey,
+    pub old_tier: RakebackTier,
+    pub new_tier: RakebackTier,
+    pub total_wagered: u64,
+}
+
+#[event]
+pub struct InsuranceFeeUpdatedEvent {
+    pub old_bps: u16,
+    pub new_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceDepositEvent {
+    pub mint: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+}
+
+/// Insurance funds moved into a market vault
+#[event]
+pub struct InsuranceDrawdownEvent {
+    pub market: Pubkey,
+    /// Claimant whose payout was covered, or the admin for
+    /// `draw_from_insurance`
+    pub user: Pubkey,
+    pub drawdown_amount: u64,
+}
+
+#[event]
+pub struct VaultToleranceUpdatedEvent {
+    pub old_tolerance: Option<u64>,
+    pub new_tolerance: Option<u64>,
+    pub timestamp: i64,
+}
+
+/// Custody balance against the pool it should hold, at resolution
+#[event]
+pub struct VaultReconciliationEvent {
+    pub market: Pubkey,
+    pub vault_balance: u64,
+    pub expected_balance: u64,
+    /// vault_balance - expected_balance
+    pub delta: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminTimelockUpdatedEvent {
+    pub old_delay: i64,
+    pub new_delay: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminActionQueuedEvent {
+    pub action: AdminAction,
+    pub action_hash: [u8; 32],
+    pub execute_after: i64,
+}
+
+#[event]
+pub struct AdminActionExecutedEvent {
+    pub action_hash: [u8; 32],
+}
+
+#[event]
+pub struct AdminActionCancelledEvent {
+    pub action_hash: [u8; 32],
+}
+
+#[event]
+pub struct GovernanceUpdatedEvent {
+    pub old_realm: Option<Pubkey>,
+    pub realm: Pubkey,
+    /// Governance PDA that signs the realm's proposals for Config
+    pub governance: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminTransferCancelledEvent {
+    pub admin: Pubkey,
+    pub cancelled_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminTransferAcceptedEvent {
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketCreationFeeUpdatedEvent {
+    pub old_fee: u64,
+    pub new_fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreationFeeCollectedEvent {
+    pub authority: Pubkey,
+    pub market: Pubkey,
+    pub fee: u64,
+}
+
+#[event]
+pub struct CreationFeesWithdrawnEvent {
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ResolverStakeChangedEvent {
+    pub resolver: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferProposedEvent {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferAcceptedEvent {
+    pub market: Pubkey,
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+    /// Resolver after the transfer
+    pub resolver: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ResolverUpdatedEvent {
+    pub market: Pubkey,
+    pub old_resolver: Pubkey,
+    pub new_resolver: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ResolutionProposedEvent {
+    pub market: Pubkey,
+    pub winning_outcome: u8,
+    pub challenge_deadline: i64,
+}
+
+#[event]
+pub struct ResolutionChallengedEvent {
+    pub market: Pubkey,
+    pub disputant: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ResolutionOverriddenEvent {
+    pub market: Pubkey,
+    pub admin: Pubkey,
+    pub proposed_outcome: Option<u8>,
+    pub winning_outcome: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreatorFeeCollectedEvent {
+    pub market: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub remaining_pool: u64,
+}
+
+#[event]
+pub struct FeesWithdrawnEvent {
+    pub market: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketCreatedEvent {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    pub question: String,
+    pub metadata_uri: String,
+    pub outcome_labels: Vec<String>,
+    pub expiry_timestamp: i64,
+    pub betting_close_timestamp: i64,
+}
+
+#[event]
+pub struct BetPlacedEvent {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    /// Gross deposit, including `fee`
+    pub amount: u64,
+    pub fee: u64,
+    /// keccak256 of the encrypted payload, for the MXE to match submissions
+    pub payload_hash: [u8; 32],
+    /// The user's position after this bet, net of fees
+    pub position_total: u64,
+    /// Distinct bettors on the market after this bet
+    pub total_positions: u32,
+    /// Market pool including this bet
+    pub total_pool_after: u64,
+    /// Side pools; 0 until `record_pool_split` or `reveal_side` fill them in
+    pub yes_pool_after: u64,
+    pub no_pool_after: u64,
+    pub timestamp: i64,
+    /// CRC-32 of the critical fields, see `bet_event_checksum`
+    pub checksum: u32,
+}
+
+#[event]
+pub struct MarketResolvedEvent {
+    pub market: Pubkey,
+    pub result: MarketResult,
+    pub total_pool: u64,
+    /// Tracked YES/NO pool of the winning side
+    pub winning_pool: u64,
+    /// Tracked YES/NO pool of the losing side
+    pub losing_pool: u64,
+    /// From `record_resolution_evidence`, if the resolver recorded any
+    pub evidence_hash: Option<[u8; 32]>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParentMarketSetEvent {
+    pub market: Pubkey,
+    pub parent_market: Pubkey,
+    pub required_outcome: MarketResult,
+}
+
+#[event]
+pub struct MarketVoidedEvent {
+    pub market: Pubkey,
+    pub parent_market: Pubkey,
+    /// What the parent resolved to instead of the required outcome
+    pub parent_result: MarketResult,
+    /// Refundable through `claim_refund`
+    pub total_pool: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ResolutionEvidenceRecordedEvent {
+    pub market: Pubkey,
+    pub resolver: Pubkey,
+    pub evidence_hash: [u8; 32],
+    pub evidence_uri: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LeaderboardUpdatedEvent {
+    pub user: Pubkey,
+    /// 0-based position after re-sorting
+    pub rank: u32,
+    pub brier_score_numerator: i64,
+    pub brier_score_denominator: u64,
+    pub markets_resolved: u32,
+}
+
+#[event]
+pub struct MarketSeriesCreatedEvent {
+    pub series: Pubkey,
+    pub series_id: Pubkey,
+    pub creator: Pubkey,
+    pub title: String,
+}
+
+#[event]
+pub struct MarketAddedToSeriesEvent {
+    pub series: Pubkey,
+    pub market: Pubkey,
+    pub index: u32,
+}
+
+#[event]
+pub struct SeriesMarketsEvent {
+    pub series: Pubkey,
+    pub title: String,
+    pub markets: Vec<Pubkey>,
+}
+
+#[event]
+pub struct MarketTagsUpdatedEvent {
+    pub market: Pubkey,
+    pub tags: Vec<String>,
+}
+
+#[event]
+pub struct MarketCategorizedEvent {
+    pub market: Pubkey,
+    pub category: MarketCategory,
+}
+
+#[event]
+pub struct MarketMetadataUpdatedEvent {
+    pub market: Pubkey,
+    pub old_uri: String,
+    pub new_uri: String,
+}
+
+#[event]
+pub struct ResolutionCriteriaSetEvent {
+    pub market: Pubkey,
+    pub criteria: ResolutionCriteria,
+    pub expiry_timestamp: i64,
+}
+
+#[event]
+pub struct QuestionUpdatedEvent {
+    pub market: Pubkey,
+    pub old_question_v2: Option<String>,
+    pub new_question_v2: Option<String>,
+}
+
+#[event]
+pub struct ExpiryExtendedEvent {
+    pub market: Pubkey,
+    pub old_expiry_timestamp: i64,
+    pub new_expiry_timestamp: i64,
+    pub timestamp: i64,
+}
 
-    // Verify signature count = 1
-    let num_signatures = ix.data[0];
-    require!(
-        num_signatures == 1,
-        ErrorCode::InvalidSignatureCount
-    );
+#[event]
+pub struct MarketDisputedEvent {
+    pub market: Pubkey,
+    pub disputant: Pubkey,
+    pub reason_hash: [u8; 32],
+    pub bond: u64,
+    pub timestamp: i64,
+}
 
-    // Extract offsets (little-endian u16)
-    let pubkey_offset = u16::from_le_bytes([ix.data[4], ix.data[5]]) as usize;
-    let signature_offset = u16::from_le_bytes([ix.data[2], ix.data[3]]) as usize;
-    let message_offset = u16::from_le_bytes([ix.data[6], ix.data[7]]) as usize;
-    let message_len = u16::from_le_bytes([ix.data[8], ix.data[9]]) as usize;
+#[event]
+pub struct UserLimitsSetEvent {
+    pub user: Pubkey,
+    pub lifetime_volume_cap: u64,
+    pub daily_volume_cap: u64,
+    pub timestamp: i64,
+}
 
-    // Verify public key matches MXE_PUBKEY
-    require!(
-        pubkey_offset + 32 <= ix.data.len(),
-        ErrorCode::InvalidEd25519Data
-    );
-    let pubkey = &ix.data[pubkey_offset..pubkey_offset + 32];
-    require!(
-        pubkey == MXE_PUBKEY,
-        ErrorCode::InvalidMXEPublicKey
-    );
+#[event]
+pub struct AllowedMintAddedEvent {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub timestamp: i64,
+}
 
-    // Verify signature matches
-    require!(
-        signature_offset + 64 <= ix.data.len(),
-        ErrorCode::InvalidEd25519Data
-    );
-    let signature = &ix.data[signature_offset..signature_offset + 64];
-    require!(
-        signature == expected_signature,
-        ErrorCode::SignatureMismatch
-    );
+#[event]
+pub struct AllowedMintRemovedEvent {
+    pub mint: Pubkey,
+    pub timestamp: i64,
+}
 
-    // Verify message matches
-    require!(
-        message_len == 32,
-        ErrorCode::InvalidMessageLength
-    );
-    require!(
-        message_offset + message_len <= ix.data.len(),
-        ErrorCode::InvalidEd25519Data
-    );
-    let message = &ix.data[message_offset..message_offset + message_len];
-    require!(
-        message == expected_message,
-        ErrorCode::MessageMismatch
-    );
+#[event]
+pub struct PositionIncreasedEvent {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub additional_amount: u64,
+    /// Position amount after the increase
+    pub new_total: u64,
+}
 
-    // All checks passed - Ed25519 signature is valid!
-    Ok(())
+#[event]
+pub struct PositionTransferredEvent {
+    pub market: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
 }
 
-// ============================================================================
-// Accounts
-// ============================================================================
+#[event]
+pub struct SwitchboardFeedAttachedEvent {
+    pub market: Pubkey,
+    pub aggregator: Pubkey,
+    pub threshold: i128,
+    pub direction: ResolutionDirection,
+    pub staleness_threshold: u32,
+}
 
-/// Create Market Account Context
-/// 
-/// PDA DESIGN:
-/// - Market PDA: seeds = ["market", authority, question_hash]
-/// - Vault PDA: seeds = ["vault", market_pubkey]
-/// 
-/// The Vault PDA is owned by the Token Program and holds all USDC deposits.
-/// The Market PDA owns the vault via PDA authority derivation.
-/// 
-/// ADMIN RESTRICTION:
-/// - Only ADMIN_PUBKEY can sign to create markets
-/// - Check is enforced in instruction logic (not in constraint due to const limitations)
-#[derive(Accounts)]
-#[instruction(question: String, expiry_timestamp: i64)]
-pub struct CreateMarket<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = Market::LEN,
-        seeds = [b"market", authority.key().as_ref(), question.as_bytes()],
-        bump
-    )]
-    pub market: Account<'info, Market>,
+#[event]
+pub struct MarketForceResolvedEvent {
+    pub market: Pubkey,
+    pub resolver: Pubkey,
+    pub result: MarketResult,
+    pub bond: u64,
+    pub timestamp: i64,
+}
 
-    /// Vault PDA - holds all USDC deposits for this market
-    /// Authority is the vault itself (PDA as signer)
-    #[account(
-        init,
-        payer = authority,
-        token::mint = usdc_mint,
-        token::authority = vault,
-        seeds = [b"vault", market.key().as_ref()],
-        bump
-    )]
-    pub vault: Account<'info, TokenAccount>,
+#[event]
+pub struct ForceResolutionOverturnedEvent {
+    pub market: Pubkey,
+    pub resolver: Pubkey,
+    pub forced_result: MarketResult,
+    pub result: MarketResult,
+    pub bond: u64,
+    pub timestamp: i64,
+}
 
-    /// CHECK: USDC mint address (Devnet testnet mint)
-    pub usdc_mint: AccountInfo<'info>,
+#[event]
+pub struct ForceResolveBondReleasedEvent {
+    pub market: Pubkey,
+    pub resolver: Pubkey,
+    pub bond: u64,
+    pub timestamp: i64,
+}
 
-    /// Authority must be ADMIN_PUBKEY (checked in instruction)
-    #[account(mut)]
-    pub authority: Signer<'info>,
+/// Funds pulled out of a market by `emergency_withdraw`
+#[event]
+pub struct EmergencyWithdrawEvent {
+    pub market: Pubkey,
+    pub admin: Pubkey,
+    pub amount: u64,
+    pub treasury: Pubkey,
+    pub timestamp: i64,
+}
 
-    pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub rent: Sysvar<'info, Rent>,
+#[event]
+pub struct UnclaimedFundsWithdrawnEvent {
+    pub market: Pubkey,
+    pub amount: u64,
+    /// Wallet credited: the market authority or the treasury
+    pub destination: Pubkey,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct PlaceBet<'info> {
-    #[account(mut)]
-    pub market: Account<'info, Market>,
+#[event]
+pub struct UnclaimedGracePeriodUpdatedEvent {
+    pub old_period: i64,
+    pub new_period: i64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        init_if_needed,
-        payer = user,
-        space = UserPosition::LEN,
-        seeds = [b"position", market.key().as_ref(), user.key().as_ref()],
-        bump
-    )]
-    pub user_position: Account<'info, UserPosition>,
+#[event]
+pub struct ResolutionBondAmountUpdatedEvent {
+    pub old_amount: u64,
+    pub new_amount: u64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        mut,
-        seeds = [b"vault", market.key().as_ref()],
-        bump = market.vault_bump,
-    )]
-    pub vault: Account<'info, TokenAccount>,
+#[event]
+pub struct ResolutionBondReleasedEvent {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
 
-    #[account(
-        mut,
-        constraint = user_token_account.mint == market.usdc_mint,
-        constraint = user_token_account.owner == user.key(),
-    )]
-    pub user_token_account: Account<'info, TokenAccount>,
+#[event]
+pub struct ResolutionBondSlashedEvent {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
 
-    #[account(mut)]
-    pub user: Signer<'info>,
+#[event]
+pub struct RequiredCreatorStakeUpdatedEvent {
+    pub old_amount: u64,
+    pub new_amount: u64,
+    pub timestamp: i64,
+}
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+#[event]
+pub struct CreatorStakeReclaimedEvent {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct ResolveMarket<'info> {
-    #[account(
-        mut,
-        constraint = market.authority == authority.key() @ ErrorCode::Unauthorized,
-    )]
-    pub market: Account<'info, Market>,
+#[event]
+pub struct CreatorStakeSlashedEvent {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    /// Commitment to the off-chain reason for the slash
+    pub reason_hash: [u8; 32],
+    pub timestamp: i64,
+}
 
-    pub authority: Signer<'info>,
+#[event]
+pub struct DisputeClosedEvent {
+    pub market: Pubkey,
+    pub disputant: Pubkey,
+    pub bond: u64,
+    pub slashed: bool,
+    pub timestamp: i64,
 }
 
-/// Claim With Proof Account Context
-/// 
-/// CRITICAL SECURITY ACCOUNT: ix_sysvar
-/// 
-/// The ix_sysvar (Instructions Sysvar) is used to verify the Ed25519 signature.
-/// The transaction MUST include an Ed25519 instruction at index 0 with:
-/// - Public Key: MXE_PUBKEY
-/// - Signature: MXE's signature over the payout message
-/// - Message: keccak256(market || user || payout || nonce)
-/// 
-/// Solana's Ed25519 program verifies the signature BEFORE this instruction executes.
-/// We then validate that the signature is from the correct MXE public key.
-/// 
-/// This makes forgery cryptographically impossible without MXE's private key.
-#[derive(Accounts)]
-pub struct ClaimWithProof<'info> {
-    #[account(
-        seeds = [b"market", market.authority.as_ref(), market.question.as_bytes()],
-        bump = market.bump,
-    )]
-    pub market: Account<'info, Market>,
+#[event]
+pub struct MarketPausedEvent {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
 
-    #[account(
-        mut,
-        seeds = [b"position", market.key().as_ref(), user.key().as_ref()],
-        bump = user_position.bump,
-        constraint = user_position.user == user.key(),
-    )]
-    pub user_position: Account<'info, UserPosition>,
+#[event]
+pub struct MarketResumedEvent {
+    pub market: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
 
-    #[account(
-        mut,
-        seeds = [b"vault", market.key().as_ref()],
-        bump = market.vault_bump,
-    )]
-    pub vault: Account<'info, TokenAccount>,
+#[event]
+pub struct PayoutEstimateEvent {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub stake: u64,
+    pub payout_if_yes: u64,
+    pub payout_if_no: u64,
+    /// Payout per unit staked, in basis points (0 for an empty position)
+    pub odds_yes_bps: u64,
+    pub odds_no_bps: u64,
+}
 
-    #[account(
-        mut,
-        constraint = user_token_account.mint == market.usdc_mint,
-        constraint = user_token_account.owner == user.key(),
-    )]
-    pub user_token_account: Account<'info, TokenAccount>,
+#[event]
+pub struct MarketStatusEvent {
+    pub market: Pubkey,
+    /// Seconds until expiry (0 once expired)
+    pub time_to_expiry: i64,
+    pub pool_size: u64,
+    /// Positions opened (repeat bets on one position count once)
+    pub bet_count: u32,
+    pub resolved: bool,
+    pub paused: bool,
+    pub result: MarketResult,
+    pub timestamp: i64,
+}
 
-    pub user: Signer<'info>,
+#[event]
+pub struct VaultBalanceEvent {
+    pub market: Pubkey,
+    pub currency: MarketCurrency,
+    pub balance: u64,
+    pub timestamp: i64,
+}
 
-    /// CHECK: This is the Solana Instructions Sysvar
-    /// Used to verify the Ed25519 signature instruction
-    #[account(address = IX_SYSVAR_ID)]
-    pub ix_sysvar: AccountInfo<'info>,
+#[event]
+pub struct WhitelistUpdatedEvent {
+    pub market: Pubkey,
+    pub address: Pubkey,
+    /// False when the address was removed
+    pub added: bool,
+}
+
+#[event]
+pub struct MarketPageEvent {
+    pub page: u32,
+    pub total_markets: u32,
+    pub market_keys: Vec<Pubkey>,
+}
 
-    pub token_program: Program<'info, Token>,
+#[event]
+pub struct CreatorAllowlistUpdatedEvent {
+    pub creator: Pubkey,
+    /// False when the creator was removed
+    pub added: bool,
 }
 
-// ============================================================================
-// State
-// ============================================================================
+#[event]
+pub struct VaultUnderfundedEvent {
+    pub market: Pubkey,
+    pub vault_balance: u64,
+    /// `total_pool - total_claimed` of a market with claims outstanding
+    pub expected_minimum: u64,
+    pub deficit: u64,
+}
 
-#[account]
-pub struct Market {
+#[event]
+pub struct SnapshotEvent {
+    pub market: Pubkey,
+    pub commitment: [u8; 32],
+    pub authority_signed: bool,
     pub authority: Pubkey,
-    pub question: String,
     pub expiry_timestamp: i64,
     pub total_pool: u64,
+    pub min_bet: u64,
+    pub max_bet: u64,
+    pub max_pool: u64,
     pub resolved: bool,
+    pub paused: bool,
     pub result: MarketResult,
-    pub vault: Pubkey,
-    pub usdc_mint: Pubkey,
-    pub bump: u8,
-    pub vault_bump: u8,
+    pub resolution_timestamp: i64,
+    pub timestamp: i64,
 }
 
-impl Market {
-    pub const LEN: usize = 8 + // discriminator
-        32 + // authority
-        4 + 280 + // question (String with max 280 chars)
-        8 + // expiry_timestamp
-        8 + // total_pool
-        1 + // resolved
-        1 + // result enum
-        32 + // vault
-        32 + // usdc_mint
-        1 + // bump
-        1; // vault_bump
+#[event]
+pub struct PartialClaimEvent {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub partial_amount: u64,
+    /// Paid out of the proven payout so far, this installment included
+    pub total_claimed: u64,
+    pub remaining: u64,
 }
 
-#[account]
-pub struct UserPosition {
+#[event]
+pub struct ClaimEvent {
+    pub market: Pubkey,
     pub user: Pubkey,
+    pub amount: u64,
+    pub nonce: u64,
+    /// Custody balance once `amount` was paid out
+    pub vault_balance_after: u64,
+    /// Stake of the claimed position
+    pub position_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ResidualRolledEvent {
     pub market: Pubkey,
+    pub successor: Pubkey,
     pub amount: u64,
-    pub claimed: bool,
-    /// Nonce used in the claim proof (replay protection)
-    /// 
-    /// Once set to non-zero, this position cannot be claimed again.
-    /// The MXE generates a unique nonce for each payout computation.
-    /// Storing it prevents replay attacks (reusing old signatures).
-    pub nonce_used: u64,
-    pub bump: u8,
+    pub timestamp: i64,
 }
 
-impl UserPosition {
-    pub const LEN: usize = 8 + // discriminator
-        32 + // user
-        32 + // market
-        8 + // amount
-        1 + // claimed
-        8 + // nonce_used
-        1; // bump
+#[event]
+pub struct SideChangedEvent {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    /// 0 = YES, 1 = NO
+    pub old_side: u8,
+    pub new_side: u8,
+    /// keccak256 of the new encrypted payload
+    pub payload_hash: [u8; 32],
+    pub amount: u64,
+    pub yes_pool: u64,
+    pub no_pool: u64,
 }
 
-// ============================================================================
-// Enums
-// ============================================================================
+#[event]
+pub struct SideRevealedEvent {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    /// 0 = YES, 1 = NO
+    pub side: u8,
+    pub amount: u64,
+    pub yes_pool: u64,
+    pub no_pool: u64,
+}
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
-pub enum MarketResult {
-    None,
-    Yes,
-    No,
+#[event]
+pub struct PoolSplitRecordedEvent {
+    pub market: Pubkey,
+    pub yes_pool: u64,
+    pub no_pool: u64,
+    pub timestamp: i64,
 }
 
-// ============================================================================
-// Events
-// ============================================================================
+#[event]
+pub struct BatchPayoutCreatedEvent {
+    pub market: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub timestamp: i64,
+}
 
 #[event]
-pub struct MarketCreatedEvent {
+pub struct MarketClosedEvent {
     pub market: Pubkey,
     pub authority: Pubkey,
-    pub question: String,
-    pub expiry_timestamp: i64,
+    pub total_positions: u32,
+    pub timestamp: i64,
 }
 
 #[event]
-pub struct BetPlacedEvent {
+pub struct UserPnlEvent {
     pub market: Pubkey,
     pub user: Pubkey,
     pub amount: u64,
-    pub encrypted_payload: Vec<u8>,
-    pub timestamp: i64,
+    pub fees_paid: u64,
+    pub payout: u64,
+    /// payout - (amount + fees_paid)
+    pub pnl: i64,
 }
 
 #[event]
-pub struct MarketResolvedEvent {
+pub struct ClaimAuditEvent {
     pub market: Pubkey,
-    pub result: MarketResult,
-    pub timestamp: i64,
+    pub user: Pubkey,
+    /// Recorded payout matches the pro-rata share (or is a zero-payout loss)
+    pub correct: bool,
+    pub expected: u64,
+    pub actual: u64,
 }
 
 #[event]
-pub struct ClaimEvent {
+pub struct PositionClosedEvent {
     pub market: Pubkey,
     pub user: Pubkey,
-    pub amount: u64,
-    pub nonce: u64,
-    pub timestamp: i64,
+    pub rent_recovered: u64,
 }
 
 // ============================================================================
@@ -722,6 +12027,63 @@ pub enum ErrorCode {
     #[msg("Question must be 280 characters or less")]
     QuestionTooLong,
 
+    #[msg("Metadata URI must be 128 bytes or less")]
+    MetadataUriTooLong,
+
+    #[msg("Evidence can only be recorded in the day before expiry and until resolution")]
+    EvidenceWindowClosed,
+
+    #[msg("Evidence URI must be 128 bytes or less")]
+    EvidenceUriTooLong,
+
+    #[msg("Category index is full")]
+    CategoryIndexFull,
+
+    #[msg("A market can have at most 8 tags")]
+    TooManyTags,
+
+    #[msg("Tags must be 1 to 32 bytes and not repeated")]
+    InvalidTag,
+
+    #[msg("Tag index accounts do not match the old and new tags")]
+    TagIndexMismatch,
+
+    #[msg("Tag index is full")]
+    TagIndexFull,
+
+    #[msg("Market registry is full")]
+    RegistryFull,
+
+    #[msg("Page starts past the last registered market")]
+    PageOutOfBounds,
+
+    #[msg("Page size must be between 1 and 20")]
+    InvalidPageSize,
+
+    #[msg("Registry pages must be consecutive, from the one holding the first key")]
+    InvalidRegistryPage,
+
+    #[msg("Series already holds its maximum of 52 markets")]
+    SeriesFull,
+
+    #[msg("Series account missing or does not match series_id")]
+    SeriesNotFound,
+
+    #[msg("Series title must be 64 bytes or less")]
+    SeriesTitleTooLong,
+
+    #[msg("Leaderboard is full and the score does not beat its last entry")]
+    LeaderboardFull,
+
+    #[msg("User has no scored markets")]
+    UserNotEligible,
+
+    #[msg("Market account is still open")]
+    MarketStillOpen,
+
+    #[msg("Market is not in this index")]
+    MarketNotIndexed,
+
     #[msg("Expiry timestamp must be in the future")]
     ExpiryInPast,
 
@@ -731,18 +12093,57 @@ pub enum ErrorCode {
     #[msg("Encrypted payload too large (max 512 bytes)")]
     PayloadTooLarge,
 
+    #[msg("Minimum pool share must be at most 10000 bps")]
+    InvalidSlippageBps,
+
+    #[msg("Position is a smaller share of the pool than the slippage limit allows")]
+    SlippageExceeded,
+
+    #[msg("Maximum bet must be 0 (unlimited) or at least the minimum bet")]
+    InvalidBetLimits,
+
+    #[msg("Bet is below the market minimum")]
+    BetBelowMinimum,
+
+    #[msg("Position would exceed the market maximum")]
+    BetAboveMaximum,
+
+    #[msg("Bet would push the pool above the market cap")]
+    PoolCapExceeded,
+
     #[msg("Market has expired")]
     MarketExpired,
 
+    #[msg("Betting has closed for this market")]
+    BettingClosed,
+
+    #[msg("Betting must close at or before expiry")]
+    InvalidBettingClose,
+
     #[msg("Market already resolved")]
     MarketResolved,
 
+    #[msg("Market is paused")]
+    MarketPaused,
+
+    #[msg("Market is not paused")]
+    MarketNotPaused,
+
+    #[msg("Protocol is paused by the admin")]
+    ProtocolPaused,
+
+    #[msg("Protocol is not paused")]
+    ProtocolNotPaused,
+
     #[msg("Arithmetic overflow")]
     Overflow,
 
     #[msg("Invalid market result")]
     InvalidResult,
 
+    #[msg("Markets need between 2 and 8 outcomes")]
+    InvalidOutcomeCount,
+
     #[msg("Market has not expired yet")]
     MarketNotExpired,
 
@@ -752,15 +12153,282 @@ pub enum ErrorCode {
     #[msg("Market not yet resolved")]
     MarketNotResolved,
 
+    #[msg("Parent market must resolve before this conditional market")]
+    ParentMarketNotResolved,
+
+    #[msg("Parent market is still inside its dispute window")]
+    ParentNotFinal,
+
+    #[msg("Parent market resolved to a different outcome than required")]
+    ConditionalRequirementNotMet,
+
+    #[msg("Parent market account missing or does not match market.parent_market")]
+    ParentMarketMismatch,
+
+    #[msg("Conditional markets are resolved by their resolver, not an oracle")]
+    ConditionalMarketNeedsResolver,
+
+    #[msg("Market was not voided")]
+    MarketNotVoided,
+
     #[msg("User has already claimed")]
     AlreadyClaimed,
 
+    #[msg("Installment exceeds what is left of the proven payout")]
+    PartialClaimExceedsProof,
+
+    #[msg("Proven payout has been claimed in full")]
+    NothingLeftToClaim,
+
+    #[msg("Position cannot be transferred to its current owner")]
+    CannotTransferToSelf,
+
     #[msg("Insufficient vault balance")]
     InsufficientVaultBalance,
 
+    #[msg("Vault holds less than the market's unclaimed pool")]
+    VaultInsolvency,
+
+    #[msg("Claims are blocked until the dispute window closes")]
+    DisputeWindowActive,
+
+    #[msg("Claim deadline has passed")]
+    ClaimDeadlinePassed,
+
+    #[msg("Unclaimed funds cannot be withdrawn before the claim deadline")]
+    UnclaimedDeadlineNotReached,
+
+    #[msg("Unclaimed funds can only go to the market authority or the treasury")]
+    InvalidUnclaimedDestination,
+
+    #[msg("Emergency withdrawals open a year after expiry")]
+    EmergencyGraceNotElapsed,
+
+    #[msg("Insurance fund holds nothing for this mint")]
+    InsuranceFundEmpty,
+
+    #[msg("Drawdown exceeds the insurance fund balance")]
+    DrawdownExceedsFund,
+
+    #[msg("Insurance vault of the market mint is required while insurance fees are on")]
+    InsuranceVaultMissing,
+
+    #[msg("Vault balance drifted from the market pool beyond the tolerance")]
+    VaultInconsistent,
+
+    #[msg("Dispute window has closed")]
+    DisputeWindowClosed,
+
     #[msg("Unauthorized: Only admin can create markets")]
     Unauthorized,
 
+    #[msg("Token-2022 is not enabled for this market")]
+    Token22NotSupported,
+
+    #[msg("Mint is not on the protocol's allowed mint list")]
+    UnsupportedMint,
+
+    #[msg("Allowed mint list is full")]
+    TooManyAllowedMints,
+
+    #[msg("Accounts do not match the market currency")]
+    CurrencyMismatch,
+
+    #[msg("Fee exceeds the protocol maximum")]
+    FeeTooHigh,
+
+    #[msg("Rakeback thresholds must increase and rebates stay within the maximum fee")]
+    InvalidRakebackSchedule,
+
+    #[msg("Fee vault balance is too low")]
+    InsufficientFeeBalance,
+
+    #[msg("Authority cannot cover the market creation fee")]
+    InsufficientCreationFee,
+
+    #[msg("Bet would exceed the user's daily volume cap")]
+    DailyLimitExceeded,
+
+    #[msg("Bet would exceed the user's lifetime volume cap")]
+    LifetimeLimitExceeded,
+
+    #[msg("User is not on this market's whitelist")]
+    UserNotWhitelisted,
+
+    #[msg("Address is already whitelisted")]
+    AlreadyWhitelisted,
+
+    #[msg("Whitelist already holds its maximum of 200 entries")]
+    WhitelistFull,
+
+    #[msg("Creator is already allowlisted")]
+    CreatorAlreadyAllowed,
+
+    #[msg("Creator is not on the allowlist")]
+    CreatorNotAllowed,
+
+    #[msg("Creator allowlist already holds its maximum of 50 creators")]
+    CreatorAllowlistFull,
+
+    #[msg("Market is not denominated in wrapped SOL")]
+    NotWrappedSolMarket,
+
+    #[msg("Authority cannot cover the resolution bond")]
+    InsufficientBond,
+
+    #[msg("Resolution bond is locked until the dispute window passes")]
+    BondLocked,
+
+    #[msg("Resolution bond has open disputes")]
+    BondDisputed,
+
+    #[msg("Creator stake was slashed")]
+    StakeSlashed,
+
+    #[msg("Creator stake was already reclaimed")]
+    StakeAlreadyReclaimed,
+
+    #[msg("Creator stake is locked until the dispute window passes")]
+    DisputeWindowNotExpired,
+
+    #[msg("No open dispute to uphold")]
+    NoOpenDispute,
+
+    #[msg("Force resolution is not yet allowed for this market")]
+    ForceResolveDelayNotElapsed,
+
+    #[msg("Counter-result matches the forced result")]
+    ForceResultUnchanged,
+
+    #[msg("No admin transfer has been proposed")]
+    NoPendingAdminTransfer,
+
+    #[msg("Signer is not the pending admin")]
+    NotPendingAdmin,
+
+    #[msg("Signer or proposal does not belong to the attached governance")]
+    GovernanceCheckFailed,
+
+    #[msg("Governance proposal has not passed")]
+    ProposalNotExecuted,
+
+    #[msg("No market authority transfer has been proposed")]
+    NoPendingAuthorityTransfer,
+
+    #[msg("Signer is not the pending market authority")]
+    NotPendingAuthority,
+
+    #[msg("Resolver stake is below the protocol minimum")]
+    InsufficientResolverStake,
+
+    #[msg("Resolver stake is locked until the last dispute window closes")]
+    ResolverStakeLocked,
+
+    #[msg("Position has not been claimed yet")]
+    PositionNotSettled,
+
+    #[msg("Some positions have not claimed yet")]
+    UnclaimedPositions,
+
+    #[msg("Market custody still holds funds")]
+    VaultNotEmpty,
+
+    #[msg("Successor market is not the one configured for this market")]
+    InvalidSuccessorMarket,
+
+    #[msg("Successor market must settle in the same currency and mint")]
+    SuccessorMintMismatch,
+
+    #[msg("Market vault has not been initialized")]
+    VaultNotInitialized,
+
+    #[msg("Market vault is already initialized")]
+    VaultAlreadyInitialized,
+
+    #[msg("Vault and fee vault must be created together")]
+    IncompleteVaultAccounts,
+
+    #[msg("Batch accounts must be [position, owner] pairs for this market")]
+    InvalidBatchAccounts,
+
+    #[msg("Too many entries in one batch")]
+    BatchTooLarge,
+
+    #[msg("Market has a creator fee, commitment, proposal or parent; use resolve_market")]
+    BatchResolveUnsupported,
+
+    #[msg("Conditional markets must be claimed individually")]
+    BatchClaimUnsupported,
+
+    #[msg("Batch claim accounts must be [market, position, vault, user_token_account] groups")]
+    InvalidBatchClaimAccounts,
+
+    #[msg("Batch argument lists must match the number of account groups")]
+    BatchLengthMismatch,
+
+    #[msg("Relayer fee exceeds the payout")]
+    RelayerFeeExceedsPayout,
+
+    #[msg("Outcome labels must be empty or one per outcome")]
+    OutcomeLabelCountMismatch,
+
+    #[msg("Outcome label must be 32 bytes or less")]
+    OutcomeLabelTooLong,
+
+    #[msg("MXE key rotation is still time-locked")]
+    RotationTimeLockActive,
+
+    #[msg("Admin timelock is on; queue the change with queue_admin_action")]
+    AdminTimelockEnabled,
+
+    #[msg("Admin timelock is off; apply the change directly")]
+    AdminTimelockDisabled,
+
+    #[msg("Queued admin action is still time-locked")]
+    AdminTimelockActive,
+
+    #[msg("Action does not match the queued admin action")]
+    AdminActionNotQueued,
+
+    #[msg("No MXE key rotation is pending")]
+    NoPendingRotation,
+
+    #[msg("Market has a resolution commitment; resolve it with a reveal")]
+    ResolutionCommitmentRequired,
+
+    #[msg("A resolution has already been proposed for this market")]
+    ResolutionAlreadyProposed,
+
+    #[msg("No resolution has been proposed for this market")]
+    NoResolutionProposed,
+
+    #[msg("Proposed resolution is still open to challenge")]
+    ChallengeWindowActive,
+
+    #[msg("Challenge window has closed")]
+    ChallengeWindowClosed,
+
+    #[msg("Proposed resolution is disputed and awaits the admin")]
+    ResolutionDisputed,
+
+    #[msg("Proposed resolution is not disputed")]
+    ResolutionNotDisputed,
+
+    #[msg("Market has no resolution commitment")]
+    NoResolutionCommitment,
+
+    #[msg("Revealed outcome and salt do not match the commitment")]
+    RevealMismatch,
+
+    #[msg("New expiry must be later than the current expiry")]
+    ExpiryNotExtended,
+
+    #[msg("Metadata cannot be updated once bets exist")]
+    MetadataUpdateForbidden,
+
+    #[msg("Question cannot be updated once bets exist")]
+    QuestionUpdateForbidden,
+
     // ============================================================================
     // Security / Cryptographic Verification Errors
     // ============================================================================
@@ -768,6 +12436,30 @@ pub enum ErrorCode {
     #[msg("Nonce already used (replay protection)")]
     NonceAlreadyUsed,
 
+    #[msg("Payout proof has expired")]
+    ProofExpired,
+
+    #[msg("Payout exceeds the pool or the position's maximum winning share")]
+    PayoutExceedsBound,
+
+    #[msg("Merkle proof does not match the batch payout root")]
+    MerkleProofInvalid,
+
+    #[msg("Pool split has already been recorded for this market")]
+    PoolSplitAlreadyRecorded,
+
+    #[msg("Pools already hold sides revealed one position at a time")]
+    PoolsAlreadyRevealed,
+
+    #[msg("Pool split has not been recorded for this market")]
+    PoolSplitNotRecorded,
+
+    #[msg("Position's side has already been revealed")]
+    SideAlreadyRevealed,
+
+    #[msg("Side change must move a position off the side it is on")]
+    InvalidSideChange,
+
     #[msg("Invalid instructions sysvar account")]
     InvalidInstructionSysvar,
 
@@ -780,11 +12472,38 @@ pub enum ErrorCode {
     #[msg("Invalid Ed25519 instruction data")]
     InvalidEd25519Data,
 
-    #[msg("Invalid signature count (must be 1)")]
+    #[msg("Transaction has Ed25519 instructions besides the MXE proofs")]
+    MultipleEd25519Instructions,
+
+    #[msg("MXE proof instructions must directly precede the instruction they authorize")]
+    Ed25519InstructionMisplaced,
+
+    #[msg("Invalid signature count (1 per Ed25519 instruction, at most 5)")]
     InvalidSignatureCount,
 
-    #[msg("Public key does not match MXE_PUBKEY")]
-    InvalidMXEPublicKey,
+    #[msg("Secp256k1 instruction missing from transaction")]
+    Secp256k1InstructionMissing,
+
+    #[msg("Secp256k1 signer is not the configured MXE Ethereum address")]
+    InvalidEthAddress,
+
+    #[msg("Secp256k1 claims need the Secp256k1 signature scheme")]
+    Secp256k1SchemeInactive,
+
+    #[msg("Invalid Secp256k1 instruction data")]
+    InvalidSecp256k1Data,
+
+    #[msg("Signer is not a configured MXE key")]
+    UnknownMXESigner,
+
+    #[msg("The same MXE key signed more than once")]
+    DuplicateMXESigner,
+
+    #[msg("Not enough MXE signatures for the configured quorum")]
+    InsufficientMXESignatures,
+
+    #[msg("MXE signer set must have 1-5 keys and a quorum within it")]
+    InvalidMxeQuorum,
 
     #[msg("Signature does not match expected signature")]
     SignatureMismatch,
@@ -794,4 +12513,59 @@ pub enum ErrorCode {
 
     #[msg("Message does not match expected message")]
     MessageMismatch,
+
+    #[msg("No Groth16 verifying key has been finalized")]
+    ZkVerifierNotConfigured,
+
+    #[msg("Groth16 proof does not verify")]
+    InvalidZkProof,
+
+    // ============================================================================
+    // Oracle Resolution Errors
+    // ============================================================================
+
+    #[msg("Price feed account does not match the market's Pyth feed")]
+    PythFeedMismatch,
+
+    #[msg("Pyth price is stale or its confidence interval is too wide")]
+    PythPriceStale,
+
+    #[msg("Oracle resolution window after expiry has closed")]
+    OracleWindowClosed,
+
+    #[msg("Market has no Pyth feed configured")]
+    NoPythFeedConfigured,
+
+    #[msg("Aggregator account does not match the market's Switchboard oracle")]
+    OracleFeedMismatch,
+
+    #[msg("Market has no Switchboard oracle configured")]
+    NoOracleConfigured,
+
+    #[msg("Market can be resolved by only one oracle")]
+    MultipleOraclesConfigured,
+
+    #[msg("Market is resolved by its oracle, not by the resolver")]
+    OracleResolutionRequired,
+
+    #[msg("Resolution commitment cannot be combined with an oracle")]
+    CommitmentWithOracle,
+
+    #[msg("Switchboard round is stale or its oracles disagree too much")]
+    SwitchboardFeedStale,
+
+    #[msg("Aggregator account does not match the market's Switchboard feed")]
+    SwitchboardFeedMismatch,
+
+    #[msg("Switchboard aggregator account could not be read")]
+    SwitchboardReadError,
+
+    #[msg("Market already has positions")]
+    MarketHasPositions,
+
+    #[msg("Resolution criteria do not fit the market")]
+    InvalidResolutionCriteria,
+
+    #[msg("Market's resolution criteria do not allow this resolution path")]
+    CriteriaTypeMismatch,
 }
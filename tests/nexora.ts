@@ -1,13 +1,24 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program, BN } from "@coral-xyz/anchor";
 import { Nexora } from "../target/types/nexora";
+import { MockPyth } from "../target/types/mock_pyth";
 import { PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
 import {
+  ExtensionType,
+  NATIVE_MINT,
+  TOKEN_2022_PROGRAM_ID,
   TOKEN_PROGRAM_ID,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
   createMint,
+  getMintLen,
   getOrCreateAssociatedTokenAccount,
   mintTo,
+  transfer,
 } from "@solana/spl-token";
+import { bn254 } from "@noble/curves/bn254";
+import { keccak_256 } from "@noble/hashes/sha3";
+import { SwitchboardTestContext } from "@switchboard-xyz/sbv2-utils";
 import { assert } from "chai";
 
 describe("nexora", () => {
@@ -15,6 +26,8 @@ describe("nexora", () => {
   anchor.setProvider(provider);
 
   const program = anchor.workspace.Nexora as Program<Nexora>;
+  // Publishes Pyth-layout price accounts on localnet
+  const mockPyth = anchor.workspace.MockPyth as Program<MockPyth>;
   const authority = provider.wallet;
 
   let usdcMint: PublicKey;
@@ -22,6 +35,24 @@ describe("nexora", () => {
   let userWallet: anchor.web3.Keypair;
   let userTokenAccount: PublicKey;
 
+  // Stand-in for the Arcium MXE signer; deterministic so reruns against the
+  // same validator find the key already in Config
+  const mxeKeypair = anchor.web3.Keypair.fromSeed(new Uint8Array(32).fill(42));
+
+  // Requires a program built with `anchor build -- --features short-timelocks`
+  const shortTimelocks = process.env.NEXORA_SHORT_TIMELOCKS === "1";
+  const itWithShortTimelocks = shortTimelocks ? it : it.skip;
+  const MXE_ROTATION_DELAY_SECS = shortTimelocks ? 5 : 48 * 3600;
+  const DISPUTE_WINDOW_SECS = shortTimelocks ? 5 : 86400;
+  const MXE_ROTATION_GRACE_SECS = shortTimelocks ? 5 : 24 * 3600;
+  const FORCE_RESOLVE_DELAY_SECS = shortTimelocks ? 5 : 7 * 86400;
+  const CHALLENGE_WINDOW_SECS = shortTimelocks ? 5 : 86400;
+  const ORACLE_RESOLUTION_WINDOW_SECS = shortTimelocks ? 10 : 3600;
+
+  // Requires a local Switchboard V2 environment (`sbv2 localnet env`)
+  const itWithSwitchboard =
+    process.env.NEXORA_SWITCHBOARD_LOCALNET === "1" ? it : it.skip;
+
   before(async () => {
     // Create USDC-like token
     usdcMint = await createMint(
@@ -82,19 +113,72 @@ describe("nexora", () => {
       100 * 1e6
     );
 
+    // Protocol config is global; start every run with no fee
+    const existingConfig = await program.account.config.fetchNullable(configPda);
+    if (!existingConfig) {
+      await program.methods
+        .initializeProtocol(0, [Array.from(mxeKeypair.publicKey.toBytes())], 1)
+        .accounts({
+          config: configPda,
+          stats: statsPda,
+          admin: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    } else if (existingConfig.feeBps !== 0) {
+      await program.methods
+        .setFeeBps(0)
+        .accounts({ config: configPda, admin: authority.publicKey })
+        .rpc();
+    }
+    await allowMint(usdcMint);
+
     console.log("Authority Token Account:", authorityTokenAccount.toString());
     console.log("User Wallet:", userWallet.publicKey.toString());
     console.log("User Token Account:", userTokenAccount.toString());
   });
 
-  it("Creates a market", async () => {
-    const question = "Will Bitcoin reach $100k by end of 2026?";
-    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 86400); // 24 hours
+  // Each run mints a fresh USDC stand-in; keep the allowed list from filling
+  after(async () => {
+    await disallowMint(usdcMint);
+  });
+
+  const [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+
+  const [statsPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("stats")],
+    program.programId
+  );
+
+  const findFeeVaultPda = (marketPda: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_vault"), marketPda.toBuffer()],
+      program.programId
+    )[0];
+
+  const allowMint = (mint: PublicKey) =>
+    program.methods
+      .allowMint()
+      .accounts({ config: configPda, mint, admin: authority.publicKey })
+      .rpc();
+
+  const disallowMint = (mint: PublicKey) =>
+    program.methods
+      .disallowMint(mint)
+      .accounts({ config: configPda, admin: authority.publicKey })
+      .rpc();
 
+  const findMarketPdas = (
+    question: string,
+    creator: PublicKey = authority.publicKey
+  ) => {
     const [marketPda] = PublicKey.findProgramAddressSync(
       [
         Buffer.from("market"),
-        authority.publicKey.toBuffer(),
+        creator.toBuffer(),
         Buffer.from(question),
       ],
       program.programId
@@ -105,11 +189,161 @@ describe("nexora", () => {
       program.programId
     );
 
+    const [metadataPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), marketPda.toBuffer()],
+      program.programId
+    );
+
+    return { marketPda, vaultPda, metadataPda };
+  };
+
+  // In MarketCategory order; the index is the category_index seed byte
+  const MARKET_CATEGORIES = [
+    "sports",
+    "crypto",
+    "politics",
+    "entertainment",
+    "science",
+    "finance",
+    "other",
+  ] as const;
+  type MarketCategoryName = (typeof MARKET_CATEGORIES)[number];
+
+  const findCategoryIndexPda = (category: MarketCategoryName) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("category_index"),
+        Buffer.from([MARKET_CATEGORIES.indexOf(category)]),
+      ],
+      program.programId
+    )[0];
+
+  const [registryPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("registry")],
+    program.programId
+  );
+
+  const REGISTRY_PAGE_MAX_MARKETS = 250;
+
+  const findRegistryPagePda = (page: number) => {
+    const pageBytes = Buffer.alloc(4);
+    pageBytes.writeUInt32LE(page);
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("registry_page"), pageBytes],
+      program.programId
+    )[0];
+  };
+
+  // Page the next create_market appends to (MarketRegistry::current_page)
+  const currentRegistryPagePda = async () => {
+    const registry = await program.account.marketRegistry.fetchNullable(registryPda);
+    const pageLens: number[] = registry?.pageLens ?? [];
+    const free = pageLens.findIndex((len) => len < REGISTRY_PAGE_MAX_MARKETS);
+    return findRegistryPagePda(free === -1 ? pageLens.length : free);
+  };
+
+  type MarketOptions = {
+    metadataUri?: string;
+    minBet?: BN;
+    maxBet?: BN;
+    maxPool?: BN;
+    isToken22?: boolean;
+    numOutcomes?: number;
+    currency?: { usdc: {} } | { sol: {} };
+    creatorFeeBps?: number;
+    outcomeLabels?: string[];
+    resolutionCommitment?: number[];
+    // Leave vault creation to a separate init_vault call
+    deferVault?: boolean;
+    // Defaults to the classic-SPL usdcMint
+    mint?: PublicKey;
+    tokenProgram?: PublicKey;
+    // Switchboard aggregator for resolve_with_oracle
+    oracle?: PublicKey;
+    resolutionPriceThreshold?: BN;
+    resolutionDirection?: { above: {} } | { below: {} };
+    category?: MarketCategoryName;
+    // Pyth price account for auto_resolve_with_pyth
+    pythPriceFeed?: PublicKey;
+    // Key naming a MarketSeries created by create_market_series
+    seriesId?: PublicKey;
+    // Betting cutoff before expiry; defaults to the expiry
+    bettingCloseTimestamp?: BN;
+    // Allowlisted creator signing instead of the admin
+    creator?: anchor.web3.Keypair;
+  };
+
+  const [creatorAllowlistPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("creator_allowlist")],
+    program.programId
+  );
+
+  const findSeriesPda = (seriesId: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("series"), seriesId.toBuffer()],
+      program.programId
+    )[0];
+
+  const createMarket = async (
+    question: string,
+    expiryTimestamp: BN,
+    options: MarketOptions = {}
+  ) => {
+    const creator = options.creator?.publicKey ?? authority.publicKey;
+    const { marketPda, vaultPda, metadataPda } = findMarketPdas(question, creator);
+    const category = options.category ?? "other";
+
     await program.methods
-      .createMarket(question, expiryTimestamp)
+      .createMarket(
+        question,
+        expiryTimestamp,
+        options.metadataUri ?? "",
+        options.pythPriceFeed ?? null,
+        options.oracle ?? null,
+        options.resolutionPriceThreshold ?? null,
+        options.resolutionDirection ?? { above: {} },
+        options.minBet ?? new BN(0),
+        options.maxBet ?? new BN(0),
+        options.maxPool ?? new BN(0),
+        options.isToken22 ?? false,
+        options.numOutcomes ?? 2,
+        options.currency ?? { usdc: {} },
+        options.creatorFeeBps ?? 0,
+        options.outcomeLabels ?? [],
+        options.resolutionCommitment ?? null,
+        { [category]: {} } as any,
+        options.seriesId ?? null,
+        options.bettingCloseTimestamp ?? null
+      )
+      .accounts({
+        market: marketPda,
+        marketMetadata: metadataPda,
+        categoryIndex: findCategoryIndexPda(category),
+        registry: registryPda,
+        registryPage: await currentRegistryPagePda(),
+        series: options.seriesId ? findSeriesPda(options.seriesId) : null,
+        creatorAllowlist: options.creator ? creatorAllowlistPda : null,
+        vault: options.deferVault ? null : vaultPda,
+        feeVault: options.deferVault ? null : findFeeVaultPda(marketPda),
+        usdcMint: options.mint ?? usdcMint,
+        authority: creator,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: options.tokenProgram ?? TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers(options.creator ? [options.creator] : [])
+      .rpc();
+
+    return { marketPda, vaultPda };
+  };
+
+  const initVault = (marketPda: PublicKey, vaultPda: PublicKey) =>
+    program.methods
+      .initVault()
       .accounts({
         market: marketPda,
         vault: vaultPda,
+        feeVault: findFeeVaultPda(marketPda),
         usdcMint,
         authority: authority.publicKey,
         systemProgram: SystemProgram.programId,
@@ -118,6 +352,96 @@ describe("nexora", () => {
       })
       .rpc();
 
+  const findPositionPda = (marketPda: PublicKey, user: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("position"), marketPda.toBuffer(), user.toBuffer()],
+      program.programId
+    )[0];
+
+  // Expiry for MXE payout proofs, `secs` from now
+  const proofExpiry = (secs = 3600) =>
+    new BN(Math.floor(Date.now() / 1000) + secs);
+
+  const findUserStatsPda = (user: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("stats"), user.toBuffer()],
+      program.programId
+    )[0];
+
+  const buildBet = (
+    marketPda: PublicKey,
+    vaultPda: PublicKey,
+    amount: BN,
+    wallet: anchor.web3.Keypair = userWallet,
+    tokenAccount: PublicKey = userTokenAccount,
+    minPoolShareBps: number | null = null,
+    insuranceVault: PublicKey | null = null
+  ) =>
+    program.methods
+      .placeBet(Array.from(Buffer.from("payload")), amount, minPoolShareBps)
+      .accounts({
+        market: marketPda,
+        config: configPda,
+        userPosition: findPositionPda(marketPda, wallet.publicKey),
+        vault: vaultPda,
+        solVault: null,
+        feeVault: findFeeVaultPda(marketPda),
+        solFeeVault: null,
+        userLimits: null,
+        whitelist: null,
+        userTokenAccount: tokenAccount,
+        usdcMint,
+        insuranceVault,
+        userStats: findUserStatsPda(wallet.publicKey),
+        user: wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([wallet]);
+
+  const placeBet = async (
+    marketPda: PublicKey,
+    vaultPda: PublicKey,
+    amount: BN,
+    wallet: anchor.web3.Keypair = userWallet,
+    tokenAccount: PublicKey = userTokenAccount
+  ) =>
+    buildBet(marketPda, vaultPda, amount, wallet, tokenAccount).rpc({
+      commitment: "confirmed",
+    });
+
+  const expectError = async (action: Promise<unknown>, code: string) => {
+    try {
+      await action;
+      assert.fail(`Expected ${code}`);
+    } catch (err: any) {
+      assert.include(err.toString(), code);
+    }
+  };
+
+  const tokenBalance = async (account: PublicKey) =>
+    new BN((await provider.connection.getTokenAccountBalance(account)).value.amount);
+
+  it("Creates a market", async () => {
+    const question = "Will Bitcoin reach $100k by end of 2026?";
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 86400); // 24 hours
+
+    const [marketPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("market"),
+        authority.publicKey.toBuffer(),
+        Buffer.from(question),
+      ],
+      program.programId
+    );
+
+    const [vaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), marketPda.toBuffer()],
+      program.programId
+    );
+
+    await createMarket(question, expiryTimestamp);
+
     const marketAccount = await program.account.market.fetch(marketPda);
     
     assert.equal(marketAccount.question, question);
@@ -169,12 +493,21 @@ describe("nexora", () => {
     const betAmount = new BN(5 * 1e6); // 5 USDC
 
     await program.methods
-      .placeBet(Array.from(encryptedPayload), betAmount)
+      .placeBet(Array.from(encryptedPayload), betAmount, null)
       .accounts({
         market: marketPda,
+        config: configPda,
         userPosition: userPositionPda,
         vault: vaultPda,
+        solVault: null,
+        feeVault: findFeeVaultPda(marketPda),
+        solFeeVault: null,
+        userLimits: null,
+        whitelist: null,
         userTokenAccount,
+        usdcMint,
+        insuranceVault: null,
+        userStats: findUserStatsPda(userWallet.publicKey),
         user: userWallet.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
@@ -211,25 +544,14 @@ describe("nexora", () => {
     );
 
     // Create market
-    await program.methods
-      .createMarket(question, expiryTimestamp)
-      .accounts({
-        market: marketPda,
-        vault: vaultPda,
-        usdcMint,
-        authority: authority.publicKey,
-        systemProgram: SystemProgram.programId,
-        tokenProgram: TOKEN_PROGRAM_ID,
-        rent: SYSVAR_RENT_PUBKEY,
-      })
-      .rpc();
+    await createMarket(question, expiryTimestamp);
 
     // Wait for expiry
     await new Promise((resolve) => setTimeout(resolve, 3000));
 
     // Resolve as YES
     await program.methods
-      .resolveMarket({ yes: {} })
+      .resolveMarket(0)
       .accounts({
         market: marketPda,
         authority: authority.publicKey,
@@ -271,18 +593,7 @@ describe("nexora", () => {
     );
 
     // Create market
-    await program.methods
-      .createMarket(question, expiryTimestamp)
-      .accounts({
-        market: marketPda,
-        vault: vaultPda,
-        usdcMint,
-        authority: authority.publicKey,
-        systemProgram: SystemProgram.programId,
-        tokenProgram: TOKEN_PROGRAM_ID,
-        rent: SYSVAR_RENT_PUBKEY,
-      })
-      .rpc();
+    await createMarket(question, expiryTimestamp);
 
     // Place bet
     const payload = {
@@ -298,12 +609,21 @@ describe("nexora", () => {
     );
 
     await program.methods
-      .placeBet(Array.from(encryptedPayload), new BN(10 * 1e6))
+      .placeBet(Array.from(encryptedPayload), new BN(10 * 1e6), null)
       .accounts({
         market: marketPda,
+        config: configPda,
         userPosition: userPositionPda,
         vault: vaultPda,
+        solVault: null,
+        feeVault: findFeeVaultPda(marketPda),
+        solFeeVault: null,
+        userLimits: null,
+        whitelist: null,
         userTokenAccount,
+        usdcMint,
+        insuranceVault: null,
+        userStats: findUserStatsPda(userWallet.publicKey),
         user: userWallet.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
@@ -316,7 +636,7 @@ describe("nexora", () => {
 
     // Resolve
     await program.methods
-      .resolveMarket({ yes: {} })
+      .resolveMarket(0)
       .accounts({
         market: marketPda,
         authority: authority.publicKey,
@@ -335,7 +655,9 @@ describe("nexora", () => {
         market: marketPda,
         userPosition: userPositionPda,
         vault: vaultPda,
+        solVault: null,
         userTokenAccount,
+        usdcMint,
         user: userWallet.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
@@ -358,4 +680,6217 @@ describe("nexora", () => {
 
     console.log("✅ Payout claimed successfully");
   });
+
+  it("Extends market expiry", async () => {
+    const question = "Will the launch be delayed?";
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const { marketPda } = await createMarket(question, expiryTimestamp);
+
+    const newExpiry = expiryTimestamp.add(new BN(86400));
+    await program.methods
+      .extendExpiry(newExpiry)
+      .accounts({
+        market: marketPda,
+        authority: authority.publicKey,
+      })
+      .rpc();
+
+    const marketAccount = await program.account.market.fetch(marketPda);
+    assert.equal(
+      marketAccount.expiryTimestamp.toNumber(),
+      newExpiry.toNumber()
+    );
+
+    console.log("✅ Market expiry extended");
+  });
+
+  it("Rejects shortening market expiry", async () => {
+    const question = "Will the launch be moved up?";
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const { marketPda } = await createMarket(question, expiryTimestamp);
+
+    try {
+      await program.methods
+        .extendExpiry(expiryTimestamp.sub(new BN(60)))
+        .accounts({
+          market: marketPda,
+          authority: authority.publicKey,
+        })
+        .rpc();
+      assert.fail("Shortened expiry was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "ExpiryNotExtended");
+    }
+
+    const marketAccount = await program.account.market.fetch(marketPda);
+    assert.equal(
+      marketAccount.expiryTimestamp.toNumber(),
+      expiryTimestamp.toNumber()
+    );
+
+    console.log("✅ Expiry shortening rejected");
+  });
+
+  // Requires a program built with `anchor build -- --features structured-logs`
+  const itWithStructuredLogs =
+    process.env.NEXORA_STRUCTURED_LOGS === "1" ? it : it.skip;
+
+  itWithStructuredLogs("Emits structured log lines", async () => {
+    const question = "Will structured logs appear?";
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const { marketPda, vaultPda } = await createMarket(
+      question,
+      expiryTimestamp
+    );
+
+    const betAmount = new BN(1 * 1e6);
+    const signature = await placeBet(marketPda, vaultPda, betAmount);
+
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const line = tx.meta.logMessages.find((log) =>
+      log.includes("nexora:place_bet")
+    );
+
+    assert.ok(line, "place_bet log line missing");
+    assert.include(line, `market=${marketPda.toString()}`);
+    assert.include(line, `amount=${betAmount.toString()}`);
+    assert.include(line, `pool=${betAmount.toString()}`);
+
+    console.log("✅ Structured log emitted:", line);
+  });
+
+  it("Updates the question before any bets", async () => {
+    const question = "Will BTC clsoe above $90k?";
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const { marketPda } = await createMarket(question, expiryTimestamp);
+
+    const corrected = "Will BTC close above $90k?";
+    await program.methods
+      .updateQuestion(corrected)
+      .accounts({
+        market: marketPda,
+        authority: authority.publicKey,
+      })
+      .rpc();
+
+    // The seed question stays, so the address can still be derived
+    const marketAccount = await program.account.market.fetch(marketPda);
+    assert.equal(marketAccount.question, question);
+    assert.equal(marketAccount.questionV2, corrected);
+    assert.ok(findMarketPdas(question).marketPda.equals(marketPda));
+
+    console.log("✅ Question updated");
+  });
+
+  it("Rejects question updates after the first bet", async () => {
+    const question = "Will ETH close above $5k?";
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const { marketPda, vaultPda } = await createMarket(
+      question,
+      expiryTimestamp
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    try {
+      await program.methods
+        .updateQuestion("Will ETH close above $6k?")
+        .accounts({
+          market: marketPda,
+          authority: authority.publicKey,
+        })
+        .rpc();
+      assert.fail("Question edit after a bet was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "QuestionUpdateForbidden");
+    }
+
+    const marketAccount = await program.account.market.fetch(marketPda);
+    assert.equal(marketAccount.question, question);
+    assert.isNull(marketAccount.questionV2);
+
+    console.log("✅ Question update refused after bet");
+  });
+
+  it("Sets a corrected display question without touching the seed", async () => {
+    const question = "Will SOL clsoe above $300?";
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const { marketPda, vaultPda } = await createMarket(question, expiryTimestamp);
+    const updateMarketQuestion = (corrected: string) =>
+      program.methods
+        .updateMarketQuestion(corrected)
+        .accounts({
+          market: marketPda,
+          authority: authority.publicKey,
+        });
+
+    const corrected = "Will SOL close above $300?";
+    const { events } = await updateMarketQuestion(corrected).simulate();
+    const event = events.find((e) => e.name === "QuestionUpdatedEvent");
+    assert.isNull(event.data.oldQuestionV2);
+    assert.equal(event.data.newQuestionV2, corrected);
+    await updateMarketQuestion(corrected).rpc();
+
+    const marketAccount = await program.account.market.fetch(marketPda);
+    assert.equal(marketAccount.question, question);
+    assert.equal(marketAccount.questionV2, corrected);
+    assert.ok(findMarketPdas(question).marketPda.equals(marketPda));
+
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    try {
+      await updateMarketQuestion("Will SOL close above $400?").rpc();
+      assert.fail("Display question edited after a bet");
+    } catch (error) {
+      assert.include(error.toString(), "QuestionUpdateForbidden");
+    }
+
+    console.log("✅ Display question corrected");
+  });
+
+  it("Enforces the minimum bet", async () => {
+    const question = "Will min bets hold?";
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const { marketPda, vaultPda } = await createMarket(
+      question,
+      expiryTimestamp,
+      { minBet: new BN(1 * 1e6) }
+    );
+
+    try {
+      await placeBet(marketPda, vaultPda, new BN(1));
+      assert.fail("Dust bet was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "BetBelowMinimum");
+    }
+
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    const marketAccount = await program.account.market.fetch(marketPda);
+    assert.equal(marketAccount.totalPool.toNumber(), 1 * 1e6);
+
+    console.log("✅ Minimum bet enforced");
+  });
+
+  it("Enforces the maximum cumulative position", async () => {
+    const question = "Will max bets hold?";
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const { marketPda, vaultPda } = await createMarket(
+      question,
+      expiryTimestamp,
+      { maxBet: new BN(3 * 1e6) }
+    );
+
+    await placeBet(marketPda, vaultPda, new BN(2 * 1e6));
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    try {
+      await placeBet(marketPda, vaultPda, new BN(1));
+      assert.fail("Position above maximum was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "BetAboveMaximum");
+    }
+
+    const position = await program.account.userPosition.fetch(
+      findPositionPda(marketPda, userWallet.publicKey)
+    );
+    assert.equal(position.amount.toNumber(), 3 * 1e6);
+
+    console.log("✅ Maximum position enforced");
+  });
+
+  it("Allows unlimited positions when max bet is 0", async () => {
+    const question = "Will unlimited bets hold?";
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const { marketPda, vaultPda } = await createMarket(
+      question,
+      expiryTimestamp
+    );
+
+    await placeBet(marketPda, vaultPda, new BN(1));
+    await placeBet(marketPda, vaultPda, new BN(20 * 1e6));
+
+    const position = await program.account.userPosition.fetch(
+      findPositionPda(marketPda, userWallet.publicKey)
+    );
+    assert.equal(position.amount.toNumber(), 20 * 1e6 + 1);
+
+    console.log("✅ Unlimited market accepts any size");
+  });
+
+  it("Caps the total pool", async () => {
+    const question = "Will the pool cap hold?";
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const { marketPda, vaultPda } = await createMarket(
+      question,
+      expiryTimestamp,
+      { maxPool: new BN(3 * 1e6) }
+    );
+
+    await placeBet(marketPda, vaultPda, new BN(2 * 1e6));
+
+    try {
+      await placeBet(marketPda, vaultPda, new BN(1 * 1e6 + 1));
+      assert.fail("Bet above the pool cap was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "PoolCapExceeded");
+    }
+
+    // Exactly filling the cap is allowed
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    let marketAccount = await program.account.market.fetch(marketPda);
+    assert.equal(marketAccount.totalPool.toNumber(), 3 * 1e6);
+
+    try {
+      await placeBet(marketPda, vaultPda, new BN(1));
+      assert.fail("Bet on a full pool was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "PoolCapExceeded");
+    }
+
+    marketAccount = await program.account.market.fetch(marketPda);
+    assert.equal(marketAccount.totalPool.toNumber(), 3 * 1e6);
+
+    console.log("✅ Pool cap enforced");
+  });
+
+  const snapshotCommitment = async (marketPda: PublicKey) => {
+    const { events } = await program.methods
+      .exportSnapshot()
+      .accounts({ market: marketPda, authority: null })
+      .simulate();
+    const event = events.find((e) => e.name === "SnapshotEvent");
+    return Buffer.from(event.data.commitment as number[]).toString("hex");
+  };
+
+  it("Exports a stable market snapshot", async () => {
+    const question = "Will snapshots be stable?";
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const { marketPda, vaultPda } = await createMarket(
+      question,
+      expiryTimestamp
+    );
+
+    const first = await snapshotCommitment(marketPda);
+    const second = await snapshotCommitment(marketPda);
+    assert.equal(first, second);
+
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    const afterBet = await snapshotCommitment(marketPda);
+    assert.notEqual(afterBet, first);
+
+    await program.methods
+      .extendExpiry(expiryTimestamp.add(new BN(60)))
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    const afterExtend = await snapshotCommitment(marketPda);
+    assert.notEqual(afterExtend, afterBet);
+
+    console.log("✅ Snapshot digest tracks market state");
+  });
+
+  it("Resolves a 4-outcome market", async () => {
+    const question = "Which of 4 candidates wins?";
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 2);
+    const { marketPda, vaultPda } = await createMarket(
+      question,
+      expiryTimestamp,
+      { numOutcomes: 4 }
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    try {
+      await program.methods
+        .resolveMarket(4)
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+      assert.fail("Out-of-range outcome was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidResult");
+    }
+
+    await program.methods
+      .resolveMarket(2)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    const marketAccount = await program.account.market.fetch(marketPda);
+    assert.equal(marketAccount.numOutcomes, 4);
+    assert.equal(marketAccount.resolved, true);
+    assert.deepEqual(marketAccount.result, { outcome: { 0: 2 } });
+
+    // Claims use the same MXE-signed payout path as binary markets; right
+    // after resolution they are held back by the dispute window
+    try {
+      await program.methods
+        .claimWithProof(new BN(1 * 1e6), new BN(1), proofExpiry(), [Array(64).fill(0)])
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: findPositionPda(marketPda, userWallet.publicKey),
+          vault: vaultPda,
+          solVault: null,
+          userTokenAccount,
+          usdcMint,
+          insuranceFund: null,
+          insuranceVault: null,
+          userStats: findUserStatsPda(userWallet.publicKey),
+          user: userWallet.publicKey,
+          ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([userWallet])
+        .rpc();
+      assert.fail("Claim inside the dispute window was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "DisputeWindowActive");
+    }
+
+    console.log("✅ Multi-outcome market resolved to outcome 2");
+  });
+
+  it("Accepts relayed claims without a user signature", async () => {
+    const question = "Will relayers settle claims?";
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 2);
+    const { marketPda, vaultPda } = await createMarket(
+      question,
+      expiryTimestamp
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    // The provider wallet relays: only it signs the transaction, and the
+    // request reaches the claim checks rather than failing on signers
+    try {
+      await program.methods
+        .claimWithProofRelayed(
+          new BN(1 * 1e6),
+          new BN(0),
+          new BN(1),
+          proofExpiry(),
+          [Array(64).fill(0)]
+        )
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: findPositionPda(marketPda, userWallet.publicKey),
+          userStats: findUserStatsPda(userWallet.publicKey),
+          vault: vaultPda,
+          solVault: null,
+          userTokenAccount,
+          usdcMint,
+          user: userWallet.publicKey,
+          relayer: authority.publicKey,
+          relayerTokenAccount: null,
+          ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Relayed claim inside the dispute window was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "DisputeWindowActive");
+    }
+
+    // Payouts are pinned to the user's own token account
+    try {
+      await program.methods
+        .claimWithProofRelayed(
+          new BN(1 * 1e6),
+          new BN(0),
+          new BN(1),
+          proofExpiry(),
+          [Array(64).fill(0)]
+        )
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: findPositionPda(marketPda, userWallet.publicKey),
+          userStats: findUserStatsPda(userWallet.publicKey),
+          vault: vaultPda,
+          solVault: null,
+          userTokenAccount: authorityTokenAccount,
+          usdcMint,
+          user: userWallet.publicKey,
+          relayer: authority.publicKey,
+          relayerTokenAccount: null,
+          ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Relayer redirected the payout");
+    } catch (error) {
+      assert.include(error.toString(), "ConstraintRaw");
+    }
+
+    console.log("✅ Relayed claim flow enforced");
+  });
+
+  it("Skims the protocol fee into the fee vault", async () => {
+    const question = "Protocol fee market";
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 86400);
+    const { marketPda, vaultPda } = await createMarket(
+      question,
+      expiryTimestamp
+    );
+    const feeVaultPda = findFeeVaultPda(marketPda);
+
+    await program.methods
+      .setFeeBps(250)
+      .accounts({ config: configPda, admin: authority.publicKey })
+      .rpc();
+
+    try {
+      // 2.5% of 1_000_001 rounds down to 25_000
+      await placeBet(marketPda, vaultPda, new BN(1_000_001));
+
+      let market = await program.account.market.fetch(marketPda);
+      assert.equal(market.totalPool.toNumber(), 975_001);
+      assert.equal(market.feesCollected.toNumber(), 25_000);
+      let feeVault = await provider.connection.getTokenAccountBalance(feeVaultPda);
+      assert.equal(feeVault.value.amount, "25000");
+
+      await program.methods
+        .setFeeBps(0)
+        .accounts({ config: configPda, admin: authority.publicKey })
+        .rpc();
+      await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+      market = await program.account.market.fetch(marketPda);
+      assert.equal(market.totalPool.toNumber(), 1_975_001);
+      assert.equal(market.feesCollected.toNumber(), 25_000);
+      const position = await program.account.userPosition.fetch(
+        findPositionPda(marketPda, userWallet.publicKey)
+      );
+      assert.equal(position.amount.toNumber(), 1_975_001);
+      feeVault = await provider.connection.getTokenAccountBalance(feeVaultPda);
+      assert.equal(feeVault.value.amount, "25000");
+    } finally {
+      await program.methods
+        .setFeeBps(0)
+        .accounts({ config: configPda, admin: authority.publicKey })
+        .rpc();
+    }
+
+    console.log("✅ Protocol fee collected");
+  });
+
+  it("Pays the creator fee once at resolution", async () => {
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 2);
+
+    try {
+      await createMarket("Creator fee too high", expiryTimestamp, {
+        creatorFeeBps: 501,
+      });
+      assert.fail("Creator fee above the cap was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "FeeTooHigh");
+    }
+
+    const { marketPda, vaultPda } = await createMarket(
+      "Creator fee market",
+      expiryTimestamp,
+      { creatorFeeBps: 500 }
+    );
+    await placeBet(marketPda, vaultPda, new BN(2 * 1e6));
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    const resolveAccounts = {
+      market: marketPda,
+      vault: vaultPda,
+      solVault: null,
+      creatorFeeAccount: authorityTokenAccount,
+      usdcMint,
+      authority: authority.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    };
+    const creatorBefore = await provider.connection.getTokenAccountBalance(
+      authorityTokenAccount
+    );
+
+    await program.methods.resolveMarket(0).accounts(resolveAccounts).rpc();
+
+    const market = await program.account.market.fetch(marketPda);
+    assert.equal(market.creatorFeeCollected.toNumber(), 100_000);
+    assert.equal(market.totalPool.toNumber(), 1_900_000);
+    const vault = await provider.connection.getTokenAccountBalance(vaultPda);
+    assert.equal(vault.value.amount, "1900000");
+
+    try {
+      await program.methods.resolveMarket(1).accounts(resolveAccounts).rpc();
+      assert.fail("Market was resolved twice");
+    } catch (error) {
+      assert.include(error.toString(), "AlreadyResolved");
+    }
+
+    const creatorAfter = await provider.connection.getTokenAccountBalance(
+      authorityTokenAccount
+    );
+    assert.equal(
+      Number(creatorAfter.value.amount) - Number(creatorBefore.value.amount),
+      100_000
+    );
+
+    console.log("✅ Creator fee charged once");
+  });
+
+  it("Requires the minimum resolver stake to resolve", async () => {
+    const [resolverStakePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("resolver_stake"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+    const { marketPda } = await createMarket(
+      "Resolver stake market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    const stake = (lamports: number) =>
+      program.methods
+        .stakeResolver(new BN(lamports))
+        .accounts({
+          resolverStake: resolverStakePda,
+          resolver: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    const setMinStake = (lamports: number) =>
+      program.methods
+        .setMinResolverStake(new BN(lamports))
+        .accounts({ config: configPda, admin: authority.publicKey })
+        .rpc();
+
+    await setMinStake(0.5 * anchor.web3.LAMPORTS_PER_SOL);
+    try {
+      await stake(0.2 * anchor.web3.LAMPORTS_PER_SOL);
+
+      // Wait for expiry
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+
+      const resolveAccounts = {
+        market: marketPda,
+        config: configPda,
+        resolverStake: resolverStakePda,
+        authority: authority.publicKey,
+      };
+      try {
+        await program.methods.resolveMarket(0).accounts(resolveAccounts).rpc();
+        assert.fail("Under-staked resolver resolved the market");
+      } catch (error) {
+        assert.include(error.toString(), "InsufficientResolverStake");
+      }
+
+      await stake(0.3 * anchor.web3.LAMPORTS_PER_SOL);
+      await program.methods.resolveMarket(0).accounts(resolveAccounts).rpc();
+
+      const market = await program.account.market.fetch(marketPda);
+      assert.equal(market.resolved, true);
+
+      // Stake backing a fresh resolution cannot be pulled
+      try {
+        await program.methods
+          .unstakeResolver(new BN(1))
+          .accounts({
+            resolverStake: resolverStakePda,
+            resolver: authority.publicKey,
+          })
+          .rpc();
+        assert.fail("Stake withdrawn inside the dispute window");
+      } catch (error) {
+        assert.include(error.toString(), "ResolverStakeLocked");
+      }
+    } finally {
+      await setMinStake(0);
+    }
+
+    console.log("✅ Resolver stake enforced");
+  });
+
+  it("Refuses to close an unsettled position", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Close position market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    const closeAccounts = {
+      market: marketPda,
+      userPosition: findPositionPda(marketPda, userWallet.publicKey),
+      user: userWallet.publicKey,
+    };
+
+    try {
+      await program.methods
+        .closeUserPosition()
+        .accounts(closeAccounts)
+        .signers([userWallet])
+        .rpc();
+      assert.fail("Position closed before resolution");
+    } catch (error) {
+      assert.include(error.toString(), "MarketNotResolved");
+    }
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .closeUserPosition()
+        .accounts(closeAccounts)
+        .signers([userWallet])
+        .rpc();
+      assert.fail("Unclaimed position was closed");
+    } catch (error) {
+      assert.include(error.toString(), "PositionNotSettled");
+    }
+
+    console.log("✅ Unsettled positions stay open");
+  });
+
+  it("Closes a market only once every position has claimed", async () => {
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 2);
+    const open = await createMarket("Close market with bets", expiryTimestamp);
+    const empty = await createMarket("Close market without bets", expiryTimestamp);
+    await placeBet(open.marketPda, open.vaultPda, new BN(1 * 1e6));
+
+    const openMarket = await program.account.market.fetch(open.marketPda);
+    assert.equal(openMarket.totalPositions, 1);
+    assert.equal(openMarket.claimedPositions, 0);
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    const closeMarket = async (marketPda: PublicKey, vaultPda: PublicKey) =>
+      program.methods
+        .closeMarket()
+        .accounts({
+          market: marketPda,
+          vault: vaultPda,
+          feeVault: findFeeVaultPda(marketPda),
+          solVault: null,
+          solFeeVault: null,
+          registry: registryPda,
+          registryPage: findRegistryPagePda(
+            (await program.account.market.fetch(marketPda)).registryPage
+          ),
+          authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    for (const { marketPda } of [open, empty]) {
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+    }
+
+    try {
+      await closeMarket(open.marketPda, open.vaultPda);
+      assert.fail("Market closed with an unclaimed position");
+    } catch (error) {
+      assert.include(error.toString(), "UnclaimedPositions");
+    }
+
+    const { registryPage } = await program.account.market.fetch(empty.marketPda);
+    const registryBefore = await program.account.marketRegistry.fetch(registryPda);
+    await closeMarket(empty.marketPda, empty.vaultPda);
+    assert.isNull(await program.account.market.fetchNullable(empty.marketPda));
+    // The freed slot counts towards the page again
+    const registryAfter = await program.account.marketRegistry.fetch(registryPda);
+    assert.equal(registryAfter.totalMarkets, registryBefore.totalMarkets - 1);
+    assert.equal(
+      registryAfter.pageLens[registryPage],
+      registryBefore.pageLens[registryPage] - 1
+    );
+    assert.isNull(await provider.connection.getAccountInfo(empty.vaultPda));
+    const registryPages = await program.account.marketRegistryPage.all();
+    for (const { account } of registryPages) {
+      const keys = account.markets.map((key) => key.toString());
+      assert.notInclude(keys, empty.marketPda.toString());
+    }
+
+    console.log("✅ Settled market closed");
+  });
+
+  it("Previews the payout message without moving funds", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Payout preview market",
+      new BN(Math.floor(Date.now() / 1000) + 3600)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    const payout = new BN(1_500_000);
+    const relayerFee = new BN(10_000);
+    const nonce = new BN(7);
+    const expiry = proofExpiry();
+
+    const before = await snapshotCommitment(marketPda);
+    const message: number[] = await program.methods
+      .previewPayout(userWallet.publicKey, payout, relayerFee, nonce, expiry)
+      .accounts({ market: marketPda })
+      .view();
+
+    // keccak256(program_id || market || user || payout_le || relayer_fee_le
+    //           || nonce_le || expiry_le)
+    const expected = keccak_256(
+      Buffer.concat([
+        program.programId.toBuffer(),
+        marketPda.toBuffer(),
+        userWallet.publicKey.toBuffer(),
+        payout.toArrayLike(Buffer, "le", 8),
+        relayerFee.toArrayLike(Buffer, "le", 8),
+        nonce.toArrayLike(Buffer, "le", 8),
+        expiry.toArrayLike(Buffer, "le", 8),
+      ])
+    );
+    assert.equal(
+      Buffer.from(message).toString("hex"),
+      Buffer.from(expected).toString("hex")
+    );
+    assert.equal(await snapshotCommitment(marketPda), before);
+
+    console.log("✅ Payout preview matches construct_payout_message");
+  });
+
+  it("Batch close rejects unsettled or malformed batches", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Batch close market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    const positionPda = findPositionPda(marketPda, userWallet.publicKey);
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    const batchClose = (remaining: anchor.web3.AccountMeta[]) =>
+      program.methods
+        .batchClosePositions()
+        .accounts({ market: marketPda })
+        .remainingAccounts(remaining)
+        .rpc();
+    const position = { pubkey: positionPda, isSigner: false, isWritable: true };
+    const owner = {
+      pubkey: userWallet.publicKey,
+      isSigner: false,
+      isWritable: true,
+    };
+
+    try {
+      await batchClose([position]);
+      assert.fail("Unpaired batch was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidBatchAccounts");
+    }
+
+    try {
+      await batchClose([position, { ...owner, pubkey: authority.publicKey }]);
+      assert.fail("Rent redirected to a non-owner");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidBatchAccounts");
+    }
+
+    try {
+      await batchClose([position, owner]);
+      assert.fail("Unclaimed position was closed");
+    } catch (error) {
+      assert.include(error.toString(), "PositionNotSettled");
+    }
+    assert.isNotNull(await provider.connection.getAccountInfo(positionPda));
+
+    console.log("✅ Batch close guards enforced");
+  });
+
+  it("Stores outcome labels for multi-outcome markets", async () => {
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 3600);
+
+    try {
+      await createMarket("Mismatched labels market", expiryTimestamp, {
+        numOutcomes: 3,
+        outcomeLabels: ["Team A", "Team B"],
+      });
+      assert.fail("Label count mismatch was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "OutcomeLabelCountMismatch");
+    }
+
+    const labels = ["Team A", "Team B", "Draw"];
+    await createMarket("Labelled market", expiryTimestamp, {
+      numOutcomes: 3,
+      outcomeLabels: labels,
+    });
+    const { metadataPda } = findMarketPdas("Labelled market");
+    const metadata = await program.account.marketMetadata.fetch(metadataPda);
+    assert.deepEqual(metadata.outcomeLabels, labels);
+
+    console.log("✅ Outcome labels validated and stored");
+  });
+
+  it("Time-locks MXE key rotation", async () => {
+    const rotationAccounts = { config: configPda, admin: authority.publicKey };
+
+    try {
+      await program.methods.finalizeMxeRotation().accounts(rotationAccounts).rpc();
+      assert.fail("Finalized without a pending rotation");
+    } catch (error) {
+      assert.include(error.toString(), "NoPendingRotation");
+    }
+
+    const newKey = anchor.web3.Keypair.generate().publicKey.toBytes();
+    await program.methods
+      .proposeMxeRotation([Array.from(newKey)], 1)
+      .accounts(rotationAccounts)
+      .rpc();
+
+    const config = await program.account.config.fetch(configPda);
+    assert.deepEqual(config.pendingMxePubkeys, [Array.from(newKey)]);
+    assert.deepEqual(config.mxePubkeys, [
+      Array.from(mxeKeypair.publicKey.toBytes()),
+    ]);
+    assert.isAtLeast(
+      config.rotationUnlockTime.toNumber(),
+      Math.floor(Date.now() / 1000) + MXE_ROTATION_DELAY_SECS - 60
+    );
+
+    try {
+      await program.methods.finalizeMxeRotation().accounts(rotationAccounts).rpc();
+      assert.fail("Rotation finalized inside the time-lock");
+    } catch (error) {
+      assert.include(error.toString(), "RotationTimeLockActive");
+    }
+
+    console.log("✅ MXE rotation time-lock enforced");
+  });
+
+  // Ed25519 verify instruction over `message`, plus the signature the
+  // program expects as an argument
+  const signMessage = (signer: anchor.web3.Keypair, message: Buffer) => {
+    const ix = anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: signer.secretKey,
+      message,
+    });
+    // web3.js layout: 16-byte header, 32-byte pubkey, then the signature
+    const signature = Array.from(ix.data.subarray(48, 112));
+    return { ix, signature };
+  };
+
+  const payoutMessage = (
+    marketPda: PublicKey,
+    user: PublicKey,
+    payout: BN,
+    nonce: BN,
+    expiry: BN,
+    programId: PublicKey = program.programId,
+    relayerFee: BN = new BN(0)
+  ) =>
+    Buffer.from(
+      keccak_256(
+        Buffer.concat([
+          programId.toBuffer(),
+          marketPda.toBuffer(),
+          user.toBuffer(),
+          payout.toArrayLike(Buffer, "le", 8),
+          relayerFee.toArrayLike(Buffer, "le", 8),
+          nonce.toArrayLike(Buffer, "le", 8),
+          expiry.toArrayLike(Buffer, "le", 8),
+        ])
+      )
+    );
+
+  const signPayout = (
+    signer: anchor.web3.Keypair,
+    marketPda: PublicKey,
+    user: PublicKey,
+    payout: BN,
+    nonce: BN,
+    expiry: BN,
+    programId: PublicKey = program.programId,
+    relayerFee: BN = new BN(0)
+  ) =>
+    signMessage(
+      signer,
+      payoutMessage(marketPda, user, payout, nonce, expiry, programId, relayerFee)
+    );
+
+  // Secp256k1 instruction signing `message` with `privateKey`
+  const signSecp256k1 = (privateKey: Buffer, message: Buffer) => {
+    const ix = anchor.web3.Secp256k1Program.createInstructionWithPrivateKey({
+      privateKey,
+      message,
+    });
+    // web3.js layout: 12-byte header, 20-byte eth address, then r || s
+    return {
+      ix,
+      ethAddress: Array.from(ix.data.subarray(12, 32)),
+      signature: Array.from(ix.data.subarray(32, 96)),
+    };
+  };
+
+  // Move the MXE to `scheme` through the time-locked proposal
+  const switchScheme = async (scheme: object, ethAddress: number[]) => {
+    const accounts = { config: configPda, admin: authority.publicKey };
+    await program.methods
+      .proposeSignatureScheme(scheme as any, ethAddress)
+      .accounts(accounts)
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (MXE_ROTATION_DELAY_SECS + 1) * 1000)
+    );
+    await program.methods.finalizeSignatureScheme().accounts(accounts).rpc();
+  };
+
+  const rotateMxeKeys = async (
+    next: anchor.web3.Keypair[],
+    requiredSignatures = 1
+  ) => {
+    const rotationAccounts = { config: configPda, admin: authority.publicKey };
+    await program.methods
+      .proposeMxeRotation(
+        next.map((kp) => Array.from(kp.publicKey.toBytes())),
+        requiredSignatures
+      )
+      .accounts(rotationAccounts)
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (MXE_ROTATION_DELAY_SECS + 1) * 1000)
+    );
+    await program.methods.finalizeMxeRotation().accounts(rotationAccounts).rpc();
+  };
+
+  const waitOutRotationGrace = () =>
+    new Promise((resolve) =>
+      setTimeout(resolve, (MXE_ROTATION_GRACE_SECS + 1) * 1000)
+    );
+
+  // claim_with_proof for userWallet with one Ed25519 proof per signer
+  const buildClaim = (
+    marketPda: PublicKey,
+    vaultPda: PublicKey,
+    by: anchor.web3.Keypair[],
+    payout: BN,
+    nonce: BN,
+    expiry = proofExpiry(),
+    tokenProgram: PublicKey = TOKEN_PROGRAM_ID
+  ) => {
+    const proofs = by.map((signer) =>
+      signPayout(signer, marketPda, userWallet.publicKey, payout, nonce, expiry)
+    );
+    return program.methods
+      .claimWithProof(
+        payout,
+        nonce,
+        expiry,
+        proofs.map((p) => p.signature)
+      )
+      .accounts({
+        market: marketPda,
+        config: configPda,
+        userPosition: findPositionPda(marketPda, userWallet.publicKey),
+        vault: vaultPda,
+        solVault: null,
+        userTokenAccount,
+        usdcMint,
+        insuranceFund: null,
+        insuranceVault: null,
+        userStats: findUserStatsPda(userWallet.publicKey),
+        user: userWallet.publicKey,
+        ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram,
+        systemProgram: SystemProgram.programId,
+      })
+      .preInstructions(proofs.map((p) => p.ix))
+      .signers([userWallet]);
+  };
+
+  const claimWithSigners = (
+    marketPda: PublicKey,
+    vaultPda: PublicKey,
+    by: anchor.web3.Keypair[],
+    payout: BN,
+    nonce: BN,
+    expiry = proofExpiry()
+  ) => buildClaim(marketPda, vaultPda, by, payout, nonce, expiry).rpc();
+
+  itWithShortTimelocks("Verifies claims against the rotated MXE key", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "MXE rotation claim market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    const newMxe = anchor.web3.Keypair.generate();
+    await rotateMxeKeys([newMxe]);
+    // Rotation waited past the dispute window as well
+    assert.isAtMost(DISPUTE_WINDOW_SECS, MXE_ROTATION_DELAY_SECS);
+
+    const payout = new BN(1 * 1e6);
+    const nonce = new BN(1);
+    const positionPda = findPositionPda(marketPda, userWallet.publicKey);
+    const claim = (signer: anchor.web3.Keypair) =>
+      claimWithSigners(marketPda, vaultPda, [signer], payout, nonce);
+
+    // Past the grace window only the new key is accepted
+    await waitOutRotationGrace();
+
+    try {
+      try {
+        await claim(mxeKeypair);
+        assert.fail("Proof from the retired MXE key was accepted");
+      } catch (error) {
+        assert.include(error.toString(), "UnknownMXESigner");
+      }
+
+      await claim(newMxe);
+      const position = await program.account.userPosition.fetch(positionPda);
+      assert.equal(position.claimed, true);
+    } finally {
+      await rotateMxeKeys([mxeKeypair]);
+    }
+
+    console.log("✅ Claims follow the rotated MXE key");
+  });
+
+  it("Resolves a committed market only with a matching reveal", async () => {
+    const salt = Array.from(anchor.web3.Keypair.generate().publicKey.toBytes());
+    const commitment = Array.from(
+      keccak_256(Buffer.concat([Buffer.from([1]), Buffer.from(salt)]))
+    );
+    const { marketPda } = await createMarket(
+      "Commit-reveal market",
+      new BN(Math.floor(Date.now() / 1000) + 2),
+      { resolutionCommitment: commitment }
+    );
+    const resolveAccounts = { market: marketPda, authority: authority.publicKey };
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    try {
+      await program.methods.resolveMarket(0).accounts(resolveAccounts).rpc();
+      assert.fail("Committed market resolved without a reveal");
+    } catch (error) {
+      assert.include(error.toString(), "ResolutionCommitmentRequired");
+    }
+
+    try {
+      await program.methods
+        .resolveWithReveal(0, salt)
+        .accounts(resolveAccounts)
+        .rpc();
+      assert.fail("Reveal of a different outcome was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "RevealMismatch");
+    }
+
+    await program.methods
+      .resolveWithReveal(1, salt)
+      .accounts(resolveAccounts)
+      .rpc();
+
+    const market = await program.account.market.fetch(marketPda);
+    assert.equal(market.resolved, true);
+    assert.deepEqual(market.result, { no: {} });
+    assert.isNull(market.resolutionCommitment);
+
+    console.log("✅ Commit-reveal resolution verified");
+  });
+
+  itWithShortTimelocks("Requires an m-of-n MXE signature quorum", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "MXE quorum claim market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    const signers = [0, 1, 2].map(() => anchor.web3.Keypair.generate());
+    await rotateMxeKeys(signers, 2);
+
+    const payout = new BN(1 * 1e6);
+    const nonce = new BN(1);
+    const positionPda = findPositionPda(marketPda, userWallet.publicKey);
+    const claim = (by: anchor.web3.Keypair[]) =>
+      claimWithSigners(marketPda, vaultPda, by, payout, nonce);
+
+    try {
+      for (const [by, expected] of [
+        [[signers[0]], "InsufficientMXESignatures"],
+        [[signers[1], signers[1]], "DuplicateMXESigner"],
+        [[signers[0], mxeKeypair], "UnknownMXESigner"],
+      ] as [anchor.web3.Keypair[], string][]) {
+        try {
+          await claim(by);
+          assert.fail(`Claim accepted without a valid quorum (${expected})`);
+        } catch (error) {
+          assert.include(error.toString(), expected);
+        }
+      }
+
+      await claim([signers[2], signers[0]]);
+      const position = await program.account.userPosition.fetch(positionPda);
+      assert.equal(position.claimed, true);
+    } finally {
+      await rotateMxeKeys([mxeKeypair]);
+    }
+
+    console.log("✅ MXE quorum enforced");
+  });
+
+  itWithShortTimelocks("Accepts the previous MXE key until the grace deadline", async () => {
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 2);
+    const early = await createMarket("MXE grace early claim", expiryTimestamp);
+    const late = await createMarket("MXE grace late claim", expiryTimestamp);
+    for (const { marketPda, vaultPda } of [early, late]) {
+      await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    }
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    for (const { marketPda } of [early, late]) {
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+    }
+
+    await rotateMxeKeys([anchor.web3.Keypair.generate()]);
+    const config = await program.account.config.fetch(configPda);
+    assert.deepEqual(config.previousMxePubkeys, [
+      Array.from(mxeKeypair.publicKey.toBytes()),
+    ]);
+
+    const payout = new BN(1 * 1e6);
+    const nonce = new BN(1);
+    try {
+      await claimWithSigners(
+        early.marketPda,
+        early.vaultPda,
+        [mxeKeypair],
+        payout,
+        nonce
+      );
+      const position = await program.account.userPosition.fetch(
+        findPositionPda(early.marketPda, userWallet.publicKey)
+      );
+      assert.equal(position.claimed, true);
+
+      await waitOutRotationGrace();
+      try {
+        await claimWithSigners(
+          late.marketPda,
+          late.vaultPda,
+          [mxeKeypair],
+          payout,
+          nonce
+        );
+        assert.fail("Previous MXE key accepted after the grace deadline");
+      } catch (error) {
+        assert.include(error.toString(), "UnknownMXESigner");
+      }
+    } finally {
+      await rotateMxeKeys([mxeKeypair]);
+    }
+
+    console.log("✅ MXE rotation grace window enforced");
+  });
+
+  const readPnl = (marketPda: PublicKey): Promise<BN> =>
+    program.methods
+      .readUserPnl()
+      .accounts({
+        market: marketPda,
+        userPosition: findPositionPda(marketPda, userWallet.publicKey),
+      })
+      .view();
+
+  it("Refuses P&L for an unclaimed position", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Unclaimed P&L market",
+      new BN(Math.floor(Date.now() / 1000) + 3600)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    try {
+      await readPnl(marketPda);
+      assert.fail("P&L reported before claiming");
+    } catch (error) {
+      assert.include(error.toString(), "PositionNotSettled");
+    }
+  });
+
+  itWithShortTimelocks("Reports realized P&L for winners and losers", async () => {
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 2);
+    const winning = await createMarket("Winning P&L market", expiryTimestamp);
+    const losing = await createMarket("Losing P&L market", expiryTimestamp);
+    for (const { marketPda, vaultPda } of [winning, losing]) {
+      await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+      // Counterparty stake so the winner can be paid more than they put in
+      await placeBet(
+        marketPda,
+        vaultPda,
+        new BN(1 * 1e6),
+        authority.payer,
+        authorityTokenAccount
+      );
+    }
+
+    // Wait for expiry, then out the dispute window
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    for (const { marketPda } of [winning, losing]) {
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+    }
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    await claimWithSigners(
+      winning.marketPda,
+      winning.vaultPda,
+      [mxeKeypair],
+      new BN(2 * 1e6),
+      new BN(1)
+    );
+    await claimWithSigners(
+      losing.marketPda,
+      losing.vaultPda,
+      [mxeKeypair],
+      new BN(0),
+      new BN(1)
+    );
+
+    assert.equal((await readPnl(winning.marketPda)).toNumber(), 1 * 1e6);
+    assert.equal((await readPnl(losing.marketPda)).toNumber(), -1 * 1e6);
+
+    console.log("✅ Realized P&L reported");
+  });
+
+  itWithShortTimelocks("Rejects payout proofs past their expiry", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Proof expiry market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    // Wait for expiry, then out the dispute window
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const payout = new BN(1 * 1e6);
+    const nonce = new BN(1);
+    try {
+      await claimWithSigners(
+        marketPda,
+        vaultPda,
+        [mxeKeypair],
+        payout,
+        nonce,
+        proofExpiry(-60)
+      );
+      assert.fail("Expired proof was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "ProofExpired");
+    }
+
+    await claimWithSigners(
+      marketPda,
+      vaultPda,
+      [mxeKeypair],
+      payout,
+      nonce,
+      proofExpiry(60)
+    );
+    const position = await program.account.userPosition.fetch(
+      findPositionPda(marketPda, userWallet.publicKey)
+    );
+    assert.equal(position.claimed, true);
+
+    console.log("✅ Payout proofs expire");
+  });
+
+  // Batch payout tree over keccak256(user || payout || nonce) leaves, with
+  // pairs hashed in ascending order as verify_merkle_proof expects
+  const batchLeaf = (user: PublicKey, payout: BN, nonce: BN) =>
+    Buffer.from(
+      keccak_256(
+        Buffer.concat([
+          user.toBuffer(),
+          payout.toArrayLike(Buffer, "le", 8),
+          nonce.toArrayLike(Buffer, "le", 8),
+        ])
+      )
+    );
+
+  const hashPair = (a: Buffer, b: Buffer) =>
+    Buffer.from(
+      keccak_256(
+        Buffer.compare(a, b) <= 0 ? Buffer.concat([a, b]) : Buffer.concat([b, a])
+      )
+    );
+
+  const signBatchRoot = (
+    signer: anchor.web3.Keypair,
+    marketPda: PublicKey,
+    root: Buffer
+  ) =>
+    signMessage(
+      signer,
+      Buffer.from(
+        keccak_256(
+          Buffer.concat([
+            Buffer.from("nexora:batch_root:v1"),
+            marketPda.toBuffer(),
+            root,
+          ])
+        )
+      )
+    );
+
+  const createBatchPayout = (
+    marketPda: PublicKey,
+    root: Buffer,
+    signer: anchor.web3.Keypair = mxeKeypair
+  ) => {
+    const { ix, signature } = signBatchRoot(signer, marketPda, root);
+    return program.methods
+      .createBatchPayout(Array.from(root), [signature])
+      .accounts({
+        market: marketPda,
+        config: configPda,
+        payer: authority.publicKey,
+        ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .preInstructions([ix])
+      .rpc();
+  };
+
+  it("Rejects a batch payout root not signed by the MXE", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Forged batch root market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    const root = batchLeaf(userWallet.publicKey, new BN(1 * 1e6), new BN(1));
+    try {
+      await createBatchPayout(marketPda, root, anchor.web3.Keypair.generate());
+      assert.fail("Batch root from an unknown key was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "UnknownMXESigner");
+    }
+  });
+
+  itWithShortTimelocks("Claims payouts from a Merkle batch root", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Batch payout market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    await placeBet(
+      marketPda,
+      vaultPda,
+      new BN(1 * 1e6),
+      authority.payer,
+      authorityTokenAccount
+    );
+
+    // Wait for expiry, then out the dispute window
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const payout = new BN(2 * 1e6);
+    const nonce = new BN(1);
+    const userLeaf = batchLeaf(userWallet.publicKey, payout, nonce);
+    const otherLeaf = batchLeaf(authority.publicKey, new BN(0), new BN(2));
+    await createBatchPayout(marketPda, hashPair(userLeaf, otherLeaf));
+
+    const claimFromBatch = (amount: BN) =>
+      program.methods
+        .claimFromBatch(amount, nonce, [Array.from(otherLeaf)])
+        .accounts({
+          market: marketPda,
+          userPosition: findPositionPda(marketPda, userWallet.publicKey),
+          userStats: findUserStatsPda(userWallet.publicKey),
+          vault: vaultPda,
+          solVault: null,
+          userTokenAccount,
+          usdcMint,
+          user: userWallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([userWallet])
+        .rpc();
+
+    try {
+      await claimFromBatch(payout.add(new BN(1)));
+      assert.fail("Payout outside the batch root was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "MerkleProofInvalid");
+    }
+
+    const balanceBefore = await provider.connection.getTokenAccountBalance(
+      userTokenAccount
+    );
+    const statsBefore = await program.account.userStats.fetch(
+      findUserStatsPda(userWallet.publicKey)
+    );
+    await claimFromBatch(payout);
+    const statsAfter = await program.account.userStats.fetch(
+      findUserStatsPda(userWallet.publicKey)
+    );
+    assert.equal(statsAfter.totalClaimed.sub(statsBefore.totalClaimed).toNumber(), payout.toNumber());
+    const balanceAfter = await provider.connection.getTokenAccountBalance(
+      userTokenAccount
+    );
+    assert.equal(
+      parseInt(balanceAfter.value.amount) - parseInt(balanceBefore.value.amount),
+      payout.toNumber()
+    );
+
+    const position = await program.account.userPosition.fetch(
+      findPositionPda(marketPda, userWallet.publicKey)
+    );
+    assert.equal(position.claimed, true);
+
+    console.log("✅ Batch payout claimed with a Merkle proof");
+  });
+
+  it("Blocks bets until a deferred vault is initialized", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Deferred vault market",
+      new BN(Math.floor(Date.now() / 1000) + 3600),
+      { deferVault: true }
+    );
+    let marketAccount = await program.account.market.fetch(marketPda);
+    assert.equal(marketAccount.vaultInitialized, false);
+
+    // The vault accounts do not exist yet, so none can be passed
+    try {
+      await program.methods
+        .placeBet(Array.from(Buffer.from("payload")), new BN(1 * 1e6), null)
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: findPositionPda(marketPda, userWallet.publicKey),
+          vault: null,
+          solVault: null,
+          feeVault: null,
+          solFeeVault: null,
+          userLimits: null,
+          whitelist: null,
+          userTokenAccount,
+          usdcMint,
+          insuranceVault: null,
+          userStats: findUserStatsPda(userWallet.publicKey),
+          user: userWallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([userWallet])
+        .rpc();
+      assert.fail("Bet accepted before the vault existed");
+    } catch (error) {
+      assert.include(error.toString(), "VaultNotInitialized");
+    }
+
+    await initVault(marketPda, vaultPda);
+    marketAccount = await program.account.market.fetch(marketPda);
+    assert.equal(marketAccount.vaultInitialized, true);
+    assert.equal(marketAccount.vault.toString(), vaultPda.toString());
+
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    const position = await program.account.userPosition.fetch(
+      findPositionPda(marketPda, userWallet.publicKey)
+    );
+    assert.equal(position.amount.toNumber(), 1 * 1e6);
+
+    console.log("✅ Deferred vault gates betting");
+  });
+
+  itWithShortTimelocks("Rejects payout proofs signed for another program ID", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Foreign program proof market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    // Wait for expiry, then out the dispute window
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const payout = new BN(1 * 1e6);
+    const nonce = new BN(1);
+    const expiry = proofExpiry();
+    const { ix, signature } = signPayout(
+      mxeKeypair,
+      marketPda,
+      userWallet.publicKey,
+      payout,
+      nonce,
+      expiry,
+      anchor.web3.Keypair.generate().publicKey
+    );
+
+    try {
+      await program.methods
+        .claimWithProof(payout, nonce, expiry, [signature])
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: findPositionPda(marketPda, userWallet.publicKey),
+          vault: vaultPda,
+          solVault: null,
+          userTokenAccount,
+          usdcMint,
+          insuranceFund: null,
+          insuranceVault: null,
+          userStats: findUserStatsPda(userWallet.publicKey),
+          user: userWallet.publicKey,
+          ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ix])
+        .signers([userWallet])
+        .rpc();
+      assert.fail("Proof for another deployment was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "MessageMismatch");
+    }
+
+    console.log("✅ Payout proofs are bound to the program ID");
+  });
+
+  // record_pool_split signed by the test MXE key
+  const recordPoolSplit = (marketPda: PublicKey, yesPool: BN, noPool: BN) => {
+    const { ix, signature } = signMessage(
+      mxeKeypair,
+      Buffer.from(
+        keccak_256(
+          Buffer.concat([
+            marketPda.toBuffer(),
+            yesPool.toArrayLike(Buffer, "le", 8),
+            noPool.toArrayLike(Buffer, "le", 8),
+          ])
+        )
+      )
+    );
+    return program.methods
+      .recordPoolSplit(yesPool, noPool, [signature])
+      .accounts({
+        market: marketPda,
+        config: configPda,
+        ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      })
+      .preInstructions([ix])
+      .rpc();
+  };
+
+  it("Records the MXE-attested pool split once", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Pool split market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    await recordPoolSplit(marketPda, new BN(1 * 1e6), new BN(0));
+    const marketAccount = await program.account.market.fetch(marketPda);
+    assert.equal(marketAccount.yesPool.toNumber(), 1 * 1e6);
+    assert.equal(marketAccount.noPool.toNumber(), 0);
+
+    try {
+      await recordPoolSplit(marketPda, new BN(0), new BN(1 * 1e6));
+      assert.fail("Pool split was overwritten");
+    } catch (error) {
+      assert.include(error.toString(), "PoolSplitAlreadyRecorded");
+    }
+
+    // An empty split counts as recorded too
+    const empty = await createMarket(
+      "Empty pool split market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: empty.marketPda, authority: authority.publicKey })
+      .rpc();
+    await recordPoolSplit(empty.marketPda, new BN(0), new BN(0));
+    try {
+      await recordPoolSplit(empty.marketPda, new BN(1 * 1e6), new BN(0));
+      assert.fail("Empty pool split was overwritten");
+    } catch (error) {
+      assert.include(error.toString(), "PoolSplitAlreadyRecorded");
+    }
+
+    console.log("✅ Pool split recorded");
+  });
+
+  it("Tracks protocol-wide stats", async () => {
+    const before = await program.account.protocolStats.fetch(statsPda);
+
+    const { marketPda, vaultPda } = await createMarket(
+      "Protocol stats market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(3 * 1e6));
+
+    let stats = await program.account.protocolStats.fetch(statsPda);
+    assert.equal(stats.totalMarkets, before.totalMarkets + 1);
+    assert.equal(stats.activeMarkets, before.activeMarkets + 1);
+    assert.equal(stats.totalBets.toNumber(), before.totalBets.toNumber() + 1);
+    assert.equal(
+      stats.totalVolume.toNumber(),
+      before.totalVolume.toNumber() + 3 * 1e6
+    );
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    stats = await program.account.protocolStats.fetch(statsPda);
+    assert.equal(stats.activeMarkets, before.activeMarkets);
+
+    console.log("✅ Protocol stats updated");
+  });
+
+  itWithShortTimelocks("Audits claimed payouts against the pool split", async () => {
+    const expiryTimestamp = new BN(Math.floor(Date.now() / 1000) + 2);
+    const honest = await createMarket("Honest payout audit market", expiryTimestamp);
+    const skewed = await createMarket("Skewed payout audit market", expiryTimestamp);
+    for (const { marketPda, vaultPda } of [honest, skewed]) {
+      await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+      await placeBet(
+        marketPda,
+        vaultPda,
+        new BN(1 * 1e6),
+        authority.payer,
+        authorityTokenAccount
+      );
+    }
+
+    // Wait for expiry, then out the dispute window
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    for (const { marketPda } of [honest, skewed]) {
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+      await recordPoolSplit(marketPda, new BN(1 * 1e6), new BN(1 * 1e6));
+    }
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    // Pro-rata share is 1 * 2 / 1 = 2 USDC; the skewed MXE pays 1.5
+    await claimWithSigners(
+      honest.marketPda,
+      honest.vaultPda,
+      [mxeKeypair],
+      new BN(2 * 1e6),
+      new BN(1)
+    );
+    await claimWithSigners(
+      skewed.marketPda,
+      skewed.vaultPda,
+      [mxeKeypair],
+      new BN(1_500_000),
+      new BN(1)
+    );
+
+    const audit = (marketPda: PublicKey): Promise<boolean> =>
+      program.methods
+        .verifyClaimCorrectness()
+        .accounts({
+          market: marketPda,
+          userPosition: findPositionPda(marketPda, userWallet.publicKey),
+        })
+        .view();
+
+    assert.equal(await audit(honest.marketPda), true);
+    assert.equal(await audit(skewed.marketPda), false);
+
+    console.log("✅ Claimed payouts audited");
+  });
+
+  itWithShortTimelocks("Claims with a zero nonce exactly once", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Zero nonce market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    // Wait for expiry, then out the dispute window
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const payout = new BN(1 * 1e6);
+    const nonce = new BN(0);
+    await claimWithSigners(marketPda, vaultPda, [mxeKeypair], payout, nonce);
+    const position = await program.account.userPosition.fetch(
+      findPositionPda(marketPda, userWallet.publicKey)
+    );
+    assert.equal(position.claimed, true);
+    assert.equal(position.nonceUsed.toNumber(), 0);
+
+    try {
+      await claimWithSigners(marketPda, vaultPda, [mxeKeypair], payout, nonce);
+      assert.fail("Zero-nonce proof was replayed");
+    } catch (error) {
+      assert.include(error.toString(), "AlreadyClaimed");
+    }
+
+    console.log("✅ Zero nonce claimed once");
+  });
+
+  it("Rolls a settled market's residual into its successor", async () => {
+    const settled = await createMarket(
+      "Weekly market (week 1)",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    const next = await createMarket(
+      "Weekly market (week 2)",
+      new BN(Math.floor(Date.now() / 1000) + 3600)
+    );
+    const stranger = await createMarket(
+      "Unrelated successor market",
+      new BN(Math.floor(Date.now() / 1000) + 3600)
+    );
+    await program.methods
+      .setSuccessorMarket(next.marketPda)
+      .accounts({ market: settled.marketPda, authority: authority.publicKey })
+      .rpc();
+
+    // Dust left in the vault with no positions to claim it
+    const residual = 500_000;
+    await transfer(
+      provider.connection,
+      authority.payer,
+      authorityTokenAccount,
+      settled.vaultPda,
+      authority.publicKey,
+      residual
+    );
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: settled.marketPda, authority: authority.publicKey })
+      .rpc();
+
+    const rollInto = (successor: { marketPda: PublicKey; vaultPda: PublicKey }) =>
+      program.methods
+        .rollResidual()
+        .accounts({
+          market: settled.marketPda,
+          successorMarket: successor.marketPda,
+          vault: settled.vaultPda,
+          solVault: null,
+          successorVault: successor.vaultPda,
+          successorSolVault: null,
+          usdcMint,
+          authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    try {
+      await rollInto(stranger);
+      assert.fail("Residual rolled into a market that is not the successor");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidSuccessorMarket");
+    }
+
+    await rollInto(next);
+
+    const settledVault = await provider.connection.getTokenAccountBalance(
+      settled.vaultPda
+    );
+    const nextVault = await provider.connection.getTokenAccountBalance(
+      next.vaultPda
+    );
+    assert.equal(parseInt(settledVault.value.amount), 0);
+    assert.equal(parseInt(nextVault.value.amount), residual);
+
+    const nextMarket = await program.account.market.fetch(next.marketPda);
+    assert.equal(nextMarket.seededLiquidity.toNumber(), residual);
+    assert.equal(nextMarket.totalPool.toNumber(), residual);
+
+    console.log("✅ Residual rolled into successor");
+  });
+
+  itWithShortTimelocks("Bounds claim payouts by the pool and stake", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Payout bound market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    // Wait for expiry, then out the dispute window
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    // Validly signed, but larger than the whole pool
+    try {
+      await claimWithSigners(
+        marketPda,
+        vaultPda,
+        [mxeKeypair],
+        new BN(5 * 1e6),
+        new BN(1)
+      );
+      assert.fail("Payout above the pool was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "PayoutExceedsBound");
+    }
+
+    await claimWithSigners(
+      marketPda,
+      vaultPda,
+      [mxeKeypair],
+      new BN(1 * 1e6),
+      new BN(1)
+    );
+    const position = await program.account.userPosition.fetch(
+      findPositionPda(marketPda, userWallet.publicKey)
+    );
+    assert.equal(position.payout.toNumber(), 1 * 1e6);
+
+    console.log("✅ Claim payouts bounded");
+  });
+
+  it("Charges and withdraws the market creation fee", async () => {
+    const [creationFeeVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_vault")],
+      program.programId
+    );
+    const adminAccounts = { config: configPda, admin: authority.publicKey };
+    const fee = 10_000_000; // 0.01 SOL, above the vault's rent-exempt minimum
+
+    await program.methods
+      .setMarketCreationFee(new BN(fee))
+      .accounts(adminAccounts)
+      .rpc();
+    try {
+      const before = await provider.connection.getBalance(creationFeeVault);
+      await createMarket(
+        "Market with creation fee",
+        new BN(Math.floor(Date.now() / 1000) + 3600)
+      );
+      const collected = (await provider.connection.getBalance(creationFeeVault)) - before;
+      assert.equal(collected, fee);
+
+      await program.methods
+        .withdrawCreationFees(new BN(collected + before))
+        .accounts({
+          creationFeeVault,
+          destination: authority.publicKey,
+          admin: authority.publicKey,
+        })
+        .rpc();
+      assert.equal(await provider.connection.getBalance(creationFeeVault), 0);
+    } finally {
+      await program.methods
+        .setMarketCreationFee(new BN(0))
+        .accounts(adminAccounts)
+        .rpc();
+    }
+
+    console.log("✅ Market creation fee collected and withdrawn");
+  });
+
+  it("Transfers the admin role in two steps", async () => {
+    const newAdmin = anchor.web3.Keypair.generate();
+    const stranger = anchor.web3.Keypair.generate();
+
+    try {
+      await program.methods
+        .acceptAdminTransfer()
+        .accounts({ config: configPda, newAdmin: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Accepted without a proposal");
+    } catch (error) {
+      assert.include(error.toString(), "NoPendingAdminTransfer");
+    }
+
+    await program.methods
+      .proposeAdminTransfer(newAdmin.publicKey)
+      .accounts({ config: configPda, admin: authority.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .acceptAdminTransfer()
+        .accounts({ config: configPda, newAdmin: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Stranger accepted the admin transfer");
+    } catch (error) {
+      assert.include(error.toString(), "NotPendingAdmin");
+    }
+
+    await program.methods
+      .acceptAdminTransfer()
+      .accounts({ config: configPda, newAdmin: newAdmin.publicKey })
+      .signers([newAdmin])
+      .rpc();
+    let config = await program.account.config.fetch(configPda);
+    assert.equal(config.admin.toString(), newAdmin.publicKey.toString());
+    assert.isNull(config.pendingAdmin);
+
+    try {
+      await program.methods
+        .setFeeBps(100)
+        .accounts({ config: configPda, admin: authority.publicKey })
+        .rpc();
+      assert.fail("Former admin kept admin rights");
+    } catch (error) {
+      assert.include(error.toString(), "Unauthorized");
+    }
+
+    // Hand the role back so later tests keep running as the original admin
+    await program.methods
+      .proposeAdminTransfer(authority.publicKey)
+      .accounts({ config: configPda, admin: newAdmin.publicKey })
+      .signers([newAdmin])
+      .rpc();
+    await program.methods
+      .acceptAdminTransfer()
+      .accounts({ config: configPda, newAdmin: authority.publicKey })
+      .rpc();
+    config = await program.account.config.fetch(configPda);
+    assert.equal(config.admin.toString(), authority.publicKey.toString());
+
+    console.log("✅ Admin transferred and handed back");
+  });
+
+  it("Cancels a pending admin transfer", async () => {
+    const nominee = anchor.web3.Keypair.generate();
+
+    await program.methods
+      .proposeAdminTransfer(nominee.publicKey)
+      .accounts({ config: configPda, admin: authority.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .cancelAdminTransfer()
+        .accounts({ config: configPda, admin: nominee.publicKey })
+        .signers([nominee])
+        .rpc();
+      assert.fail("Nominee cancelled the transfer");
+    } catch (error) {
+      assert.include(error.toString(), "Unauthorized");
+    }
+
+    await program.methods
+      .cancelAdminTransfer()
+      .accounts({ config: configPda, admin: authority.publicKey })
+      .rpc();
+    const config = await program.account.config.fetch(configPda);
+    assert.isNull(config.pendingAdmin);
+    assert.equal(config.admin.toString(), authority.publicKey.toString());
+
+    try {
+      await program.methods
+        .acceptAdminTransfer()
+        .accounts({ config: configPda, newAdmin: nominee.publicKey })
+        .signers([nominee])
+        .rpc();
+      assert.fail("Accepted a cancelled transfer");
+    } catch (error) {
+      assert.include(error.toString(), "NoPendingAdminTransfer");
+    }
+
+    console.log("✅ Admin transfer cancelled");
+  });
+
+  itWithShortTimelocks("Claims several markets in one transaction", async () => {
+    const expiry = new BN(Math.floor(Date.now() / 1000) + 2);
+    const first = await createMarket("Batch claim market A", expiry);
+    const second = await createMarket("Batch claim market B", expiry);
+    const markets = [first, second];
+    for (const { marketPda, vaultPda } of markets) {
+      await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    }
+
+    // Wait for expiry, then out the dispute window
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    for (const { marketPda } of markets) {
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+    }
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const payout = new BN(1 * 1e6);
+    const nonces = [new BN(1), new BN(2)];
+    const expiries = markets.map(() => proofExpiry());
+    const groups = markets.flatMap(({ marketPda, vaultPda }) => [
+      { pubkey: marketPda, isWritable: true, isSigner: false },
+      {
+        pubkey: findPositionPda(marketPda, userWallet.publicKey),
+        isWritable: true,
+        isSigner: false,
+      },
+      { pubkey: vaultPda, isWritable: true, isSigner: false },
+      { pubkey: userTokenAccount, isWritable: true, isSigner: false },
+    ]);
+    const batchClaim = (signers: anchor.web3.Keypair[]) => {
+      const proofs = markets.map(({ marketPda }, i) =>
+        signPayout(
+          signers[i],
+          marketPda,
+          userWallet.publicKey,
+          payout,
+          nonces[i],
+          expiries[i]
+        )
+      );
+      return program.methods
+        .batchClaim(
+          markets.map(() => payout),
+          nonces,
+          expiries,
+          proofs.map((p) => [p.signature])
+        )
+        .accounts({
+          userStats: findUserStatsPda(userWallet.publicKey),
+          usdcMint,
+          user: userWallet.publicKey,
+          ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(groups)
+        .preInstructions(proofs.map((p) => p.ix))
+        .signers([userWallet])
+        .rpc();
+    };
+
+    // A bad signature on the second claim reverts the first one too
+    const before = await provider.connection.getTokenAccountBalance(userTokenAccount);
+    try {
+      await batchClaim([mxeKeypair, anchor.web3.Keypair.generate()]);
+      assert.fail("Batch with a forged proof was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "UnknownMXESigner");
+    }
+    const afterFailure = await provider.connection.getTokenAccountBalance(userTokenAccount);
+    assert.equal(afterFailure.value.amount, before.value.amount);
+    const untouched = await program.account.userPosition.fetch(
+      findPositionPda(first.marketPda, userWallet.publicKey)
+    );
+    assert.isFalse(untouched.claimed);
+
+    const statsBefore = await program.account.userStats.fetch(
+      findUserStatsPda(userWallet.publicKey)
+    );
+    await batchClaim([mxeKeypair, mxeKeypair]);
+    const after = await provider.connection.getTokenAccountBalance(userTokenAccount);
+    assert.equal(
+      Number(after.value.amount) - Number(before.value.amount),
+      2 * 1e6
+    );
+    const statsAfter = await program.account.userStats.fetch(
+      findUserStatsPda(userWallet.publicKey)
+    );
+    assert.equal(statsAfter.totalClaimed.sub(statsBefore.totalClaimed).toNumber(), 2 * 1e6);
+    assert.equal(statsAfter.wins.sub(statsBefore.wins).toNumber(), 2);
+    for (const { marketPda } of markets) {
+      const position = await program.account.userPosition.fetch(
+        findPositionPda(marketPda, userWallet.publicKey)
+      );
+      assert.isTrue(position.claimed);
+    }
+
+    console.log("✅ Two markets claimed in one transaction");
+  });
+
+  it("Halts betting and market creation while the protocol is paused", async () => {
+    const adminAccounts = { config: configPda, admin: authority.publicKey };
+    const { marketPda, vaultPda } = await createMarket(
+      "Protocol pause market",
+      new BN(Math.floor(Date.now() / 1000) + 3600)
+    );
+
+    await program.methods.emergencyPauseProtocol().accounts(adminAccounts).rpc();
+    try {
+      try {
+        await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+        assert.fail("Bet accepted while the protocol was paused");
+      } catch (error) {
+        assert.include(error.toString(), "ProtocolPaused");
+      }
+      try {
+        await createMarket(
+          "Market created while paused",
+          new BN(Math.floor(Date.now() / 1000) + 3600)
+        );
+        assert.fail("Market created while the protocol was paused");
+      } catch (error) {
+        assert.include(error.toString(), "ProtocolPaused");
+      }
+    } finally {
+      await program.methods.resumeProtocol().accounts(adminAccounts).rpc();
+    }
+
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    const market = await program.account.market.fetch(marketPda);
+    assert.equal(market.totalPool.toNumber(), 1 * 1e6);
+
+    console.log("✅ Protocol pause blocks bets and creation, resume restores them");
+  });
+
+  itWithShortTimelocks("Pays a signed relayer fee out of the payout", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Relayer fee market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    // Wait for expiry, then out the dispute window
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const payout = new BN(1 * 1e6);
+    const relayerFee = new BN(100_000);
+    const nonce = new BN(1);
+    const expiry = proofExpiry();
+    const { ix, signature } = signPayout(
+      mxeKeypair,
+      marketPda,
+      userWallet.publicKey,
+      payout,
+      nonce,
+      expiry,
+      program.programId,
+      relayerFee
+    );
+    const relayed = (fee: BN) =>
+      program.methods
+        .claimWithProofRelayed(payout, fee, nonce, expiry, [signature])
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: findPositionPda(marketPda, userWallet.publicKey),
+          userStats: findUserStatsPda(userWallet.publicKey),
+          vault: vaultPda,
+          solVault: null,
+          userTokenAccount,
+          usdcMint,
+          user: userWallet.publicKey,
+          relayer: authority.publicKey,
+          relayerTokenAccount: authorityTokenAccount,
+          ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([ix])
+        .rpc();
+
+    // The fee is part of the signed message, so the relayer cannot skim more
+    try {
+      await relayed(new BN(200_000));
+      assert.fail("Relayer took more than the signed fee");
+    } catch (error) {
+      assert.include(error.toString(), "MessageMismatch");
+    }
+
+    const balance = async (account: PublicKey) =>
+      Number((await provider.connection.getTokenAccountBalance(account)).value.amount);
+    const userBefore = await balance(userTokenAccount);
+    const relayerBefore = await balance(authorityTokenAccount);
+    await relayed(relayerFee);
+    assert.equal(
+      (await balance(userTokenAccount)) - userBefore,
+      payout.sub(relayerFee).toNumber()
+    );
+    assert.equal(
+      (await balance(authorityTokenAccount)) - relayerBefore,
+      relayerFee.toNumber()
+    );
+
+    console.log("✅ Relayer fee enforced by the MXE signature");
+  });
+
+  it("Pools only the net amount of a Token-2022 transfer-fee mint", async () => {
+    // 1% transfer fee, withheld in the destination account
+    const mint = anchor.web3.Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: authority.publicKey,
+          newAccountPubkey: mint.publicKey,
+          space: mintLen,
+          lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          mint.publicKey,
+          authority.publicKey,
+          authority.publicKey,
+          100,
+          BigInt(1_000_000_000),
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(
+          mint.publicKey,
+          6,
+          authority.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID
+        )
+      ),
+      [mint]
+    );
+    const userAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority.payer,
+      mint.publicKey,
+      userWallet.publicKey,
+      false,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await mintTo(
+      provider.connection,
+      authority.payer,
+      mint.publicKey,
+      userAta.address,
+      authority.publicKey,
+      10 * 1e6,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    await allowMint(mint.publicKey);
+    let marketPda: PublicKey, vaultPda: PublicKey;
+    try {
+      ({ marketPda, vaultPda } = await createMarket(
+        "Token-2022 transfer fee market",
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        { isToken22: true, mint: mint.publicKey, tokenProgram: TOKEN_2022_PROGRAM_ID }
+      ));
+    } finally {
+      await disallowMint(mint.publicKey);
+    }
+
+    await program.methods
+      .placeBet(Array.from(Buffer.from("payload")), new BN(1 * 1e6), null)
+      .accounts({
+        market: marketPda,
+        config: configPda,
+        userPosition: findPositionPda(marketPda, userWallet.publicKey),
+        vault: vaultPda,
+        solVault: null,
+        feeVault: findFeeVaultPda(marketPda),
+        solFeeVault: null,
+        userLimits: null,
+        whitelist: null,
+        userTokenAccount: userAta.address,
+        usdcMint: mint.publicKey,
+        insuranceVault: null,
+        userStats: findUserStatsPda(userWallet.publicKey),
+        user: userWallet.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([userWallet])
+      .rpc();
+
+    // 1% of the 1 token stake is withheld by the mint
+    const received = 990_000;
+    const market = await program.account.market.fetch(marketPda);
+    assert.equal(market.totalPool.toNumber(), received);
+    const position = await program.account.userPosition.fetch(
+      findPositionPda(marketPda, userWallet.publicKey)
+    );
+    assert.equal(position.amount.toNumber(), received);
+
+    console.log("✅ Token-2022 transfer fee excluded from the pool");
+  });
+
+  it("Locks the resolution bond and slashes it on an upheld dispute", async () => {
+    const adminAccounts = { config: configPda, admin: authority.publicKey };
+    const bondAmount = 50_000_000; // 0.05 SOL
+
+    await program.methods
+      .setResolutionBondAmount(new BN(bondAmount))
+      .accounts(adminAccounts)
+      .rpc();
+    let marketPda: PublicKey;
+    try {
+      ({ marketPda } = await createMarket(
+        "Resolution bond market",
+        new BN(Math.floor(Date.now() / 1000) + 2)
+      ));
+    } finally {
+      await program.methods
+        .setResolutionBondAmount(new BN(0))
+        .accounts(adminAccounts)
+        .rpc();
+    }
+    const [bondPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("bond"), marketPda.toBuffer()],
+      program.programId
+    );
+    const rent = await provider.connection.getMinimumBalanceForRentExemption(
+      (await provider.connection.getAccountInfo(bondPda)).data.length
+    );
+    assert.equal(await provider.connection.getBalance(bondPda), rent + bondAmount);
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .releaseResolutionBond()
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+      assert.fail("Bond released inside the dispute window");
+    } catch (error) {
+      assert.include(error.toString(), "BondLocked");
+    }
+
+    try {
+      await program.methods
+        .slashResolutionBond()
+        .accounts({ market: marketPda, admin: authority.publicKey })
+        .rpc();
+      assert.fail("Bond slashed without a dispute");
+    } catch (error) {
+      assert.include(error.toString(), "NoOpenDispute");
+    }
+
+    await program.methods
+      .disputeMarketResolution(Array(32).fill(7))
+      .accounts({ market: marketPda, disputant: userWallet.publicKey })
+      .signers([userWallet])
+      .rpc();
+    await program.methods
+      .slashResolutionBond()
+      .accounts({ market: marketPda, admin: authority.publicKey })
+      .rpc();
+
+    const bond = await program.account.resolutionBond.fetch(bondPda);
+    assert.equal(bond.amount.toNumber(), 0);
+    assert.equal(bond.openDisputes, 1);
+    assert.equal(await provider.connection.getBalance(bondPda), rent);
+
+    console.log("✅ Resolution bond locked and slashed");
+  });
+
+  itWithShortTimelocks("Releases the resolution bond after the dispute window", async () => {
+    const adminAccounts = { config: configPda, admin: authority.publicKey };
+    await program.methods
+      .setResolutionBondAmount(new BN(50_000_000))
+      .accounts(adminAccounts)
+      .rpc();
+    let marketPda: PublicKey;
+    try {
+      ({ marketPda } = await createMarket(
+        "Released bond market",
+        new BN(Math.floor(Date.now() / 1000) + 2)
+      ));
+    } finally {
+      await program.methods
+        .setResolutionBondAmount(new BN(0))
+        .accounts(adminAccounts)
+        .rpc();
+    }
+    const [bondPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("bond"), marketPda.toBuffer()],
+      program.programId
+    );
+
+    // Wait for expiry, then out the dispute window
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    await program.methods
+      .releaseResolutionBond()
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    assert.isNull(await provider.connection.getAccountInfo(bondPda));
+
+    console.log("✅ Resolution bond released");
+  });
+
+  itWithShortTimelocks("Bets native SOL into a wSOL market and claims it unwrapped", async () => {
+    const airdropSig = await provider.connection.requestAirdrop(
+      userWallet.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+
+    // Stays listed: it is the same mint on every run
+    await allowMint(NATIVE_MINT);
+    const { marketPda, vaultPda } = await createMarket(
+      "Wrapped SOL market",
+      new BN(Math.floor(Date.now() / 1000) + 2),
+      { mint: NATIVE_MINT }
+    );
+    const stake = new BN(anchor.web3.LAMPORTS_PER_SOL);
+
+    // No wSOL account needed: lamports are wrapped straight into the vault
+    await program.methods
+      .placeBetSol(Array.from(Buffer.from("payload")), stake, null)
+      .accounts({
+        market: marketPda,
+        config: configPda,
+        userPosition: findPositionPda(marketPda, userWallet.publicKey),
+        vault: vaultPda,
+        solVault: null,
+        feeVault: findFeeVaultPda(marketPda),
+        solFeeVault: null,
+        userLimits: null,
+        whitelist: null,
+        userTokenAccount: null,
+        usdcMint: NATIVE_MINT,
+        insuranceVault: null,
+        user: userWallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([userWallet])
+      .rpc();
+    const vault = await provider.connection.getTokenAccountBalance(vaultPda);
+    assert.equal(vault.value.amount, stake.toString());
+
+    // Wait for expiry, then out the dispute window
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const wsolAccount = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority.payer,
+      NATIVE_MINT,
+      userWallet.publicKey
+    );
+    const nonce = new BN(1);
+    const expiry = proofExpiry();
+    const { ix, signature } = signPayout(
+      mxeKeypair,
+      marketPda,
+      userWallet.publicKey,
+      stake,
+      nonce,
+      expiry
+    );
+    const before = await provider.connection.getBalance(userWallet.publicKey);
+    await program.methods
+      .claimWithProofSol(stake, nonce, expiry, [signature], true)
+      .accounts({
+        market: marketPda,
+        config: configPda,
+        userPosition: findPositionPda(marketPda, userWallet.publicKey),
+        vault: vaultPda,
+        solVault: null,
+        userTokenAccount: wsolAccount.address,
+        usdcMint: NATIVE_MINT,
+        insuranceFund: null,
+        insuranceVault: null,
+        userStats: findUserStatsPda(userWallet.publicKey),
+        user: userWallet.publicKey,
+        ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .preInstructions([ix])
+      .signers([userWallet])
+      .rpc();
+
+    // The wSOL account is closed: payout plus its rent arrive as lamports
+    assert.isNull(await provider.connection.getAccountInfo(wsolAccount.address));
+    const gained = (await provider.connection.getBalance(userWallet.publicKey)) - before;
+    assert.isAtLeast(gained, stake.toNumber());
+
+    console.log("✅ Native SOL bet wrapped and claimed back unwrapped");
+  });
+
+  it("Caps bets by the user's admin-set limits", async () => {
+    const bettor = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(
+      bettor.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+    const bettorAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority.payer,
+      usdcMint,
+      bettor.publicKey
+    );
+    await mintTo(
+      provider.connection,
+      authority.payer,
+      usdcMint,
+      bettorAta.address,
+      authority.publicKey,
+      10 * 1e6
+    );
+    const [limitsPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("limits"), bettor.publicKey.toBuffer()],
+      program.programId
+    );
+    const setLimits = (lifetime: number, daily: number) =>
+      program.methods
+        .setUserLimits(bettor.publicKey, {
+          lifetimeVolumeCap: new BN(lifetime),
+          dailyVolumeCap: new BN(daily),
+        })
+        .accounts({ userLimits: limitsPda, admin: authority.publicKey })
+        .rpc();
+
+    const { marketPda, vaultPda } = await createMarket(
+      "User limits market",
+      new BN(Math.floor(Date.now() / 1000) + 3600)
+    );
+    const bet = (amount: number) =>
+      program.methods
+        .placeBet(Array.from(Buffer.from("payload")), new BN(amount), null)
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: findPositionPda(marketPda, bettor.publicKey),
+          vault: vaultPda,
+          solVault: null,
+          feeVault: findFeeVaultPda(marketPda),
+          solFeeVault: null,
+          userLimits: limitsPda,
+          whitelist: null,
+          userTokenAccount: bettorAta.address,
+          usdcMint,
+          insuranceVault: null,
+          userStats: findUserStatsPda(bettor.publicKey),
+          user: bettor.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([bettor])
+        .rpc();
+
+    await setLimits(0, 2 * 1e6);
+    await bet(1.5 * 1e6);
+    try {
+      await bet(1 * 1e6);
+      assert.fail("Bet above the daily cap was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "DailyLimitExceeded");
+    }
+
+    await setLimits(2 * 1e6, 0);
+    try {
+      await bet(1 * 1e6);
+      assert.fail("Bet above the lifetime cap was accepted");
+    } catch (error) {
+      assert.include(error.toString(), "LifetimeLimitExceeded");
+    }
+    await bet(0.5 * 1e6);
+
+    const limits = await program.account.userBettingLimits.fetch(limitsPda);
+    assert.equal(limits.volumeToday.toNumber(), 2 * 1e6);
+
+    console.log("✅ Daily and lifetime caps enforced");
+  });
+
+  it("Refuses to force-resolve before the grace period", async () => {
+    const { marketPda } = await createMarket(
+      "Early force resolve market",
+      new BN(Math.floor(Date.now() / 1000) + 3600)
+    );
+
+    try {
+      await program.methods
+        .forceResolve({ yes: {} })
+        .accounts({ market: marketPda, resolver: userWallet.publicKey })
+        .signers([userWallet])
+        .rpc();
+      assert.fail("Market force-resolved before the grace period");
+    } catch (error) {
+      assert.include(error.toString(), "ForceResolveDelayNotElapsed");
+    }
+
+    console.log("✅ Force resolution waits for the grace period");
+  });
+
+  itWithShortTimelocks("Force-resolves a stalled market and slashes an overturned bond", async () => {
+    const stranger = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(
+      stranger.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+
+    const { marketPda } = await createMarket(
+      "Force resolved market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    const [forceResolutionPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("force_resolve"), marketPda.toBuffer()],
+      program.programId
+    );
+
+    // Wait out expiry plus the force-resolve delay
+    await new Promise((resolve) =>
+      setTimeout(resolve, (FORCE_RESOLVE_DELAY_SECS + 3) * 1000)
+    );
+
+    try {
+      await program.methods
+        .forceResolve({ none: {} })
+        .accounts({ market: marketPda, resolver: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Market force-resolved to None");
+    } catch (error) {
+      assert.include(error.toString(), "InvalidResult");
+    }
+
+    await program.methods
+      .forceResolve({ yes: {} })
+      .accounts({ market: marketPda, resolver: stranger.publicKey })
+      .signers([stranger])
+      .rpc();
+    const record = await program.account.forceResolution.fetch(forceResolutionPda);
+    assert.equal(record.resolver.toString(), stranger.publicKey.toString());
+    assert.equal(record.bond.toNumber(), 500_000_000);
+
+    const balanceBefore = await provider.connection.getBalance(stranger.publicKey);
+    await program.methods
+      .overturnForceResolution({ no: {} })
+      .accounts({
+        market: marketPda,
+        resolver: stranger.publicKey,
+        authority: authority.publicKey,
+        admin: authority.publicKey,
+      })
+      .rpc();
+
+    const market = await program.account.market.fetch(marketPda);
+    assert.deepEqual(market.result, { no: {} });
+    assert.isNull(await provider.connection.getAccountInfo(forceResolutionPda));
+    // Only the record's rent comes back; the bond went to the treasury
+    const refunded =
+      (await provider.connection.getBalance(stranger.publicKey)) - balanceBefore;
+    assert.isBelow(refunded, 500_000_000);
+
+    console.log("✅ Overturned force resolution slashed");
+  });
+
+  itWithShortTimelocks("Returns the force-resolve bond after the dispute window", async () => {
+    const stranger = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(
+      stranger.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+
+    const { marketPda } = await createMarket(
+      "Force resolve release market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await new Promise((resolve) =>
+      setTimeout(resolve, (FORCE_RESOLVE_DELAY_SECS + 3) * 1000)
+    );
+    await program.methods
+      .forceResolve({ yes: {} })
+      .accounts({ market: marketPda, resolver: stranger.publicKey })
+      .signers([stranger])
+      .rpc();
+
+    try {
+      await program.methods
+        .releaseForceResolveBond()
+        .accounts({ market: marketPda, resolver: stranger.publicKey })
+        .rpc();
+      assert.fail("Bond released inside the dispute window");
+    } catch (error) {
+      assert.include(error.toString(), "BondLocked");
+    }
+
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+    await program.methods
+      .releaseForceResolveBond()
+      .accounts({ market: marketPda, resolver: stranger.publicKey })
+      .rpc();
+    assert.isAbove(
+      await provider.connection.getBalance(stranger.publicKey),
+      0.9 * anchor.web3.LAMPORTS_PER_SOL
+    );
+
+    console.log("✅ Force-resolve bond returned");
+  });
+
+  it("Creates markets only against allowed mints", async () => {
+    const allowedMint = await createMint(
+      provider.connection,
+      authority.payer,
+      authority.publicKey,
+      null,
+      6
+    );
+    const wrongDecimalsMint = await createMint(
+      provider.connection,
+      authority.payer,
+      authority.publicKey,
+      null,
+      9
+    );
+    const expiry = new BN(Math.floor(Date.now() / 1000) + 3600);
+
+    try {
+      await createMarket("Unlisted 9-decimal mint market", expiry, {
+        mint: wrongDecimalsMint,
+      });
+      assert.fail("Market created against an unlisted mint");
+    } catch (error) {
+      assert.include(error.toString(), "UnsupportedMint");
+    }
+
+    await allowMint(allowedMint);
+    try {
+      const { marketPda } = await createMarket("Allowed mint market", expiry, {
+        mint: allowedMint,
+      });
+      const market = await program.account.market.fetch(marketPda);
+      assert.equal(market.usdcMint.toString(), allowedMint.toString());
+    } finally {
+      await disallowMint(allowedMint);
+    }
+
+    console.log("✅ Unlisted mints rejected at market creation");
+  });
+
+  itWithSwitchboard("Resolves price markets from a Switchboard feed", async () => {
+    const switchboard = await SwitchboardTestContext.loadFromEnv(provider as any);
+    // 100.000000000 at SWITCHBOARD_THRESHOLD_DECIMALS = 9
+    const threshold = new BN(100).mul(new BN(10).pow(new BN(9)));
+
+    const resolveFromFeed = async (question: string, feedValue: number) => {
+      const aggregator = await switchboard.createStaticFeed(feedValue);
+      const { marketPda } = await createMarket(
+        question,
+        new BN(Math.floor(Date.now() / 1000) + 2),
+        { oracle: aggregator.publicKey, resolutionPriceThreshold: threshold }
+      );
+
+      // Wait for expiry; no market authority signature is needed
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      const resolveFromRound = () =>
+        program.methods
+          .resolveWithOracle()
+          .accounts({ market: marketPda, aggregator: aggregator.publicKey })
+          .rpc();
+
+      // The round was opened before expiry
+      try {
+        await resolveFromRound();
+        assert.fail("Resolved on a round from before expiry");
+      } catch (err: any) {
+        assert.include(err.toString(), "SwitchboardFeedStale");
+      }
+
+      await switchboard.updateStaticFeed(aggregator, feedValue);
+      await resolveFromRound();
+      return program.account.market.fetch(marketPda);
+    };
+
+    const above = await resolveFromFeed("Switchboard feed above threshold", 110);
+    assert.isTrue(above.resolved);
+    assert.deepEqual(above.result, { yes: {} });
+
+    const below = await resolveFromFeed("Switchboard feed below threshold", 90);
+    assert.deepEqual(below.result, { no: {} });
+
+    console.log("✅ Switchboard feed resolved both markets");
+  });
+
+  it("Transfers a position and merges it into the recipient's", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Position transfer market",
+      new BN(Math.floor(Date.now() / 1000) + 3600)
+    );
+    await placeBet(marketPda, vaultPda, new BN(2 * 1e6));
+    await placeBet(
+      marketPda,
+      vaultPda,
+      new BN(3 * 1e6),
+      authority.payer,
+      authorityTokenAccount
+    );
+    const fromPda = findPositionPda(marketPda, userWallet.publicKey);
+    const toPda = findPositionPda(marketPda, authority.publicKey);
+
+    try {
+      await program.methods
+        .transferPosition(userWallet.publicKey)
+        .accounts({
+          market: marketPda,
+          userPosition: fromPda,
+          newPosition: fromPda,
+          newOwnerStats: findUserStatsPda(userWallet.publicKey),
+          user: userWallet.publicKey,
+        })
+        .signers([userWallet])
+        .rpc();
+      assert.fail("Position transferred to its own owner");
+    } catch (error) {
+      assert.include(error.toString(), "CannotTransferToSelf");
+    }
+
+    await program.methods
+      .transferPosition(authority.publicKey)
+      .accounts({
+        market: marketPda,
+        userPosition: fromPda,
+        newPosition: toPda,
+        newOwnerStats: findUserStatsPda(authority.publicKey),
+        user: userWallet.publicKey,
+      })
+      .signers([userWallet])
+      .rpc();
+
+    assert.isNull(await provider.connection.getAccountInfo(fromPda));
+    const merged = await program.account.userPosition.fetch(toPda);
+    assert.equal(merged.amount.toNumber(), 5 * 1e6);
+    const market = await program.account.market.fetch(marketPda);
+    assert.equal(market.totalPositions, 1);
+
+    console.log("✅ Position transferred and merged");
+  });
+
+  it("Blocks claims until a proposed resolution is finalized", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Proposed resolution market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .proposeResolution(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    try {
+      await claimWithSigners(marketPda, vaultPda, [mxeKeypair], new BN(1 * 1e6), new BN(1));
+      assert.fail("Claimed against a proposed resolution");
+    } catch (error) {
+      assert.include(error.toString(), "MarketNotResolved");
+    }
+    try {
+      await program.methods
+        .finalizeResolution()
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+      assert.fail("Finalized inside the challenge window");
+    } catch (error) {
+      assert.include(error.toString(), "ChallengeWindowActive");
+    }
+    try {
+      await program.methods
+        .resolveMarket(1)
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+      assert.fail("Resolved around the challenge window");
+    } catch (error) {
+      assert.include(error.toString(), "ResolutionAlreadyProposed");
+    }
+
+    console.log("✅ Claims wait for finalization");
+  });
+
+  itWithShortTimelocks("Holds a disputed proposal until the admin overrides it", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Disputed proposal market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .proposeResolution(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await program.methods
+      .disputeResolution()
+      .accounts({
+        market: marketPda,
+        userPosition: findPositionPda(marketPda, userWallet.publicKey),
+        disputant: userWallet.publicKey,
+      })
+      .signers([userWallet])
+      .rpc();
+
+    // The window passing does not release a disputed proposal
+    await new Promise((resolve) =>
+      setTimeout(resolve, (CHALLENGE_WINDOW_SECS + 1) * 1000)
+    );
+    try {
+      await program.methods
+        .finalizeResolution()
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+      assert.fail("Finalized a disputed proposal");
+    } catch (error) {
+      assert.include(error.toString(), "ResolutionDisputed");
+    }
+
+    await program.methods
+      .overrideResolution(1)
+      .accounts({ market: marketPda, config: configPda, admin: authority.publicKey })
+      .rpc();
+    await program.methods
+      .finalizeResolution()
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    const market = await program.account.market.fetch(marketPda);
+    assert.isTrue(market.resolved);
+    assert.deepEqual(market.result, { no: {} });
+    assert.isNull(market.proposedOutcome);
+
+    console.log("✅ Disputed proposal settled by the admin");
+  });
+
+  it("Increases a position without a new payload", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Increase position market",
+      new BN(Math.floor(Date.now() / 1000) + 3600)
+    );
+    const increase = (wallet: anchor.web3.Keypair, tokenAccount: PublicKey) =>
+      program.methods
+        .increasePosition(new BN(2 * 1e6))
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: findPositionPda(marketPda, wallet.publicKey),
+          vault: vaultPda,
+          solVault: null,
+          feeVault: findFeeVaultPda(marketPda),
+          solFeeVault: null,
+          userLimits: null,
+          whitelist: null,
+          userTokenAccount: tokenAccount,
+          usdcMint,
+          insuranceVault: null,
+          userStats: findUserStatsPda(wallet.publicKey),
+          user: wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([wallet])
+        .rpc();
+
+    try {
+      await increase(authority.payer, authorityTokenAccount);
+      assert.fail("Increased a position that does not exist");
+    } catch (error) {
+      assert.include(error.toString(), "AccountNotInitialized");
+    }
+
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    await increase(userWallet, userTokenAccount);
+
+    const position = await program.account.userPosition.fetch(
+      findPositionPda(marketPda, userWallet.publicKey)
+    );
+    assert.equal(position.amount.toNumber(), 3 * 1e6);
+    const market = await program.account.market.fetch(marketPda);
+    assert.equal(market.totalPool.toNumber(), 3 * 1e6);
+    assert.equal(market.totalPositions, 1);
+
+    console.log("✅ Position increased in place");
+  });
+
+  it("Reports payout odds, market status and vault balance via views", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "View instructions market",
+      new BN(Math.floor(Date.now() / 1000) + 3600)
+    );
+    await placeBet(marketPda, vaultPda, new BN(2 * 1e6));
+    const findEvent = (events: any[], name: string) =>
+      events.find((e) => e.name === name).data;
+
+    const estimate = await program.methods
+      .getUserPayoutEstimate(new BN(5 * 1e6), new BN(0))
+      .accounts({
+        market: marketPda,
+        userPosition: findPositionPda(marketPda, userWallet.publicKey),
+      })
+      .simulate();
+    const odds = findEvent(estimate.events, "PayoutEstimateEvent");
+    assert.equal(odds.oddsYesBps.toNumber(), 25_000);
+    assert.equal(odds.oddsNoBps.toNumber(), 0);
+
+    const status = await program.methods
+      .getMarketStatus()
+      .accounts({ market: marketPda })
+      .simulate();
+    const marketStatus = findEvent(status.events, "MarketStatusEvent");
+    assert.equal(marketStatus.poolSize.toNumber(), 2 * 1e6);
+    assert.equal(marketStatus.betCount, 1);
+    assert.isAbove(marketStatus.timeToExpiry.toNumber(), 0);
+
+    const balance = await program.methods
+      .getVaultBalance()
+      .accounts({ market: marketPda, vault: vaultPda, solVault: null })
+      .simulate();
+    assert.equal(
+      findEvent(balance.events, "VaultBalanceEvent").balance.toNumber(),
+      2 * 1e6
+    );
+
+    console.log("✅ View instructions report computed state");
+  });
+
+  it("Lets a delegated resolver resolve and rejects the old authority", async () => {
+    const committee = anchor.web3.Keypair.generate();
+    const { marketPda } = await createMarket(
+      "Delegated resolver market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await program.methods
+      .setResolver(committee.publicKey)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    try {
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+      assert.fail("Authority resolved after delegating");
+    } catch (error) {
+      assert.include(error.toString(), "Unauthorized");
+    }
+
+    await program.methods
+      .resolveMarket(1)
+      .accounts({ market: marketPda, authority: committee.publicKey })
+      .signers([committee])
+      .rpc();
+    const market = await program.account.market.fetch(marketPda);
+    assert.equal(market.resolver.toString(), committee.publicKey.toString());
+    assert.deepEqual(market.result, { no: {} });
+
+    console.log("✅ Resolution delegated to the committee");
+  });
+
+  it("Sweeps unclaimed payouts after the claim deadline", async () => {
+    const adminAccounts = { config: configPda, admin: authority.publicKey };
+    const defaultGracePeriod = (await program.account.config.fetch(configPda))
+      .unclaimedGracePeriod;
+    await program.methods
+      .setUnclaimedGracePeriod(new BN(3))
+      .accounts(adminAccounts)
+      .rpc();
+    let marketPda: PublicKey, vaultPda: PublicKey;
+    try {
+      ({ marketPda, vaultPda } = await createMarket(
+        "Unclaimed payouts market",
+        new BN(Math.floor(Date.now() / 1000) + 2)
+      ));
+    } finally {
+      await program.methods
+        .setUnclaimedGracePeriod(defaultGracePeriod)
+        .accounts(adminAccounts)
+        .rpc();
+    }
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    const withdraw = () =>
+      program.methods
+        .withdrawUnclaimed(new BN(1 * 1e6))
+        .accounts({
+          market: marketPda,
+          vault: vaultPda,
+          solVault: null,
+          usdcMint,
+          destinationTokenAccount: authorityTokenAccount,
+          destination: null,
+          authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    try {
+      await withdraw();
+      assert.fail("Swept before the claim deadline");
+    } catch (error) {
+      assert.include(error.toString(), "UnclaimedDeadlineNotReached");
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    try {
+      await claimWithSigners(marketPda, vaultPda, [mxeKeypair], new BN(1 * 1e6), new BN(1));
+      assert.fail("Claimed after the claim deadline");
+    } catch (error) {
+      assert.include(error.toString(), "ClaimDeadlinePassed");
+    }
+    await withdraw();
+
+    const vault = await provider.connection.getTokenAccountBalance(vaultPda);
+    assert.equal(vault.value.amount, "0");
+
+    console.log("✅ Unclaimed payouts swept after the deadline");
+  });
+
+  it("Builds the YES/NO pools from revealed sides", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Revealed sides market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(2 * 1e6));
+    await placeBet(
+      marketPda,
+      vaultPda,
+      new BN(3 * 1e6),
+      authority.payer,
+      authorityTokenAccount
+    );
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    const revealSide = (wallet: anchor.web3.Keypair, side: number) => {
+      const { ix, signature } = signMessage(
+        mxeKeypair,
+        Buffer.from(
+          keccak_256(
+            Buffer.concat([
+              Buffer.from("nexora:side_reveal:v1"),
+              marketPda.toBuffer(),
+              wallet.publicKey.toBuffer(),
+              Buffer.from([side]),
+            ])
+          )
+        )
+      );
+      return program.methods
+        .revealSide(side, [signature])
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: findPositionPda(marketPda, wallet.publicKey),
+          user: wallet.publicKey,
+          ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .preInstructions([ix])
+        .signers([wallet])
+        .rpc();
+    };
+
+    await revealSide(userWallet, 0);
+    await revealSide(authority.payer, 1);
+    try {
+      await revealSide(userWallet, 0);
+      assert.fail("Side revealed twice");
+    } catch (error) {
+      assert.include(error.toString(), "SideAlreadyRevealed");
+    }
+
+    // A bulk split would count the revealed stakes twice
+    try {
+      await recordPoolSplit(marketPda, new BN(2 * 1e6), new BN(3 * 1e6));
+      assert.fail("Pool split recorded over revealed sides");
+    } catch (error) {
+      assert.include(error.toString(), "PoolsAlreadyRevealed");
+    }
+
+    const market = await program.account.market.fetch(marketPda);
+    assert.equal(market.yesPool.toNumber(), 2 * 1e6);
+    assert.equal(market.noPool.toNumber(), 3 * 1e6);
+    assert.isAtMost(
+      market.yesPool.toNumber() + market.noPool.toNumber(),
+      market.totalPool.toNumber()
+    );
+
+    console.log("✅ Revealed sides fill the YES/NO pools");
+  });
+
+  itWithSwitchboard("Auto-resolves from an attached Switchboard feed", async () => {
+    const switchboard = await SwitchboardTestContext.loadFromEnv(provider as any);
+    const aggregator = await switchboard.createStaticFeed(110);
+    const { marketPda } = await createMarket(
+      "Attached Switchboard feed market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    const [feedConfigPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("sw_config"), marketPda.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .attachSwitchboardFeed(
+        new BN(100).mul(new BN(10).pow(new BN(9))),
+        { above: {} },
+        60
+      )
+      .accounts({ market: marketPda, aggregator: aggregator.publicKey })
+      .rpc();
+
+    const feedConfig = await program.account.switchboardFeedConfig.fetch(
+      feedConfigPda
+    );
+    assert.equal(feedConfig.aggregator.toString(), aggregator.publicKey.toString());
+    assert.equal(feedConfig.stalenessThreshold, 60);
+
+    // A market already resolved by `market.oracle` takes no second feed
+    const oracleMarket = await createMarket(
+      "Switchboard oracle market",
+      new BN(Math.floor(Date.now() / 1000) + 3600),
+      {
+        oracle: aggregator.publicKey,
+        resolutionPriceThreshold: new BN(100).mul(new BN(10).pow(new BN(9))),
+      }
+    );
+    try {
+      await program.methods
+        .attachSwitchboardFeed(new BN(1), { above: {} }, 60)
+        .accounts({ market: oracleMarket.marketPda, aggregator: aggregator.publicKey })
+        .rpc();
+      assert.fail("Attached a feed to a market with an oracle");
+    } catch (err: any) {
+      assert.include(err.toString(), "MultipleOraclesConfigured");
+    }
+
+    // A different aggregator is rejected
+    const otherAggregator = await switchboard.createStaticFeed(90);
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    // Only rounds opened after expiry count
+    await switchboard.updateStaticFeed(aggregator, 110);
+    await switchboard.updateStaticFeed(otherAggregator, 90);
+    try {
+      await program.methods
+        .autoResolveWithSwitchboard()
+        .accounts({ market: marketPda, aggregator: otherAggregator.publicKey })
+        .rpc();
+      assert.fail("Should have rejected a mismatched aggregator");
+    } catch (err: any) {
+      assert.include(err.toString(), "SwitchboardFeedMismatch");
+    }
+
+    await program.methods
+      .autoResolveWithSwitchboard()
+      .accounts({ market: marketPda, aggregator: aggregator.publicKey })
+      .rpc();
+    const market = await program.account.market.fetch(marketPda);
+    assert.isTrue(market.resolved);
+    assert.deepEqual(market.result, { yes: {} });
+
+    console.log("✅ Attached Switchboard feed resolved the market");
+  });
+
+  it("Counts distinct bettors once per position", async () => {
+    const bettor = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(
+      bettor.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+    const bettorAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority.payer,
+      usdcMint,
+      bettor.publicKey
+    );
+    await mintTo(
+      provider.connection,
+      authority.payer,
+      usdcMint,
+      bettorAta.address,
+      authority.publicKey,
+      10 * 1e6
+    );
+
+    const { marketPda, vaultPda } = await createMarket(
+      "Bettor count market",
+      new BN(Math.floor(Date.now() / 1000) + 3600)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1e6));
+    await placeBet(marketPda, vaultPda, new BN(1e6));
+    await placeBet(
+      marketPda,
+      vaultPda,
+      new BN(1e6),
+      authority.payer,
+      authorityTokenAccount
+    );
+    await placeBet(marketPda, vaultPda, new BN(1e6), bettor, bettorAta.address);
+    await placeBet(marketPda, vaultPda, new BN(1e6), bettor, bettorAta.address);
+
+    const market = await program.account.market.fetch(marketPda);
+    assert.equal(market.totalPositions, 3);
+
+    console.log("✅ Repeat bets did not inflate the bettor count");
+  });
+
+  it("Indexes markets by category", async () => {
+    const { marketPda } = await createMarket(
+      "Science category market",
+      new BN(Math.floor(Date.now() / 1000) + 3600),
+      { category: "science" }
+    );
+    const { marketPda: otherPda } = await createMarket(
+      "Finance category market",
+      new BN(Math.floor(Date.now() / 1000) + 3600),
+      { category: "finance" }
+    );
+
+    const market = await program.account.market.fetch(marketPda);
+    assert.deepEqual(market.category, { science: {} });
+
+    const science = await program.account.categoryIndex.fetch(
+      findCategoryIndexPda("science")
+    );
+    const scienceKeys = science.markets.map((key) => key.toString());
+    assert.include(scienceKeys, marketPda.toString());
+    assert.notInclude(scienceKeys, otherPda.toString());
+
+    // Live markets stay indexed
+    try {
+      await program.methods
+        .removeFromCategoryIndex(marketPda)
+        .accounts({
+          categoryIndex: findCategoryIndexPda("science"),
+          market: marketPda,
+        })
+        .rpc();
+      assert.fail("Should have kept an open market indexed");
+    } catch (err: any) {
+      assert.include(err.toString(), "MarketStillOpen");
+    }
+
+    console.log("✅ Markets indexed under their category");
+  });
+
+  itWithShortTimelocks("Tracks per-user lifetime stats across markets", async () => {
+    const statsPda = findUserStatsPda(userWallet.publicKey);
+    const before = await program.account.userStats
+      .fetch(statsPda)
+      .catch(() => null);
+    const delta = (field: string, after: any) =>
+      after[field].toNumber() - (before ? before[field].toNumber() : 0);
+
+    const expiry = new BN(Math.floor(Date.now() / 1000) + 3);
+    const first = await createMarket("User stats market A", expiry);
+    const second = await createMarket("User stats market B", expiry);
+    await placeBet(first.marketPda, first.vaultPda, new BN(2 * 1e6));
+    await placeBet(second.marketPda, second.vaultPda, new BN(1 * 1e6));
+    // A repeat bet adds volume but no new market
+    await placeBet(second.marketPda, second.vaultPda, new BN(1 * 1e6));
+
+    let stats = await program.account.userStats.fetch(statsPda);
+    assert.equal(stats.user.toString(), userWallet.publicKey.toString());
+    assert.equal(delta("totalWagered", stats), 4 * 1e6);
+    assert.equal(delta("marketsEntered", stats), 2);
+
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+    for (const { marketPda } of [first, second]) {
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+    }
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    // A winning claim on the first market, a losing one on the second
+    await claimWithSigners(
+      first.marketPda,
+      first.vaultPda,
+      [mxeKeypair],
+      new BN(1 * 1e6),
+      new BN(1)
+    );
+    await claimWithSigners(
+      second.marketPda,
+      second.vaultPda,
+      [mxeKeypair],
+      new BN(0),
+      new BN(1)
+    );
+
+    stats = await program.account.userStats.fetch(statsPda);
+    assert.equal(delta("totalClaimed", stats), 1 * 1e6);
+    assert.equal(delta("wins", stats), 1);
+    assert.equal(delta("marketsEntered", stats), 2);
+
+    console.log("✅ User stats follow bets and claims across markets");
+  });
+
+  itWithShortTimelocks("Reports post-transaction state in bet and claim events", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Indexer events market",
+      new BN(Math.floor(Date.now() / 1000) + 3)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    const bet = buildBet(marketPda, vaultPda, new BN(2 * 1e6));
+    const betEvent = (await bet.simulate()).events.find(
+      (e) => e.name === "BetPlacedEvent"
+    ).data;
+    await bet.rpc({ commitment: "confirmed" });
+    const market = await program.account.market.fetch(marketPda);
+    assert.equal(betEvent.totalPoolAfter.toString(), market.totalPool.toString());
+    const position = await program.account.userPosition.fetch(
+      findPositionPda(marketPda, userWallet.publicKey)
+    );
+    assert.equal(betEvent.positionTotal.toString(), position.amount.toString());
+    assert.deepEqual(
+      Buffer.from(betEvent.payloadHash),
+      Buffer.from(keccak_256(Buffer.from("payload")))
+    );
+    const crc32 = (data: Buffer) => {
+      let crc = 0xffffffff;
+      for (const byte of data) {
+        crc ^= byte;
+        for (let i = 0; i < 8; i++) crc = (crc >>> 1) ^ (0xedb88320 & -(crc & 1));
+      }
+      return (~crc) >>> 0;
+    };
+    assert.equal(
+      betEvent.checksum,
+      crc32(
+        Buffer.concat([
+          marketPda.toBuffer(),
+          userWallet.publicKey.toBuffer(),
+          new BN(2 * 1e6).toArrayLike(Buffer, "le", 8),
+          Buffer.from(betEvent.payloadHash),
+          betEvent.positionTotal.toArrayLike(Buffer, "le", 8),
+          betEvent.totalPoolAfter.toArrayLike(Buffer, "le", 8),
+        ])
+      )
+    );
+
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const claim = buildClaim(
+      marketPda,
+      vaultPda,
+      [mxeKeypair],
+      new BN(1 * 1e6),
+      new BN(1)
+    );
+    const claimEvent = (await claim.simulate()).events.find(
+      (e) => e.name === "ClaimEvent"
+    ).data;
+    await claim.rpc();
+    const vault = await provider.connection.getTokenAccountBalance(vaultPda);
+    const position = await program.account.userPosition.fetch(
+      findPositionPda(marketPda, userWallet.publicKey)
+    );
+    assert.equal(claimEvent.vaultBalanceAfter.toString(), vault.value.amount);
+    assert.equal(claimEvent.positionAmount.toString(), position.amount.toString());
+
+    console.log("✅ Bet and claim events carry post-transaction state");
+  });
+
+  itWithShortTimelocks("Scores forecasters and ranks them on the leaderboard", async () => {
+    const statsPda = findUserStatsPda(userWallet.publicKey);
+    const { marketPda, vaultPda } = await createMarket(
+      "Leaderboard market",
+      new BN(Math.floor(Date.now() / 1000) + 3)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    await placeBet(
+      marketPda,
+      vaultPda,
+      new BN(1 * 1e6),
+      authority.payer,
+      authorityTokenAccount
+    );
+
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const before = await program.account.userStats.fetch(statsPda);
+    const payout = new BN(1.5 * 1e6);
+    await claimWithSigners(marketPda, vaultPda, [mxeKeypair], payout, new BN(1));
+
+    // Winner's forecast is stake / payout; the score adds (1 - p)²
+    const position = await program.account.userPosition.fetch(
+      findPositionPda(marketPda, userWallet.publicKey)
+    );
+    const probability = position.amount.muln(10_000).div(payout).toNumber();
+    const stats = await program.account.userStats.fetch(statsPda);
+    assert.equal(
+      stats.brierScoreNumerator.sub(before.brierScoreNumerator).toNumber(),
+      (10_000 - probability) ** 2
+    );
+    assert.equal(stats.marketsResolved, before.marketsResolved + 1);
+
+    const [leaderboardPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("leaderboard")],
+      program.programId
+    );
+    await program.methods
+      .updateLeaderboard()
+      .accounts({ leaderboard: leaderboardPda, userStats: statsPda })
+      .rpc();
+
+    const leaderboard = await program.account.forecastersLeaderboard.fetch(
+      leaderboardPda
+    );
+    const entry = leaderboard.entries.find(
+      (e) => e.user.toString() === userWallet.publicKey.toString()
+    );
+    assert.equal(
+      entry.brierScoreNumerator.toString(),
+      stats.brierScoreNumerator.toString()
+    );
+    assert.equal(entry.marketsResolved, stats.marketsResolved);
+
+    // Users without a scored market cannot be listed
+    try {
+      await program.methods
+        .updateLeaderboard()
+        .accounts({
+          leaderboard: leaderboardPda,
+          userStats: findUserStatsPda(authority.publicKey),
+        })
+        .rpc();
+      assert.fail("Should have rejected a user with no scored markets");
+    } catch (err: any) {
+      assert.include(err.toString(), "UserNotEligible");
+    }
+
+    console.log("✅ Claims scored and ranked on the leaderboard");
+  });
+
+  it("Links recurring markets into a series", async () => {
+    const seriesId = anchor.web3.Keypair.generate().publicKey;
+    const seriesPda = findSeriesPda(seriesId);
+    await program.methods
+      .createMarketSeries("Weekly BTC close")
+      .accounts({ series: seriesPda, seriesId })
+      .rpc();
+
+    const expiry = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const { marketPda: week1 } = await createMarket("BTC above 100k week 1", expiry, {
+      seriesId,
+    });
+    const { marketPda: week2 } = await createMarket("BTC above 100k week 2", expiry, {
+      seriesId,
+    });
+
+    const market = await program.account.market.fetch(week2);
+    assert.equal(market.seriesId.toString(), seriesId.toString());
+    assert.equal(market.seriesIndex, 1);
+
+    const { events } = await program.methods
+      .getSeriesMarkets()
+      .accounts({ series: seriesPda })
+      .simulate();
+    const listed = events.find((e) => e.name === "SeriesMarketsEvent").data;
+    assert.deepEqual(
+      listed.markets.map((key) => key.toString()),
+      [week1.toString(), week2.toString()]
+    );
+
+    // A series_id without its series account is rejected
+    try {
+      await program.methods
+        .createMarket(
+          "BTC above 100k orphan week",
+          expiry,
+          "",
+          null,
+          null,
+          null,
+          { above: {} },
+          new BN(0),
+          new BN(0),
+          new BN(0),
+          false,
+          2,
+          { usdc: {} },
+          0,
+          [],
+          null,
+          { other: {} },
+          seriesId,
+          null
+        )
+        .accounts({
+          market: findMarketPdas("BTC above 100k orphan week").marketPda,
+          marketMetadata: findMarketPdas("BTC above 100k orphan week").metadataPda,
+          categoryIndex: findCategoryIndexPda("other"),
+          registry: registryPda,
+          registryPage: await currentRegistryPagePda(),
+          series: null,
+          creatorAllowlist: null,
+          vault: null,
+          feeVault: null,
+          usdcMint,
+          authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail("Should have required the series account");
+    } catch (err: any) {
+      assert.include(err.toString(), "SeriesNotFound");
+    }
+
+    console.log("✅ Markets chained into a series in order");
+  });
+
+  it("Reports the payout basis in MarketResolvedEvent", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Resolution pools market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(3 * 1e6));
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    const resolve = program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey });
+    const event = (await resolve.simulate()).events.find(
+      (e) => e.name === "MarketResolvedEvent"
+    ).data;
+    await resolve.rpc();
+
+    const market = await program.account.market.fetch(marketPda);
+    assert.equal(event.totalPool.toString(), market.totalPool.toString());
+    // No YES/NO split is known yet at resolution
+    assert.equal(event.winningPool.toString(), market.yesPool.toString());
+    assert.equal(event.losingPool.toString(), market.noPool.toString());
+
+    console.log("✅ Resolution event carries the pool totals");
+  });
+
+  itWithShortTimelocks("Verifies claims signed with a Secp256k1 MXE key", async () => {
+    const privateKey = Buffer.from(anchor.web3.Keypair.generate().secretKey.subarray(0, 32));
+
+    const { marketPda, vaultPda } = await createMarket(
+      "Secp256k1 claim market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    const payout = new BN(1 * 1e6);
+    const nonce = new BN(1);
+    const expiry = proofExpiry();
+    const proof = signSecp256k1(
+      privateKey,
+      payoutMessage(marketPda, userWallet.publicKey, payout, nonce, expiry)
+    );
+
+    await switchScheme({ secp256k1: {} }, proof.ethAddress);
+    try {
+      // Ed25519 proofs no longer verify
+      try {
+        await claimWithSigners(marketPda, vaultPda, [mxeKeypair], payout, nonce, expiry);
+        assert.fail("Ed25519 proof accepted under the Secp256k1 scheme");
+      } catch (err: any) {
+        assert.include(err.toString(), "Secp256k1InstructionMissing");
+      }
+
+      await program.methods
+        .claimWithProof(payout, nonce, expiry, [proof.signature])
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: findPositionPda(marketPda, userWallet.publicKey),
+          userStats: findUserStatsPda(userWallet.publicKey),
+          vault: vaultPda,
+          solVault: null,
+          userTokenAccount,
+          usdcMint,
+          insuranceFund: null,
+          insuranceVault: null,
+          user: userWallet.publicKey,
+          ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([proof.ix])
+        .signers([userWallet])
+        .rpc();
+      const position = await program.account.userPosition.fetch(
+        findPositionPda(marketPda, userWallet.publicKey)
+      );
+      assert.isTrue(position.claimed);
+    } finally {
+      await switchScheme({ ed25519: {} }, Array(20).fill(0));
+    }
+
+    console.log("✅ Secp256k1 MXE proofs verified");
+  });
+
+  itWithShortTimelocks("Rejects claims carrying stray Ed25519 instructions", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Stray Ed25519 market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const payout = new BN(1 * 1e6);
+    const nonce = new BN(1);
+    const expiry = proofExpiry();
+    const proof = signPayout(
+      mxeKeypair,
+      marketPda,
+      userWallet.publicKey,
+      payout,
+      nonce,
+      expiry
+    );
+    // A valid but unrelated Ed25519 instruction after the real proof
+    const stray = signMessage(
+      anchor.web3.Keypair.generate(),
+      Buffer.alloc(32, 7)
+    );
+    const claim = (preInstructions: anchor.web3.TransactionInstruction[]) =>
+      program.methods
+        .claimWithProof(payout, nonce, expiry, [proof.signature])
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: findPositionPda(marketPda, userWallet.publicKey),
+          userStats: findUserStatsPda(userWallet.publicKey),
+          vault: vaultPda,
+          solVault: null,
+          userTokenAccount,
+          usdcMint,
+          insuranceFund: null,
+          insuranceVault: null,
+          user: userWallet.publicKey,
+          ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions(preInstructions)
+        .signers([userWallet])
+        .rpc();
+
+    try {
+      await claim([proof.ix, stray.ix]);
+      assert.fail("Claim with two Ed25519 instructions was accepted");
+    } catch (err: any) {
+      assert.include(err.toString(), "MultipleEd25519Instructions");
+    }
+
+    await claim([proof.ix]);
+    const position = await program.account.userPosition.fetch(
+      findPositionPda(marketPda, userWallet.publicKey)
+    );
+    assert.isTrue(position.claimed);
+
+    console.log("✅ Only the MXE proof may carry an Ed25519 instruction");
+  });
+
+  it("Commits resolution evidence into the resolved event", async () => {
+    const { marketPda } = await createMarket(
+      "Resolution evidence market",
+      new BN(Math.floor(Date.now() / 1000) + 3)
+    );
+    const [evidencePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("evidence"), marketPda.toBuffer()],
+      program.programId
+    );
+    const evidenceHash = Array.from(
+      keccak_256(Buffer.from('{"source":"exchange close","price":101000}'))
+    );
+
+    // Only the resolver may record evidence
+    try {
+      await program.methods
+        .recordResolutionEvidence(evidenceHash, "ipfs://evidence")
+        .accounts({ market: marketPda, evidence: evidencePda, resolver: userWallet.publicKey })
+        .signers([userWallet])
+        .rpc();
+      assert.fail("Non-resolver recorded evidence");
+    } catch (err: any) {
+      assert.include(err.toString(), "Unauthorized");
+    }
+
+    await program.methods
+      .recordResolutionEvidence(evidenceHash, "ipfs://evidence")
+      .accounts({ market: marketPda, evidence: evidencePda, resolver: authority.publicKey })
+      .rpc();
+    const evidence = await program.account.resolutionEvidence.fetch(evidencePda);
+    assert.deepEqual(Array.from(evidence.evidenceHash), evidenceHash);
+    assert.equal(evidence.evidenceUri, "ipfs://evidence");
+
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+    const resolve = program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey });
+    const event = (await resolve.simulate()).events.find(
+      (e) => e.name === "MarketResolvedEvent"
+    ).data;
+    assert.deepEqual(Array.from(event.evidenceHash), evidenceHash);
+    await resolve.rpc();
+
+    // The window closes at resolution
+    try {
+      await program.methods
+        .recordResolutionEvidence(evidenceHash, "ipfs://late")
+        .accounts({ market: marketPda, evidence: evidencePda, resolver: authority.publicKey })
+        .rpc();
+      assert.fail("Evidence recorded after resolution");
+    } catch (err: any) {
+      assert.include(err.toString(), "EvidenceWindowClosed");
+    }
+
+    console.log("✅ Resolution evidence committed on-chain");
+  });
+
+  itWithShortTimelocks("Requires the MXE proof directly before the claim", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Proof ordering market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const payout = new BN(1 * 1e6);
+    const nonce = new BN(1);
+    const expiry = proofExpiry();
+    const proof = signPayout(
+      mxeKeypair,
+      marketPda,
+      userWallet.publicKey,
+      payout,
+      nonce,
+      expiry
+    );
+    const claim = (preInstructions: anchor.web3.TransactionInstruction[]) =>
+      program.methods
+        .claimWithProof(payout, nonce, expiry, [proof.signature])
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: findPositionPda(marketPda, userWallet.publicKey),
+          userStats: findUserStatsPda(userWallet.publicKey),
+          vault: vaultPda,
+          solVault: null,
+          userTokenAccount,
+          usdcMint,
+          insuranceFund: null,
+          insuranceVault: null,
+          user: userWallet.publicKey,
+          ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions(preInstructions)
+        .signers([userWallet])
+        .rpc();
+
+    // Anything between the proof and the claim breaks the binding
+    try {
+      await claim([
+        proof.ix,
+        anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+      ]);
+      assert.fail("Claim not directly after its proof was accepted");
+    } catch (err: any) {
+      assert.include(err.toString(), "Ed25519InstructionMisplaced");
+    }
+
+    await claim([proof.ix]);
+    const position = await program.account.userPosition.fetch(
+      findPositionPda(marketPda, userWallet.publicKey)
+    );
+    assert.isTrue(position.claimed);
+
+    console.log("✅ MXE proofs bound to the instruction right after them");
+  });
+
+  itWithShortTimelocks("Claims through the Secp256k1 variant only under its scheme", async () => {
+    const privateKey = Buffer.from(anchor.web3.Keypair.generate().secretKey.subarray(0, 32));
+
+    const { marketPda, vaultPda } = await createMarket(
+      "Secp256k1 variant market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    const payout = new BN(0.5 * 1e6);
+    const nonce = new BN(1);
+    const expiry = proofExpiry();
+    const proof = signSecp256k1(
+      privateKey,
+      payoutMessage(marketPda, userWallet.publicKey, payout, nonce, expiry)
+    );
+    const claim = (amount: BN) =>
+      program.methods
+        .claimWithProofSecp256k1(amount, nonce, expiry, proof.signature)
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: findPositionPda(marketPda, userWallet.publicKey),
+          userStats: findUserStatsPda(userWallet.publicKey),
+          vault: vaultPda,
+          solVault: null,
+          userTokenAccount,
+          usdcMint,
+          insuranceFund: null,
+          insuranceVault: null,
+          user: userWallet.publicKey,
+          ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([proof.ix])
+        .signers([userWallet])
+        .rpc();
+
+    // Ed25519 keeps no Ethereum address, so the variant stays closed
+    await switchScheme({ ed25519: {} }, proof.ethAddress);
+    const config = await program.account.config.fetch(configPda);
+    assert.deepEqual(config.mxeEthAddress, Array(20).fill(0));
+    try {
+      await claim(payout);
+      assert.fail("Secp256k1 claim accepted under the Ed25519 scheme");
+    } catch (err: any) {
+      assert.include(err.toString(), "Secp256k1SchemeInactive");
+    }
+
+    await switchScheme({ secp256k1: {} }, proof.ethAddress);
+    try {
+      // Signed for 0.5 USDC, submitted for 0.4
+      try {
+        await claim(new BN(0.4 * 1e6));
+        assert.fail("Tampered secp256k1 payout accepted");
+      } catch (err: any) {
+        assert.include(err.toString(), "MessageMismatch");
+      }
+
+      await claim(payout);
+      const position = await program.account.userPosition.fetch(
+        findPositionPda(marketPda, userWallet.publicKey)
+      );
+      assert.isTrue(position.claimed);
+    } finally {
+      await switchScheme({ ed25519: {} }, Array(20).fill(0));
+    }
+
+    console.log("✅ Secp256k1 claim variant verified");
+  });
+
+  itWithShortTimelocks("Voids and refunds a conditional market whose parent misses", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const parent = await createMarket("Conditional parent market", new BN(now + 2));
+    const child = await createMarket("Conditional child market", new BN(now + 8));
+    const parentAccount = [
+      { pubkey: parent.marketPda, isWritable: false, isSigner: false },
+    ];
+
+    // Conditional on the parent resolving YES
+    await program.methods
+      .setParentMarket(0)
+      .accounts({
+        market: child.marketPda,
+        parentMarket: parent.marketPda,
+        authority: authority.publicKey,
+      })
+      .rpc();
+
+    // Open parent: betting is allowed early
+    const stake = new BN(1 * 1e6);
+    await buildBet(child.marketPda, child.vaultPda, stake)
+      .remainingAccounts(parentAccount)
+      .rpc();
+
+    const resolveChild = () =>
+      program.methods
+        .resolveMarket(0)
+        .accounts({ market: child.marketPda, authority: authority.publicKey })
+        .remainingAccounts(parentAccount)
+        .rpc();
+    try {
+      await resolveChild();
+      assert.fail("Conditional market resolved before its parent");
+    } catch (err: any) {
+      assert.include(err.toString(), "ParentMarketNotResolved");
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(1)
+      .accounts({ market: parent.marketPda, authority: authority.publicKey })
+      .rpc();
+
+    try {
+      await buildBet(child.marketPda, child.vaultPda, stake)
+        .remainingAccounts(parentAccount)
+        .rpc();
+      assert.fail("Bet accepted after the parent missed its outcome");
+    } catch (err: any) {
+      assert.include(err.toString(), "ConditionalRequirementNotMet");
+    }
+
+    // The parent's result can still be overturned inside its dispute window
+    try {
+      await resolveChild();
+      assert.fail("Conditional market resolved inside its parent's dispute window");
+    } catch (err: any) {
+      assert.include(err.toString(), "ParentNotFinal");
+    }
+
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+    await resolveChild();
+    const market = await program.account.market.fetch(child.marketPda);
+    assert.isTrue(market.resolved);
+    assert.deepEqual(market.result, { none: {} });
+
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+    const positionPda = findPositionPda(child.marketPda, userWallet.publicKey);
+    const { amount } = await program.account.userPosition.fetch(positionPda);
+    await program.methods
+      .claimRefund()
+      .accounts({
+        market: child.marketPda,
+        config: configPda,
+        userPosition: positionPda,
+        userStats: findUserStatsPda(userWallet.publicKey),
+        vault: child.vaultPda,
+        solVault: null,
+        userTokenAccount,
+        usdcMint,
+        insuranceFund: null,
+        insuranceVault: null,
+        user: userWallet.publicKey,
+        ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(parentAccount)
+      .signers([userWallet])
+      .rpc();
+    const position = await program.account.userPosition.fetch(positionPda);
+    assert.isTrue(position.claimed);
+    assert.equal(position.payout.toString(), amount.toString());
+
+    console.log("✅ Conditional market voided and refunded");
+  });
+
+  itWithShortTimelocks("Verifies Groth16 payout proofs against the stored key", async () => {
+    // Test key with a known trapdoor: alpha = a*G1, beta = b*G2,
+    // gamma = delta = G2 and IC[i] = k[i]*G1. Knowing a, b and k lets the
+    // test build valid proofs for any public inputs without a circuit.
+    const r = BigInt("0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001");
+    const G1 = bn254.G1.ProjectivePoint.BASE;
+    const G2 = bn254.G2.ProjectivePoint.BASE;
+    const [a, b, c] = [7, 11, 13].map(BigInt);
+    const k = [17, 19, 23, 29, 31].map(BigInt);
+    const be32 = (value: bigint) => Buffer.from(value.toString(16).padStart(64, "0"), "hex");
+    const g1Bytes = (point: typeof G1) => {
+      const { x, y } = point.toAffine();
+      return Array.from(Buffer.concat([be32(x), be32(y)]));
+    };
+    const g2Bytes = (point: typeof G2) => {
+      const { x, y } = point.toAffine();
+      return Array.from(Buffer.concat([be32(x.c1), be32(x.c0), be32(y.c1), be32(y.c0)]));
+    };
+    const verifyingKey = {
+      alphaG1: g1Bytes(G1.multiply(a)),
+      betaG2: g2Bytes(G2.multiply(b)),
+      gammaG2: g2Bytes(G2),
+      deltaG2: g2Bytes(G2),
+      ic: k.map((scalar) => g1Bytes(G1.multiply(scalar))),
+    };
+
+    // Same encoding as zk_payout_inputs
+    const keyInput = (key: PublicKey) => {
+      const hash = Buffer.from(keccak_256(key.toBuffer()));
+      hash[0] = 0;
+      return BigInt("0x" + hash.toString("hex"));
+    };
+    const prove = (market: PublicKey, user: PublicKey, payout: BN, nonce: BN) => {
+      const inputs = [
+        keyInput(market),
+        keyInput(user),
+        BigInt(payout.toString()),
+        BigInt(nonce.toString()),
+      ];
+      const vkX = inputs.reduce((sum, input, i) => sum + input * k[i + 1], k[0]);
+      // e(A, G2) = e(alpha, beta) * e(vk_x, G2) * e(C, G2)
+      return {
+        proofA: g1Bytes(G1.multiply((a * b + vkX + c) % r)),
+        proofB: g2Bytes(G2),
+        proofC: g1Bytes(G1.multiply(c)),
+      };
+    };
+
+    const [zkVerifyingKeyPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("zk_vk")],
+      program.programId
+    );
+    const adminAccounts = {
+      config: configPda,
+      zkVerifyingKey: zkVerifyingKeyPda,
+      admin: authority.publicKey,
+    };
+    await program.methods
+      .proposeZkVerifyingKey(verifyingKey)
+      .accounts({ ...adminAccounts, systemProgram: SystemProgram.programId })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (MXE_ROTATION_DELAY_SECS + 1) * 1000)
+    );
+    await program.methods.finalizeZkVerifyingKey().accounts(adminAccounts).rpc();
+
+    const { marketPda, vaultPda } = await createMarket(
+      "Groth16 claim market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const payout = new BN(0.5 * 1e6);
+    const nonce = new BN(1);
+    const proof = prove(marketPda, userWallet.publicKey, payout, nonce);
+    const claim = (amount: BN) =>
+      program.methods
+        .claimWithZkProof(amount, nonce, proof.proofA, proof.proofB, proof.proofC)
+        .accounts({
+          claim: {
+            market: marketPda,
+            config: configPda,
+            userPosition: findPositionPda(marketPda, userWallet.publicKey),
+            userStats: findUserStatsPda(userWallet.publicKey),
+            vault: vaultPda,
+            solVault: null,
+            userTokenAccount,
+            usdcMint,
+            insuranceFund: null,
+            insuranceVault: null,
+            user: userWallet.publicKey,
+            ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          },
+          zkVerifyingKey: zkVerifyingKeyPda,
+        })
+        .signers([userWallet])
+        .rpc();
+
+    // Proven for 0.5 USDC, submitted for 0.4
+    try {
+      await claim(new BN(0.4 * 1e6));
+      assert.fail("Groth16 proof accepted for the wrong payout");
+    } catch (err: any) {
+      assert.include(err.toString(), "InvalidZkProof");
+    }
+
+    await claim(payout);
+    const position = await program.account.userPosition.fetch(
+      findPositionPda(marketPda, userWallet.publicKey)
+    );
+    assert.isTrue(position.claimed);
+    assert.equal(position.payout.toNumber(), payout.toNumber());
+
+    console.log("✅ Groth16 payout proofs verified");
+  });
+
+  itWithShortTimelocks("Verifies vault solvency before and after claims", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Vault solvency market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    const verifySolvency = () =>
+      program.methods
+        .verifyVaultSolvency()
+        .accounts({ market: marketPda, vault: vaultPda, solVault: null })
+        .rpc();
+
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    await verifySolvency();
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const payout = new BN(0.5 * 1e6);
+    await claimWithSigners(marketPda, vaultPda, [mxeKeypair], payout, new BN(1), proofExpiry());
+    const market = await program.account.market.fetch(marketPda);
+    assert.equal(market.totalClaimed.toNumber(), payout.toNumber());
+    await verifySolvency();
+
+    console.log("✅ Vault solvency verified");
+  });
+
+  itWithShortTimelocks("Locks creator stakes until reclaimed or slashed", async () => {
+    const stake = 50_000_000;
+    const adminAccounts = { config: configPda, admin: authority.publicKey };
+    await program.methods
+      .setRequiredCreatorStake(new BN(stake))
+      .accounts(adminAccounts)
+      .rpc();
+    let reclaimed: PublicKey, slashed: PublicKey;
+    try {
+      const expiry = new BN(Math.floor(Date.now() / 1000) + 2);
+      ({ marketPda: reclaimed } = await createMarket("Reclaimed stake market", expiry));
+      ({ marketPda: slashed } = await createMarket("Slashed stake market", expiry));
+    } finally {
+      await program.methods
+        .setRequiredCreatorStake(new BN(0))
+        .accounts(adminAccounts)
+        .rpc();
+    }
+    const market = await program.account.market.fetch(reclaimed);
+    assert.equal(market.creatorStake.toNumber(), stake);
+
+    const reclaim = (marketPda: PublicKey) =>
+      program.methods
+        .reclaimCreatorStake()
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+    await program.methods
+      .slashCreatorStake(Array(32).fill(7))
+      .accounts({ market: slashed, admin: authority.publicKey })
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    for (const marketPda of [reclaimed, slashed]) {
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+    }
+    await expectError(reclaim(reclaimed), "DisputeWindowNotExpired");
+
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+    await expectError(reclaim(slashed), "StakeSlashed");
+    await reclaim(reclaimed);
+    await expectError(reclaim(reclaimed), "StakeAlreadyReclaimed");
+
+    const [stakePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("creator_stake"), reclaimed.toBuffer()],
+      program.programId
+    );
+    const creatorStake = await program.account.creatorStake.fetch(stakePda);
+    assert.isTrue(creatorStake.reclaimed);
+
+    console.log("✅ Creator stakes reclaimed and slashed");
+  });
+
+  it("Restricts whitelisted markets to listed addresses", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Whitelisted market",
+      new BN(Math.floor(Date.now() / 1000) + 86400)
+    );
+    const [whitelistPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("whitelist"), marketPda.toBuffer()],
+      program.programId
+    );
+    const updateWhitelist = (add: boolean, address: PublicKey) =>
+      (add ? program.methods.addToWhitelist(address) : program.methods.removeFromWhitelist(address))
+        .accounts({
+          market: marketPda,
+          whitelist: whitelistPda,
+          config: configPda,
+          admin: authority.publicKey,
+        })
+        .rpc();
+    const bet = () =>
+      program.methods
+        .placeBet(Array.from(Buffer.from("payload")), new BN(1 * 1e6), null)
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: findPositionPda(marketPda, userWallet.publicKey),
+          vault: vaultPda,
+          solVault: null,
+          feeVault: findFeeVaultPda(marketPda),
+          solFeeVault: null,
+          userLimits: null,
+          whitelist: whitelistPda,
+          userTokenAccount,
+          usdcMint,
+          insuranceVault: null,
+          userStats: findUserStatsPda(userWallet.publicKey),
+          user: userWallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([userWallet])
+        .rpc();
+    const expectNotWhitelisted = async () => {
+      try {
+        await bet();
+        assert.fail("Bet accepted from an unlisted address");
+      } catch (err: any) {
+        assert.include(err.toString(), "UserNotWhitelisted");
+      }
+    };
+
+    // The first entry turns the whitelist on
+    await updateWhitelist(true, anchor.web3.Keypair.generate().publicKey);
+    assert.isTrue((await program.account.market.fetch(marketPda)).whitelistEnabled);
+    await expectNotWhitelisted();
+
+    await updateWhitelist(true, userWallet.publicKey);
+    await bet();
+
+    await updateWhitelist(false, userWallet.publicKey);
+    await expectNotWhitelisted();
+    const whitelist = await program.account.whitelist.fetch(whitelistPda);
+    assert.equal(whitelist.entries.length, 1);
+
+    console.log("✅ Whitelist enforced");
+  });
+
+  it("Reverts a bet whose pool share slipped below the limit", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Slippage guard?",
+      Math.floor(Date.now() / 1000) + 3600
+    );
+    const minShareBps = 4000;
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6), authority.payer, authorityTokenAccount);
+
+    // 1 of 2 USDC in the pool is a 50% share
+    await buildBet(marketPda, vaultPda, new BN(1 * 1e6), userWallet, userTokenAccount, minShareBps)
+      .rpc({ commitment: "confirmed" });
+    const positionPda = findPositionPda(marketPda, userWallet.publicKey);
+    const before = await program.account.userPosition.fetch(positionPda);
+
+    // The pool grows before the next bet lands: 2 of 6 USDC is ~33%
+    await placeBet(marketPda, vaultPda, new BN(3 * 1e6), authority.payer, authorityTokenAccount);
+    try {
+      await buildBet(marketPda, vaultPda, new BN(1 * 1e6), userWallet, userTokenAccount, minShareBps)
+        .rpc({ commitment: "confirmed" });
+      assert.fail("Bet accepted below the minimum pool share");
+    } catch (err: any) {
+      assert.include(err.toString(), "SlippageExceeded");
+    }
+
+    const after = await program.account.userPosition.fetch(positionPda);
+    assert.equal(after.amount.toString(), before.amount.toString());
+
+    console.log("✅ Slippage guard enforced");
+  });
+
+  it("Resolves several markets in one batch", async () => {
+    const expiry = new BN(Math.floor(Date.now() / 1000) + 2);
+    const markets = [];
+    for (const question of ["Batch one?", "Batch two?", "Batch three?"]) {
+      markets.push((await createMarket(question, expiry)).marketPda);
+    }
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    const batchResolve = (entries: [PublicKey, number][]) =>
+      program.methods
+        .batchResolveMarkets(
+          entries.map(([market, winningOutcome]) => ({ market, winningOutcome }))
+        )
+        .accounts({
+          config: configPda,
+          resolverStake: null,
+          authority: authority.publicKey,
+        })
+        .remainingAccounts(
+          entries.map(([pubkey]) => ({ pubkey, isWritable: true, isSigner: false }))
+        )
+        .rpc();
+
+    await batchResolve([[markets[0], 0], [markets[1], 1]]);
+    const first = await program.account.market.fetch(markets[0]);
+    const second = await program.account.market.fetch(markets[1]);
+    assert.deepEqual(first.result, { yes: {} });
+    assert.deepEqual(second.result, { no: {} });
+
+    // One bad entry reverts the whole batch
+    try {
+      await batchResolve([[markets[2], 0], [markets[0], 0]]);
+      assert.fail("Batch resolved an already resolved market");
+    } catch (err: any) {
+      assert.include(err.toString(), "AlreadyResolved");
+    }
+    assert.isFalse((await program.account.market.fetch(markets[2])).resolved);
+
+    try {
+      await batchResolve(Array(11).fill([markets[2], 0]));
+      assert.fail("Oversized batch accepted");
+    } catch (err: any) {
+      assert.include(err.toString(), "BatchTooLarge");
+    }
+
+    console.log("✅ Batch resolution is atomic");
+  });
+
+  it("Stops taking bets at the betting close before expiry", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const { marketPda, vaultPda } = await createMarket(
+      "Closes at kickoff?",
+      new BN(now + 3600),
+      { bettingCloseTimestamp: new BN(now + 3) }
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+    try {
+      await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+      assert.fail("Bet accepted after betting closed");
+    } catch (err: any) {
+      assert.include(err.toString(), "BettingClosed");
+    }
+
+    try {
+      await createMarket("Closes after expiry?", new BN(now + 60), {
+        bettingCloseTimestamp: new BN(now + 120),
+      });
+      assert.fail("Betting close after expiry accepted");
+    } catch (err: any) {
+      assert.include(err.toString(), "InvalidBettingClose");
+    }
+
+    console.log("✅ Betting close enforced before expiry");
+  });
+
+  it("Attaches structured resolution criteria with create_market_v2", async () => {
+    const expiry = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const { marketPda } = await createMarket("Structured terms?", expiry);
+    const [marketV2Pda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market_v2"), marketPda.toBuffer()],
+      program.programId
+    );
+    const createV2 = (criteria: any) =>
+      program.methods
+        .createMarketV2(criteria, expiry)
+        .accounts({
+          market: marketPda,
+          marketV2: marketV2Pda,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    // Price criteria need the market's Pyth feed
+    try {
+      await createV2({
+        priceAbove: { mint: usdcMint, thresholdUiAmount: 100000.5, atTime: expiry },
+      });
+      assert.fail("Price criteria accepted without a Pyth feed");
+    } catch (err: any) {
+      assert.include(err.toString(), "InvalidResolutionCriteria");
+    }
+
+    await createV2({ manualAdmin: {} });
+    const marketV2 = await program.account.marketV2.fetch(marketV2Pda);
+    assert.deepEqual(marketV2.resolutionCriteria, { manualAdmin: {} });
+    assert.equal(marketV2.expiryTimestamp.toString(), expiry.toString());
+
+    try {
+      await program.methods
+        .autoResolveWithPyth()
+        .accounts({ market: marketPda, priceFeed: marketPda, marketV2: marketV2Pda })
+        .rpc();
+      assert.fail("Manual market resolved from Pyth");
+    } catch (err: any) {
+      assert.include(err.toString(), "CriteriaTypeMismatch");
+    }
+
+    console.log("✅ Structured criteria recorded and enforced");
+  });
+
+  it("Moves a position between sides while betting is open", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Change of heart?",
+      new BN(Math.floor(Date.now() / 1000) + 3600)
+    );
+    await placeBet(marketPda, vaultPda, new BN(2 * 1e6));
+    const positionPda = findPositionPda(marketPda, userWallet.publicKey);
+
+    const changeSide = (oldSide: number, newSide: number) => {
+      const payload = Buffer.from(`side ${newSide}`);
+      const { ix, signature } = signMessage(
+        mxeKeypair,
+        Buffer.from(
+          keccak_256(
+            Buffer.concat([
+              Buffer.from("nexora:side_change:v1"),
+              marketPda.toBuffer(),
+              userWallet.publicKey.toBuffer(),
+              Buffer.from([oldSide, newSide]),
+              Buffer.from(keccak_256(payload)),
+            ])
+          )
+        )
+      );
+      return program.methods
+        .changeSide(Array.from(payload), oldSide, newSide, [signature])
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: positionPda,
+          user: userWallet.publicKey,
+          ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .preInstructions([ix])
+        .signers([userWallet])
+        .rpc();
+    };
+
+    const before = await program.account.market.fetch(marketPda);
+    const { amount } = await program.account.userPosition.fetch(positionPda);
+
+    // YES -> NO: the revealed stake lands on the NO side
+    await changeSide(0, 1);
+    let market = await program.account.market.fetch(marketPda);
+    assert.equal(market.yesPool.toString(), "0");
+    assert.equal(market.noPool.toString(), amount.toString());
+
+    // The position is now known to be on NO
+    try {
+      await changeSide(0, 1);
+      assert.fail("Side change from the wrong side accepted");
+    } catch (err: any) {
+      assert.include(err.toString(), "InvalidSideChange");
+    }
+
+    await changeSide(1, 0);
+    market = await program.account.market.fetch(marketPda);
+    assert.equal(market.yesPool.toString(), amount.toString());
+    assert.equal(market.noPool.toString(), "0");
+    assert.equal(market.totalPool.toString(), before.totalPool.toString());
+    const position = await program.account.userPosition.fetch(positionPda);
+    assert.equal(position.amount.toString(), amount.toString());
+
+    console.log("✅ Side changes rebalance the side pools");
+  });
+
+  itWithShortTimelocks("Pays a proven payout out in installments", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Installment claims market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const payout = new BN(0.6 * 1e6);
+    const nonce = new BN(7);
+    const expiry = proofExpiry();
+    const positionPda = findPositionPda(marketPda, userWallet.publicKey);
+    const partialClaim = (amount: number, signed: boolean) => {
+      const proofs = signed
+        ? [signPayout(mxeKeypair, marketPda, userWallet.publicKey, payout, nonce, expiry)]
+        : [];
+      return program.methods
+        .partialClaim(payout, nonce, expiry, proofs.map((p) => p.signature), new BN(amount))
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: positionPda,
+          vault: vaultPda,
+          solVault: null,
+          userTokenAccount,
+          usdcMint,
+          insuranceFund: null,
+          insuranceVault: null,
+          userStats: findUserStatsPda(userWallet.publicKey),
+          user: userWallet.publicKey,
+          ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions(proofs.map((p) => p.ix))
+        .signers([userWallet])
+        .rpc();
+    };
+    await partialClaim(0.4 * 1e6, true);
+    let position = await program.account.userPosition.fetch(positionPda);
+    assert.equal(position.claimedAmount.toNumber(), 0.4 * 1e6);
+    assert.isFalse(position.claimed);
+
+    await expectError(partialClaim(0.3 * 1e6, false), "PartialClaimExceedsProof");
+    // The rest cannot be taken through a full claim
+    await expectError(
+      claimWithSigners(marketPda, vaultPda, [mxeKeypair], payout, nonce, expiry),
+      "AlreadyClaimed"
+    );
+
+    await partialClaim(0.2 * 1e6, false);
+    position = await program.account.userPosition.fetch(positionPda);
+    assert.equal(position.claimedAmount.toNumber(), payout.toNumber());
+    assert.isTrue(position.claimed);
+    await expectError(partialClaim(1, false), "AlreadyClaimed");
+
+    console.log("✅ Installments add up to the proven payout");
+  });
+
+  itWithShortTimelocks("Lets a position's buyer claim it", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Sold position market",
+      new BN(Math.floor(Date.now() / 1000) + 3)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    // A buyer who has never bet anywhere
+    const buyer = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(
+      buyer.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+    const buyerAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      authority.payer,
+      usdcMint,
+      buyer.publicKey
+    );
+    const buyerPositionPda = findPositionPda(marketPda, buyer.publicKey);
+    await program.methods
+      .transferPosition(buyer.publicKey)
+      .accounts({
+        market: marketPda,
+        userPosition: findPositionPda(marketPda, userWallet.publicKey),
+        newPosition: buyerPositionPda,
+        newOwnerStats: findUserStatsPda(buyer.publicKey),
+        user: userWallet.publicKey,
+      })
+      .signers([userWallet])
+      .rpc();
+
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const { amount } = await program.account.userPosition.fetch(buyerPositionPda);
+    const nonce = new BN(1);
+    const expiry = proofExpiry();
+    const proof = signPayout(mxeKeypair, marketPda, buyer.publicKey, amount, nonce, expiry);
+    await program.methods
+      .claimWithProof(amount, nonce, expiry, [proof.signature])
+      .accounts({
+        market: marketPda,
+        config: configPda,
+        userPosition: buyerPositionPda,
+        vault: vaultPda,
+        solVault: null,
+        userTokenAccount: buyerAta.address,
+        usdcMint,
+        insuranceFund: null,
+        insuranceVault: null,
+        userStats: findUserStatsPda(buyer.publicKey),
+        user: buyer.publicKey,
+        ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .preInstructions([proof.ix])
+      .signers([buyer])
+      .rpc();
+
+    const position = await program.account.userPosition.fetch(buyerPositionPda);
+    assert.isTrue(position.claimed);
+    const balance = await provider.connection.getTokenAccountBalance(buyerAta.address);
+    assert.equal(balance.value.amount, amount.toString());
+
+    console.log("✅ Buyer claimed the transferred position");
+  });
+
+  it("Lets allowlisted creators create markets", async () => {
+    const creator = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(
+      creator.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+    const expiry = new BN(Math.floor(Date.now() / 1000) + 3600);
+    const updateAllowlist = (add: boolean) =>
+      (add ? program.methods.addCreator(creator.publicKey) : program.methods.removeCreator(creator.publicKey))
+        .accounts({
+          creatorAllowlist: creatorAllowlistPda,
+          config: configPda,
+          admin: authority.publicKey,
+        })
+        .rpc();
+    const expectUnauthorized = async (question: string) => {
+      try {
+        await createMarket(question, expiry, { creator });
+        assert.fail("Market created by an unlisted creator");
+      } catch (err: any) {
+        assert.include(err.toString(), "Unauthorized");
+      }
+    };
+
+    await updateAllowlist(true);
+    const { marketPda } = await createMarket("Community market?", expiry, { creator });
+    const market = await program.account.market.fetch(marketPda);
+    assert.equal(market.authority.toString(), creator.publicKey.toString());
+
+    await updateAllowlist(false);
+    await expectUnauthorized("Community market again?");
+    const allowlist = await program.account.creatorAllowlist.fetch(creatorAllowlistPda);
+    assert.notInclude(
+      allowlist.creators.map((key) => key.toString()),
+      creator.publicKey.toString()
+    );
+
+    console.log("✅ Creator allowlist enforced");
+  });
+
+  it("Pages through the market registry", async () => {
+    const { marketPda } = await createMarket(
+      "Registry paging market",
+      new BN(Math.floor(Date.now() / 1000) + 3600)
+    );
+
+    const registry = await program.account.marketRegistry.fetch(registryPda);
+    const pageLens: number[] = registry.pageLens;
+    assert.equal(
+      registry.totalMarkets,
+      pageLens.reduce((sum, len) => sum + len, 0)
+    );
+    const pageAccounts = pageLens.map((_, page) => ({
+      pubkey: findRegistryPagePda(page),
+      isWritable: false,
+      isSigner: false,
+    }));
+
+    // Registry page holding the key at position `offset`
+    const firstRegistryPage = (offset: number) => {
+      let page = 0;
+      while (offset >= pageLens[page]) {
+        offset -= pageLens[page];
+        page += 1;
+      }
+      return page;
+    };
+
+    const getPage = (page: number, pageSize: number, startPage: number) =>
+      program.methods
+        .getMarketPage(page, pageSize)
+        .accounts({ registry: registryPda })
+        .remainingAccounts(pageAccounts.slice(startPage))
+        .simulate();
+
+    const pageSize = 20;
+    const listed: string[] = [];
+    for (let page = 0; page * pageSize < registry.totalMarkets; page++) {
+      const { events } = await getPage(page, pageSize, firstRegistryPage(page * pageSize));
+      const event = events.find((e) => e.name === "MarketPageEvent");
+      assert.equal(event.data.page, page);
+      assert.equal(event.data.totalMarkets, registry.totalMarkets);
+      listed.push(...event.data.marketKeys.map((key) => key.toString()));
+    }
+    assert.equal(listed.length, registry.totalMarkets);
+    assert.equal(new Set(listed).size, listed.length);
+    assert.include(listed, marketPda.toString());
+
+    const outOfBounds = Math.ceil(registry.totalMarkets / pageSize);
+    try {
+      await getPage(outOfBounds, pageSize, 0);
+      assert.fail("Listed a page past the end of the registry");
+    } catch (err: any) {
+      assert.include(err.toString(), "PageOutOfBounds");
+    }
+    try {
+      await getPage(0, 21, 0);
+      assert.fail("Listed a page over the size limit");
+    } catch (err: any) {
+      assert.include(err.toString(), "InvalidPageSize");
+    }
+
+    console.log("✅ Market registry paged");
+  });
+
+  it("Tags markets and keeps tag indices in sync", async () => {
+    const { marketPda } = await createMarket(
+      "Tagged market",
+      new BN(Math.floor(Date.now() / 1000) + 3600)
+    );
+    const [marketTagsPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("tags"), marketPda.toBuffer()],
+      program.programId
+    );
+    const findTagIndexPda = (tag: string) =>
+      PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("tag_index"),
+          Buffer.from(keccak_256(Buffer.from(tag))).subarray(0, 8),
+        ],
+        program.programId
+      )[0];
+    const tagIndexMarkets = async (tag: string) =>
+      (await program.account.tagIndex.fetch(findTagIndexPda(tag))).markets.map(
+        (key) => key.toString()
+      );
+
+    for (const tag of ["SOL", "NFT", "2024 election"]) {
+      if (!(await program.account.tagIndex.fetchNullable(findTagIndexPda(tag)))) {
+        await program.methods
+          .initTagIndex(tag)
+          .accounts({
+            tagIndex: findTagIndexPda(tag),
+            payer: authority.publicKey,
+          })
+          .rpc();
+      }
+    }
+
+    const setTags = (oldTags: string[], tags: string[]) =>
+      program.methods
+        .setMarketTags(tags)
+        .accounts({
+          market: marketPda,
+          marketTags: marketTagsPda,
+          authority: authority.publicKey,
+        })
+        .remainingAccounts(
+          [...oldTags, ...tags].map((tag) => ({
+            pubkey: findTagIndexPda(tag),
+            isWritable: true,
+            isSigner: false,
+          }))
+        );
+
+    const { events } = await setTags([], ["SOL", "NFT"]).simulate();
+    const event = events.find((e) => e.name === "MarketTagsUpdatedEvent");
+    assert.deepEqual(event.data.tags, ["SOL", "NFT"]);
+    await setTags([], ["SOL", "NFT"]).rpc();
+    assert.include(await tagIndexMarkets("SOL"), marketPda.toString());
+    assert.include(await tagIndexMarkets("NFT"), marketPda.toString());
+
+    await setTags(["SOL", "NFT"], ["NFT", "2024 election"]).rpc();
+    const marketTags = await program.account.marketTags.fetch(marketTagsPda);
+    assert.deepEqual(marketTags.tags, ["NFT", "2024 election"]);
+    assert.notInclude(await tagIndexMarkets("SOL"), marketPda.toString());
+    const nftMarkets = await tagIndexMarkets("NFT");
+    assert.equal(
+      nftMarkets.filter((key) => key === marketPda.toString()).length,
+      1
+    );
+    assert.include(await tagIndexMarkets("2024 election"), marketPda.toString());
+
+    try {
+      await setTags([], Array.from({ length: 9 }, (_, i) => `tag${i}`)).rpc();
+      assert.fail("Set more than 8 tags");
+    } catch (err: any) {
+      assert.include(err.toString(), "TooManyTags");
+    }
+
+    console.log("✅ Market tags indexed");
+  });
+
+  it("Time-locks Config changes while the admin timelock is on", async () => {
+    const TIMELOCK_SECS = 3;
+    const adminAccounts = { config: configPda, admin: authority.publicKey };
+    const queue = (action: any) =>
+      program.methods.queueAdminAction(action).accounts(adminAccounts).rpc();
+    const execute = (action: any) =>
+      program.methods.executeAdminAction(action).accounts(adminAccounts).rpc();
+    const waitForTimelock = () =>
+      new Promise((resolve) => setTimeout(resolve, (TIMELOCK_SECS + 2) * 1000));
+
+    const { feeBps: originalFeeBps } = await program.account.config.fetch(configPda);
+    await program.methods
+      .setAdminTimelock(new BN(TIMELOCK_SECS))
+      .accounts(adminAccounts)
+      .rpc();
+
+    try {
+      try {
+        await program.methods.setFeeBps(150).accounts(adminAccounts).rpc();
+        assert.fail("Fee changed instantly under the timelock");
+      } catch (err: any) {
+        assert.include(err.toString(), "AdminTimelockEnabled");
+      }
+      // A realm attached instantly could then bypass the timelock
+      try {
+        await program.methods
+          .setGovernance(anchor.web3.Keypair.generate().publicKey)
+          .accounts(adminAccounts)
+          .rpc();
+        assert.fail("Governance attached instantly under the timelock");
+      } catch (err: any) {
+        assert.include(err.toString(), "AdminTimelockEnabled");
+      }
+
+      const feeChange = { setFeeBps: { feeBps: 150 } };
+      await queue(feeChange);
+      try {
+        await execute(feeChange);
+        assert.fail("Queued action ran before its delay");
+      } catch (err: any) {
+        assert.include(err.toString(), "AdminTimelockActive");
+      }
+      try {
+        await execute({ setFeeBps: { feeBps: 900 } });
+        assert.fail("Ran an action that was never queued");
+      } catch (err: any) {
+        assert.include(err.toString(), "AdminActionNotQueued");
+      }
+
+      // The emergency pause stays instant
+      await program.methods
+        .emergencyPauseProtocol()
+        .accounts(adminAccounts)
+        .rpc();
+      await program.methods.resumeProtocol().accounts(adminAccounts).rpc();
+
+      await waitForTimelock();
+      await execute(feeChange);
+      const config = await program.account.config.fetch(configPda);
+      assert.equal(config.feeBps, 150);
+      assert.isNull(config.queuedAdminAction);
+    } finally {
+      const feeReset = { setFeeBps: { feeBps: originalFeeBps } };
+      await queue(feeReset);
+      await waitForTimelock();
+      await execute(feeReset);
+      const timelockOff = { setAdminTimelock: { adminTimelockSecs: new BN(0) } };
+      await queue(timelockOff);
+      await waitForTimelock();
+      await execute(timelockOff);
+    }
+
+    const config = await program.account.config.fetch(configPda);
+    assert.equal(config.feeBps, originalFeeBps);
+    assert.equal(config.adminTimelockSecs.toNumber(), 0);
+
+    console.log("✅ Admin timelock enforced");
+  });
+
+  itWithShortTimelocks("Allows emergency withdrawals only when paused and long expired", async () => {
+    const adminAccounts = { config: configPda, admin: authority.publicKey };
+    const { marketPda, vaultPda } = await createMarket(
+      "Emergency withdraw market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    const stranded = await tokenBalance(vaultPda);
+
+    const emergencyWithdraw = () =>
+      program.methods
+        .emergencyWithdraw(stranded)
+        .accounts({
+          config: configPda,
+          market: marketPda,
+          vault: vaultPda,
+          solVault: null,
+          usdcMint,
+          treasuryTokenAccount: authorityTokenAccount,
+          treasury: authority.publicKey,
+          admin: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        });
+
+    // Wait for expiry
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    try {
+      await emergencyWithdraw().rpc();
+      assert.fail("Emergency withdrawal while the protocol was live");
+    } catch (err: any) {
+      assert.include(err.toString(), "ProtocolNotPaused");
+    }
+
+    await program.methods.emergencyPauseProtocol().accounts(adminAccounts).rpc();
+    try {
+      try {
+        await emergencyWithdraw().rpc();
+        assert.fail("Emergency withdrawal before the grace period");
+      } catch (err: any) {
+        assert.include(err.toString(), "EmergencyGraceNotElapsed");
+      }
+
+      // Wait out EMERGENCY_WITHDRAW_GRACE_SECS (short-timelocks build)
+      await new Promise((resolve) => setTimeout(resolve, 6000));
+      const treasuryBefore = await tokenBalance(authorityTokenAccount);
+      const { events } = await emergencyWithdraw().simulate();
+      const event = events.find((e) => e.name === "EmergencyWithdrawEvent");
+      assert.equal(event.data.amount.toString(), stranded.toString());
+      await emergencyWithdraw().rpc();
+
+      assert.isTrue((await tokenBalance(vaultPda)).isZero());
+      const treasuryAfter = await tokenBalance(authorityTokenAccount);
+      assert.equal(treasuryAfter.sub(treasuryBefore).toString(), stranded.toString());
+    } finally {
+      await program.methods.resumeProtocol().accounts(adminAccounts).rpc();
+    }
+
+    console.log("✅ Emergency withdrawal guarded");
+  });
+
+  it("Rebates fees as users climb rakeback tiers", async () => {
+    const adminAccounts = { config: configPda, admin: authority.publicKey };
+    const bettor = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(
+      bettor.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+    const bettorTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        authority.payer,
+        usdcMint,
+        bettor.publicKey
+      )
+    ).address;
+    await mintTo(
+      provider.connection,
+      authority.payer,
+      usdcMint,
+      bettorTokenAccount,
+      authority.publicKey,
+      10 * 1e6
+    );
+
+    const { marketPda, vaultPda } = await createMarket(
+      "Rakeback market",
+      new BN(Math.floor(Date.now() / 1000) + 3600)
+    );
+    const original = await program.account.config.fetch(configPda);
+    await program.methods.setFeeBps(500).accounts(adminAccounts).rpc();
+    await program.methods
+      .setRakebackSchedule(
+        [new BN(2 * 1e6), new BN(5 * 1e6), new BN(8 * 1e6)],
+        [0, 100, 200, 300]
+      )
+      .accounts(adminAccounts)
+      .rpc();
+
+    const positionAmount = async () =>
+      (await program.account.userPosition.fetch(
+        findPositionPda(marketPda, bettor.publicKey)
+      )).amount.toNumber();
+
+    try {
+      // Bronze pays the full 5% on the bet that reaches Silver
+      const bet = buildBet(marketPda, vaultPda, new BN(3 * 1e6), bettor, bettorTokenAccount);
+      const { events } = await bet.simulate();
+      const upgrade = events.find((e) => e.name === "TierUpgradeEvent");
+      assert.deepEqual(upgrade.data.oldTier, { bronze: {} });
+      assert.deepEqual(upgrade.data.newTier, { silver: {} });
+      await bet.rpc();
+      assert.equal(await positionAmount(), 3 * 1e6 - 150_000);
+
+      const stats = await program.account.userStats.fetch(
+        findUserStatsPda(bettor.publicKey)
+      );
+      assert.deepEqual(stats.rakebackTier, { silver: {} });
+
+      // Silver pays 5% - 1% = 4%
+      await placeBet(marketPda, vaultPda, new BN(1 * 1e6), bettor, bettorTokenAccount);
+      assert.equal(await positionAmount(), 3 * 1e6 - 150_000 + 1 * 1e6 - 40_000);
+
+      try {
+        await program.methods
+          .setRakebackSchedule([new BN(5), new BN(5), new BN(6)], [0, 0, 0, 0])
+          .accounts(adminAccounts)
+          .rpc();
+        assert.fail("Accepted thresholds that do not increase");
+      } catch (err: any) {
+        assert.include(err.toString(), "InvalidRakebackSchedule");
+      }
+    } finally {
+      await program.methods.setFeeBps(original.feeBps).accounts(adminAccounts).rpc();
+      await program.methods
+        .setRakebackSchedule(original.rakebackThresholds, original.rakebackBps)
+        .accounts(adminAccounts)
+        .rpc();
+    }
+
+    console.log("✅ Rakeback tiers applied");
+  });
+
+  itWithShortTimelocks("Covers vault shortfalls from the insurance fund", async () => {
+    const adminAccounts = { config: configPda, admin: authority.publicKey };
+    const [insuranceFundPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("insurance")],
+      program.programId
+    );
+    const [insuranceVaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("insurance_vault"), usdcMint.toBuffer()],
+      program.programId
+    );
+
+    if (!(await program.account.insuranceFund.fetchNullable(insuranceFundPda))) {
+      await program.methods
+        .initializeInsuranceFund()
+        .accounts({
+          config: configPda,
+          insuranceFund: insuranceFundPda,
+          admin: authority.publicKey,
+        })
+        .rpc();
+    }
+    await program.methods
+      .insuranceFundDeposit(new BN(2 * 1e6))
+      .accounts({
+        insuranceFund: insuranceFundPda,
+        insuranceVault: insuranceVaultPda,
+        usdcMint,
+        depositorTokenAccount: authorityTokenAccount,
+        depositor: authority.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const drainer = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(
+      drainer.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+    const drainerTokenAccount = (
+      await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        authority.payer,
+        usdcMint,
+        drainer.publicKey
+      )
+    ).address;
+    await mintTo(
+      provider.connection,
+      authority.payer,
+      usdcMint,
+      drainerTokenAccount,
+      authority.publicKey,
+      1 * 1e6
+    );
+
+    const { marketPda, vaultPda } = await createMarket(
+      "Insurance shortfall market",
+      new BN(Math.floor(Date.now() / 1000) + 3)
+    );
+    const original = await program.account.config.fetch(configPda);
+    await program.methods.setFeeBps(100).accounts(adminAccounts).rpc();
+    await program.methods.setInsuranceFeeBps(5000).accounts(adminAccounts).rpc();
+    try {
+      const insuranceBefore = await tokenBalance(insuranceVaultPda);
+      try {
+        await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+        assert.fail("Bet skipped the insurance vault");
+      } catch (err: any) {
+        assert.include(err.toString(), "InsuranceVaultMissing");
+      }
+      const bet = (wallet: anchor.web3.Keypair, tokenAccount: PublicKey) =>
+        buildBet(marketPda, vaultPda, new BN(1 * 1e6), wallet, tokenAccount, null, insuranceVaultPda)
+          .rpc({ commitment: "confirmed" });
+      await bet(userWallet, userTokenAccount);
+      await bet(drainer, drainerTokenAccount);
+      // Half of each 1% fee
+      assert.equal(
+        (await tokenBalance(insuranceVaultPda)).sub(insuranceBefore).toNumber(),
+        2 * 5_000
+      );
+    } finally {
+      await program.methods.setInsuranceFeeBps(original.insuranceFeeBps).accounts(adminAccounts).rpc();
+      await program.methods.setFeeBps(original.feeBps).accounts(adminAccounts).rpc();
+    }
+
+    // Wait for expiry, then out the dispute window
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const claim = (
+      wallet: anchor.web3.Keypair,
+      tokenAccount: PublicKey,
+      payout: BN,
+      insured: boolean
+    ) => {
+      const expiry = proofExpiry();
+      const proof = signPayout(mxeKeypair, marketPda, wallet.publicKey, payout, new BN(1), expiry);
+      return program.methods
+        .claimWithProof(payout, new BN(1), expiry, [proof.signature])
+        .accounts({
+          market: marketPda,
+          config: configPda,
+          userPosition: findPositionPda(marketPda, wallet.publicKey),
+          vault: vaultPda,
+          solVault: null,
+          userTokenAccount: tokenAccount,
+          usdcMint,
+          insuranceFund: insured ? insuranceFundPda : null,
+          insuranceVault: insured ? insuranceVaultPda : null,
+          userStats: findUserStatsPda(wallet.publicKey),
+          user: wallet.publicKey,
+          ixSysvar: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions([proof.ix])
+        .signers([wallet]);
+    };
+
+    // A faulty proof for the whole pool empties the vault
+    const pool = await tokenBalance(vaultPda);
+    await claim(drainer, drainerTokenAccount, pool, false).rpc();
+    const payout = new BN(990_000);
+    try {
+      await claim(userWallet, userTokenAccount, payout, false).rpc();
+      assert.fail("Claim paid from an empty vault");
+    } catch (err: any) {
+      assert.include(err.toString(), "InsufficientVaultBalance");
+    }
+
+    const insuranceBefore = await tokenBalance(insuranceVaultPda);
+    const { events } = await claim(userWallet, userTokenAccount, payout, true).simulate();
+    const drawdown = events.find((e) => e.name === "InsuranceDrawdownEvent");
+    assert.equal(drawdown.data.market.toString(), marketPda.toString());
+    assert.equal(drawdown.data.user.toString(), userWallet.publicKey.toString());
+    assert.equal(drawdown.data.drawdownAmount.toNumber(), payout.toNumber());
+    await claim(userWallet, userTokenAccount, payout, true).rpc();
+    assert.equal(
+      insuranceBefore.sub(await tokenBalance(insuranceVaultPda)).toNumber(),
+      payout.toNumber()
+    );
+
+    // Manual drawdowns are bounded by the fund
+    const drawFromInsurance = (amount: BN) =>
+      program.methods
+        .drawFromInsurance(amount)
+        .accounts({
+          config: configPda,
+          market: marketPda,
+          vault: vaultPda,
+          insuranceFund: insuranceFundPda,
+          insuranceVault: insuranceVaultPda,
+          usdcMint,
+          admin: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    const available = await tokenBalance(insuranceVaultPda);
+    try {
+      await drawFromInsurance(available.addn(1));
+      assert.fail("Drew more than the fund holds");
+    } catch (err: any) {
+      assert.include(err.toString(), "DrawdownExceedsFund");
+    }
+    await drawFromInsurance(new BN(1_000));
+    assert.equal((await tokenBalance(vaultPda)).toNumber(), 1_000);
+
+    console.log("✅ Insurance fund covered the shortfall");
+  });
+
+  itWithShortTimelocks("Leaves no half-claimed position when the payout transfer fails", async () => {
+    const { marketPda, vaultPda } = await createMarket(
+      "Failed payout transfer market",
+      new BN(Math.floor(Date.now() / 1000) + 3)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+
+    const positionPda = findPositionPda(marketPda, userWallet.publicKey);
+    const payout = (await program.account.userPosition.fetch(positionPda)).amount;
+    const statsBefore = await program.account.userStats.fetch(
+      findUserStatsPda(userWallet.publicKey)
+    );
+
+    // The claim is recorded before the transfer, which then fails
+    try {
+      await buildClaim(
+        marketPda,
+        vaultPda,
+        [mxeKeypair],
+        payout,
+        new BN(1),
+        proofExpiry(),
+        TOKEN_2022_PROGRAM_ID
+      ).rpc();
+      assert.fail("Transfer through an unsupported token program succeeded");
+    } catch (err: any) {
+      assert.include(err.toString(), "Token22NotSupported");
+    }
+
+    let position = await program.account.userPosition.fetch(positionPda);
+    assert.isFalse(position.claimed);
+    assert.equal(position.claimedAmount.toNumber(), 0);
+    assert.equal(position.nonceUsed.toNumber(), 0);
+    const market = await program.account.market.fetch(marketPda);
+    assert.equal(market.claimedPositions, 0);
+    assert.equal(market.totalClaimed.toNumber(), 0);
+    const statsAfter = await program.account.userStats.fetch(
+      findUserStatsPda(userWallet.publicKey)
+    );
+    assert.equal(
+      statsAfter.totalClaimed.toString(),
+      statsBefore.totalClaimed.toString()
+    );
+
+    // The same proof still claims once the transfer can go through
+    await claimWithSigners(marketPda, vaultPda, [mxeKeypair], payout, new BN(1));
+    position = await program.account.userPosition.fetch(positionPda);
+    assert.isTrue(position.claimed);
+    assert.equal(position.claimedAmount.toString(), payout.toString());
+
+    console.log("✅ Failed payout transfer rolled back the claim");
+  });
+
+  it("Reconciles the vault against the pool at resolution", async () => {
+    const adminAccounts = { config: configPda, admin: authority.publicKey };
+    const expiry = () => new BN(Math.floor(Date.now() / 1000) + 2);
+    const consistent = await createMarket("Consistent vault market", expiry());
+    const inflated = await createMarket("Inflated vault market", expiry());
+    await placeBet(consistent.marketPda, consistent.vaultPda, new BN(1 * 1e6));
+    await placeBet(inflated.marketPda, inflated.vaultPda, new BN(1 * 1e6));
+
+    // Tokens sent straight to the vault bypass the pool accounting
+    await mintTo(
+      provider.connection,
+      authority.payer,
+      usdcMint,
+      inflated.vaultPda,
+      authority.publicKey,
+      1 * 1e6
+    );
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    const resolve = (marketPda: PublicKey) =>
+      program.methods
+        .resolveMarket(0)
+        .accounts({ market: marketPda, authority: authority.publicKey });
+
+    await program.methods.setVaultTolerance(new BN(0)).accounts(adminAccounts).rpc();
+    try {
+      const { events } = await resolve(consistent.marketPda).simulate();
+      const reconciliation = events.find((e) => e.name === "VaultReconciliationEvent");
+      const pool = (await program.account.market.fetch(consistent.marketPda)).totalPool;
+      assert.equal(reconciliation.data.expectedBalance.toString(), pool.toString());
+      assert.equal(reconciliation.data.vaultBalance.toString(), pool.toString());
+      assert.equal(reconciliation.data.delta.toNumber(), 0);
+      await resolve(consistent.marketPda).rpc();
+
+      try {
+        await resolve(inflated.marketPda).rpc();
+        assert.fail("Resolved a market whose vault drifted");
+      } catch (err: any) {
+        assert.include(err.toString(), "VaultInconsistent");
+      }
+    } finally {
+      await program.methods.setVaultTolerance(null).accounts(adminAccounts).rpc();
+    }
+
+    // Without a tolerance the drift is only reported
+    const { events } = await resolve(inflated.marketPda).simulate();
+    const reconciliation = events.find((e) => e.name === "VaultReconciliationEvent");
+    assert.equal(reconciliation.data.delta.toNumber(), 1 * 1e6);
+    await resolve(inflated.marketPda).rpc();
+    assert.isTrue((await program.account.market.fetch(inflated.marketPda)).resolved);
+
+    console.log("✅ Vault reconciliation flagged the inflated vault");
+  });
+
+  it("Transfers market authority in two steps", async () => {
+    const { marketPda } = await createMarket(
+      "Authority transfer market",
+      new BN(Math.floor(Date.now() / 1000) + 3600)
+    );
+    const multisig = anchor.web3.Keypair.generate();
+    const impostor = anchor.web3.Keypair.generate();
+    const accept = (signer: anchor.web3.Keypair) =>
+      program.methods
+        .acceptAuthorityTransfer()
+        .accounts({ market: marketPda, newAuthority: signer.publicKey })
+        .signers([signer]);
+
+    try {
+      await accept(multisig).rpc();
+      assert.fail("Accepted a transfer nobody proposed");
+    } catch (err: any) {
+      assert.include(err.toString(), "NoPendingAuthorityTransfer");
+    }
+
+    try {
+      await program.methods
+        .proposeAuthorityTransfer(impostor.publicKey)
+        .accounts({ market: marketPda, authority: impostor.publicKey })
+        .signers([impostor])
+        .rpc();
+      assert.fail("Non-authority proposed a transfer");
+    } catch (err: any) {
+      assert.include(err.toString(), "Unauthorized");
+    }
+
+    const { events } = await program.methods
+      .proposeAuthorityTransfer(multisig.publicKey)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .simulate();
+    const proposed = events.find((e) => e.name === "AuthorityTransferProposedEvent");
+    assert.equal(proposed.data.pendingAuthority.toString(), multisig.publicKey.toString());
+    await program.methods
+      .proposeAuthorityTransfer(multisig.publicKey)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+    let market = await program.account.market.fetch(marketPda);
+    assert.equal(market.pendingAuthority.toString(), multisig.publicKey.toString());
+    assert.equal(market.authority.toString(), authority.publicKey.toString());
+
+    try {
+      await accept(impostor).rpc();
+      assert.fail("Impostor accepted the transfer");
+    } catch (err: any) {
+      assert.include(err.toString(), "NotPendingAuthority");
+    }
+
+    await accept(multisig).rpc();
+    market = await program.account.market.fetch(marketPda);
+    assert.equal(market.authority.toString(), multisig.publicKey.toString());
+    assert.equal(market.resolver.toString(), multisig.publicKey.toString());
+    assert.isNull(market.pendingAuthority);
+
+    // The old authority lost its powers over the market
+    try {
+      await program.methods
+        .setResolver(authority.publicKey)
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+      assert.fail("Previous authority still controls the market");
+    } catch (err: any) {
+      assert.include(err.toString(), "Unauthorized");
+    }
+
+    console.log("✅ Market authority moved to the multisig");
+  });
+
+  it("Lets an attached governance realm sign Config changes", async () => {
+    const splGovernanceProgramId = new PublicKey(
+      "GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCVZw"
+    );
+    const outsider = anchor.web3.Keypair.generate();
+    const realm = anchor.web3.Keypair.generate().publicKey;
+    const [governancePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("account-governance"), realm.toBuffer(), configPda.toBuffer()],
+      splGovernanceProgramId
+    );
+    const original = await program.account.config.fetch(configPda);
+    const setFeeAs = (signer: anchor.web3.Keypair) =>
+      program.methods
+        .setFeeBps(original.feeBps)
+        .accounts({ config: configPda, admin: signer.publicKey })
+        .signers([signer])
+        .rpc();
+
+    if (!original.governance) {
+      try {
+        await setFeeAs(outsider);
+        assert.fail("Outsider changed the fee");
+      } catch (err: any) {
+        assert.include(err.toString(), "Unauthorized");
+      }
+    }
+
+    try {
+      await program.methods
+        .setGovernance(realm)
+        .accounts({ config: configPda, admin: outsider.publicKey })
+        .signers([outsider])
+        .rpc();
+      assert.fail("Outsider attached a governance realm");
+    } catch (err: any) {
+      assert.include(err.toString(), "Unauthorized");
+    }
+
+    const { events } = await program.methods
+      .setGovernance(realm)
+      .accounts({ config: configPda, admin: authority.publicKey })
+      .simulate();
+    const updated = events.find((e) => e.name === "GovernanceUpdatedEvent");
+    assert.equal(updated.data.governance.toString(), governancePda.toString());
+    await program.methods
+      .setGovernance(realm)
+      .accounts({ config: configPda, admin: authority.publicKey })
+      .rpc();
+    const config = await program.account.config.fetch(configPda);
+    assert.equal(config.governance.toString(), realm.toString());
+
+    // Only the realm's governance PDA can stand in for the admin
+    try {
+      await setFeeAs(outsider);
+      assert.fail("Outsider passed as governance");
+    } catch (err: any) {
+      assert.include(err.toString(), "GovernanceCheckFailed");
+    }
+    try {
+      await program.methods
+        .emergencyPauseProtocol()
+        .accounts({ config: configPda, admin: outsider.publicKey })
+        .signers([outsider])
+        .rpc();
+      assert.fail("Outsider paused the protocol");
+    } catch (err: any) {
+      assert.include(err.toString(), "GovernanceCheckFailed");
+    }
+
+    // The admin keeps its powers
+    await program.methods
+      .setFeeBps(original.feeBps)
+      .accounts({ config: configPda, admin: authority.publicKey })
+      .rpc();
+
+    console.log("✅ Governance realm attached to Config");
+  });
+
+  // Pyth V2 price account size (pyth_sdk_solana::state::PriceAccount)
+  const PYTH_PRICE_ACCOUNT_LEN = 3312;
+
+  const createPythFeed = async () => {
+    const feed = anchor.web3.Keypair.generate();
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: authority.publicKey,
+          newAccountPubkey: feed.publicKey,
+          lamports: await provider.connection.getMinimumBalanceForRentExemption(
+            PYTH_PRICE_ACCOUNT_LEN
+          ),
+          space: PYTH_PRICE_ACCOUNT_LEN,
+          programId: mockPyth.programId,
+        })
+      ),
+      [feed]
+    );
+    return feed.publicKey;
+  };
+
+  // `price` at exponent -8 with a 0.01% confidence interval
+  const publishPythPrice = (feed: PublicKey, price: BN, publishTime: BN) =>
+    mockPyth.methods
+      .setPrice(price, -8, price.divn(10_000), publishTime)
+      .accounts({ price: feed })
+      .rpc();
+
+  // 200.00000000 at exponent -8
+  const PYTH_THRESHOLD = new BN(200).mul(new BN(10).pow(new BN(8)));
+
+  const createPythMarket = async (
+    question: string,
+    expiry: BN,
+    options: MarketOptions = {}
+  ) => {
+    const feed = await createPythFeed();
+    const { marketPda, vaultPda } = await createMarket(question, expiry, {
+      ...options,
+      pythPriceFeed: feed,
+      resolutionPriceThreshold: PYTH_THRESHOLD,
+    });
+    return { marketPda, vaultPda, feed };
+  };
+
+  const findMarketV2Pda = (marketPda: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("market_v2"), marketPda.toBuffer()],
+      program.programId
+    )[0];
+
+  const resolveWithPyth = (marketPda: PublicKey, feed: PublicKey) =>
+    program.methods
+      .autoResolveWithPyth()
+      .accounts({ market: marketPda, priceFeed: feed, marketV2: findMarketV2Pda(marketPda) })
+      .rpc();
+
+  it("Resolves price markets from a Pyth price published after expiry", async () => {
+    const expiry = new BN(Math.floor(Date.now() / 1000) + 2);
+    const above = await createPythMarket("Pyth price above threshold", expiry);
+    const below = await createPythMarket("Pyth price below threshold", expiry);
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await publishPythPrice(above.feed, PYTH_THRESHOLD.addn(1), expiry);
+    await publishPythPrice(below.feed, PYTH_THRESHOLD.subn(1), expiry.addn(1));
+    await resolveWithPyth(above.marketPda, above.feed);
+    await resolveWithPyth(below.marketPda, below.feed);
+
+    const aboveMarket = await program.account.market.fetch(above.marketPda);
+    assert.isTrue(aboveMarket.resolved);
+    assert.deepEqual(aboveMarket.result, { yes: {} });
+    const belowMarket = await program.account.market.fetch(below.marketPda);
+    assert.deepEqual(belowMarket.result, { no: {} });
+
+    console.log("✅ Pyth prices resolved both markets");
+  });
+
+  it("Rejects Pyth prices published before expiry", async () => {
+    const expiry = new BN(Math.floor(Date.now() / 1000) + 2);
+    const { marketPda, feed } = await createPythMarket("Stale Pyth price market", expiry);
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await publishPythPrice(feed, PYTH_THRESHOLD.addn(1), expiry.subn(1));
+    try {
+      await resolveWithPyth(marketPda, feed);
+      assert.fail("Resolved on a price from before expiry");
+    } catch (err: any) {
+      assert.include(err.toString(), "PythPriceStale");
+    }
+    assert.isFalse((await program.account.market.fetch(marketPda)).resolved);
+
+    console.log("✅ Pre-expiry Pyth price rejected");
+  });
+
+  itWithShortTimelocks("Closes Pyth resolution once the window has passed", async () => {
+    const expiry = new BN(Math.floor(Date.now() / 1000) + 2);
+    const { marketPda, feed } = await createPythMarket("Late Pyth price market", expiry);
+
+    // A price published late in the window is only usable inside it
+    await new Promise((resolve) =>
+      setTimeout(resolve, (ORACLE_RESOLUTION_WINDOW_SECS + 4) * 1000)
+    );
+    await publishPythPrice(
+      feed,
+      PYTH_THRESHOLD.addn(1),
+      expiry.addn(ORACLE_RESOLUTION_WINDOW_SECS)
+    );
+    try {
+      await resolveWithPyth(marketPda, feed);
+      assert.fail("Resolved after the oracle window closed");
+    } catch (err: any) {
+      assert.include(err.toString(), "OracleWindowClosed");
+    }
+
+    console.log("✅ Pyth resolution closed after the window");
+  });
+
+  it("Pays the creator fee on oracle resolution", async () => {
+    const expiry = new BN(Math.floor(Date.now() / 1000) + 2);
+    const { marketPda, vaultPda, feed } = await createPythMarket(
+      "Pyth creator fee market",
+      expiry,
+      { creatorFeeBps: 500 }
+    );
+    await placeBet(marketPda, vaultPda, new BN(2 * 1e6));
+    const creatorBefore = await provider.connection.getTokenAccountBalance(
+      authorityTokenAccount
+    );
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await publishPythPrice(feed, PYTH_THRESHOLD.addn(1), expiry);
+    await program.methods
+      .autoResolveWithPyth()
+      .accounts({
+        market: marketPda,
+        priceFeed: feed,
+        marketV2: findMarketV2Pda(marketPda),
+        vault: vaultPda,
+        solVault: null,
+        creatorFeeAccount: authorityTokenAccount,
+        creator: null,
+        usdcMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const market = await program.account.market.fetch(marketPda);
+    assert.equal(market.creatorFeeCollected.toNumber(), 100_000);
+    assert.equal(market.totalPool.toNumber(), 1_900_000);
+    const creatorAfter = await provider.connection.getTokenAccountBalance(
+      authorityTokenAccount
+    );
+    assert.equal(
+      Number(creatorAfter.value.amount) - Number(creatorBefore.value.amount),
+      100_000
+    );
+
+    console.log("✅ Oracle resolution paid the creator fee");
+  });
+
+  it("Keeps a bonded authority from closing disputes against itself", async () => {
+    const adminAccounts = { config: configPda, admin: authority.publicKey };
+    const creator = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(
+      creator.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+    const updateAllowlist = (add: boolean) =>
+      (add ? program.methods.addCreator(creator.publicKey) : program.methods.removeCreator(creator.publicKey))
+        .accounts({
+          creatorAllowlist: creatorAllowlistPda,
+          config: configPda,
+          admin: authority.publicKey,
+        })
+        .rpc();
+
+    await updateAllowlist(true);
+    await program.methods
+      .setResolutionBondAmount(new BN(50_000_000))
+      .accounts(adminAccounts)
+      .rpc();
+    let marketPda: PublicKey;
+    try {
+      ({ marketPda } = await createMarket(
+        "Self-judged dispute market",
+        new BN(Math.floor(Date.now() / 1000) + 2),
+        { creator }
+      ));
+    } finally {
+      await program.methods
+        .setResolutionBondAmount(new BN(0))
+        .accounts(adminAccounts)
+        .rpc();
+      await updateAllowlist(false);
+    }
+    const [bondPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("bond"), marketPda.toBuffer()],
+      program.programId
+    );
+    const [disputePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("dispute"), marketPda.toBuffer(), userWallet.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: creator.publicKey })
+      .signers([creator])
+      .rpc();
+    await program.methods
+      .disputeMarketResolution(Array(32).fill(9))
+      .accounts({ market: marketPda, disputant: userWallet.publicKey })
+      .signers([userWallet])
+      .rpc();
+
+    const closeDispute = (closer: anchor.web3.Keypair | null, slash: boolean) =>
+      program.methods
+        .closeDispute(slash)
+        .accounts({
+          market: marketPda,
+          dispute: disputePda,
+          resolutionBond: bondPda,
+          disputant: userWallet.publicKey,
+          authority: closer ? closer.publicKey : authority.publicKey,
+        })
+        .signers(closer ? [closer] : [])
+        .rpc();
+
+    // Closing the dispute would let the authority release its own bond
+    for (const slash of [false, true]) {
+      try {
+        await closeDispute(creator, slash);
+        assert.fail("Bonded authority closed a dispute against itself");
+      } catch (err: any) {
+        assert.include(err.toString(), "Unauthorized");
+      }
+    }
+    assert.equal((await program.account.resolutionBond.fetch(bondPda)).openDisputes, 1);
+
+    // The admin rules instead
+    await closeDispute(null, false);
+    assert.isNull(await provider.connection.getAccountInfo(disputePda));
+    assert.equal((await program.account.resolutionBond.fetch(bondPda)).openDisputes, 0);
+
+    console.log("✅ Only the admin closes disputes on a bonded market");
+  });
+
+  it("Rejects markets with both a Pyth feed and a Switchboard oracle", async () => {
+    try {
+      await createMarket(
+        "Two oracle market",
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        {
+          pythPriceFeed: anchor.web3.Keypair.generate().publicKey,
+          oracle: anchor.web3.Keypair.generate().publicKey,
+          resolutionPriceThreshold: new BN(100),
+        }
+      );
+      assert.fail("Market created with two oracles");
+    } catch (err: any) {
+      assert.include(err.toString(), "MultipleOraclesConfigured");
+    }
+
+    console.log("✅ Markets resolve from a single oracle");
+  });
+
+  it("Keeps the resolver from resolving oracle markets by hand", async () => {
+    const expiry = new BN(Math.floor(Date.now() / 1000) + 2);
+    const { marketPda } = await createPythMarket("Oracle-only Pyth market", expiry);
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    try {
+      await program.methods
+        .resolveMarket(0)
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+      assert.fail("Resolver resolved a Pyth market");
+    } catch (err: any) {
+      assert.include(err.toString(), "OracleResolutionRequired");
+    }
+    try {
+      await program.methods
+        .proposeResolution(0)
+        .accounts({ market: marketPda, authority: authority.publicKey })
+        .rpc();
+      assert.fail("Resolver proposed a result for a Pyth market");
+    } catch (err: any) {
+      assert.include(err.toString(), "OracleResolutionRequired");
+    }
+    assert.isFalse((await program.account.market.fetch(marketPda)).resolved);
+
+    // A committed result cannot be paired with an oracle either
+    try {
+      await createMarket(
+        "Committed oracle market",
+        new BN(Math.floor(Date.now() / 1000) + 3600),
+        {
+          pythPriceFeed: anchor.web3.Keypair.generate().publicKey,
+          resolutionPriceThreshold: PYTH_THRESHOLD,
+          resolutionCommitment: Array(32).fill(7),
+        }
+      );
+      assert.fail("Market created with a commitment and an oracle");
+    } catch (err: any) {
+      assert.include(err.toString(), "CommitmentWithOracle");
+    }
+
+    console.log("✅ Oracle markets resolve only from their oracle");
+  });
+
+  it("Resolves V2 price criteria from the price at at_time", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    const expiry = new BN(now + 4);
+    const atTime = expiry.subn(1);
+    const feed = await createPythFeed();
+    const { marketPda } = await createMarket("Pyth price at a set time", expiry, {
+      pythPriceFeed: feed,
+      bettingCloseTimestamp: atTime,
+    });
+    const createV2 = (at: BN) =>
+      program.methods
+        .createMarketV2(
+          { priceAbove: { mint: usdcMint, thresholdUiAmount: 200, atTime: at } },
+          expiry
+        )
+        .accounts({
+          market: marketPda,
+          marketV2: findMarketV2Pda(marketPda),
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    // The observation cannot come before betting closes
+    try {
+      await createV2(atTime.subn(1));
+      assert.fail("at_time before betting close accepted");
+    } catch (err: any) {
+      assert.include(err.toString(), "InvalidResolutionCriteria");
+    }
+    await createV2(atTime);
+
+    await new Promise((resolve) => setTimeout(resolve, 5000));
+    await publishPythPrice(feed, PYTH_THRESHOLD.addn(1), atTime.subn(1));
+    try {
+      await resolveWithPyth(marketPda, feed);
+      assert.fail("Resolved on a price from before at_time");
+    } catch (err: any) {
+      assert.include(err.toString(), "PythPriceStale");
+    }
+
+    await publishPythPrice(feed, PYTH_THRESHOLD.addn(1), atTime);
+    await resolveWithPyth(marketPda, feed);
+    const market = await program.account.market.fetch(marketPda);
+    assert.deepEqual(market.result, { yes: {} });
+
+    console.log("✅ V2 price criteria resolved at at_time");
+  });
+
+  itWithShortTimelocks("Files disputes inside the window and returns or slashes their bonds", async () => {
+    const DISPUTE_BOND_LAMPORTS = 100_000_000;
+    const { marketPda, vaultPda } = await createMarket(
+      "Disputed resolution market",
+      new BN(Math.floor(Date.now() / 1000) + 2)
+    );
+    await placeBet(marketPda, vaultPda, new BN(1 * 1e6));
+    const rival = anchor.web3.Keypair.generate();
+    const airdropSig = await provider.connection.requestAirdrop(
+      rival.publicKey,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropSig);
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+    await program.methods
+      .resolveMarket(0)
+      .accounts({ market: marketPda, authority: authority.publicKey })
+      .rpc();
+
+    const [bondPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("bond"), marketPda.toBuffer()],
+      program.programId
+    );
+    const disputePda = (disputant: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("dispute"), marketPda.toBuffer(), disputant.toBuffer()],
+        program.programId
+      )[0];
+    const dispute = (disputant: anchor.web3.Keypair) =>
+      program.methods
+        .disputeMarketResolution(Array(32).fill(5))
+        .accounts({ market: marketPda, disputant: disputant.publicKey })
+        .signers([disputant])
+        .rpc();
+    // Unbonded market: its authority reviews the disputes
+    const closeDispute = (disputant: PublicKey, slash: boolean) =>
+      program.methods
+        .closeDispute(slash)
+        .accounts({
+          market: marketPda,
+          dispute: disputePda(disputant),
+          resolutionBond: bondPda,
+          disputant,
+          authority: authority.publicKey,
+        })
+        .rpc();
+
+    await dispute(userWallet);
+    await dispute(rival);
+    const record = await program.account.disputeRecord.fetch(disputePda(rival.publicKey));
+    assert.equal(record.disputant.toString(), rival.publicKey.toString());
+    assert.equal(record.bond.toNumber(), DISPUTE_BOND_LAMPORTS);
+    assert.equal((await program.account.resolutionBond.fetch(bondPda)).openDisputes, 2);
+
+    // Payouts wait out the dispute window
+    try {
+      await claimWithSigners(marketPda, vaultPda, [mxeKeypair], new BN(1 * 1e6), new BN(1));
+      assert.fail("Claim inside the dispute window was accepted");
+    } catch (err: any) {
+      assert.include(err.toString(), "DisputeWindowActive");
+    }
+
+    // Upheld: the bond comes back with the record's rent
+    const userBefore = await provider.connection.getBalance(userWallet.publicKey);
+    await closeDispute(userWallet.publicKey, false);
+    const returned = (await provider.connection.getBalance(userWallet.publicKey)) - userBefore;
+    assert.isAbove(returned, DISPUTE_BOND_LAMPORTS);
+
+    // Rejected: only the rent comes back, the bond goes to the treasury
+    const rivalBefore = await provider.connection.getBalance(rival.publicKey);
+    await closeDispute(rival.publicKey, true);
+    const refunded = (await provider.connection.getBalance(rival.publicKey)) - rivalBefore;
+    assert.isAbove(refunded, 0);
+    assert.isBelow(refunded, DISPUTE_BOND_LAMPORTS);
+    assert.isNull(await provider.connection.getAccountInfo(disputePda(rival.publicKey)));
+    assert.equal((await program.account.resolutionBond.fetch(bondPda)).openDisputes, 0);
+
+    await new Promise((resolve) =>
+      setTimeout(resolve, (DISPUTE_WINDOW_SECS + 1) * 1000)
+    );
+    try {
+      await dispute(userWallet);
+      assert.fail("Dispute filed after the window");
+    } catch (err: any) {
+      assert.include(err.toString(), "DisputeWindowClosed");
+    }
+
+    await claimWithSigners(marketPda, vaultPda, [mxeKeypair], new BN(1 * 1e6), new BN(1));
+    const position = await program.account.userPosition.fetch(
+      findPositionPda(marketPda, userWallet.publicKey)
+    );
+    assert.isTrue(position.claimed);
+
+    console.log("✅ Disputes filed, returned and slashed");
+  });
 });